@@ -0,0 +1,18 @@
+use std::env;
+use std::path::Path;
+
+fn main() {
+    println!("cargo:rerun-if-changed=schemas/greeter.fbs");
+    println!("cargo:rerun-if-changed=schemas/common.fbs");
+    let out_dir = env::var("OUT_DIR").unwrap();
+    flatc_rust::run(flatc_rust::Args {
+        inputs: &[
+            Path::new("schemas/greeter.fbs"),
+            Path::new("schemas/common.fbs"),
+        ],
+        out_dir: Path::new(&out_dir),
+        tonic: true,
+        ..Default::default()
+    })
+    .expect("flatc");
+}
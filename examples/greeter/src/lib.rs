@@ -0,0 +1,178 @@
+use std::net::SocketAddr;
+use std::pin::Pin;
+
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status, Streaming};
+
+#[allow(dead_code, unused_imports, clippy::all)]
+pub mod common_generated {
+    include!(concat!(env!("OUT_DIR"), "/common_generated.rs"));
+}
+
+#[allow(dead_code, unused_imports, clippy::all)]
+pub mod greeter_generated {
+    include!(concat!(env!("OUT_DIR"), "/greeter_generated.rs"));
+}
+
+use self::common_generated::common;
+use self::greeter_generated::greeter_messages::{Ack, HelloReply, HelloRequest};
+use self::greeter_generated::my_game::hello_world;
+use self::greeter_generated::{greeter_client::GreeterClient, greeter_server};
+
+pub fn request(name: &str) -> HelloRequest {
+    let mut builder = flatbuffers::FlatBufferBuilder::new();
+    let name = builder.create_string(name);
+    let request = hello_world::HelloRequest::create(
+        &mut builder,
+        &hello_world::HelloRequestArgs { name: Some(name) },
+    );
+    builder.finish(request, None);
+    HelloRequest::from_builder(&builder).expect("a valid HelloRequest")
+}
+
+fn reply(message: &str) -> HelloReply {
+    let mut builder = flatbuffers::FlatBufferBuilder::new();
+    let message = builder.create_string(message);
+    let reply = hello_world::HelloReply::create(
+        &mut builder,
+        &hello_world::HelloReplyArgs {
+            message: Some(message),
+        },
+    );
+    builder.finish(reply, None);
+    HelloReply::from_builder(&builder).expect("a valid HelloReply")
+}
+
+fn name(request: &HelloRequest) -> &str {
+    request.get().name().unwrap_or("stranger")
+}
+
+type ReplyStream = Pin<Box<dyn Stream<Item = Result<HelloReply, Status>> + Send>>;
+
+pub struct Greeter;
+
+#[tonic::async_trait]
+impl greeter_server::Greeter for Greeter {
+    async fn say_hello(
+        &self,
+        request: Request<HelloRequest>,
+    ) -> Result<Response<HelloReply>, Status> {
+        let message = format!("Hello, {}!", name(request.get_ref()));
+        Ok(Response::new(reply(&message)))
+    }
+
+    type SayManyHellosStream = ReplyStream;
+
+    async fn say_many_hellos(
+        &self,
+        request: Request<HelloRequest>,
+    ) -> Result<Response<ReplyStream>, Status> {
+        let name = name(request.get_ref()).to_owned();
+        let replies = (1..=3).map(move |i| Ok(reply(&format!("Hello #{}, {}!", i, name))));
+        Ok(Response::new(Box::pin(tokio_stream::iter(replies))))
+    }
+
+    async fn collect(
+        &self,
+        request: Request<Streaming<HelloRequest>>,
+    ) -> Result<Response<Ack>, Status> {
+        let mut requests = request.into_inner();
+        let mut count = 0;
+        while let Some(request) = requests.next().await {
+            request?;
+            count += 1;
+        }
+        let mut builder = flatbuffers::FlatBufferBuilder::new();
+        let ack = common::Ack::create(&mut builder, &common::AckArgs { count });
+        builder.finish(ack, None);
+        Ok(Response::new(
+            Ack::from_builder(&builder).expect("a valid Ack"),
+        ))
+    }
+
+    type ChatStream = ReplyStream;
+
+    async fn chat(
+        &self,
+        request: Request<Streaming<HelloRequest>>,
+    ) -> Result<Response<ReplyStream>, Status> {
+        let replies = request
+            .into_inner()
+            .map(|request| request.map(|request| reply(&format!("Hi, {}!", name(&request)))));
+        Ok(Response::new(Box::pin(replies)))
+    }
+}
+
+/// Serve `Greeter` on a free local port
+pub async fn serve() -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("a free port");
+    let addr = listener.local_addr().expect("the bound address");
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(greeter_server::GreeterServer::new(Greeter))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+    );
+    addr
+}
+
+/// Call every RPC of `Greeter` served at `addr` and collect the replies
+pub async fn greet(addr: SocketAddr) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut client = GreeterClient::connect(format!("http://{}", addr)).await?;
+    let mut messages = Vec::new();
+
+    let hello = client.say_hello(request("Orc")).await?.into_inner();
+    messages.extend(hello.get().message().map(str::to_owned));
+
+    let mut hellos = client.say_many_hellos(request("Elf")).await?.into_inner();
+    while let Some(hello) = hellos.next().await {
+        messages.extend(hello?.get().message().map(str::to_owned));
+    }
+
+    let requests = vec![request("Orc"), request("Elf")];
+    let ack = client
+        .collect(tokio_stream::iter(requests))
+        .await?
+        .into_inner();
+    messages.push(format!("Collected {} greetings", ack.get().count()));
+
+    let requests = vec![request("Dwarf"), request("Hobbit")];
+    let mut chat = client
+        .chat(tokio_stream::iter(requests))
+        .await?
+        .into_inner();
+    while let Some(hi) = chat.next().await {
+        messages.extend(hi?.get().message().map(str::to_owned));
+    }
+
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod test {
+    use tonic::server::NamedService;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn greeter_round_trip() {
+        assert_eq!(
+            <greeter_server::GreeterServer<Greeter> as NamedService>::NAME,
+            "MyGame.HelloWorld.Greeter"
+        );
+        let addr = serve().await;
+        assert_eq!(
+            greet(addr).await.unwrap(),
+            [
+                "Hello, Orc!",
+                "Hello #1, Elf!",
+                "Hello #2, Elf!",
+                "Hello #3, Elf!",
+                "Collected 2 greetings",
+                "Hi, Dwarf!",
+                "Hi, Hobbit!",
+            ]
+        );
+    }
+}
@@ -0,0 +1,7 @@
+#[tokio::main]
+async fn main() {
+    let addr = greeter::serve().await;
+    for message in greeter::greet(addr).await.expect("greet") {
+        println!("{}", message);
+    }
+}
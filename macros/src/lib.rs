@@ -0,0 +1,168 @@
+//! Inline compilation of FlatBuffers schemas, the companion of [`flatc-rust`] for the crates
+//! which would rather not have a build script (e.g. small tools and examples).
+//!
+//! [`flatbuffers_schema!`] compiles the schema with `flatc` (found in `$PATH`, or in `FLATC`
+//! environment variable, if set) at macro-expansion time and expands to the generated Rust code:
+//!
+//! ```ignore
+//! mod monster {
+//!     flatc_rust_macros::flatbuffers_schema!("schemas/monster.fbs");
+//! }
+//!
+//! mod game {
+//!     flatc_rust_macros::flatbuffers_schema!("schemas/game.fbs", includes = ["schemas/include/"]);
+//! }
+//! ```
+//!
+//! The paths are relative to the root of the crate (`CARGO_MANIFEST_DIR`), and the crate is
+//! recompiled once the schema or any file it includes changes. The crate has to depend on
+//! `flatbuffers` like with the helpers generated by a build script.
+//!
+//! [`flatc-rust`]: https://docs.rs/flatc-rust
+//! [`flatbuffers_schema!`]: macro.flatbuffers_schema.html
+
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+extern crate proc_macro;
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use flatc_rust::{Args, Flatc};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{bracketed, Ident, LitStr, Token};
+
+/// The input of [`flatbuffers_schema!`]: the schema followed by the optional include directories
+struct Input {
+    schema: LitStr,
+    includes: Vec<LitStr>,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Input> {
+        let schema = input.parse()?;
+        let mut includes = Vec::new();
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: Ident = input.parse()?;
+            if key != "includes" {
+                return Err(syn::Error::new(key.span(), "expected `includes = [...]`"));
+            }
+            input.parse::<Token![=]>()?;
+            let paths;
+            bracketed!(paths in input);
+            includes = Punctuated::<LitStr, Token![,]>::parse_terminated(&paths)?
+                .into_iter()
+                .collect();
+            input.parse::<Option<Token![,]>>()?;
+        }
+        Ok(Input { schema, includes })
+    }
+}
+
+/// Compile the FlatBuffers schema with `flatc` and expand to the generated Rust code
+///
+/// The first argument is the path of the schema, the optional `includes = [...]` lists the
+/// include directories (both are relative to `CARGO_MANIFEST_DIR`). The `flatc` failures are
+/// reported as compilation errors.
+#[proc_macro]
+pub fn flatbuffers_schema(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    expand(input.into()).into()
+}
+
+fn expand(input: TokenStream) -> TokenStream {
+    let input: Input = match syn::parse2(input) {
+        Ok(input) => input,
+        Err(e) => return e.to_compile_error(),
+    };
+    match generate(&input) {
+        Ok(tokens) => tokens,
+        Err(e) => syn::Error::new(input.schema.span(), format!("flatc: {}", e)).to_compile_error(),
+    }
+}
+
+fn generate(input: &Input) -> flatc_rust::Result<TokenStream> {
+    let base_dir = env::var_os("CARGO_MANIFEST_DIR").map_or_else(PathBuf::new, PathBuf::from);
+    let schema = base_dir.join(input.schema.value());
+    let includes: Vec<PathBuf> = input
+        .includes
+        .iter()
+        .map(|include| base_dir.join(include.value()))
+        .collect();
+    let includes: Vec<&Path> = includes.iter().map(PathBuf::as_path).collect();
+
+    let flatc = Flatc::from_env_path();
+
+    // First check with have good `flatc`
+    flatc.check()?;
+
+    let temp_dir = env::temp_dir();
+    let args = Args {
+        inputs: &[&schema],
+        includes: &includes,
+        out_dir: &temp_dir,
+        quiet: true,
+        ..Default::default()
+    };
+    let source = flatc.generate_to_string(&schema, args)?;
+    let code: TokenStream = source.parse().map_err(|e| {
+        flatc_rust::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("the generated code does not parse: {:?}", e),
+        )
+    })?;
+
+    // Make Cargo recompile the crate once the schemas change
+    let dependencies: Vec<String> = flatc
+        .depfile(args)?
+        .dependencies()
+        .iter()
+        .map(|dependency| dependency.to_string_lossy().into_owned())
+        .collect();
+    Ok(quote! {
+        #(const _: &[u8] = include_bytes!(#dependencies);)*
+        #code
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn expand_splices_generated_code() -> flatc_rust::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        fs::create_dir(temp_dir.path().join("include"))?;
+        fs::write(
+            temp_dir.path().join("include").join("common.fbs"),
+            "table Common { id: int; }",
+        )?;
+        let schema = temp_dir.path().join("monster.fbs");
+        fs::write(
+            &schema,
+            "include \"common.fbs\";\ntable Monster { common: Common; } root_type Monster;",
+        )?;
+        let schema = schema.to_str().expect("UTF-8 path");
+        let include = temp_dir.path().join("include");
+        let include = include.to_str().expect("UTF-8 path");
+
+        let expanded = expand(quote!(#schema, includes = [#include])).to_string();
+        assert!(expanded.contains("pub struct Monster"));
+        assert!(expanded.contains("common.fbs"));
+        assert!(!expanded.contains("compile_error"));
+
+        let expanded = expand(quote!(#schema)).to_string();
+        assert!(expanded.contains("compile_error"));
+        assert!(expanded.contains("flatc: "));
+
+        let expanded = expand(quote!(#schema, excludes = [])).to_string();
+        assert!(expanded.contains("expected `includes = [...]`"));
+
+        Ok(())
+    }
+}
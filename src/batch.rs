@@ -0,0 +1,63 @@
+//! Aggregated failures of the per-file `flatc` invocations.
+
+use std::error;
+use std::fmt;
+use std::path::PathBuf;
+
+/// An input which `flatc` failed to compile in a per-file run
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputFailure {
+    /// The input file
+    pub input: PathBuf,
+    /// The diagnostics printed by `flatc`
+    pub diagnostics: String,
+}
+
+/// The error listing every input which failed to compile with [`Invocation::PerFile`], so all
+/// the broken schemas are reported by a single run
+///
+/// It is returned wrapped into [`Error`] of `Other` kind:
+///
+/// ```no_run
+/// # let args = flatc_rust::Args::default();
+/// if let Err(e) = flatc_rust::run(args) {
+///     if let Some(e) = e
+///         .get_ref()
+///         .and_then(|e| e.downcast_ref::<flatc_rust::BatchError>())
+///     {
+///         for failure in &e.failures {
+///             eprintln!("{}:\n{}", failure.input.display(), failure.diagnostics);
+///         }
+///     }
+/// }
+/// ```
+///
+/// [`Invocation::PerFile`]: enum.Invocation.html#variant.PerFile
+/// [`Error`]: type.Error.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchError {
+    /// The failures in the order of the inputs
+    pub failures: Vec<InputFailure>,
+    /// The number of inputs in the run
+    pub input_count: usize,
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "flatc failed to compile {} of {} input(s):",
+            self.failures.len(),
+            self.input_count
+        )?;
+        for failure in &self.failures {
+            write!(f, "\n  - {}", failure.input.display())?;
+            for line in failure.diagnostics.lines() {
+                write!(f, "\n      {}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for BatchError {}
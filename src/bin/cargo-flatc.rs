@@ -0,0 +1,280 @@
+//! `cargo flatc` subcommand (requires `cli` feature).
+//!
+//! Runs the codegen described in `flatc-rust.toml` (or, if there is no config file, in the
+//! `[package.metadata.flatc-rust]` sections of the workspace members) without a full
+//! `cargo build`.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use flatc_rust::config::{Config, CONFIG_FILE_NAME};
+use flatc_rust::format;
+use flatc_rust::workspace::{self, WorkspaceConfig};
+use flatc_rust::{Args, Error, Flatc, Result};
+
+const USAGE: &str = "\
+Run FlatBuffers codegen described in flatc-rust.toml or Cargo metadata
+
+Usage:
+    cargo flatc [build] [OPTIONS]
+    cargo flatc check [OPTIONS]
+    cargo flatc sync [OPTIONS]
+    cargo flatc fmt [--check] [--manifest-path PATH] [FILE...]
+    cargo flatc convert --schema SCHEMA (--to-binary | --to-json) [--allow-non-utf8]
+                        [--unknown-json] -o OUT_DIR FILE...
+
+Commands:
+    build      Generate the helpers (default)
+    check      Verify that the generated helpers are up to date
+    sync       Update the generated helpers in place, rewriting the changed files only
+    fmt        Format the schemas (the workspace ones if no FILE is given)
+    convert    Convert data files between JSON and binary
+
+Options:
+    --config PATH           Config file [default: flatc-rust.toml]
+    --manifest-path PATH    Workspace manifest used when there is no config file
+    --out-dir PATH          Output root used when there is no config file
+                            [default: target/flatbuffers]
+    --flatc PATH            flatc executable [default: flatc from $PATH]
+    --allow-non-utf8        Pass non-UTF-8 strings through on conversion
+    --unknown-json          Skip the JSON fields missing from the schema on conversion
+    --check                 List the unformatted schemas instead of formatting them
+    -h, --help              Print this help
+";
+
+fn err_other<E>(error: E) -> Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    Error::other(error)
+}
+
+#[derive(Default)]
+struct Options {
+    command: String,
+    config: Option<PathBuf>,
+    manifest_path: Option<PathBuf>,
+    out_dir: Option<PathBuf>,
+    flatc: Option<PathBuf>,
+    schema: Option<PathBuf>,
+    to_binary: bool,
+    to_json: bool,
+    allow_non_utf8: bool,
+    unknown_json: bool,
+    check: bool,
+    files: Vec<PathBuf>,
+}
+
+fn parse_options<I: Iterator<Item = String>>(mut args: I) -> Result<Options> {
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .map(PathBuf::from)
+                .ok_or_else(|| err_other(format!("{} requires a value", name)))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => options.command = "help".to_owned(),
+            "--config" => options.config = Some(value(&arg)?),
+            "--manifest-path" => options.manifest_path = Some(value(&arg)?),
+            "--out-dir" | "-o" => options.out_dir = Some(value(&arg)?),
+            "--flatc" => options.flatc = Some(value(&arg)?),
+            "--schema" => options.schema = Some(value(&arg)?),
+            "--to-binary" => options.to_binary = true,
+            "--to-json" => options.to_json = true,
+            "--allow-non-utf8" => options.allow_non_utf8 = true,
+            "--unknown-json" => options.unknown_json = true,
+            "--check" => options.check = true,
+            "build" | "check" | "sync" | "fmt" | "convert" if options.command.is_empty() => {
+                options.command = arg;
+            }
+            _ if arg.starts_with('-') => {
+                return Err(err_other(format!("unknown option `{}`", arg)));
+            }
+            _ if options.command == "convert" || options.command == "fmt" => {
+                options.files.push(PathBuf::from(arg))
+            }
+            _ => return Err(err_other(format!("unknown command `{}`", arg))),
+        }
+    }
+    if options.command.is_empty() {
+        options.command = "build".to_owned();
+    }
+    Ok(options)
+}
+
+fn main() {
+    // `cargo flatc ...` runs `cargo-flatc flatc ...`
+    let args = env::args()
+        .skip(1)
+        .skip_while(|arg| arg == "flatc")
+        .collect::<Vec<_>>();
+
+    let exit_code = match parse_options(args.into_iter()).and_then(|options| run(&options)) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            2
+        }
+    };
+    process::exit(exit_code);
+}
+
+/// Run the command, returns `false` if the check has failed
+fn run(options: &Options) -> Result<bool> {
+    if options.command == "help" {
+        print!("{}", USAGE);
+        return Ok(true);
+    }
+    if options.command == "fmt" {
+        return format(options);
+    }
+
+    let flatc = match options.flatc {
+        Some(ref path) => Flatc::from_path(path),
+        None => Flatc::from_env_path(),
+    };
+    flatc.check()?;
+
+    match options.command.as_str() {
+        "convert" => convert(&flatc, options).map(|()| true),
+        command => {
+            let config_path = options
+                .config
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(CONFIG_FILE_NAME));
+            let files = if options.config.is_some() || config_path.exists() {
+                let config = Config::from_path(&config_path)?;
+                match command {
+                    "check" => config.outdated_files(&flatc)?,
+                    "sync" => config.sync(&flatc)?,
+                    _ => return config.run(&flatc).map(|()| true),
+                }
+            } else {
+                run_workspace(&flatc, options, command)?
+            };
+            if command == "sync" {
+                for updated_file in &files {
+                    eprintln!("updated: {}", updated_file.display());
+                }
+                return Ok(true);
+            }
+            for outdated_file in &files {
+                eprintln!("outdated: {}", outdated_file.display());
+            }
+            Ok(files.is_empty())
+        }
+    }
+}
+
+/// Run the command for the workspace members, returns the outdated (or updated) files
+fn run_workspace(flatc: &Flatc, options: &Options, command: &str) -> Result<Vec<PathBuf>> {
+    let out_dir = options
+        .out_dir
+        .clone()
+        .unwrap_or_else(|| Path::new("target").join("flatbuffers"));
+    let config = WorkspaceConfig {
+        manifest_path: options.manifest_path.as_deref(),
+        out_dir: &out_dir,
+        ..Default::default()
+    };
+    if command == "build" {
+        workspace::compile_workspace(config)?;
+        return Ok(Vec::new());
+    }
+
+    let schemas = workspace::discover_schemas(config.manifest_path)?;
+    let includes = schemas.includes();
+    let mut files = Vec::new();
+    for member in &schemas.members {
+        let inputs: Vec<&Path> = member.inputs.iter().map(PathBuf::as_path).collect();
+        let out_dir = config.member_out_dir(&member.package);
+        let args = Args {
+            lang: config.lang,
+            inputs: &inputs,
+            out_dir: &out_dir,
+            includes: &includes,
+            ..Default::default()
+        };
+        if command == "sync" {
+            files.extend(flatc.sync(args)?);
+        } else {
+            files.extend(flatc.outdated_files(args)?);
+        }
+    }
+    Ok(files)
+}
+
+/// Format the schemas, returns `false` if some are not formatted in the check mode
+fn format(options: &Options) -> Result<bool> {
+    let files = if options.files.is_empty() {
+        let schemas = workspace::discover_schemas(options.manifest_path.as_deref())?;
+        schemas
+            .members
+            .into_iter()
+            .flat_map(|member| member.inputs)
+            .filter(|input| {
+                input
+                    .extension()
+                    .is_some_and(|extension| extension == "fbs")
+            })
+            .collect()
+    } else {
+        options.files.clone()
+    };
+    let unformatted_files = format::format_files(&files, options.check)?;
+    let status = if options.check {
+        "unformatted"
+    } else {
+        "formatted"
+    };
+    for file in &unformatted_files {
+        eprintln!("{}: {}", status, file.display());
+    }
+    Ok(!options.check || unformatted_files.is_empty())
+}
+
+fn convert(flatc: &Flatc, options: &Options) -> Result<()> {
+    let schema = options
+        .schema
+        .as_deref()
+        .ok_or_else(|| err_other("convert requires --schema"))?;
+    let out_dir = options
+        .out_dir
+        .as_deref()
+        .ok_or_else(|| err_other("convert requires -o OUT_DIR"))?;
+    if options.files.is_empty() {
+        return Err(err_other("convert requires at least one FILE"));
+    }
+
+    let mut inputs = vec![schema];
+    match (options.to_binary, options.to_json) {
+        (true, false) => {
+            inputs.extend(options.files.iter().map(PathBuf::as_path));
+            flatc.run(Args {
+                lang: "binary",
+                inputs: &inputs,
+                out_dir,
+                allow_non_utf8: options.allow_non_utf8,
+                unknown_json: options.unknown_json,
+                ..Default::default()
+            })
+        }
+        (false, true) => {
+            // Binary files have to follow `--`
+            inputs.push(Path::new("--"));
+            inputs.extend(options.files.iter().map(PathBuf::as_path));
+            flatc.run(Args {
+                inputs: &inputs,
+                out_dir,
+                allow_non_utf8: options.allow_non_utf8,
+                ..Args::json_conversion()
+            })
+        }
+        _ => Err(err_other(
+            "convert requires either --to-binary or --to-json",
+        )),
+    }
+}
@@ -0,0 +1,275 @@
+//! Helpers for Cargo build scripts (`build.rs`).
+//!
+//! Cargo passes the build configuration to build scripts through environment variables, see
+//! [the Cargo reference](https://doc.rust-lang.org/cargo/reference/environment-variables.html#environment-variables-cargo-sets-for-build-scripts).
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use crate::Result;
+
+/// The conventional name of the environment variable pointing to the generated helpers (see
+/// [`Args::rustc_env`])
+///
+/// [`Args::rustc_env`]: ../struct.Args.html#structfield.rustc_env
+pub const DEFAULT_OUT_DIR_ENV: &str = "FLATC_OUT_DIR";
+
+/// Set the environment variable for the compilation of the crate (`cargo:rustc-env=NAME=VALUE`),
+/// so it can be read with `env!("NAME")`
+pub fn rustc_env<V: AsRef<OsStr> + ?Sized>(name: &str, value: &V) {
+    println!(
+        "cargo:rustc-env={}={}",
+        name,
+        value.as_ref().to_string_lossy()
+    );
+}
+
+/// Rerun the build script if the file changes (`cargo:rerun-if-changed=PATH`)
+pub fn rerun_if_changed<P: AsRef<Path> + ?Sized>(path: &P) {
+    println!("cargo:rerun-if-changed={}", path.as_ref().display());
+}
+
+/// Show the warning in the output of Cargo (`cargo:warning=MESSAGE`)
+pub fn warning(message: &str) {
+    for line in message.lines() {
+        println!("cargo:warning={}", line);
+    }
+}
+
+/// The target triple of the host, i.e. the triple `flatc` has to be built for (`HOST`)
+pub fn host() -> Option<String> {
+    env::var("HOST").ok()
+}
+
+/// The target triple the crate is being compiled for (`TARGET`)
+pub fn target() -> Option<String> {
+    env::var("TARGET").ok()
+}
+
+/// The number of jobs Cargo runs at once (`NUM_JOBS`, i.e. `-j` or the number of CPUs)
+pub fn num_jobs() -> Option<usize> {
+    env::var("NUM_JOBS").ok()?.parse().ok()
+}
+
+/// Check whether the build script runs for a cross-compilation (`TARGET` differs from `HOST`)
+pub fn is_cross_compiling() -> bool {
+    match (host(), target()) {
+        (Some(host), Some(target)) => host != target,
+        _ => false,
+    }
+}
+
+/// The per-target output directory: `base/<TARGET>` when cross-compiling, `base` otherwise
+///
+/// Use it for the output directories shared between the targets (e.g. `target/flatbuffers/`),
+/// so the helpers generated for different targets do not clobber each other. `OUT_DIR` is
+/// already unique per target.
+pub fn per_target_out_dir(base: &Path) -> PathBuf {
+    match target() {
+        Some(target) if is_cross_compiling() => base.join(target),
+        _ => base.to_owned(),
+    }
+}
+
+/// The subdirectory of `OUT_DIR` the package-namespaced helpers go to (see [`package_out_dir`])
+///
+/// [`package_out_dir`]: fn.package_out_dir.html
+pub const PACKAGE_OUT_DIR_PREFIX: &str = "flatbuffers";
+
+/// The package-namespaced output directory: `OUT_DIR/flatbuffers/<CARGO_PKG_NAME>`
+///
+/// The helpers of different packages never collide there, even if the directory ends up
+/// shared (e.g. published with [`publish_schemas`], or copied around by other tools).
+///
+/// ```no_run
+/// // build.rs
+/// use std::path::Path;
+///
+/// let out_dir = flatc_rust::build_script::package_out_dir().expect("OUT_DIR");
+/// flatc_rust::run(flatc_rust::Args {
+///     inputs: &[Path::new("schemas/message.fbs")],
+///     out_dir: &out_dir,
+///     ..Default::default()
+/// })
+/// .expect("flatc");
+/// ```
+///
+/// [`publish_schemas`]: fn.publish_schemas.html
+pub fn package_out_dir() -> Result<PathBuf> {
+    let var = |name: &str| {
+        env::var_os(name).ok_or_else(|| crate::err_other(format!("{} is not set", name)))
+    };
+    Ok(Path::new(&var("OUT_DIR")?)
+        .join(PACKAGE_OUT_DIR_PREFIX)
+        .join(var("CARGO_PKG_NAME")?))
+}
+
+/// The name of the codegen cache directory inside the target directory (see
+/// [`shared_cache_dir`])
+///
+/// [`shared_cache_dir`]: fn.shared_cache_dir.html
+pub const CACHE_DIR_NAME: &str = "flatc-cache";
+
+/// The codegen cache directory shared by the build scripts of the workspace (see
+/// [`Args::cache_dir`]): `target/flatc-cache` (`target/<TARGET>/flatc-cache` when building for
+/// an explicit target), found from `OUT_DIR` (`target/<PROFILE>/build/<PACKAGE>-<HASH>/out`)
+///
+/// [`Args::cache_dir`]: ../struct.Args.html#structfield.cache_dir
+pub fn shared_cache_dir() -> Result<PathBuf> {
+    let out_dir = env::var_os("OUT_DIR").ok_or_else(|| crate::err_other("OUT_DIR is not set"))?;
+    let out_dir = Path::new(&out_dir);
+    out_dir
+        .ancestors()
+        .find(|dir| dir.file_name().is_some_and(|name| name == "build"))
+        .and_then(Path::parent)
+        .and_then(Path::parent)
+        .map(|target_dir| target_dir.join(CACHE_DIR_NAME))
+        .ok_or_else(|| {
+            crate::err_other(format!(
+                "OUT_DIR {} is not inside a Cargo target directory",
+                out_dir.display()
+            ))
+        })
+}
+
+/// The metadata key of the published schema directory (`DEP_<LINKS>_SCHEMA_DIR`)
+pub const SCHEMA_DIR_KEY: &str = "schema_dir";
+
+/// The metadata key of the published generated helpers directory (`DEP_<LINKS>_GENERATED_DIR`)
+pub const GENERATED_DIR_KEY: &str = "generated_dir";
+
+/// Publish the metadata to the build scripts of the dependent crates (`cargo:KEY=VALUE`)
+///
+/// It requires the `links` key in the `[package]` section of `Cargo.toml`; the build scripts of
+/// the crates directly depending on the package get the value as `DEP_<LINKS>_<KEY>`
+/// environment variable.
+pub fn metadata<V: AsRef<OsStr> + ?Sized>(key: &str, value: &V) {
+    println!("cargo:{}={}", key, value.as_ref().to_string_lossy());
+}
+
+/// Publish the schema directory (and the generated helpers directory) of the package, so the
+/// dependent crates can locate the `.fbs` files (e.g. include them in their own schemas)
+///
+/// # Examples
+///
+/// With `links = "messages"` in `Cargo.toml` of the schema-owning crate:
+///
+/// ```no_run
+/// // build.rs of the `messages` crate
+/// use std::path::Path;
+///
+/// let out_dir = std::env::var("OUT_DIR").unwrap();
+/// flatc_rust::run(flatc_rust::Args {
+///     inputs: &[Path::new("schemas/message.fbs")],
+///     out_dir: Path::new(&out_dir),
+///     ..Default::default()
+/// })
+/// .expect("flatc");
+/// flatc_rust::build_script::publish_schemas(Path::new("schemas"), Some(Path::new(&out_dir)))
+///     .expect("publish schemas");
+/// ```
+///
+/// The build scripts of the dependent crates get `DEP_MESSAGES_SCHEMA_DIR` and
+/// `DEP_MESSAGES_GENERATED_DIR` then.
+pub fn publish_schemas(schema_dir: &Path, generated_dir: Option<&Path>) -> Result<()> {
+    metadata(SCHEMA_DIR_KEY, &schema_dir.canonicalize()?);
+    if let Some(generated_dir) = generated_dir {
+        metadata(GENERATED_DIR_KEY, &generated_dir.canonicalize()?);
+    }
+    Ok(())
+}
+
+/// The environment variable of the metadata published by the dependency with the `links` key
+/// (`DEP_<LINKS>_<KEY>`)
+fn dependency_metadata_env(links: &str, key: &str) -> String {
+    format!("DEP_{}_{}", links, key)
+        .to_ascii_uppercase()
+        .replace('-', "_")
+}
+
+/// The schema directory published by the dependency with the `links` key (see
+/// [`publish_schemas`]), i.e. the include path (`Args::includes`) for the schemas of the
+/// dependent crates
+///
+/// [`publish_schemas`]: fn.publish_schemas.html
+pub fn dependency_schema_dir(links: &str) -> Option<PathBuf> {
+    env::var_os(dependency_metadata_env(links, SCHEMA_DIR_KEY)).map(PathBuf::from)
+}
+
+/// The schema directories published by all the dependencies (every `DEP_<LINKS>_SCHEMA_DIR`,
+/// see [`publish_schemas`]) in the order of the variable names, i.e. the include paths for the
+/// schemas sharing the types of the dependencies (see [`Args::dependency_schemas`])
+///
+/// [`publish_schemas`]: fn.publish_schemas.html
+/// [`Args::dependency_schemas`]: ../struct.Args.html#structfield.dependency_schemas
+pub fn dependency_schema_dirs() -> Vec<PathBuf> {
+    dependency_schema_dirs_in(env::vars_os())
+}
+
+/// [`dependency_schema_dirs`] published in the environment variables `vars`
+///
+/// [`dependency_schema_dirs`]: fn.dependency_schema_dirs.html
+pub(crate) fn dependency_schema_dirs_in<I>(vars: I) -> Vec<PathBuf>
+where
+    I: IntoIterator<Item = (OsString, OsString)>,
+{
+    let suffix = format!("_{}", SCHEMA_DIR_KEY.to_ascii_uppercase());
+    let mut schema_dirs: Vec<(String, PathBuf)> = vars
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let name = name.into_string().ok()?;
+            let links = name.strip_prefix("DEP_")?.strip_suffix(&suffix)?;
+            if links.is_empty() {
+                return None;
+            }
+            Some((name, PathBuf::from(value)))
+        })
+        .collect();
+    schema_dirs.sort();
+    schema_dirs.into_iter().map(|(_, dir)| dir).collect()
+}
+
+/// The generated helpers directory published by the dependency with the `links` key (see
+/// [`publish_schemas`])
+///
+/// [`publish_schemas`]: fn.publish_schemas.html
+pub fn dependency_generated_dir(links: &str) -> Option<PathBuf> {
+    env::var_os(dependency_metadata_env(links, GENERATED_DIR_KEY)).map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dependency_metadata_env_follows_cargo_naming() {
+        assert_eq!(
+            dependency_metadata_env("shared-messages", SCHEMA_DIR_KEY),
+            "DEP_SHARED_MESSAGES_SCHEMA_DIR"
+        );
+        assert_eq!(
+            dependency_metadata_env("messages", GENERATED_DIR_KEY),
+            "DEP_MESSAGES_GENERATED_DIR"
+        );
+    }
+
+    #[test]
+    fn dependency_schema_dirs_are_found_in_vars() {
+        let vars = [
+            ("DEP_SHARED_SCHEMA_DIR", "shared/schemas"),
+            ("DEP_MESSAGES_GENERATED_DIR", "messages/generated"),
+            ("DEP__SCHEMA_DIR", "nothing"),
+            ("DEP_MESSAGES_SCHEMA_DIR", "messages/schemas"),
+            ("PATH", "/usr/bin"),
+        ]
+        .map(|(name, value)| (OsString::from(name), OsString::from(value)));
+        assert_eq!(
+            dependency_schema_dirs_in(vars),
+            vec![
+                PathBuf::from("messages/schemas"),
+                PathBuf::from("shared/schemas")
+            ]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Bundling of a schema and its include closure into a single self-contained `.fbs`.
+//!
+//! It is useful to publish a single artifact to the consumers and the registries which do not
+//! support multi-file schemas:
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! let bundle = flatc_rust::bundle::Bundle::from_schema(
+//!     Path::new("schemas/monster.fbs"),
+//!     &[Path::new("schemas/include/")],
+//! )
+//! .expect("bundle");
+//! bundle.write("target/monster.bundle.fbs").expect("write");
+//! ```
+//!
+//! The includes are resolved like `flatc` does: in the include directories first, then in the
+//! directory of the schema. Every file is bundled once (after the files it includes), with its
+//! `include` statements removed and its namespace reset, so the declarations keep their
+//! namespaces. The included files lose their `root_type`, `file_identifier` and
+//! `file_extension` (which `flatc` ignores anyway), the repeated `attribute` declarations are
+//! dropped, and so are the repeated type declarations if they are identical (conflicting ones are
+//! reported).
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::schema::{parse_statements, StatementKind};
+use crate::{err_other, Result};
+
+/// A schema bundled with its include closure
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bundle {
+    /// The bundled schema source
+    pub source: String,
+    /// The bundled files in the order of bundling (the schema itself is the last one)
+    pub files: Vec<PathBuf>,
+}
+
+impl Bundle {
+    /// Bundle the schema with the files it includes (transitively)
+    pub fn from_schema(schema: &Path, includes: &[&Path]) -> Result<Bundle> {
+        let mut search_dirs: Vec<PathBuf> = includes.iter().map(|dir| dir.to_path_buf()).collect();
+        search_dirs.push(schema.parent().map_or_else(PathBuf::new, Path::to_path_buf));
+        let mut bundler = Bundler {
+            search_dirs,
+            visited: HashSet::new(),
+            attributes: HashSet::new(),
+            declarations: HashMap::new(),
+            chunks: Vec::new(),
+            files: Vec::new(),
+        };
+        bundler.add(schema, true)?;
+
+        let mut source = format!(
+            "// Bundled by flatc-rust from {} and its includes\n",
+            schema
+                .file_name()
+                .unwrap_or(schema.as_os_str())
+                .to_string_lossy()
+        );
+        for (index, chunk) in bundler.chunks.iter().enumerate() {
+            source.push('\n');
+            if index > 0 {
+                source.push_str("namespace;\n");
+            }
+            source.push_str(chunk.trim_end());
+            source.push('\n');
+        }
+        Ok(Bundle {
+            source,
+            files: bundler.files,
+        })
+    }
+
+    /// Write the bundled schema into the file
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, &self.source)
+    }
+}
+
+struct Bundler {
+    search_dirs: Vec<PathBuf>,
+    /// The canonical paths of the files added so far (to bundle them once regardless of the
+    /// path form)
+    visited: HashSet<PathBuf>,
+    attributes: HashSet<String>,
+    /// The whitespace-normalized source of the declarations by the qualified names, with the
+    /// files declaring them
+    declarations: HashMap<String, (String, PathBuf)>,
+    chunks: Vec<String>,
+    files: Vec<PathBuf>,
+}
+
+impl Bundler {
+    fn add(&mut self, path: &Path, is_root: bool) -> Result<()> {
+        if !self.visited.insert(fs::canonicalize(path)?) {
+            return Ok(());
+        }
+        let source = fs::read_to_string(path)?;
+        let (_, statements) = parse_statements(&source)
+            .map_err(|e| err_other(format!("{}: {}", path.display(), e)))?;
+
+        for statement in &statements {
+            if let StatementKind::Include(include) = &statement.kind {
+                let included = self.resolve(include).ok_or_else(|| {
+                    err_other(format!(
+                        "{}: unable to locate include file {} (looked up in {})",
+                        path.display(),
+                        include,
+                        self.search_dirs
+                            .iter()
+                            .map(|dir| dir.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                })?;
+                self.add(&included, false)?;
+            }
+        }
+
+        let mut chunk = format!("// {}\n", path.display());
+        let mut copied = 0;
+        for statement in statements {
+            let text = &source[statement.span.clone()];
+            let keep = match &statement.kind {
+                StatementKind::Include(_) => false,
+                StatementKind::RootDeclaration => is_root,
+                StatementKind::Attribute(attribute) => self.attributes.insert(attribute.clone()),
+                StatementKind::Declaration(name) => {
+                    let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    match self.declarations.get(name) {
+                        Some((existing, _)) if *existing == normalized => false,
+                        Some((_, declared_in)) => {
+                            return Err(err_other(format!(
+                                "{}: conflicting declaration of {} (already declared in {})",
+                                path.display(),
+                                name,
+                                declared_in.display()
+                            )))
+                        }
+                        None => {
+                            self.declarations
+                                .insert(name.clone(), (normalized, path.to_path_buf()));
+                            true
+                        }
+                    }
+                }
+                StatementKind::Other => true,
+            };
+            if !keep {
+                chunk.push_str(&source[copied..statement.span.start]);
+                copied = statement.span.end;
+            }
+        }
+        chunk.push_str(&source[copied..]);
+        self.chunks.push(chunk);
+        self.files.push(path.to_path_buf());
+        Ok(())
+    }
+
+    fn resolve(&self, include: &str) -> Option<PathBuf> {
+        self.search_dirs
+            .iter()
+            .map(|dir| dir.join(include))
+            .find(|path| path.is_file())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::diff::SchemaDiff;
+    use crate::Flatc;
+
+    #[test]
+    fn bundle_is_equivalent_to_included_schemas() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let include_dir = temp_dir.path().join("include");
+        fs::create_dir_all(&include_dir)?;
+        fs::write(
+            include_dir.join("common.fbs"),
+            "namespace Common;\nenum Color : ubyte { Red, Green }\ntable Tag { name: string; }\n\
+             root_type Tag;\n",
+        )?;
+        fs::write(
+            include_dir.join("weapon.fbs"),
+            "include \"common.fbs\";\nattribute \"priority\";\nnamespace Game;\n\
+             table Weapon (priority: 1) { color: Common.Color; }\n",
+        )?;
+        let schema_path = temp_dir.path().join("monster.fbs");
+        fs::write(
+            &schema_path,
+            "include \"weapon.fbs\";\ninclude \"./common.fbs\";\nattribute \"priority\";\n\
+             table Monster { weapon: Game.Weapon; color: Common.Color = Green; }\n\
+             root_type Monster;\nfile_identifier \"MONS\";\n",
+        )?;
+
+        let bundle = Bundle::from_schema(&schema_path, &[&include_dir])?;
+        assert_eq!(
+            bundle.files,
+            vec![
+                include_dir.join("common.fbs"),
+                include_dir.join("weapon.fbs"),
+                schema_path.clone(),
+            ]
+        );
+        assert!(!bundle.source.contains("include \""));
+        assert_eq!(bundle.source.matches("attribute \"priority\";").count(), 1);
+        assert_eq!(bundle.source.matches("root_type").count(), 1);
+
+        let bundle_path = temp_dir.path().join("bundle").join("monster.fbs");
+        fs::create_dir_all(bundle_path.parent().expect("bundle dir"))?;
+        bundle.write(&bundle_path)?;
+        let flatc = Flatc::from_env_path();
+        let diff = SchemaDiff::between(
+            &flatc.binary_schema(&schema_path, &[&include_dir])?,
+            &flatc.binary_schema(&bundle_path, &[])?,
+        );
+        assert!(diff.is_empty(), "{}", diff);
+
+        fs::write(
+            temp_dir.path().join("conflicting.fbs"),
+            "include \"weapon.fbs\";\nnamespace Game;\ntable Weapon { damage: int; }\n",
+        )?;
+        let error = Bundle::from_schema(&temp_dir.path().join("conflicting.fbs"), &[&include_dir])
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("conflicting declaration of Game.Weapon"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,495 @@
+//! The codegen cache shared by the runs (see [`Args::cache_dir`]).
+//!
+//! Every entry is a directory named after the hash of the `flatc` version, the `flatc` arguments
+//! (with the absolute paths, but without the output directory) and the content of the inputs with
+//! the schemas they include, holding the files `flatc` has generated (before any processing of
+//! the args, e.g. [`Args::rename`]). An entry is populated at most once: the runs with the same
+//! key wait for the one compiling the schemas, and then copy its files.
+//!
+//! A [`CacheBackend`] (e.g. [`HttpCache`]) set with [`Flatc::with_cache_backend`] shares the
+//! entries between the machines (e.g. the CI runners): the entries missing locally are fetched
+//! from it before running `flatc`, and the new ones are uploaded to it as bundles of the
+//! generated files. The failures of the backend are logged as warnings, and the run goes on
+//! without it.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use flatc_rust::cache::HttpCache;
+//!
+//! # fn try_main() -> flatc_rust::Result<()> {
+//! let mut cache = HttpCache::new("https://cache.example.com/flatc");
+//! cache.headers = vec!["Authorization: Bearer <token>".to_owned()];
+//! flatc_rust::Flatc::from_env_path()
+//!     .with_cache_backend(cache)
+//!     .run(flatc_rust::Args {
+//!         inputs: &[Path::new("schemas/monster.fbs")],
+//!         out_dir: Path::new("target/flatbuffers/"),
+//!         cache_dir: Some(Path::new("target/flatc-cache/")),
+//!         ..Default::default()
+//!     })?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Args::cache_dir`]: ../struct.Args.html#structfield.cache_dir
+//! [`Args::rename`]: ../struct.Args.html#structfield.rename
+//! [`CacheBackend`]: trait.CacheBackend.html
+//! [`HttpCache`]: struct.HttpCache.html
+//! [`Flatc::with_cache_backend`]: ../struct.Flatc.html#method.with_cache_backend
+
+use std::collections::BTreeSet;
+use std::convert::{TryFrom, TryInto};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{self, Component, Path, PathBuf};
+use std::process;
+
+use sha2::{Digest, Sha256};
+
+use crate::lock::DirLock;
+use crate::report::sha256_hex;
+use crate::temp::TempDir;
+use crate::{err_other, schema, Args, Error, Result};
+
+/// The storage of the cache entries shared between the machines, e.g. an HTTP or S3-style
+/// object store
+///
+/// A bundle is an opaque blob, which has to be returned as it was stored.
+pub trait CacheBackend: Send + Sync {
+    /// The bundle stored under the key, or `None` if there is none
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Store the bundle under the key
+    fn put(&self, key: &str, bundle: &[u8]) -> Result<()>;
+}
+
+/// The backend storing the bundles at `<base_url>/<key>` over HTTP(S) with `curl` (`GET` and
+/// `PUT`, a `404` is a miss), e.g. a generic cache server or a presigned object store endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpCache {
+    /// The URL the keys are appended to
+    pub base_url: String,
+    /// The headers sent with every request, e.g. `Authorization: Bearer <token>`
+    pub headers: Vec<String>,
+}
+
+impl HttpCache {
+    /// The backend without extra headers
+    pub fn new<U: Into<String>>(base_url: U) -> HttpCache {
+        HttpCache {
+            base_url: base_url.into(),
+            headers: Vec::new(),
+        }
+    }
+
+    fn curl(&self, key: &str) -> process::Command {
+        let mut cmd = process::Command::new("curl");
+        cmd.args(["--silent", "--show-error", "--location"]);
+        for header in &self.headers {
+            cmd.arg("--header").arg(header);
+        }
+        cmd.arg(format!("{}/{}", self.base_url.trim_end_matches('/'), key));
+        cmd
+    }
+}
+
+impl CacheBackend for HttpCache {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let temp_dir = TempDir::new()?;
+        let bundle_path = temp_dir.path().join(key);
+        let output = self
+            .curl(key)
+            .arg("--output")
+            .arg(&bundle_path)
+            .args(["--write-out", "%{http_code}"])
+            .stdin(process::Stdio::null())
+            .stderr(process::Stdio::inherit())
+            .output()?;
+        let code = String::from_utf8_lossy(&output.stdout);
+        match code.trim() {
+            _ if !output.status.success() => Err(err_other(format!(
+                "failed to get {} from {} ({})",
+                key, self.base_url, output.status
+            ))),
+            "200" => Ok(Some(fs::read(&bundle_path)?)),
+            "404" => Ok(None),
+            code => Err(err_other(format!(
+                "failed to get {} from {} (HTTP {})",
+                key, self.base_url, code
+            ))),
+        }
+    }
+
+    fn put(&self, key: &str, bundle: &[u8]) -> Result<()> {
+        let mut child = self
+            .curl(key)
+            .args(["--fail", "--upload-file", "-"])
+            .stdin(process::Stdio::piped())
+            .stdout(process::Stdio::null())
+            .spawn()?;
+        child.stdin.take().expect("piped stdin").write_all(bundle)?;
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(err_other(format!(
+                "failed to put {} to {} ({})",
+                key, self.base_url, status
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The locked cache entry, which is released on drop
+pub(crate) struct CacheEntry {
+    cache_dir: PathBuf,
+    key: String,
+    dir: PathBuf,
+    _lock: DirLock,
+}
+
+impl CacheEntry {
+    /// Block until the entry of the key is not populated by another run
+    pub(crate) fn lock(cache_dir: &Path, key: &str) -> Result<CacheEntry> {
+        let dir = cache_dir.join(key);
+        let lock = DirLock::acquire(&dir)?;
+        Ok(CacheEntry {
+            cache_dir: cache_dir.to_owned(),
+            key: key.to_owned(),
+            dir,
+            _lock: lock,
+        })
+    }
+
+    pub(crate) fn is_populated(&self) -> bool {
+        self.dir.is_dir()
+    }
+
+    /// Copy the cached files into `out_dir`
+    pub(crate) fn restore(&self, out_dir: &Path) -> Result<()> {
+        copy_files(&self.dir, &cached_files(&self.dir)?, out_dir)
+    }
+
+    /// Populate the entry from the backend, if it has the bundle; return whether it has
+    pub(crate) fn fetch(&self, backend: &dyn CacheBackend) -> bool {
+        let result = backend.get(&self.key).and_then(|bundle| match bundle {
+            Some(bundle) => self.populate(|dir| unpack(&bundle, dir)).map(|()| true),
+            None => Ok(false),
+        });
+        result.unwrap_or_else(|e| {
+            log::warn!("failed to fetch the cache entry {}: {}", self.key, e);
+            false
+        })
+    }
+
+    /// Populate the entry with the files generated into `out_dir` (and upload them to the
+    /// backend)
+    pub(crate) fn store(
+        &self,
+        out_dir: &Path,
+        generated_files: &[PathBuf],
+        backend: Option<&dyn CacheBackend>,
+    ) -> Result<()> {
+        self.populate(|dir| copy_files(out_dir, generated_files, dir))?;
+        if let Some(backend) = backend {
+            let result =
+                pack(out_dir, generated_files).and_then(|bundle| backend.put(&self.key, &bundle));
+            if let Err(e) = result {
+                log::warn!("failed to upload the cache entry {}: {}", self.key, e);
+            }
+        }
+        Ok(())
+    }
+
+    fn populate<F: FnOnce(&Path) -> Result<()>>(&self, f: F) -> Result<()> {
+        fs::create_dir_all(&self.cache_dir)?;
+        // Populate a staging directory first, so an interrupted run leaves no partial entry
+        let staging_dir = TempDir::new_in(&self.cache_dir)?;
+        f(staging_dir.path())?;
+        fs::rename(staging_dir.path(), &self.dir)
+            .map_err(|e| err_other(format!("{}: {}", self.dir.display(), e)))
+    }
+}
+
+/// The key of the cache entry of the run
+pub(crate) fn key(flatc_version: &str, args: &Args) -> Result<String> {
+    let cmd_args = args.flatc_args_with(|path| path::absolute(path))?;
+    let mut hasher = Sha256::new();
+    hasher.update(flatc_version);
+    let mut cmd_args = cmd_args.iter();
+    while let Some(arg) = cmd_args.next() {
+        if arg == "-o" {
+            cmd_args.next();
+            continue;
+        }
+        hasher.update([0]);
+        hasher.update(arg.to_string_lossy().as_bytes());
+    }
+    for schema_file in schema_files(args) {
+        hasher.update([0]);
+        hasher.update(schema_file.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(fs::read(&schema_file)?);
+    }
+    Ok(sha256_hex(&hasher.finalize()))
+}
+
+/// The absolute paths of the inputs and of the schemas they include (transitively); an include
+/// is resolved against every directory `flatc` may search, so the key changes if any of the
+/// candidates does
+fn schema_files(args: &Args) -> BTreeSet<PathBuf> {
+    let mut search_dirs: Vec<PathBuf> = args
+        .inputs
+        .iter()
+        .filter_map(|input| input.parent())
+        .chain(args.includes.iter().copied())
+        .filter_map(|dir| path::absolute(dir).ok())
+        .collect();
+    search_dirs.extend(std::env::current_dir().ok());
+
+    let mut schema_files = BTreeSet::new();
+    let mut pending: Vec<PathBuf> = args
+        .inputs
+        .iter()
+        .filter(|input| input.as_os_str() != "--")
+        .filter_map(|input| path::absolute(input).ok())
+        .collect();
+    while let Some(path) = pending.pop() {
+        if !path.is_file() || schema_files.contains(&path) {
+            continue;
+        }
+        if path.extension().is_some_and(|extension| extension == "fbs") {
+            let includes = schema::Schema::from_path(&path)
+                .map(|schema| schema.includes)
+                .unwrap_or_default();
+            for include in includes {
+                pending.extend(
+                    path.parent()
+                        .into_iter()
+                        .chain(search_dirs.iter().map(PathBuf::as_path))
+                        .map(|dir| dir.join(&include)),
+                );
+            }
+        }
+        schema_files.insert(path);
+    }
+    schema_files
+}
+
+/// The files of the directory (recursively)
+fn cached_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_owned()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                pending.push(entry.path());
+            } else {
+                files.push(entry.path());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// The bundle of the files of `dir`: the `/`-separated relative path (prefixed with its length,
+/// a little-endian `u32`) and the content (prefixed with its length, a little-endian `u64`) of
+/// every file
+pub(crate) fn pack(dir: &Path, files: &[PathBuf]) -> Result<Vec<u8>> {
+    let mut bundle = Vec::new();
+    for file in files {
+        let relative_path = file.strip_prefix(dir).map_err(err_other)?;
+        let relative_path = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_str())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| err_other(format!("{} is not UTF-8", file.display())))?
+            .join("/");
+        let content = fs::read(file)?;
+        bundle.extend((relative_path.len() as u32).to_le_bytes());
+        bundle.extend(relative_path.as_bytes());
+        bundle.extend((content.len() as u64).to_le_bytes());
+        bundle.extend(content);
+    }
+    Ok(bundle)
+}
+
+/// Write the files of the bundle into `dir`
+fn unpack(mut bundle: &[u8], dir: &Path) -> Result<()> {
+    fn invalid(message: &str) -> Error {
+        Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid cache bundle: {}", message),
+        )
+    }
+    fn take<'a>(bundle: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+        if bundle.len() < len {
+            return Err(invalid("truncated"));
+        }
+        let (taken, rest) = bundle.split_at(len);
+        *bundle = rest;
+        Ok(taken)
+    }
+
+    while !bundle.is_empty() {
+        let len = u32::from_le_bytes(take(&mut bundle, 4)?.try_into().expect("4 bytes"));
+        let relative_path = std::str::from_utf8(take(&mut bundle, len as usize)?)
+            .map_err(|_| invalid("the path is not UTF-8"))?;
+        let is_relative = !relative_path.is_empty()
+            && Path::new(relative_path)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+        if !is_relative {
+            return Err(invalid(&format!(
+                "{} is outside of the entry",
+                relative_path
+            )));
+        }
+        let len = u64::from_le_bytes(take(&mut bundle, 8)?.try_into().expect("8 bytes"));
+        let content = take(&mut bundle, usize::try_from(len).map_err(err_other)?)?;
+        let path = dir.join(relative_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)?;
+    }
+    Ok(())
+}
+
+/// Copy the files of `from_dir` into `to_dir` keeping their relative paths
+fn copy_files(from_dir: &Path, files: &[PathBuf], to_dir: &Path) -> Result<()> {
+    for file in files {
+        let relative_path = file.strip_prefix(from_dir).map_err(err_other)?;
+        let destination = to_dir.join(relative_path);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(file, &destination)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{run, Flatc};
+
+    #[derive(Clone, Default)]
+    struct MemoryCache(Arc<Mutex<HashMap<String, Vec<u8>>>>);
+
+    impl CacheBackend for MemoryCache {
+        fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+            Ok(self.0.lock().expect("lock").get(key).cloned())
+        }
+
+        fn put(&self, key: &str, bundle: &[u8]) -> Result<()> {
+            self.0
+                .lock()
+                .expect("lock")
+                .insert(key.to_owned(), bundle.to_owned());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn run_reuses_cached_output_until_included_schema_changes() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let cache_dir = temp_dir.path().join("flatc-cache");
+        let input_path = temp_dir.path().join("monster.fbs");
+        let weapon_path = temp_dir.path().join("weapon.fbs");
+        fs::write(
+            &input_path,
+            "include \"weapon.fbs\";\ntable Monster { weapon: Weapon; }",
+        )?;
+        fs::write(&weapon_path, "table Weapon { damage: int; }")?;
+        let run_into = |out_dir: &Path| {
+            run(Args {
+                inputs: &[&input_path],
+                out_dir,
+                cache_dir: Some(&cache_dir),
+                ..Default::default()
+            })?;
+            fs::read_to_string(out_dir.join("monster_generated.rs"))
+        };
+
+        let generated = run_into(&temp_dir.path().join("crate-a"))?;
+        let entries = fs::read_dir(&cache_dir)?.collect::<std::io::Result<Vec<_>>>()?;
+        assert_eq!(entries.len(), 1);
+        // Mark the cached file to tell the reuse from a recompilation
+        let cached_file = entries[0].path().join("monster_generated.rs");
+        fs::write(&cached_file, format!("{}// cached\n", generated))?;
+        assert!(run_into(&temp_dir.path().join("crate-b"))?.ends_with("// cached\n"));
+        // Every entry point reuses it
+        let out_dir = temp_dir.path().join("crate-d");
+        let output = Flatc::from_env_path().run_with_output(Args {
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            cache_dir: Some(&cache_dir),
+            ..Default::default()
+        })?;
+        assert!(output.status.success());
+        assert_eq!(
+            output.generated_files,
+            vec![out_dir.join("monster_generated.rs")]
+        );
+        assert!(fs::read_to_string(out_dir.join("monster_generated.rs"))?.ends_with("// cached\n"));
+
+        fs::write(&weapon_path, "table Weapon { damage: long; }")?;
+        assert_eq!(run_into(&temp_dir.path().join("crate-c"))?, generated);
+        assert_eq!(fs::read_dir(&cache_dir)?.count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn backend_shares_entries_between_cache_dirs() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("monster.fbs");
+        fs::write(&input_path, "table Monster { hp: short; }")?;
+        let backend = MemoryCache::default();
+        let flatc = Flatc::from_env_path().with_cache_backend(backend.clone());
+        let run_on_machine = |machine: &str| {
+            let machine_dir = temp_dir.path().join(machine);
+            flatc.run(Args {
+                inputs: &[&input_path],
+                out_dir: &machine_dir.join("out"),
+                cache_dir: Some(&machine_dir.join("flatc-cache")),
+                ..Default::default()
+            })?;
+            fs::read_to_string(machine_dir.join("out/monster_generated.rs"))
+        };
+
+        let generated = run_on_machine("runner-1")?;
+        let key = {
+            let bundles = backend.0.lock().expect("lock");
+            assert_eq!(bundles.len(), 1);
+            bundles.keys().next().expect("the key").clone()
+        };
+        // Replace the bundle with a marked one to tell the fetch from a recompilation
+        let marked_dir = temp_dir.path().join("marked");
+        fs::create_dir_all(&marked_dir)?;
+        let marked_file = marked_dir.join("monster_generated.rs");
+        fs::write(&marked_file, format!("{}// fetched\n", generated))?;
+        backend.put(&key, &pack(&marked_dir, &[marked_file])?)?;
+        assert!(run_on_machine("runner-2")?.ends_with("// fetched\n"));
+        assert!(temp_dir
+            .path()
+            .join("runner-2/flatc-cache")
+            .join(&key)
+            .is_dir());
+
+        let mut escaping = pack(&marked_dir, &[marked_dir.join("monster_generated.rs")])?;
+        escaping.splice(
+            4..4 + "monster_generated.rs".len(),
+            *b"../monster_generated",
+        );
+        let error = unpack(&escaping, &temp_dir.path().join("unpacked")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        Ok(())
+    }
+}
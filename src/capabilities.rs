@@ -0,0 +1,59 @@
+//! The `flatc` versions supporting the flags.
+
+use std::ffi::OsString;
+
+/// The flags supported only by the recent `flatc` versions: the flag, the generator it is
+/// limited to (if any) and the first `flatc` version supporting it
+///
+/// The flags supported by `flatc` 1.10.0 (the oldest one generating Rust) are not listed, so the
+/// common runs do not spend an extra `flatc --version` call.
+const FLAG_VERSIONS: &[(&str, Option<&str>, &str)] = &[
+    ("--swift", None, "1.12.0"),
+    ("--kotlin", None, "1.12.0"),
+    ("--gen-object-api", Some("rust"), "2.0.0"),
+    ("--bfbs-filenames", None, "2.0.0"),
+    ("--require-explicit-ids", None, "2.0.0"),
+    ("--cs-gen-json-serializer", None, "2.0.0"),
+    ("--rust-module-root-file", None, "2.0.5"),
+    ("--rust-serialize", None, "2.0.6"),
+];
+
+/// The flags of the `flatc` command line which are not supported by every `flatc`, with the first
+/// versions supporting them
+pub(crate) fn required_versions(
+    lang: &str,
+    cmd_args: &[OsString],
+) -> Vec<(&'static str, &'static str)> {
+    FLAG_VERSIONS
+        .iter()
+        .filter(|&&(flag, flag_lang, _)| {
+            flag_lang.is_none_or(|flag_lang| flag_lang == lang)
+                && cmd_args.iter().any(|arg| arg == flag)
+        })
+        .map(|&(flag, _, version)| (flag, version))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn required_versions_consider_generator() {
+        let cmd_args: Vec<OsString> = ["--rust", "--gen-object-api", "-o", "out", "test.fbs"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        assert_eq!(
+            required_versions("rust", &cmd_args),
+            vec![("--gen-object-api", "2.0.0")]
+        );
+
+        let cmd_args: Vec<OsString> = ["--cpp", "--gen-object-api", "test.fbs"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        assert!(required_versions("cpp", &cmd_args).is_empty());
+        assert!(required_versions("rust", &[OsString::from("--rust")]).is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+//! Coloring of the `flatc` diagnostics re-emitted after capturing.
+
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, IsTerminal};
+
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Whether to color the `flatc` diagnostics re-emitted after capturing (see
+/// [`Flatc::with_color`])
+///
+/// [`Flatc::with_color`]: struct.Flatc.html#method.with_color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Color the diagnostics if stderr is a terminal, unless `NO_COLOR` environment variable is
+    /// set or `TERM` is `dumb`
+    #[default]
+    Auto,
+    /// Always color the diagnostics
+    Always,
+    /// Never color the diagnostics
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether the diagnostics are colored in the current environment
+    pub(crate) fn is_enabled(self) -> bool {
+        self.is_enabled_with(
+            env::var_os("NO_COLOR"),
+            env::var_os("TERM"),
+            io::stderr().is_terminal(),
+        )
+    }
+
+    fn is_enabled_with(
+        self,
+        no_color: Option<OsString>,
+        term: Option<OsString>,
+        is_terminal: bool,
+    ) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                no_color.is_none_or(|no_color| no_color.is_empty())
+                    && term.is_none_or(|term| term != "dumb")
+                    && is_terminal
+            }
+        }
+    }
+}
+
+/// Color the error (red) and warning (yellow) lines of the diagnostics, making their
+/// `file:line` locations bold
+pub(crate) fn colorize(diagnostics: &str) -> String {
+    diagnostics
+        .split_inclusive('\n')
+        .map(|line| {
+            let (line, newline) = match line.strip_suffix('\n') {
+                Some(line) => (line, "\n"),
+                None => (line, ""),
+            };
+            colorize_line(line) + newline
+        })
+        .collect()
+}
+
+fn colorize_line(line: &str) -> String {
+    let (severity_index, color) = match (line.find("error:"), line.find("warning:")) {
+        (Some(index), _) => (index, RED),
+        (None, Some(index)) => (index, YELLOW),
+        (None, None) => return line.to_owned(),
+    };
+    let (location, message) = line.split_at(severity_index);
+    let location_start = location.len() - location.trim_start().len();
+    let (indent, location) = location.split_at(location_start);
+    if location.is_empty() {
+        format!("{}{}{}{}", indent, color, message, RESET)
+    } else {
+        format!(
+            "{}{}{}{}{}{}{}",
+            indent, BOLD, location, RESET, color, message, RESET
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn colorize_highlights_errors_and_locations() {
+        assert_eq!(
+            colorize("error:\n  test.fbs:1: 27: error: expecting: ;\nflatc: usage\n"),
+            "\x1b[31merror:\x1b[0m\n  \x1b[1mtest.fbs:1: 27: \x1b[0m\x1b[31merror: expecting: \
+             ;\x1b[0m\nflatc: usage\n"
+        );
+        assert_eq!(
+            colorize("warning: field is deprecated"),
+            "\x1b[33mwarning: field is deprecated\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn auto_color_respects_no_color_and_terminal() {
+        assert!(ColorChoice::Auto.is_enabled_with(None, Some("xterm".into()), true));
+        assert!(!ColorChoice::Auto.is_enabled_with(Some("1".into()), None, true));
+        assert!(!ColorChoice::Auto.is_enabled_with(None, Some("dumb".into()), true));
+        assert!(!ColorChoice::Auto.is_enabled_with(None, None, false));
+        assert!(ColorChoice::Always.is_enabled_with(Some("1".into()), None, false));
+        assert!(!ColorChoice::Never.is_enabled_with(None, None, true));
+    }
+}
@@ -0,0 +1,284 @@
+//! Parsing and rendering of raw `flatc` command lines.
+
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use crate::{err_other, ArgsOwned, Result};
+
+/// The `flatc` generator flags (the first one becomes [`ArgsOwned::lang`])
+const GENERATORS: &[(&str, &str)] = &[
+    ("-b", "binary"),
+    ("-c", "cpp"),
+    ("-n", "csharp"),
+    ("-d", "dart"),
+    ("-g", "go"),
+    ("-j", "java"),
+    ("-t", "json"),
+    ("-l", "lua"),
+    ("-p", "python"),
+    ("-r", "rust"),
+    ("-T", "ts"),
+    ("", "jsonschema"),
+    ("", "kotlin"),
+    ("", "lobster"),
+    ("", "nim"),
+    ("", "php"),
+    ("", "proto"),
+    ("", "swift"),
+];
+
+/// The `flatc` options (not covered by [`ArgsOwned`] fields) followed by a value
+const OPTIONS_WITH_VALUE: &[&str] = &[
+    "--cpp-include",
+    "--cpp-ptr-type",
+    "--cpp-str-type",
+    "--cpp-field-case-style",
+    "--cpp-std",
+    "--object-prefix",
+    "--object-suffix",
+    "--go-namespace",
+    "--go-import",
+    "--go-module-name",
+    "--java-package-prefix",
+    "--proto-namespace-suffix",
+    "--proto-id-gap",
+    "--filename-suffix",
+    "--filename-ext",
+    "--include-prefix",
+    "--root-type",
+];
+
+/// Split the command line into words like a POSIX shell does (quotes and backslash escapes are
+/// supported, expansions are not)
+pub(crate) fn split(command_line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = command_line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(word) = word.take() {
+                    words.push(word);
+                }
+            }
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(err_other("unterminated single quote")),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some('\n') => (),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(err_other("unterminated double quote")),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(err_other("unterminated double quote")),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => (),
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => return Err(err_other("trailing backslash")),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Quote the word for a POSIX shell (the words without special characters are left as is)
+pub(crate) fn quote(word: &str) -> Cow<'_, str> {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=+,@%^".contains(c);
+    if !word.is_empty() && word.chars().all(is_safe) {
+        return Cow::Borrowed(word);
+    }
+    Cow::Owned(format!("'{}'", word.replace('\'', "'\\''")))
+}
+
+/// Render the words as a shell command line, quoting them as needed
+pub(crate) fn render<I, S>(words: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    words
+        .into_iter()
+        .map(|word| quote(&word.as_ref().to_string_lossy()).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn generator(word: &str) -> Option<&'static str> {
+    GENERATORS
+        .iter()
+        .find(|&&(short, long)| {
+            (!short.is_empty() && word == short)
+                || word.strip_prefix("--").is_some_and(|name| name == long)
+        })
+        .map(|&(_, long)| long)
+}
+
+/// Parse the `flatc` command line (the leading `flatc` program is optional)
+pub(crate) fn parse(command_line: &str) -> Result<ArgsOwned> {
+    let mut words = split(command_line)?.into_iter().peekable();
+    if words.peek().is_some_and(|program| {
+        Path::new(program)
+            .file_stem()
+            .is_some_and(|stem| stem == "flatc")
+    }) {
+        words.next();
+    }
+
+    let mut args = ArgsOwned {
+        lang: String::new(),
+        ..Default::default()
+    };
+    let mut out_dir = None;
+    while let Some(word) = words.next() {
+        if let Some(generator) = generator(&word) {
+            match generator {
+                _ if args.lang.is_empty() => args.lang = generator.to_owned(),
+                "binary" => args.binary = true,
+                "json" => args.json = true,
+                "jsonschema" => args.jsonschema = true,
+                _ => args.extra.push(word),
+            }
+            continue;
+        }
+
+        let mut value = |name: &str| {
+            words
+                .next()
+                .ok_or_else(|| err_other(format!("{} requires a value", name)))
+        };
+        match word.as_str() {
+            "-o" => out_dir = Some(PathBuf::from(value(&word)?)),
+            "-I" => args.includes.push(value(&word)?.into()),
+            "--schema" => args.schema = true,
+            "--bfbs-filenames" => args.bfbs_filenames = Some(value(&word)?.into()),
+            "--require-explicit-ids" => args.require_explicit_ids = true,
+            "--conform" => args.conform = Some(value(&word)?.into()),
+            "--conform-includes" => args.conform_includes.push(value(&word)?.into()),
+            "--no-warnings" => args.no_warnings = true,
+            "--allow-non-utf8" => args.allow_non_utf8 = true,
+            "--unknown-json" => args.unknown_json = true,
+            "--rust-module-root-file" => args.rust_module_root_file = true,
+            "--rust-serialize" => args.rust_serialize = true,
+            "--gen-json-emit" => args.gen_json_emit = true,
+            "--cs-gen-json-serializer" => args.cs_gen_json_serializer = true,
+            "--" => {
+                args.inputs.push(PathBuf::from("--"));
+                args.inputs.extend(words.by_ref().map(PathBuf::from));
+            }
+            "-h" | "--help" | "--version" => {
+                return Err(err_other(format!("{} does not run code generation", word)));
+            }
+            _ if OPTIONS_WITH_VALUE.contains(&word.as_str()) => {
+                let option_value = value(&word)?;
+                args.extra.push(word);
+                args.extra.push(option_value);
+            }
+            _ if word.starts_with('-') => args.extra.push(word),
+            _ => args.inputs.push(word.into()),
+        }
+    }
+
+    if args.lang.is_empty() {
+        return Err(err_other("no generator (e.g. --rust) is specified"));
+    }
+    args.out_dir = out_dir.unwrap_or_else(|| PathBuf::from("."));
+    Ok(args)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_handles_quotes_and_escapes() -> Result<()> {
+        assert_eq!(
+            split(r#"flatc -o 'out dir' -I "inc \"x\"" my\ schema.fbs"#)?,
+            vec![
+                "flatc",
+                "-o",
+                "out dir",
+                "-I",
+                r#"inc "x""#,
+                "my schema.fbs"
+            ]
+        );
+        assert!(split("flatc 'unterminated").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn render_quotes_special_words() -> Result<()> {
+        let words = [
+            "flatc",
+            "-o",
+            "out dir",
+            "it's.fbs",
+            "",
+            "--filename-suffix=_fb",
+        ];
+        let command_line = render(words);
+        assert_eq!(
+            command_line,
+            r#"flatc -o 'out dir' 'it'\''s.fbs' '' --filename-suffix=_fb"#
+        );
+        assert_eq!(split(&command_line)?, words);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_maps_flags_to_args() -> Result<()> {
+        let args = parse(
+            "/usr/bin/flatc --rust --binary -o out -I inc --gen-object-api \
+             --filename-suffix _fb foo.fbs bar.fbs",
+        )?;
+
+        assert_eq!(args.lang, "rust");
+        assert!(args.binary);
+        assert_eq!(args.out_dir, PathBuf::from("out"));
+        assert_eq!(args.includes, vec![PathBuf::from("inc")]);
+        assert_eq!(
+            args.inputs,
+            vec![PathBuf::from("foo.fbs"), PathBuf::from("bar.fbs")]
+        );
+        assert_eq!(
+            args.extra,
+            vec!["--gen-object-api", "--filename-suffix", "_fb"]
+        );
+        assert!(args.create_out_dir);
+
+        let args = parse("-t --strict-json -o out schema.fbs -- data.bin")?;
+        assert_eq!(args.lang, "json");
+        assert_eq!(args.inputs.len(), 3);
+
+        let args = parse("--rust --jsonschema -o out schema.fbs")?;
+        assert_eq!(args.lang, "rust");
+        assert!(args.jsonschema);
+
+        assert!(parse("-o out foo.fbs").is_err());
+        assert!(parse("--rust -o").is_err());
+
+        Ok(())
+    }
+}
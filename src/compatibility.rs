@@ -0,0 +1,250 @@
+//! Breaking-change checks of the schema evolution, e.g. to fail `cargo test` when a field id is
+//! reused:
+//!
+//! ```no_run
+//! #[test]
+//! fn schema_is_backward_compatible() {
+//!     flatc_rust::compatibility::check(
+//!         "baseline/monster.bfbs",
+//!         "schemas/monster.fbs",
+//!         flatc_rust::compatibility::Policy::BackwardCompatible,
+//!     )
+//!     .unwrap();
+//! }
+//! ```
+//!
+//! The changes are found with [`diff`] and judged by the FlatBuffers evolution rules, which are
+//! stricter than the wire format in the same places as `flatc --conform` (see
+//! [`Args::conform`]): the fields have to be deprecated instead of removed, and the defaults must
+//! not change.
+//!
+//! [`diff`]: ../diff/index.html
+//! [`Args::conform`]: ../struct.Args.html#structfield.conform
+
+use std::error;
+use std::fmt;
+use std::path::Path;
+
+use crate::diff::{Change, SchemaDiff};
+use crate::{Flatc, Result};
+
+/// The compatibility required between the old and the new version of the schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// The new schema reads the data written with the old schema (upgrade the readers first)
+    BackwardCompatible,
+    /// The old schema reads the data written with the new schema (upgrade the writers first)
+    ForwardCompatible,
+    /// Both backward and forward compatible
+    Full,
+    /// Any change is allowed (the schemas still have to compile)
+    None,
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Policy::BackwardCompatible => "backward compatibility",
+            Policy::ForwardCompatible => "forward compatibility",
+            Policy::Full => "full compatibility",
+            Policy::None => "no compatibility",
+        })
+    }
+}
+
+/// A change violating the compatibility policy
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// The change
+    pub change: Change,
+    /// Whether the change prevents the new schema from reading the old data
+    pub breaks_backward: bool,
+    /// Whether the change prevents the old schema from reading the new data
+    pub breaks_forward: bool,
+    /// Why the change is breaking
+    pub reason: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.change, self.reason)
+    }
+}
+
+/// The error listing every change violating the policy (see [`check`])
+///
+/// It is returned wrapped into [`Error`] of `InvalidData` kind, like [`ValidationError`].
+///
+/// [`check`]: fn.check.html
+/// [`Error`]: ../type.Error.html
+/// [`ValidationError`]: ../struct.ValidationError.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompatibilityError {
+    /// The violated policy
+    pub policy: Policy,
+    /// The violations in the order of the changes
+    pub violations: Vec<Violation>,
+}
+
+impl fmt::Display for CompatibilityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "schema changes break {}:", self.policy)?;
+        for violation in &self.violations {
+            write!(f, "\n  - {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for CompatibilityError {}
+
+/// Whether the change breaks the backward and the forward compatibility, and why
+fn classify(change: &Change) -> Option<(bool, bool, &'static str)> {
+    Some(match change {
+        Change::ObjectAdded { .. }
+        | Change::ObjectRemoved { .. }
+        | Change::FieldAdded { .. }
+        | Change::FieldRenamed { .. }
+        | Change::FieldDeprecated { .. }
+        | Change::EnumAdded { .. }
+        | Change::EnumRemoved { .. } => return None,
+        Change::RootTypeChanged { .. } => (true, true, "the buffers have another root table"),
+        Change::ObjectKindChanged { .. } => {
+            (true, true, "tables and structs are laid out differently")
+        }
+        Change::StructLayoutChanged { .. } => (true, true, "structs cannot evolve"),
+        Change::FieldRemoved { .. } => (
+            true,
+            true,
+            "deprecate the field instead, so its id is never reused",
+        ),
+        Change::FieldIdReused { .. } => (true, true, "the data of the old field is misread"),
+        Change::FieldTypeChanged { .. } => (true, true, "the field data is misread"),
+        Change::FieldDefaultChanged { .. } => {
+            (true, true, "the absent values are read differently")
+        }
+        Change::FieldRequiredChanged { required: true, .. } => {
+            (true, false, "the old data may lack the field")
+        }
+        Change::FieldRequiredChanged {
+            required: false, ..
+        } => (false, true, "the old readers expect the field"),
+        Change::EnumTypeChanged { .. } => (true, true, "the values are misread"),
+        Change::EnumValueAdded { .. } => (false, true, "the old readers do not know the value"),
+        Change::EnumValueRemoved { .. } => (true, false, "the old data may contain the value"),
+        Change::EnumValueChanged { .. } => (true, true, "the values are misread"),
+    })
+}
+
+/// The changes of the diff violating the policy
+pub fn violations(diff: &SchemaDiff, policy: Policy) -> Vec<Violation> {
+    diff.changes
+        .iter()
+        .filter_map(|change| {
+            let (breaks_backward, breaks_forward, reason) = classify(change)?;
+            let violates = match policy {
+                Policy::BackwardCompatible => breaks_backward,
+                Policy::ForwardCompatible => breaks_forward,
+                Policy::Full => breaks_backward || breaks_forward,
+                Policy::None => false,
+            };
+            Some(Violation {
+                change: change.clone(),
+                breaks_backward,
+                breaks_forward,
+                reason: reason.to_owned(),
+            })
+            .filter(|_| violates)
+        })
+        .collect()
+}
+
+/// Check the compatibility of the schema versions with `flatc` found in `$PATH` (see
+/// [`Flatc::check_compatibility`])
+///
+/// [`Flatc::check_compatibility`]: ../struct.Flatc.html#method.check_compatibility
+pub fn check<P: AsRef<Path>, Q: AsRef<Path>>(
+    old_schema: P,
+    new_schema: Q,
+    policy: Policy,
+) -> Result<()> {
+    let flatc = Flatc::from_env_path();
+
+    // First check with have good `flatc`
+    flatc.check()?;
+
+    flatc.check_compatibility(old_schema.as_ref(), new_schema.as_ref(), policy)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn check_reports_violations_of_policy() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let old_path = temp_dir.path().join("old.fbs");
+        fs::write(
+            &old_path,
+            "enum Color : ubyte { Red, Green, Blue }\n\
+             table Monster { name: string (id: 0); hp: short (id: 1); level: int (id: 2); }\n\
+             root_type Monster;\n",
+        )?;
+        let evolved_path = temp_dir.path().join("evolved.fbs");
+        fs::write(
+            &evolved_path,
+            "enum Color : ubyte { Red, Green, Blue, Black }\n\
+             table Monster {\n\
+               title: string (id: 0);\n\
+               hp: short (id: 1, deprecated);\n\
+               level: int (id: 2);\n\
+               mana: int (id: 3);\n\
+             }\n\
+             root_type Monster;\n",
+        )?;
+        let broken_path = temp_dir.path().join("broken.fbs");
+        fs::write(
+            &broken_path,
+            "enum Color : ubyte { Red, Green }\n\
+             table Monster { name: string (id: 0); hp: short (id: 1); xp: float (id: 2); }\n\
+             root_type Monster;\n",
+        )?;
+
+        check(&old_path, &evolved_path, Policy::BackwardCompatible)?;
+        check(&old_path, &broken_path, Policy::None)?;
+
+        let error = check(&old_path, &evolved_path, Policy::Full).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        let error = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<CompatibilityError>())
+            .expect("CompatibilityError");
+        assert_eq!(
+            error.to_string(),
+            "schema changes break full compatibility:\n  \
+             - Color.Black: added (= 3) (the old readers do not know the value)"
+        );
+
+        let error = check(&old_path, &broken_path, Policy::BackwardCompatible).unwrap_err();
+        let error = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<CompatibilityError>())
+            .expect("CompatibilityError");
+        let changes: Vec<_> = error
+            .violations
+            .iter()
+            .map(|violation| violation.change.to_string())
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                "Monster: id 2 reused: `level: int` -> `xp: float`",
+                "Color.Blue: removed (= 2)",
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+//! Config-file driven compilation (requires `config` feature).
+//!
+//! The config file (conventionally [`CONFIG_FILE_NAME`]) describes groups of schemas compiled
+//! with the same args:
+//!
+//! ```toml
+//! [[group]]
+//! inputs = ["schemas/message.fbs", "schemas/event.fbs"]
+//! includes = ["schemas/"]
+//! out_dir = "target/flatbuffers/"
+//! extra = ["--gen-object-api"]
+//!
+//! [[group]]
+//! name = "typescript"
+//! lang = "ts"
+//! inputs = ["schemas/message.fbs"]
+//! out_dir = "web/src/generated/"
+//! ```
+//!
+//! Every group accepts the fields of [`Args`] (`lang` is `rust` by default); the paths are
+//! relative to the directory of the config file.
+//!
+//! [`CONFIG_FILE_NAME`]: constant.CONFIG_FILE_NAME.html
+//! [`Args`]: ../struct.Args.html
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::{err_other, Args, Error, Flatc, Invocation, OutDirLayout, Result};
+
+/// The conventional name of the config file
+pub const CONFIG_FILE_NAME: &str = "flatc-rust.toml";
+
+/// The parsed config file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The schema groups
+    #[serde(default, rename = "group")]
+    pub groups: Vec<Group>,
+}
+
+/// A group of schemas compiled with a single `flatc` invocation
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Group {
+    /// The name used in the logs and errors (the index of the group by default)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Specify the programming language (`rust` is the default)
+    #[serde(default = "default_lang")]
+    pub lang: String,
+    /// List of `.fbs` files to compile [required to be non-empty]
+    pub inputs: Vec<PathBuf>,
+    /// Output path for the generated helpers [required]
+    pub out_dir: PathBuf,
+    /// Search for includes in the specified paths
+    #[serde(default)]
+    pub includes: Vec<PathBuf>,
+    /// Set the flatc '--binary' flag
+    #[serde(default)]
+    pub binary: bool,
+    /// Set the flatc '--schema' flag
+    #[serde(default)]
+    pub schema: bool,
+    /// Set the flatc '--json' flag
+    #[serde(default)]
+    pub json: bool,
+    /// Set the flatc '--jsonschema' flag
+    #[serde(default)]
+    pub jsonschema: bool,
+    /// Set the flatc '--bfbs-filenames' root
+    #[serde(default)]
+    pub bfbs_filenames: Option<PathBuf>,
+    /// Set the flatc '--require-explicit-ids' flag
+    #[serde(default)]
+    pub require_explicit_ids: bool,
+    /// Set the flatc '--conform' baseline schema
+    #[serde(default)]
+    pub conform: Option<PathBuf>,
+    /// Set the flatc '--conform-includes' paths
+    #[serde(default)]
+    pub conform_includes: Vec<PathBuf>,
+    /// Set the flatc '--no-warnings' flag
+    #[serde(default)]
+    pub no_warnings: bool,
+    /// Set the flatc '--allow-non-utf8' flag
+    #[serde(default)]
+    pub allow_non_utf8: bool,
+    /// Set the flatc '--unknown-json' flag
+    #[serde(default)]
+    pub unknown_json: bool,
+    /// Set the flatc '--rust-module-root-file' flag
+    #[serde(default)]
+    pub rust_module_root_file: bool,
+    /// Set the flatc '--rust-serialize' flag
+    #[serde(default)]
+    pub rust_serialize: bool,
+    /// Set the flatc '--gen-json-emit' flag
+    #[serde(default)]
+    pub gen_json_emit: bool,
+    /// Set the flatc '--cs-gen-json-serializer' flag
+    #[serde(default)]
+    pub cs_gen_json_serializer: bool,
+    /// Remove the previously generated files from `out_dir` before the run
+    #[serde(default)]
+    pub clean_out_dir: bool,
+    /// Search for includes in the schema directories published by the dependencies
+    /// (`DEP_<LINKS>_SCHEMA_DIR`)
+    #[serde(default)]
+    pub dependency_schemas: bool,
+    /// Write the declared inputs and outputs of the run into the JSON file (for Bazel or Buck)
+    #[serde(default)]
+    pub manifest: Option<PathBuf>,
+    /// Reuse the files generated by the identical runs from the cache directory
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+    /// Make the generated files byte-identical across the machines, requiring the flatc version
+    /// (e.g. `=23.5.26`)
+    #[serde(default)]
+    pub reproducible: Option<String>,
+    /// Move the generated files into `out_dir` only after a successful run
+    #[serde(default)]
+    pub atomic: bool,
+    /// Leave the unchanged files of `out_dir` untouched
+    #[serde(default)]
+    pub skip_unchanged: bool,
+    /// Make the generated files read-only
+    #[serde(default)]
+    pub read_only: bool,
+    /// Run flatc once for all the inputs (`single`) or once per input (`per-file`)
+    #[serde(default)]
+    pub invocation: Invocation,
+    /// The most flatc processes running at once (`NUM_JOBS` or the number of CPUs by default)
+    #[serde(default)]
+    pub jobs: Option<usize>,
+    /// Print the flatc output only if it fails
+    #[serde(default)]
+    pub quiet: bool,
+    /// Prepend the provenance comment to the generated files
+    #[serde(default)]
+    pub provenance: bool,
+    /// Prepend the text (e.g. a license header) as a comment to the generated files
+    #[serde(default)]
+    pub banner: Option<String>,
+    /// Append the target triple (`per-target`) and the profile (`per-target-and-profile`) to
+    /// `out_dir`
+    #[serde(default)]
+    pub out_dir_layout: OutDirLayout,
+    /// Extra args to pass to flatc
+    #[serde(default)]
+    pub extra: Vec<String>,
+}
+
+fn default_lang() -> String {
+    "rust".to_owned()
+}
+
+impl Config {
+    /// Parse the config; the relative paths are resolved against `base_dir`
+    pub fn parse(config: &str, base_dir: &Path) -> Result<Config> {
+        let mut config: Config = toml::from_str(config).map_err(err_other)?;
+        for group in &mut config.groups {
+            group.out_dir = base_dir.join(&group.out_dir);
+            for path in group
+                .inputs
+                .iter_mut()
+                .chain(group.includes.iter_mut())
+                .chain(group.bfbs_filenames.iter_mut())
+                .chain(group.conform.iter_mut())
+                .chain(group.conform_includes.iter_mut())
+                .chain(group.manifest.iter_mut())
+                .chain(group.cache_dir.iter_mut())
+            {
+                *path = base_dir.join(&*path);
+            }
+        }
+        Ok(config)
+    }
+
+    /// Read and parse the config file
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Config> {
+        let path = path.as_ref();
+        let config = fs::read_to_string(path)?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        Config::parse(&config, base_dir)
+            .map_err(|e| err_other(format!("invalid config {}: {}", path.display(), e)))
+    }
+
+    /// Compile every group with the `flatc`
+    pub fn run(&self, flatc: &Flatc) -> Result<()> {
+        for (index, group) in self.groups.iter().enumerate() {
+            group.run(flatc).map_err(|e| group.error(index, e))?;
+        }
+        Ok(())
+    }
+
+    /// List the outdated helpers of every group (see [`Flatc::outdated_files`])
+    ///
+    /// [`Flatc::outdated_files`]: ../struct.Flatc.html#method.outdated_files
+    pub fn outdated_files(&self, flatc: &Flatc) -> Result<Vec<PathBuf>> {
+        let mut outdated_files = Vec::new();
+        for (index, group) in self.groups.iter().enumerate() {
+            outdated_files.extend(
+                group
+                    .outdated_files(flatc)
+                    .map_err(|e| group.error(index, e))?,
+            );
+        }
+        Ok(outdated_files)
+    }
+
+    /// Update the committed helpers of every group in place (see [`Flatc::sync`]) and return
+    /// the updated and removed files
+    ///
+    /// [`Flatc::sync`]: ../struct.Flatc.html#method.sync
+    pub fn sync(&self, flatc: &Flatc) -> Result<Vec<PathBuf>> {
+        let mut updated_files = Vec::new();
+        for (index, group) in self.groups.iter().enumerate() {
+            updated_files.extend(group.sync(flatc).map_err(|e| group.error(index, e))?);
+        }
+        Ok(updated_files)
+    }
+}
+
+impl Group {
+    /// Compile the group with the `flatc`
+    pub fn run(&self, flatc: &Flatc) -> Result<()> {
+        self.with_args(|args| flatc.run(args))
+    }
+
+    /// List the outdated helpers of the group (see [`Flatc::outdated_files`])
+    ///
+    /// [`Flatc::outdated_files`]: ../struct.Flatc.html#method.outdated_files
+    pub fn outdated_files(&self, flatc: &Flatc) -> Result<Vec<PathBuf>> {
+        self.with_args(|args| flatc.outdated_files(args))
+    }
+
+    /// Update the committed helpers of the group in place (see [`Flatc::sync`])
+    ///
+    /// [`Flatc::sync`]: ../struct.Flatc.html#method.sync
+    pub fn sync(&self, flatc: &Flatc) -> Result<Vec<PathBuf>> {
+        self.with_args(|args| flatc.sync(args))
+    }
+
+    fn with_args<T, F: FnOnce(Args) -> T>(&self, f: F) -> T {
+        let inputs: Vec<&Path> = self.inputs.iter().map(PathBuf::as_path).collect();
+        let includes: Vec<&Path> = self.includes.iter().map(PathBuf::as_path).collect();
+        let conform_includes: Vec<&Path> =
+            self.conform_includes.iter().map(PathBuf::as_path).collect();
+        let extra: Vec<&str> = self.extra.iter().map(String::as_str).collect();
+        f(Args {
+            lang: &self.lang,
+            inputs: &inputs,
+            out_dir: &self.out_dir,
+            includes: &includes,
+            binary: self.binary,
+            schema: self.schema,
+            json: self.json,
+            jsonschema: self.jsonschema,
+            bfbs_filenames: self.bfbs_filenames.as_deref(),
+            require_explicit_ids: self.require_explicit_ids,
+            conform: self.conform.as_deref(),
+            conform_includes: &conform_includes,
+            no_warnings: self.no_warnings,
+            allow_non_utf8: self.allow_non_utf8,
+            unknown_json: self.unknown_json,
+            rust_module_root_file: self.rust_module_root_file,
+            rust_serialize: self.rust_serialize,
+            gen_json_emit: self.gen_json_emit,
+            cs_gen_json_serializer: self.cs_gen_json_serializer,
+            clean_out_dir: self.clean_out_dir,
+            dependency_schemas: self.dependency_schemas,
+            manifest: self.manifest.as_deref(),
+            cache_dir: self.cache_dir.as_deref(),
+            reproducible: self.reproducible.as_deref(),
+            atomic: self.atomic,
+            skip_unchanged: self.skip_unchanged,
+            read_only: self.read_only,
+            invocation: self.invocation,
+            jobs: self.jobs,
+            quiet: self.quiet,
+            provenance: self.provenance,
+            banner: self.banner.as_deref(),
+            out_dir_layout: self.out_dir_layout,
+            extra: &extra,
+            ..Default::default()
+        })
+    }
+
+    fn error(&self, index: usize, error: Error) -> Error {
+        err_other(format!(
+            "schema group {} failed: {}",
+            self.name.clone().unwrap_or_else(|| index.to_string()),
+            error
+        ))
+    }
+}
+
+/// Execute `flatc` found in `$PATH` for every schema group of the config file
+///
+/// # Examples
+///
+/// ```no_run
+/// // build.rs
+/// println!("cargo:rerun-if-changed=flatc-rust.toml");
+/// flatc_rust::run_from_config("flatc-rust.toml").expect("flatc");
+/// ```
+pub fn run_from_config<P: AsRef<Path>>(path: P) -> Result<()> {
+    let config = Config::from_path(path)?;
+
+    let flatc = Flatc::from_env_path();
+    // First check with have good `flatc`
+    flatc.check()?;
+
+    config.run(&flatc)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_resolves_paths_against_base_dir() -> Result<()> {
+        let config = Config::parse(
+            r#"
+            [[group]]
+            inputs = ["schemas/message.fbs"]
+            includes = ["schemas/"]
+            out_dir = "target/flatbuffers/"
+            extra = ["--gen-object-api"]
+
+            [[group]]
+            name = "typescript"
+            lang = "ts"
+            inputs = ["schemas/message.fbs"]
+            out_dir = "web/"
+            "#,
+            Path::new("/project"),
+        )?;
+
+        assert_eq!(config.groups.len(), 2);
+        assert_eq!(config.groups[0].lang, "rust");
+        assert_eq!(
+            config.groups[0].inputs,
+            vec![PathBuf::from("/project/schemas/message.fbs")]
+        );
+        assert_eq!(
+            config.groups[0].includes,
+            vec![PathBuf::from("/project/schemas/")]
+        );
+        assert_eq!(config.groups[1].lang, "ts");
+        assert_eq!(config.groups[1].out_dir, PathBuf::from("/project/web/"));
+
+        assert!(Config::parse("[[group]]\ninptus = []\n", Path::new("")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_from_config_compiles_every_group() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        fs::write(
+            temp_dir.path().join("test.fbs"),
+            "table Test { text: string; } root_type Test;",
+        )?;
+        let config_path = temp_dir.path().join(CONFIG_FILE_NAME);
+        fs::write(
+            &config_path,
+            "[[group]]\ninputs = [\"test.fbs\"]\nout_dir = \"rust\"\n\n\
+             [[group]]\nlang = \"python\"\ninputs = [\"test.fbs\"]\nout_dir = \"python\"\n",
+        )?;
+
+        run_from_config(&config_path)?;
+
+        assert!(temp_dir.path().join("rust/test_generated.rs").exists());
+        assert!(temp_dir.path().join("python/Test.py").exists());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+//! Make-style dependency files produced by `flatc -M`.
+//!
+//! `flatc` knows the full include closure of the schemas, so using its dependency output is more
+//! robust than scanning the `include` statements of `.fbs` files:
+//!
+//! ```no_run
+//! // build.rs
+//! use std::path::Path;
+//!
+//! let args = flatc_rust::Args {
+//!     inputs: &[Path::new("schemas/message.fbs")],
+//!     includes: &[Path::new("schemas/")],
+//!     out_dir: Path::new("target/flatbuffers/"),
+//!     ..Default::default()
+//! };
+//! let flatc = flatc_rust::Flatc::from_env_path();
+//! flatc.depfile(args).expect("flatc -M").emit_rerun_if_changed();
+//! flatc.run(args).expect("flatc");
+//! ```
+//!
+//! Alternatively, set [`Args::rerun_if_changed`].
+//!
+//! [`Args::rerun_if_changed`]: ../struct.Args.html#structfield.rerun_if_changed
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{build_script, err_other, Result};
+
+/// A rule of the dependency file (`TARGET...: DEPENDENCY...`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rule {
+    /// The generated files
+    pub targets: Vec<PathBuf>,
+    /// The schemas the generated files depend on (including the transitively included ones)
+    pub dependencies: Vec<PathBuf>,
+}
+
+/// A parsed Make-style dependency file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Depfile {
+    /// The rules in the order of appearance
+    pub rules: Vec<Rule>,
+}
+
+impl Depfile {
+    /// Parse the Make-style dependency rules (line continuations and `\ `-escaped spaces are
+    /// supported)
+    pub fn parse(depfile: &str) -> Result<Depfile> {
+        let mut rules = Vec::new();
+        for line in depfile.replace("\\\r\n", " ").replace("\\\n", " ").lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let separator = find_separator(line).ok_or_else(|| {
+                err_other(format!("invalid dependency rule (no separator): {}", line))
+            })?;
+            rules.push(Rule {
+                targets: split_paths(&line[..separator]),
+                dependencies: split_paths(&line[separator + 1..]),
+            });
+        }
+        Ok(Depfile { rules })
+    }
+
+    /// Read and parse the dependency file
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Depfile> {
+        Depfile::parse(&fs::read_to_string(path)?)
+    }
+
+    /// All the dependencies of all the rules (without duplicates)
+    pub fn dependencies(&self) -> Vec<&Path> {
+        let mut dependencies: Vec<&Path> = Vec::new();
+        for dependency in self.rules.iter().flat_map(|rule| &rule.dependencies) {
+            if !dependencies.contains(&dependency.as_path()) {
+                dependencies.push(dependency);
+            }
+        }
+        dependencies
+    }
+
+    /// Render the rules as a Make-style dependency file
+    pub fn to_make(&self) -> String {
+        let mut depfile = String::new();
+        for rule in &self.rules {
+            depfile.push_str(&join_paths(&rule.targets));
+            depfile.push(':');
+            if !rule.dependencies.is_empty() {
+                depfile.push(' ');
+                depfile.push_str(&join_paths(&rule.dependencies));
+            }
+            depfile.push('\n');
+        }
+        depfile
+    }
+
+    /// Write the rules into the specified file (e.g. for build systems consuming depfiles)
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_make())
+    }
+
+    /// Print `cargo:rerun-if-changed` for every dependency
+    pub fn emit_rerun_if_changed(&self) {
+        for dependency in self.dependencies() {
+            build_script::rerun_if_changed(dependency);
+        }
+    }
+}
+
+/// Find the `:` separating the targets from the dependencies (skipping Windows drive letters,
+/// e.g. `C:\`)
+fn find_separator(line: &str) -> Option<usize> {
+    line.char_indices()
+        .filter(|&(_, c)| c == ':')
+        .map(|(index, _)| index)
+        .find(|&index| {
+            line[index + 1..]
+                .chars()
+                .next()
+                .is_none_or(char::is_whitespace)
+        })
+}
+
+fn split_paths(paths: &str) -> Vec<PathBuf> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut chars = paths.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&' ') => {
+                current.push(' ');
+                chars.next();
+            }
+            c if c.is_whitespace() => {
+                if !current.is_empty() {
+                    result.push(PathBuf::from(std::mem::take(&mut current)));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        result.push(PathBuf::from(current));
+    }
+    result
+}
+
+fn join_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.to_string_lossy().replace(' ', "\\ "))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_handles_continuations_and_escapes() -> Result<()> {
+        let depfile = Depfile::parse(
+            "out/message_generated.rs: schemas/common.fbs \\\n  schemas/my\\ message.fbs\n\
+             C:\\out\\event_generated.rs: C:\\schemas\\common.fbs\n",
+        )?;
+
+        assert_eq!(
+            depfile.rules,
+            vec![
+                Rule {
+                    targets: vec![PathBuf::from("out/message_generated.rs")],
+                    dependencies: vec![
+                        PathBuf::from("schemas/common.fbs"),
+                        PathBuf::from("schemas/my message.fbs"),
+                    ],
+                },
+                Rule {
+                    targets: vec![PathBuf::from("C:\\out\\event_generated.rs")],
+                    dependencies: vec![PathBuf::from("C:\\schemas\\common.fbs")],
+                },
+            ]
+        );
+        assert_eq!(depfile.dependencies().len(), 3);
+        assert_eq!(Depfile::parse(&depfile.to_make())?, depfile);
+        assert!(Depfile::parse("no separator\n").is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,655 @@
+//! Differences between two versions of a schema at the FlatBuffers level.
+//!
+//! The schemas are compared as `flatc` has resolved them (see [`reflection`]), so the table
+//! fields are matched by their ids, which is what the binary compatibility depends on:
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! let diff = flatc_rust::diff(
+//!     Path::new("baseline/monster.fbs"),
+//!     Path::new("schemas/monster.fbs"),
+//! )
+//! .expect("diff");
+//! for change in &diff.changes {
+//!     println!("{}", change);
+//! }
+//! ```
+//!
+//! [`reflection`]: ../reflection/index.html
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::reflection::{BaseType, Enum, Field, Object, Schema};
+
+/// A change of the schema
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// The `root_type` has changed
+    RootTypeChanged {
+        /// The old root object
+        old: Option<String>,
+        /// The new root object
+        new: Option<String>,
+    },
+    /// A table or a struct has been added
+    ObjectAdded {
+        /// The qualified object name
+        object: String,
+    },
+    /// A table or a struct has been removed
+    ObjectRemoved {
+        /// The qualified object name
+        object: String,
+    },
+    /// A table has become a struct or vice versa
+    ObjectKindChanged {
+        /// The qualified object name
+        object: String,
+        /// Whether the object is a struct now
+        is_struct: bool,
+    },
+    /// The size or the field offsets of a struct have changed
+    StructLayoutChanged {
+        /// The qualified struct name
+        object: String,
+    },
+    /// A field has been added
+    FieldAdded {
+        /// The qualified object name
+        object: String,
+        /// The field name
+        field: String,
+        /// The field id
+        id: u16,
+    },
+    /// A field has been removed
+    FieldRemoved {
+        /// The qualified object name
+        object: String,
+        /// The field name
+        field: String,
+        /// The field id
+        id: u16,
+    },
+    /// The field with the same id and type has got another name
+    FieldRenamed {
+        /// The qualified object name
+        object: String,
+        /// The field id
+        id: u16,
+        /// The old field name
+        old_name: String,
+        /// The new field name
+        new_name: String,
+    },
+    /// The id of a field is used by another field of a different type
+    FieldIdReused {
+        /// The qualified object name
+        object: String,
+        /// The field id
+        id: u16,
+        /// The old field name
+        old_field: String,
+        /// The old field type
+        old_type: String,
+        /// The new field name
+        new_field: String,
+        /// The new field type
+        new_type: String,
+    },
+    /// The type of a field has changed
+    FieldTypeChanged {
+        /// The qualified object name
+        object: String,
+        /// The field name
+        field: String,
+        /// The old field type
+        old_type: String,
+        /// The new field type
+        new_type: String,
+    },
+    /// The default value of a scalar field has changed
+    FieldDefaultChanged {
+        /// The qualified object name
+        object: String,
+        /// The field name
+        field: String,
+        /// The old default value (`null` for the optional scalars)
+        old_default: String,
+        /// The new default value (`null` for the optional scalars)
+        new_default: String,
+    },
+    /// A field has become required or optional
+    FieldRequiredChanged {
+        /// The qualified object name
+        object: String,
+        /// The field name
+        field: String,
+        /// Whether the field is required now
+        required: bool,
+    },
+    /// A field has been deprecated
+    FieldDeprecated {
+        /// The qualified object name
+        object: String,
+        /// The field name
+        field: String,
+    },
+    /// An enum or a union has been added
+    EnumAdded {
+        /// The qualified enum name
+        enumeration: String,
+    },
+    /// An enum or a union has been removed
+    EnumRemoved {
+        /// The qualified enum name
+        enumeration: String,
+    },
+    /// The underlying type of an enum has changed
+    EnumTypeChanged {
+        /// The qualified enum name
+        enumeration: String,
+        /// The old underlying type
+        old_type: String,
+        /// The new underlying type
+        new_type: String,
+    },
+    /// A value of an enum (or a member of a union) has been added
+    EnumValueAdded {
+        /// The qualified enum name
+        enumeration: String,
+        /// The value name
+        name: String,
+        /// The value
+        value: i64,
+    },
+    /// A value of an enum (or a member of a union) has been removed
+    EnumValueRemoved {
+        /// The qualified enum name
+        enumeration: String,
+        /// The value name
+        name: String,
+        /// The value
+        value: i64,
+    },
+    /// A value of an enum (or a member of a union) has changed
+    EnumValueChanged {
+        /// The qualified enum name
+        enumeration: String,
+        /// The value name
+        name: String,
+        /// The old value
+        old_value: i64,
+        /// The new value
+        new_value: i64,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn or_none(name: &Option<String>) -> &str {
+            name.as_deref().unwrap_or("none")
+        }
+
+        match self {
+            Change::RootTypeChanged { old, new } => {
+                write!(f, "root_type changed: {} -> {}", or_none(old), or_none(new))
+            }
+            Change::ObjectAdded { object } => write!(f, "{}: added", object),
+            Change::ObjectRemoved { object } => write!(f, "{}: removed", object),
+            Change::ObjectKindChanged { object, is_struct } => write!(
+                f,
+                "{}: changed into a {}",
+                object,
+                if *is_struct { "struct" } else { "table" }
+            ),
+            Change::StructLayoutChanged { object } => {
+                write!(f, "{}: struct layout changed", object)
+            }
+            Change::FieldAdded { object, field, id } => {
+                write!(f, "{}.{}: added (id {})", object, field, id)
+            }
+            Change::FieldRemoved { object, field, id } => {
+                write!(f, "{}.{}: removed (id {})", object, field, id)
+            }
+            Change::FieldRenamed {
+                object,
+                id,
+                old_name,
+                new_name,
+            } => write!(
+                f,
+                "{}.{}: renamed to `{}` (id {})",
+                object, old_name, new_name, id
+            ),
+            Change::FieldIdReused {
+                object,
+                id,
+                old_field,
+                old_type,
+                new_field,
+                new_type,
+            } => write!(
+                f,
+                "{}: id {} reused: `{}: {}` -> `{}: {}`",
+                object, id, old_field, old_type, new_field, new_type
+            ),
+            Change::FieldTypeChanged {
+                object,
+                field,
+                old_type,
+                new_type,
+            } => write!(
+                f,
+                "{}.{}: type changed: {} -> {}",
+                object, field, old_type, new_type
+            ),
+            Change::FieldDefaultChanged {
+                object,
+                field,
+                old_default,
+                new_default,
+            } => write!(
+                f,
+                "{}.{}: default changed: {} -> {}",
+                object, field, old_default, new_default
+            ),
+            Change::FieldRequiredChanged {
+                object,
+                field,
+                required,
+            } => write!(
+                f,
+                "{}.{}: {}",
+                object,
+                field,
+                if *required {
+                    "became required"
+                } else {
+                    "is not required anymore"
+                }
+            ),
+            Change::FieldDeprecated { object, field } => {
+                write!(f, "{}.{}: deprecated", object, field)
+            }
+            Change::EnumAdded { enumeration } => write!(f, "{}: added", enumeration),
+            Change::EnumRemoved { enumeration } => write!(f, "{}: removed", enumeration),
+            Change::EnumTypeChanged {
+                enumeration,
+                old_type,
+                new_type,
+            } => write!(
+                f,
+                "{}: underlying type changed: {} -> {}",
+                enumeration, old_type, new_type
+            ),
+            Change::EnumValueAdded {
+                enumeration,
+                name,
+                value,
+            } => write!(f, "{}.{}: added (= {})", enumeration, name, value),
+            Change::EnumValueRemoved {
+                enumeration,
+                name,
+                value,
+            } => write!(f, "{}.{}: removed (= {})", enumeration, name, value),
+            Change::EnumValueChanged {
+                enumeration,
+                name,
+                old_value,
+                new_value,
+            } => write!(
+                f,
+                "{}.{}: value changed: {} -> {}",
+                enumeration, name, old_value, new_value
+            ),
+        }
+    }
+}
+
+/// The changes between two versions of a schema
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaDiff {
+    /// The changes: the `root_type` change first, then the objects and the enums in the order
+    /// of their names
+    pub changes: Vec<Change>,
+}
+
+impl SchemaDiff {
+    /// Compare the loaded binary schemas
+    pub fn between(old: &Schema, new: &Schema) -> SchemaDiff {
+        let mut changes = Vec::new();
+        if old.root_table != new.root_table {
+            changes.push(Change::RootTypeChanged {
+                old: old.root_table.clone(),
+                new: new.root_table.clone(),
+            });
+        }
+
+        let object_names: BTreeSet<&str> = old
+            .objects
+            .iter()
+            .chain(&new.objects)
+            .map(|object| object.name.as_str())
+            .collect();
+        for name in object_names {
+            let object = name.to_owned();
+            match (old.object(name), new.object(name)) {
+                (Some(_), None) => changes.push(Change::ObjectRemoved { object }),
+                (None, Some(_)) => changes.push(Change::ObjectAdded { object }),
+                (Some(old_object), Some(new_object)) => {
+                    diff_objects(old, old_object, new, new_object, &mut changes)
+                }
+                (None, None) => unreachable!("the name comes from one of the schemas"),
+            }
+        }
+
+        let enum_names: BTreeSet<&str> = old
+            .enums
+            .iter()
+            .chain(&new.enums)
+            .map(|enumeration| enumeration.name.as_str())
+            .collect();
+        for name in enum_names {
+            let enumeration = name.to_owned();
+            match (old.enumeration(name), new.enumeration(name)) {
+                (Some(_), None) => changes.push(Change::EnumRemoved { enumeration }),
+                (None, Some(_)) => changes.push(Change::EnumAdded { enumeration }),
+                (Some(old_enum), Some(new_enum)) => {
+                    diff_enums(old, old_enum, new, new_enum, &mut changes)
+                }
+                (None, None) => unreachable!("the name comes from one of the schemas"),
+            }
+        }
+
+        SchemaDiff { changes }
+    }
+
+    /// Whether the schemas are equivalent at the FlatBuffers level
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+fn diff_objects(
+    old_schema: &Schema,
+    old: &Object,
+    new_schema: &Schema,
+    new: &Object,
+    changes: &mut Vec<Change>,
+) {
+    let object = &old.name;
+    if old.is_struct != new.is_struct {
+        changes.push(Change::ObjectKindChanged {
+            object: object.clone(),
+            is_struct: new.is_struct,
+        });
+        return;
+    }
+
+    // The struct fields have no ids (`Field::id` is their position), so they are matched by name
+    let key = |field: &Field| {
+        if old.is_struct {
+            (0, field.name.clone())
+        } else {
+            (field.id, String::new())
+        }
+    };
+    let keys: BTreeSet<(u16, String)> = old.fields.iter().chain(&new.fields).map(key).collect();
+    for field_key in keys {
+        let old_field = old.fields.iter().find(|&field| key(field) == field_key);
+        let new_field = new.fields.iter().find(|&field| key(field) == field_key);
+        match (old_field, new_field) {
+            (Some(old_field), None) => changes.push(Change::FieldRemoved {
+                object: object.clone(),
+                field: old_field.name.clone(),
+                id: old_field.id,
+            }),
+            (None, Some(new_field)) => changes.push(Change::FieldAdded {
+                object: object.clone(),
+                field: new_field.name.clone(),
+                id: new_field.id,
+            }),
+            (Some(old_field), Some(new_field)) => diff_fields(
+                object, old_schema, old_field, new_schema, new_field, changes,
+            ),
+            (None, None) => unreachable!("the key comes from one of the objects"),
+        }
+    }
+
+    if old.is_struct {
+        let layout = |schema: &Schema, object: &Object| -> Vec<(String, String, u16)> {
+            object
+                .fields_by_id()
+                .iter()
+                .map(|field| {
+                    (
+                        field.name.clone(),
+                        schema.type_name(&field.field_type),
+                        field.offset,
+                    )
+                })
+                .collect()
+        };
+        if old.bytesize != new.bytesize || layout(old_schema, old) != layout(new_schema, new) {
+            changes.push(Change::StructLayoutChanged {
+                object: object.clone(),
+            });
+        }
+    }
+}
+
+fn diff_fields(
+    object: &str,
+    old_schema: &Schema,
+    old: &Field,
+    new_schema: &Schema,
+    new: &Field,
+    changes: &mut Vec<Change>,
+) {
+    let old_type = old_schema.type_name(&old.field_type);
+    let new_type = new_schema.type_name(&new.field_type);
+    if old.name != new.name {
+        if old_type == new_type {
+            changes.push(Change::FieldRenamed {
+                object: object.to_owned(),
+                id: new.id,
+                old_name: old.name.clone(),
+                new_name: new.name.clone(),
+            });
+        } else {
+            changes.push(Change::FieldIdReused {
+                object: object.to_owned(),
+                id: new.id,
+                old_field: old.name.clone(),
+                old_type,
+                new_field: new.name.clone(),
+                new_type,
+            });
+            return;
+        }
+    } else if old_type != new_type {
+        changes.push(Change::FieldTypeChanged {
+            object: object.to_owned(),
+            field: new.name.clone(),
+            old_type,
+            new_type,
+        });
+        return;
+    }
+
+    let old_default = default_value(old_schema, old);
+    let new_default = default_value(new_schema, new);
+    if let (Some(old_default), Some(new_default)) = (old_default, new_default) {
+        if old_default != new_default {
+            changes.push(Change::FieldDefaultChanged {
+                object: object.to_owned(),
+                field: new.name.clone(),
+                old_default,
+                new_default,
+            });
+        }
+    }
+    if old.required != new.required {
+        changes.push(Change::FieldRequiredChanged {
+            object: object.to_owned(),
+            field: new.name.clone(),
+            required: new.required,
+        });
+    }
+    if !old.deprecated && new.deprecated {
+        changes.push(Change::FieldDeprecated {
+            object: object.to_owned(),
+            field: new.name.clone(),
+        });
+    }
+}
+
+/// The default value of the scalar field as written in the schemas (the enum value names are
+/// resolved)
+fn default_value(schema: &Schema, field: &Field) -> Option<String> {
+    let base_type = field.field_type.base_type;
+    if !base_type.is_scalar() {
+        return None;
+    }
+    if field.optional {
+        return Some("null".to_owned());
+    }
+    Some(match base_type {
+        BaseType::Float | BaseType::Double => field.default_real.to_string(),
+        BaseType::Bool => (field.default_integer != 0).to_string(),
+        _ => schema
+            .enumeration_of(&field.field_type)
+            .and_then(|enumeration| {
+                enumeration
+                    .values
+                    .iter()
+                    .find(|value| value.value == field.default_integer)
+            })
+            .map_or_else(
+                || field.default_integer.to_string(),
+                |value| value.name.clone(),
+            ),
+    })
+}
+
+fn diff_enums(
+    old_schema: &Schema,
+    old: &Enum,
+    new_schema: &Schema,
+    new: &Enum,
+    changes: &mut Vec<Change>,
+) {
+    let enumeration = &old.name;
+    let old_type = old_schema.type_name(&old.underlying_type);
+    let new_type = new_schema.type_name(&new.underlying_type);
+    if !old.is_union && old_type != new_type {
+        changes.push(Change::EnumTypeChanged {
+            enumeration: enumeration.clone(),
+            old_type,
+            new_type,
+        });
+    }
+
+    for old_value in &old.values {
+        match new.value(&old_value.name) {
+            None => changes.push(Change::EnumValueRemoved {
+                enumeration: enumeration.clone(),
+                name: old_value.name.clone(),
+                value: old_value.value,
+            }),
+            Some(new_value) if new_value.value != old_value.value => {
+                changes.push(Change::EnumValueChanged {
+                    enumeration: enumeration.clone(),
+                    name: old_value.name.clone(),
+                    old_value: old_value.value,
+                    new_value: new_value.value,
+                })
+            }
+            Some(_) => (),
+        }
+    }
+    for new_value in &new.values {
+        if old.value(&new_value.name).is_none() {
+            changes.push(Change::EnumValueAdded {
+                enumeration: enumeration.clone(),
+                name: new_value.name.clone(),
+                value: new_value.value,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::Result;
+
+    #[test]
+    fn diff_reports_field_level_changes() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let old_path = temp_dir.path().join("old.fbs");
+        fs::write(
+            &old_path,
+            "namespace Game;\n\
+             enum Color : ubyte { Red, Green }\n\
+             struct Vec2 { x: float; y: float; }\n\
+             table Weapon { damage: int; }\n\
+             table Monster {\n\
+               name: string (id: 0);\n\
+               hp: short = 100 (id: 1);\n\
+               mana: short (id: 2);\n\
+               level: int (id: 3);\n\
+               pos: Vec2 (id: 4);\n\
+             }\n\
+             root_type Monster;\n",
+        )?;
+        let new_path = temp_dir.path().join("new.fbs");
+        fs::write(
+            &new_path,
+            "namespace Game;\n\
+             enum Color : ubyte { Red, Green, Blue }\n\
+             struct Vec2 { x: double; y: double; }\n\
+             table Monster {\n\
+               title: string (id: 0);\n\
+               hp: short = 150 (id: 1, deprecated);\n\
+               mana: int (id: 2);\n\
+               xp: float (id: 3);\n\
+               pos: Vec2 (id: 4);\n\
+               speed: float (id: 5);\n\
+             }\n\
+             root_type Monster;\n",
+        )?;
+
+        let diff = crate::diff(&old_path, &new_path)?;
+        assert_eq!(
+            diff.to_string(),
+            "Game.Monster.name: renamed to `title` (id 0)\n\
+             Game.Monster.hp: default changed: 100 -> 150\n\
+             Game.Monster.hp: deprecated\n\
+             Game.Monster.mana: type changed: short -> int\n\
+             Game.Monster: id 3 reused: `level: int` -> `xp: float`\n\
+             Game.Monster.speed: added (id 5)\n\
+             Game.Vec2.x: type changed: float -> double\n\
+             Game.Vec2.y: type changed: float -> double\n\
+             Game.Vec2: struct layout changed\n\
+             Game.Weapon: removed\n\
+             Game.Color.Blue: added (= 2)\n"
+        );
+        assert!(crate::diff(&new_path, &new_path)?.is_empty());
+
+        Ok(())
+    }
+}
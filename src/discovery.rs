@@ -0,0 +1,771 @@
+//! Discovery of the installed `flatc` executables.
+
+use std::env;
+use std::error;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::{trace, Error, Flatc, Result, Version};
+
+/// The file name of `flatc` executable on the host platform
+pub(crate) const FLATC_EXECUTABLE: &str = if cfg!(windows) { "flatc.exe" } else { "flatc" };
+
+/// The environment variable overriding the `flatc` executable used by
+/// [`Flatc::from_env_path`]
+///
+/// [`Flatc::from_env_path`]: struct.Flatc.html#method.from_env_path
+pub const FLATC_ENV: &str = "FLATC";
+
+/// The error returned when the `flatc` executable cannot be found
+///
+/// It is returned wrapped into [`Error`] of `NotFound` kind, and its message explains how to
+/// install `flatc`.
+///
+/// [`Error`]: type.Error.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatcNotFound {
+    /// The executable which was not found
+    pub exec: PathBuf,
+}
+
+impl fmt::Display for FlatcNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "flatc executable `{}` is not found; install the FlatBuffers compiler:\n  \
+             - Debian/Ubuntu: `apt install flatbuffers-compiler`\n  \
+             - macOS: `brew install flatbuffers`\n  \
+             - conda (any platform): `conda install -c conda-forge flatbuffers`\n  \
+             - Windows: download `flatc.exe` from https://github.com/google/flatbuffers/releases\n\
+             or set {} environment variable to the path of the flatc executable",
+            self.exec.display(),
+            FLATC_ENV
+        )
+    }
+}
+
+impl error::Error for FlatcNotFound {}
+
+/// How the `flatc` executable was chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlatcSource {
+    /// [`FLATC_ENV`] environment variable
+    ///
+    /// [`FLATC_ENV`]: constant.FLATC_ENV.html
+    EnvVar,
+    /// `$PATH` (the default of [`Flatc::from_env_path`], or found there by [`Flatc::locate`])
+    ///
+    /// [`Flatc::from_env_path`]: struct.Flatc.html#method.from_env_path
+    /// [`Flatc::locate`]: struct.Flatc.html#method.locate
+    Path,
+    /// A common installation location found by [`Flatc::locate`] (see [`discover`])
+    ///
+    /// [`Flatc::locate`]: struct.Flatc.html#method.locate
+    /// [`discover`]: fn.discover.html
+    KnownLocation,
+    /// The path given to [`Flatc::from_path`]
+    ///
+    /// [`Flatc::from_path`]: struct.Flatc.html#method.from_path
+    Explicit,
+}
+
+impl fmt::Display for FlatcSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FlatcSource::EnvVar => write!(f, "{} environment variable", FLATC_ENV),
+            FlatcSource::Path => f.write_str("$PATH"),
+            FlatcSource::KnownLocation => f.write_str("common installation location"),
+            FlatcSource::Explicit => f.write_str("explicit path"),
+        }
+    }
+}
+
+/// The environment check report of [`Flatc::check_report`], e.g. to paste into a support
+/// request (see its `Display`)
+///
+/// [`Flatc::check_report`]: struct.Flatc.html#method.check_report
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckReport {
+    /// The executable as configured (e.g. `flatc`)
+    pub exec: PathBuf,
+    /// The executable file it resolves to (none if it is not found in `$PATH`)
+    pub resolved_exec: Option<PathBuf>,
+    /// How the executable was chosen
+    pub source: FlatcSource,
+    /// The version `flatc` reports
+    pub version: Version,
+    /// The generators listed by `flatc --help` (the [`Args::lang`] values, e.g. `rust` or
+    /// `binary`), if probed
+    ///
+    /// [`Args::lang`]: struct.Args.html#structfield.lang
+    pub languages: Option<Vec<String>>,
+    /// The long flags listed by `flatc --help` (e.g. `--gen-object-api`), if probed
+    pub flags: Option<Vec<String>>,
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "flatc: {} (from {})", self.exec.display(), self.source)?;
+        match self.resolved_exec {
+            Some(ref resolved_exec) => writeln!(f, "resolved: {}", resolved_exec.display())?,
+            None => writeln!(f, "resolved: not found in $PATH")?,
+        }
+        writeln!(f, "version: {}", self.version)?;
+        writeln!(f, "os: {} {}", env::consts::OS, env::consts::ARCH)?;
+        if let Some(ref languages) = self.languages {
+            writeln!(f, "languages: {}", languages.join(" "))?;
+        }
+        if let Some(ref flags) = self.flags {
+            writeln!(f, "flags: {}", flags.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The generators and the long flags listed by `flatc --help`
+///
+/// The generators are the first block of the options, which ends with a blank line.
+pub(crate) fn parse_help(help: &str) -> (Vec<String>, Vec<String>) {
+    let mut languages = Vec::new();
+    let mut flags = Vec::new();
+    let mut in_generators = true;
+    for line in help
+        .lines()
+        .skip_while(|line| !line.starts_with("Usage"))
+        .skip(1)
+    {
+        if line.trim().is_empty() {
+            in_generators = false;
+            continue;
+        }
+        let line_flags = line
+            .split_whitespace()
+            .take_while(|word| word.starts_with('-'))
+            .map(|word| word.trim_end_matches(','))
+            .filter(|word| word.starts_with("--"));
+        for flag in line_flags {
+            if in_generators {
+                languages.push(flag.trim_start_matches('-').to_owned());
+            }
+            flags.push(flag.to_owned());
+        }
+    }
+    (languages, flags)
+}
+
+/// The environment variable lookup of the discovery (`env::var_os` outside of the tests)
+pub(crate) type Lookup<'a> = &'a dyn Fn(&str) -> Option<OsString>;
+
+/// The environment of the process
+pub(crate) fn process_env(name: &str) -> Option<OsString> {
+    env::var_os(name)
+}
+
+/// The executable `exec` stands for, looking the bare names up in `$PATH` like `exec` does
+pub(crate) fn resolve_exec(exec: &Path) -> Option<PathBuf> {
+    resolve_exec_with(exec, &process_env)
+}
+
+fn resolve_exec_with(exec: &Path, lookup: Lookup) -> Option<PathBuf> {
+    if exec.components().count() > 1 {
+        return Some(exec.to_owned());
+    }
+    path_dirs(lookup)
+        .into_iter()
+        .map(|dir| dir.join(exec))
+        .find(|path| path.is_file())
+}
+
+/// The directories of `$PATH`
+fn path_dirs(lookup: Lookup) -> Vec<PathBuf> {
+    lookup("PATH")
+        .map(|path| env::split_paths(&path).collect())
+        .unwrap_or_default()
+}
+
+/// The directories where `flatc` is commonly installed besides `$PATH`
+fn known_dirs(lookup: Lookup) -> Vec<PathBuf> {
+    let mut dirs = conda_dirs(lookup("CONDA_PREFIX"));
+    if cfg!(windows) {
+        for program_files in &["ProgramFiles", "ProgramFiles(x86)"] {
+            if let Some(program_files) = lookup(program_files) {
+                dirs.push(PathBuf::from(program_files).join("flatbuffers").join("bin"));
+            }
+        }
+    } else {
+        dirs.push(PathBuf::from("/usr/local/bin"));
+        dirs.push(PathBuf::from("/usr/bin"));
+        dirs.push(PathBuf::from("/opt/homebrew/bin"));
+        dirs.push(PathBuf::from("/opt/local/bin"));
+        dirs.push(PathBuf::from("/snap/bin"));
+    }
+    if let Some(home) = lookup("HOME").or_else(|| lookup("USERPROFILE")) {
+        dirs.push(PathBuf::from(home).join(".local").join("bin"));
+    }
+    dirs.extend(vcpkg_dirs(
+        lookup("VCPKG_ROOT"),
+        lookup("VCPKG_INSTALLED_DIR"),
+        lookup("VCPKGRS_TRIPLET").or_else(|| lookup("VCPKG_DEFAULT_TRIPLET")),
+    ));
+    dirs
+}
+
+/// The executable directories of the activated conda environment (`CONDA_PREFIX`): `bin` and
+/// `Library/bin` (used on Windows)
+fn conda_dirs(conda_prefix: Option<OsString>) -> Vec<PathBuf> {
+    conda_prefix
+        .filter(|conda_prefix| !conda_prefix.is_empty())
+        .map(|conda_prefix| {
+            let conda_prefix = PathBuf::from(conda_prefix);
+            vec![
+                conda_prefix.join("bin"),
+                conda_prefix.join("Library").join("bin"),
+            ]
+        })
+        .unwrap_or_default()
+}
+
+/// The vcpkg tools directories of `flatbuffers` port: `<installed>/<triplet>/tools/flatbuffers`,
+/// where `<installed>` is `VCPKG_INSTALLED_DIR` (manifest mode) or `VCPKG_ROOT/installed`, and
+/// every installed triplet is probed unless the triplet is specified
+fn vcpkg_dirs(
+    vcpkg_root: Option<OsString>,
+    installed_dir: Option<OsString>,
+    triplet: Option<OsString>,
+) -> Vec<PathBuf> {
+    let installed_dirs = installed_dir
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(vcpkg_root.map(|root| Path::new(&root).join("installed")));
+
+    let mut dirs = Vec::new();
+    for installed_dir in installed_dirs {
+        let triplet_dirs = match triplet {
+            Some(ref triplet) => vec![installed_dir.join(triplet)],
+            None => fs::read_dir(&installed_dir)
+                .map(|entries| {
+                    let mut triplet_dirs = entries
+                        .filter_map(|entry| Some(entry.ok()?.path()))
+                        .collect::<Vec<_>>();
+                    triplet_dirs.sort();
+                    triplet_dirs
+                })
+                .unwrap_or_default(),
+        };
+        dirs.extend(
+            triplet_dirs
+                .into_iter()
+                .map(|triplet_dir| triplet_dir.join("tools").join("flatbuffers")),
+        );
+    }
+    dirs
+}
+
+/// The architecture of the executable (in the terms of the target triples, e.g. `x86_64` or
+/// `aarch64`) according to its ELF, Mach-O or PE header; `None` if it is unknown (e.g. a script
+/// or a universal Mach-O binary)
+fn exec_arch(exec: &Path) -> Option<&'static str> {
+    let mut header = Vec::new();
+    fs::File::open(exec)
+        .ok()?
+        .take(4096)
+        .read_to_end(&mut header)
+        .ok()?;
+    let u16_at = |offset: usize, big_endian: bool| {
+        let bytes = [*header.get(offset)?, *header.get(offset + 1)?];
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let u32_le_at = |offset: usize| {
+        let bytes = header.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    if header.starts_with(b"\x7fELF") {
+        return match u16_at(18, *header.get(5)? == 2)? {
+            0x03 => Some("x86"),
+            0x08 => Some("mips"),
+            0x14 | 0x15 => Some("powerpc"),
+            0x16 => Some("s390x"),
+            0x28 => Some("arm"),
+            0x3e => Some("x86_64"),
+            0xb7 => Some("aarch64"),
+            0xf3 => Some("riscv"),
+            _ => None,
+        };
+    }
+    if header.starts_with(&[0xcf, 0xfa, 0xed, 0xfe]) {
+        return match u32_le_at(4)? {
+            0x0100_0007 => Some("x86_64"),
+            0x0100_000c => Some("aarch64"),
+            _ => None,
+        };
+    }
+    if header.starts_with(b"MZ") {
+        let pe = u32_le_at(0x3c)? as usize;
+        if header.get(pe..pe + 4)? != b"PE\0\0" {
+            return None;
+        }
+        return match u16_at(pe + 4, false)? {
+            0x014c => Some("x86"),
+            0x8664 => Some("x86_64"),
+            0xaa64 => Some("aarch64"),
+            _ => None,
+        };
+    }
+    None
+}
+
+/// The architecture of the target triple in the terms of [`exec_arch`]
+fn triple_arch(triple: &str) -> &str {
+    let arch = triple.split('-').next().unwrap_or(triple);
+    match arch {
+        "i386" | "i586" | "i686" => "x86",
+        "arm64" => "aarch64",
+        _ if arch.starts_with("arm") || arch.starts_with("thumb") => "arm",
+        _ if arch.starts_with("mips") => "mips",
+        _ if arch.starts_with("powerpc") => "powerpc",
+        _ if arch.starts_with("riscv") => "riscv",
+        _ => arch,
+    }
+}
+
+/// Check whether the executable is known to be built for another architecture than the host
+/// (`host`) while cross-compiling (for `target`), e.g. a target-architecture `flatc` which would
+/// run under binfmt and qemu
+fn is_foreign_exec(exec: &Path, host: Option<&str>, target: Option<&str>) -> bool {
+    match (host, target) {
+        (Some(host), Some(target)) if host != target => {
+            exec_arch(exec).is_some_and(|arch| arch != triple_arch(host))
+        }
+        _ => false,
+    }
+}
+
+/// [`is_foreign_exec`] for the `HOST` and `TARGET` of the environment
+fn is_foreign_exec_in(exec: &Path, lookup: Lookup) -> bool {
+    let (host, target) = (lookup("HOST"), lookup("TARGET"));
+    is_foreign_exec(
+        exec,
+        host.as_ref().and_then(|host| host.to_str()),
+        target.as_ref().and_then(|target| target.to_str()),
+    )
+}
+
+/// The candidate `flatc` paths in the order of preference (without duplicates); the ones built
+/// for another architecture than the host are skipped when cross-compiling
+pub(crate) fn candidates() -> Vec<PathBuf> {
+    candidates_with(&process_env)
+}
+
+fn candidates_with(lookup: Lookup) -> Vec<PathBuf> {
+    all_candidates(lookup)
+        .into_iter()
+        .filter(|candidate| !is_foreign_exec_in(candidate, lookup))
+        .collect()
+}
+
+/// The candidate `flatc` paths regardless of their architecture
+fn all_candidates(lookup: Lookup) -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    let mut canonical_candidates: Vec<PathBuf> = Vec::new();
+    for dir in path_dirs(lookup).into_iter().chain(known_dirs(lookup)) {
+        let candidate = dir.join(FLATC_EXECUTABLE);
+        let canonical = match candidate.canonicalize() {
+            Ok(canonical) if canonical.is_file() => canonical,
+            _ => continue,
+        };
+        if !canonical_candidates.contains(&canonical) {
+            canonical_candidates.push(canonical);
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+/// Find every `flatc` in `$PATH` and the common installation locations (including the activated
+/// conda environment, see `CONDA_PREFIX`, and the vcpkg tools directories, see `VCPKG_ROOT`) with
+/// its version
+///
+/// The executables are listed in the order of preference (`$PATH` first); the ones failing to
+/// report their version are skipped.
+///
+/// # Examples
+///
+/// ```no_run
+/// for (path, version) in flatc_rust::discover() {
+///     println!("{}: {}", path.display(), version.version());
+/// }
+/// ```
+pub fn discover() -> Vec<(PathBuf, Version)> {
+    candidates()
+        .into_iter()
+        .filter_map(|path| {
+            let version = Flatc::from_path(&path).version().ok()?;
+            Some((path, version))
+        })
+        .collect()
+}
+
+/// Find a working `flatc` (see [`Flatc::locate`])
+///
+/// [`Flatc::locate`]: struct.Flatc.html#method.locate
+pub(crate) fn locate() -> Result<Flatc> {
+    locate_with(&process_env)
+}
+
+fn locate_with(lookup: Lookup) -> Result<Flatc> {
+    let _span = trace::discovery();
+    if let Some(exec) = lookup(FLATC_ENV).filter(|exec| !exec.is_empty()) {
+        trace::discovered(Some(Path::new(&exec)), 1);
+        return Ok(Flatc::from_path(exec).with_source(FlatcSource::EnvVar));
+    }
+    let candidates = candidates_with(lookup);
+    let candidate_count = candidates.len();
+    let found = candidates
+        .into_iter()
+        .find(|candidate| Flatc::from_path(candidate).check().is_ok());
+    trace::discovered(found.as_deref(), candidate_count);
+    let found = found.map(|exec| {
+        let in_path = exec
+            .parent()
+            .is_some_and(|dir| path_dirs(lookup).iter().any(|path_dir| path_dir == dir));
+        let source = if in_path {
+            FlatcSource::Path
+        } else {
+            FlatcSource::KnownLocation
+        };
+        Flatc::from_path(exec).with_source(source)
+    });
+    found.ok_or_else(|| {
+        let foreign = all_candidates(lookup)
+            .into_iter()
+            .find(|candidate| is_foreign_exec_in(candidate, lookup));
+        match foreign {
+            Some(foreign) => Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "only {} is found, which is built for {} rather than for the host ({}); \
+                     install flatc for the host or set {} environment variable to one",
+                    foreign.display(),
+                    exec_arch(&foreign).unwrap_or("another architecture"),
+                    lookup("HOST")
+                        .as_ref()
+                        .and_then(|host| host.to_str())
+                        .unwrap_or("unknown"),
+                    FLATC_ENV
+                ),
+            ),
+            None => Error::new(
+                io::ErrorKind::NotFound,
+                FlatcNotFound {
+                    exec: PathBuf::from(FLATC_EXECUTABLE),
+                },
+            ),
+        }
+    })
+}
+
+/// The `flatc` of the entry points like [`run`]: [`Flatc::from_env_path`], unless [`FLATC_ENV`]
+/// is not set and `$PATH` has no `flatc` for the host, in which case it is located in the common
+/// installation locations (see [`Flatc::locate`])
+///
+/// [`run`]: fn.run.html
+/// [`Flatc::from_env_path`]: struct.Flatc.html#method.from_env_path
+/// [`Flatc::locate`]: struct.Flatc.html#method.locate
+pub(crate) fn default_flatc() -> Result<Flatc> {
+    default_flatc_with(&process_env)
+}
+
+pub(crate) fn default_flatc_with(lookup: Lookup) -> Result<Flatc> {
+    if let Some(exec) = lookup(FLATC_ENV).filter(|exec| !exec.is_empty()) {
+        return Ok(Flatc::from_path(exec).with_source(FlatcSource::EnvVar));
+    }
+    match resolve_exec_with(Path::new(FLATC_EXECUTABLE), lookup) {
+        Some(exec) if !is_foreign_exec_in(&exec, lookup) => {
+            Ok(Flatc::from_path("flatc").with_source(FlatcSource::Path))
+        }
+        _ => locate_with(lookup),
+    }
+}
+
+/// The absolute path of the `flatc` executable [`Flatc::locate`] finds, e.g. to log which one
+/// is about to run
+///
+/// Fails with [`FlatcNotFound`] if there is none (or [`FLATC_ENV`] names a missing one).
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn try_main() -> flatc_rust::Result<()> {
+/// println!("cargo:warning=using {}", flatc_rust::which()?.display());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Flatc::locate`]: struct.Flatc.html#method.locate
+/// [`FlatcNotFound`]: struct.FlatcNotFound.html
+/// [`FLATC_ENV`]: constant.FLATC_ENV.html
+pub fn which() -> Result<PathBuf> {
+    let flatc = locate()?;
+    let exec = resolve_exec(&flatc.exec)
+        .filter(|exec| exec.is_file())
+        .ok_or_else(|| {
+            Error::new(
+                io::ErrorKind::NotFound,
+                FlatcNotFound {
+                    exec: flatc.exec.clone(),
+                },
+            )
+        })?;
+    std::path::absolute(exec)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn missing_flatc_explains_installation() {
+        let error = Flatc::from_path("/nonexistent/flatc").check().unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        let error = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<FlatcNotFound>())
+            .expect("FlatcNotFound");
+        assert_eq!(error.exec, PathBuf::from("/nonexistent/flatc"));
+        assert!(error.to_string().contains("brew install flatbuffers"));
+    }
+
+    #[test]
+    fn conda_dirs_follow_prefix() {
+        assert_eq!(
+            conda_dirs(Some("/opt/conda/envs/build".into())),
+            vec![
+                PathBuf::from("/opt/conda/envs/build/bin"),
+                PathBuf::from("/opt/conda/envs/build/Library/bin"),
+            ]
+        );
+        assert!(conda_dirs(Some("".into())).is_empty());
+        assert!(conda_dirs(None).is_empty());
+    }
+
+    #[test]
+    fn vcpkg_dirs_probe_installed_triplets() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let installed_dir = temp_dir.path().join("installed");
+        fs::create_dir_all(installed_dir.join("x64-windows"))?;
+        fs::create_dir_all(installed_dir.join("x64-windows-static"))?;
+
+        assert_eq!(
+            vcpkg_dirs(Some(temp_dir.path().into()), None, None),
+            vec![
+                installed_dir.join("x64-windows/tools/flatbuffers"),
+                installed_dir.join("x64-windows-static/tools/flatbuffers"),
+            ]
+        );
+        assert_eq!(
+            vcpkg_dirs(
+                None,
+                Some(installed_dir.clone().into()),
+                Some("arm64-windows".into())
+            ),
+            vec![installed_dir.join("arm64-windows/tools/flatbuffers")]
+        );
+        assert!(vcpkg_dirs(None, None, None).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn exec_arch_reads_headers() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let exec = temp_dir.path().join(FLATC_EXECUTABLE);
+
+        let mut elf = b"\x7fELF\x02\x01\x01".to_vec();
+        elf.resize(18, 0);
+        elf.extend_from_slice(&0xb7u16.to_le_bytes());
+        fs::write(&exec, &elf)?;
+        assert_eq!(exec_arch(&exec), Some("aarch64"));
+        assert!(is_foreign_exec(
+            &exec,
+            Some("x86_64-unknown-linux-gnu"),
+            Some("aarch64-unknown-linux-gnu")
+        ));
+        assert!(!is_foreign_exec(
+            &exec,
+            Some("x86_64-unknown-linux-gnu"),
+            Some("x86_64-unknown-linux-gnu")
+        ));
+        assert!(!is_foreign_exec(
+            &exec,
+            Some("aarch64-apple-darwin"),
+            Some("x86_64-apple-darwin")
+        ));
+
+        let mut macho = vec![0xcf, 0xfa, 0xed, 0xfe];
+        macho.extend_from_slice(&0x0100_0007u32.to_le_bytes());
+        fs::write(&exec, &macho)?;
+        assert_eq!(exec_arch(&exec), Some("x86_64"));
+
+        let mut pe = b"MZ".to_vec();
+        pe.resize(0x3c, 0);
+        pe.extend_from_slice(&0x40u32.to_le_bytes());
+        pe.extend_from_slice(b"PE\0\0");
+        pe.extend_from_slice(&0x014cu16.to_le_bytes());
+        fs::write(&exec, &pe)?;
+        assert_eq!(exec_arch(&exec), Some("x86"));
+        assert!(!is_foreign_exec(
+            &exec,
+            Some("i686-pc-windows-msvc"),
+            Some("aarch64-pc-windows-msvc")
+        ));
+
+        fs::write(&exec, "#!/bin/sh\n")?;
+        assert_eq!(exec_arch(&exec), None);
+        assert!(!is_foreign_exec(
+            &exec,
+            Some("x86_64-unknown-linux-gnu"),
+            Some("aarch64-unknown-linux-gnu")
+        ));
+
+        let system_exec = candidates().into_iter().next().expect("flatc in $PATH");
+        assert_eq!(
+            exec_arch(&system_exec),
+            Some(triple_arch(env::consts::ARCH))
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn default_flatc_falls_back_to_conda_prefix() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let conda_prefix = temp_dir.path().join("conda");
+        let marker = temp_dir.path().join("conda-flatc-was-run");
+        fs::create_dir_all(conda_prefix.join("bin"))?;
+        let exec = conda_prefix.join("bin").join(FLATC_EXECUTABLE);
+        fs::write(
+            &exec,
+            format!(
+                "#!/bin/sh\ntouch '{}'\nexec '{}' \"$@\"\n",
+                marker.display(),
+                which()?.display()
+            ),
+        )?;
+        fs::set_permissions(&exec, fs::Permissions::from_mode(0o755))?;
+        let empty_dir = temp_dir.path().join("empty");
+        fs::create_dir_all(&empty_dir)?;
+        let lookup = |name: &str| match name {
+            "PATH" => Some(empty_dir.clone().into_os_string()),
+            "CONDA_PREFIX" => Some(conda_prefix.clone().into_os_string()),
+            _ => None,
+        };
+
+        let flatc = default_flatc_with(&lookup)?;
+        assert_eq!(flatc.exec, exec);
+        assert_eq!(flatc.source, FlatcSource::KnownLocation);
+
+        let input_path = temp_dir.path().join("test.fbs");
+        fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        crate::run_with(
+            crate::Args {
+                inputs: &[&input_path],
+                out_dir: temp_dir.path(),
+                ..Default::default()
+            },
+            &lookup,
+        )?;
+        assert!(marker.exists());
+        assert!(temp_dir.path().join("test_generated.rs").exists());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn default_flatc_falls_back_to_vcpkg_tools() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let tools_dir = temp_dir
+            .path()
+            .join("installed/x64-linux/tools/flatbuffers");
+        fs::create_dir_all(&tools_dir)?;
+        let exec = tools_dir.join(FLATC_EXECUTABLE);
+        fs::copy(which()?, &exec)?;
+        let empty_dir = temp_dir.path().join("empty");
+        fs::create_dir_all(&empty_dir)?;
+        let lookup = |name: &str| match name {
+            "PATH" => Some(empty_dir.clone().into_os_string()),
+            "VCPKG_ROOT" => Some(temp_dir.path().as_os_str().to_owned()),
+            _ => None,
+        };
+
+        // The system-wide locations (e.g. `/usr/local/bin`) come first
+        assert!(candidates_with(&lookup).contains(&exec));
+        default_flatc_with(&lookup)?.check()
+    }
+
+    #[test]
+    fn locate_finds_working_flatc() -> Result<()> {
+        locate()?.check()
+    }
+
+    #[test]
+    fn which_finds_absolute_flatc() -> Result<()> {
+        let exec = which()?;
+        assert!(exec.is_absolute());
+        assert_eq!(Flatc::from_path(&exec).version()?, locate()?.version()?);
+        Ok(())
+    }
+
+    #[test]
+    fn check_report_describes_flatc() -> Result<()> {
+        let report = Flatc::from_path("flatc").check_report(false)?;
+        assert_eq!(report.source, FlatcSource::Explicit);
+        assert!(report
+            .resolved_exec
+            .as_ref()
+            .expect("resolved flatc")
+            .is_file());
+        assert_eq!(report.version, Flatc::from_env_path().version()?);
+        assert_eq!(report.languages, None);
+        assert!(report
+            .to_string()
+            .starts_with("flatc: flatc (from explicit path)\n"));
+
+        let report = Flatc::from_path("flatc").check_report(true)?;
+        let languages = report.languages.expect("languages");
+        assert!(languages.iter().any(|language| language == "rust"));
+        assert!(!languages
+            .iter()
+            .any(|language| language == "gen-object-api"));
+        let flags = report.flags.expect("flags");
+        assert!(flags.iter().any(|flag| flag == "--gen-object-api"));
+        assert!(flags.iter().any(|flag| flag == "--rust"));
+
+        assert!(Flatc::from_path("/nonexistent/flatc")
+            .check_report(false)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn discover_finds_flatc_from_path() {
+        let discovered = discover();
+
+        let version = Flatc::from_env_path().version().expect("version");
+        assert!(discovered
+            .iter()
+            .any(|(_, discovered_version)| discovered_version.version() == version.version()));
+    }
+}
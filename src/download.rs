@@ -0,0 +1,597 @@
+//! Downloading of prebuilt `flatc` binaries (requires `download` feature).
+//!
+//! Nothing downloaded is ever used unless its SHA-256 checksum matches the expected one: the
+//! checksum is not optional, and any mismatch removes the downloaded file and fails.
+//!
+//! The files are fetched with `curl`, which is available out of the box on Linux, macOS and
+//! Windows 10+.
+//!
+//! # Offline mode
+//!
+//! Network access is forbidden when [`OFFLINE_ENV`] (or Cargo's own `CARGO_NET_OFFLINE`) is set
+//! to `1`/`true`, or when [`set_offline`] is called. Use [`offline_flatc`] to get a locally
+//! available `flatc` in that case.
+//!
+//! # Prebuilt binaries
+//!
+//! [`release_url`] selects the [official release] artifact matching the version and the host
+//! triple (the x86_64 Linux build also serves musl hosts). There are no official artifacts for
+//! some hosts (e.g. aarch64 Linux), so use [`set_release_url_resolver`] to point to your own
+//! builds.
+//!
+//! # Installation
+//!
+//! [`install`] combines the above: it downloads, verifies and unpacks the release artifact into
+//! a directory (reusing the `flatc` previously installed from the same artifact, once its checksum
+//! is verified again):
+//!
+//! ```no_run
+//! // build.rs
+//! use std::path::Path;
+//!
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! let flatc = flatc_rust::Flatc::from_path(
+//!     flatc_rust::download::install(
+//!         "23.5.26",
+//!         &Path::new(&out_dir).join("flatc"),
+//!         "<SHA-256 of the release artifact for the host>",
+//!     )
+//!     .expect("flatc installation"),
+//! );
+//! ```
+//!
+//! [official release]: https://github.com/google/flatbuffers/releases
+//! [`install`]: fn.install.html
+//! [`release_url`]: fn.release_url.html
+//! [`set_release_url_resolver`]: fn.set_release_url_resolver.html
+//! [`OFFLINE_ENV`]: constant.OFFLINE_ENV.html
+//! [`set_offline`]: fn.set_offline.html
+//! [`offline_flatc`]: fn.offline_flatc.html
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::RwLock;
+
+use log::info;
+
+use crate::report::sha256_hex;
+use crate::{err_other, Error, Flatc, Result, Version};
+
+/// The environment variable which enables the offline mode when set to `1` or `true`
+pub const OFFLINE_ENV: &str = "FLATC_RUST_OFFLINE";
+
+/// The file name of `flatc` executable on the host platform
+pub const FLATC_EXECUTABLE: &str = crate::discovery::FLATC_EXECUTABLE;
+
+const OFFLINE_UNSET: u8 = 0;
+const OFFLINE_ENABLED: u8 = 1;
+const OFFLINE_DISABLED: u8 = 2;
+
+static OFFLINE: AtomicU8 = AtomicU8::new(OFFLINE_UNSET);
+
+/// Force the offline mode on or off for the current process (overrides the environment)
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(
+        if offline {
+            OFFLINE_ENABLED
+        } else {
+            OFFLINE_DISABLED
+        },
+        Ordering::Relaxed,
+    );
+}
+
+/// Check whether network access is forbidden
+pub fn is_offline() -> bool {
+    match OFFLINE.load(Ordering::Relaxed) {
+        OFFLINE_ENABLED => true,
+        OFFLINE_DISABLED => false,
+        _ => [OFFLINE_ENV, "CARGO_NET_OFFLINE"].iter().any(|name| {
+            env::var(name).is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        }),
+    }
+}
+
+/// The URL prefix of the official `flatc` releases
+pub const RELEASES_URL: &str = "https://github.com/google/flatbuffers/releases/download";
+
+/// A function resolving the download URL of `flatc` archive for `(version, triple)`
+///
+/// Returning `None` falls back to the official release artifacts.
+pub type ReleaseUrlResolver = fn(version: &str, triple: &str) -> Option<String>;
+
+static RELEASE_URL_RESOLVER: RwLock<Option<ReleaseUrlResolver>> = RwLock::new(None);
+
+/// Override the selection of the download URL (e.g. to use a mirror or your own builds for hosts
+/// without official artifacts)
+pub fn set_release_url_resolver(resolver: ReleaseUrlResolver) {
+    *RELEASE_URL_RESOLVER
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(resolver);
+}
+
+/// The name of the official release artifact of the version (e.g. `24.3.25`) for the target
+/// triple
+///
+/// The artifact names follow the layout of every release since 2.0.0: the Linux build is
+/// statically linked (so it also runs on x86_64 musl hosts) and named after the compiler of the
+/// release, and the `Mac` build is universal since 22.9.24 (and runs under Rosetta before). There
+/// are no official aarch64 Linux builds and no builds at all before 2.0.0.
+pub fn release_asset(version: &str, triple: &str) -> Option<&'static str> {
+    let version: Version = version.parse().ok()?;
+    let release = (version.major(), version.minor(), version.patch());
+    if release < (2, 0, 0) {
+        return None;
+    }
+    let mut parts = triple.split('-');
+    let arch = parts.next()?;
+    let rest: Vec<&str> = parts.collect();
+    let os_env = |os: &str| rest.contains(&os);
+    match arch {
+        "x86_64" if os_env("linux") => Some(match release {
+            (25, ..) | (26.., ..) => "Linux.flatc.binary.clang++-18.zip",
+            (24, ..) => "Linux.flatc.binary.clang++-15.zip",
+            (22, ..) | (23, ..) => "Linux.flatc.binary.clang++-12.zip",
+            _ => "Linux.flatc.binary.clang++-9.zip",
+        }),
+        "x86_64" if os_env("darwin") && release >= (22, 9, 24) => Some("MacIntel.flatc.binary.zip"),
+        "x86_64" | "aarch64" if os_env("darwin") => Some("Mac.flatc.binary.zip"),
+        "x86_64" | "i686" if os_env("windows") => Some("Windows.flatc.binary.zip"),
+        _ => None,
+    }
+}
+
+/// The download URL of `flatc` archive of the version (e.g. `24.3.25`) for the target triple
+///
+/// The resolver set with [`set_release_url_resolver`] takes precedence over the official
+/// release artifacts.
+///
+/// [`set_release_url_resolver`]: fn.set_release_url_resolver.html
+pub fn release_url(version: &str, triple: &str) -> Result<String> {
+    let resolver = *RELEASE_URL_RESOLVER
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    resolve_release_url(version, triple, resolver)
+}
+
+fn resolve_release_url(
+    version: &str,
+    triple: &str,
+    resolver: Option<ReleaseUrlResolver>,
+) -> Result<String> {
+    if let Some(url) = resolver.and_then(|resolver| resolver(version, triple)) {
+        return Ok(url);
+    }
+    let asset = release_asset(version, triple).ok_or_else(|| {
+        err_other(format!(
+            "there is no official flatc {} binary for {}; use \
+             `flatc_rust::download::set_release_url_resolver` to provide one",
+            version, triple
+        ))
+    })?;
+    Ok(format!("{}/v{}/{}", RELEASES_URL, version, asset))
+}
+
+/// The target triple of the host (`HOST` in build scripts, the triple the crate is compiled for
+/// otherwise)
+pub fn host_triple() -> String {
+    env::var("HOST").unwrap_or_else(|_| {
+        let os = if cfg!(target_os = "macos") {
+            "apple-darwin"
+        } else if cfg!(target_os = "windows") {
+            if cfg!(target_env = "gnu") {
+                "pc-windows-gnu"
+            } else {
+                "pc-windows-msvc"
+            }
+        } else if cfg!(target_os = "linux") {
+            if cfg!(target_env = "musl") {
+                "unknown-linux-musl"
+            } else {
+                "unknown-linux-gnu"
+            }
+        } else {
+            env::consts::OS
+        };
+        format!("{}-{}", env::consts::ARCH, os)
+    })
+}
+
+/// Find a `flatc` without network access: the one previously downloaded into `cache_dir`, or
+/// the one from `$PATH`
+pub fn offline_flatc(cache_dir: &Path) -> Result<Flatc> {
+    let cached = Flatc::from_path(cache_dir.join(FLATC_EXECUTABLE));
+    if cached.check().is_ok() {
+        return Ok(cached);
+    }
+    let system = Flatc::from_env_path();
+    if system.check().is_ok() {
+        return Ok(system);
+    }
+    Err(Error::new(
+        io::ErrorKind::NotFound,
+        format!(
+            "offline mode is enabled, and flatc is neither cached in {} nor available in $PATH",
+            cache_dir.display()
+        ),
+    ))
+}
+
+/// Download `url` into `destination` if (and only if) the content matches `sha256`
+///
+/// `sha256` is the hex-encoded SHA-256 checksum of the file (e.g. from the release notes or your
+/// own audited manifest). The content is downloaded into a temporary file next to
+/// `destination`, which is renamed only after the verification succeeds.
+///
+/// Fails without touching the network in the offline mode.
+pub fn fetch_verified(url: &str, destination: &Path, sha256: &str) -> Result<()> {
+    let expected = parse_sha256(sha256)?;
+    if is_offline() {
+        return Err(err_other(format!(
+            "refusing to download {} in the offline mode",
+            url
+        )));
+    }
+
+    let mut partial = OsString::from(destination.as_os_str());
+    partial.push(".part");
+    let partial = Path::new(&partial);
+
+    let mut cmd = process::Command::new("curl");
+    cmd.args([
+        "--fail",
+        "--silent",
+        "--show-error",
+        "--location",
+        "--output",
+    ])
+    .arg(partial)
+    .arg(url)
+    .stdin(process::Stdio::null());
+    info!("spawning command {:?}", cmd);
+    let status = cmd
+        .status()
+        .map_err(|e| err_other(format!("failed to spawn `{:?}`: {}", cmd, e)))?;
+    if !status.success() {
+        let _ = fs::remove_file(partial);
+        return Err(err_other(format!("failed to download {}", url)));
+    }
+
+    if let Err(e) = verify_file(partial, &expected) {
+        let _ = fs::remove_file(partial);
+        return Err(e);
+    }
+    fs::rename(partial, destination)
+}
+
+/// Verify that the SHA-256 checksum of the file content matches the expected one
+pub fn verify_file(path: &Path, sha256: &str) -> Result<()> {
+    let expected = parse_sha256(sha256)?;
+    let actual = sha256_hex(&fs::read(path)?);
+    if actual != expected {
+        return Err(err_other(format!(
+            "checksum mismatch for {}: expected SHA-256 {}, got {}",
+            path.display(),
+            expected,
+            actual
+        )));
+    }
+    Ok(())
+}
+
+/// Install `flatc` of the version for the host into `dir` and return the path of the executable
+///
+/// The release artifact (see [`release_url`]) is downloaded with [`fetch_verified`], so its
+/// `sha256` checksum is required, and unpacked with `unzip` (`tar` on Windows). The checksums of
+/// the artifact and of the unpacked executable are recorded next to it (see
+/// [`INSTALL_RECORD`]), so if `dir` already contains `flatc` of the version installed from the
+/// artifact with the same `sha256`, it is returned without touching the network (so it works in
+/// the offline mode). The reused executable is never run unless its checksum still matches the
+/// recorded one, and a record with another artifact checksum fails the installation.
+///
+/// [`release_url`]: fn.release_url.html
+/// [`fetch_verified`]: fn.fetch_verified.html
+/// [`INSTALL_RECORD`]: constant.INSTALL_RECORD.html
+pub fn install(version: &str, dir: &Path, sha256: &str) -> Result<PathBuf> {
+    let sha256 = parse_sha256(sha256)?;
+    let exec = dir.join(FLATC_EXECUTABLE);
+    if let Some(record) = InstallRecord::read(dir)? {
+        if record.version == version {
+            record.verify(&exec, &sha256)?;
+            return Ok(exec);
+        }
+    }
+
+    let url = release_url(version, &host_triple())?;
+    fs::create_dir_all(dir)?;
+    let _ = fs::remove_file(dir.join(INSTALL_RECORD));
+    let archive = dir.join(format!("flatc-{}.zip", version));
+    fetch_verified(&url, &archive, &sha256)?;
+    let unpacked = unpack(&archive, dir);
+    let _ = fs::remove_file(&archive);
+    unpacked?;
+
+    let is_installed = Flatc::from_path(&exec)
+        .version()
+        .is_ok_and(|installed| installed.version() == version);
+    if !is_installed {
+        return Err(err_other(format!(
+            "{} does not contain flatc {} for the host",
+            url, version
+        )));
+    }
+    InstallRecord {
+        version: version.to_owned(),
+        archive_sha256: sha256,
+        exec_sha256: sha256_hex(&fs::read(&exec)?),
+    }
+    .write(dir)?;
+    Ok(exec)
+}
+
+/// The file [`install`] records the version and the checksums of the installed `flatc` into
+///
+/// [`install`]: fn.install.html
+pub const INSTALL_RECORD: &str = "flatc.sha256";
+
+/// The version and the checksums of the `flatc` installed into a directory
+#[derive(Debug, PartialEq)]
+struct InstallRecord {
+    version: String,
+    archive_sha256: String,
+    exec_sha256: String,
+}
+
+impl InstallRecord {
+    /// Read the record of the directory (none if there is no record)
+    fn read(dir: &Path) -> Result<Option<InstallRecord>> {
+        let path = dir.join(INSTALL_RECORD);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut version = None;
+        let mut archive_sha256 = None;
+        let mut exec_sha256 = None;
+        for line in content.lines() {
+            match line.split_once(' ') {
+                Some(("version", value)) => version = Some(value.to_owned()),
+                Some(("archive", value)) => archive_sha256 = Some(parse_sha256(value)?),
+                Some(("executable", value)) => exec_sha256 = Some(parse_sha256(value)?),
+                _ => (),
+            }
+        }
+        match (version, archive_sha256, exec_sha256) {
+            (Some(version), Some(archive_sha256), Some(exec_sha256)) => Ok(Some(InstallRecord {
+                version,
+                archive_sha256,
+                exec_sha256,
+            })),
+            _ => Err(err_other(format!("{} is malformed", path.display()))),
+        }
+    }
+
+    fn write(&self, dir: &Path) -> Result<()> {
+        fs::write(
+            dir.join(INSTALL_RECORD),
+            format!(
+                "version {}\narchive {}\nexecutable {}\n",
+                self.version, self.archive_sha256, self.exec_sha256
+            ),
+        )
+    }
+
+    /// Check that the executable was installed from the artifact with the checksum and has not
+    /// changed since
+    fn verify(&self, exec: &Path, archive_sha256: &str) -> Result<()> {
+        if self.archive_sha256 != archive_sha256 {
+            return Err(err_other(format!(
+                "flatc {} in {} is installed from the release artifact with SHA-256 {}, not {}",
+                self.version,
+                exec.display(),
+                self.archive_sha256,
+                archive_sha256
+            )));
+        }
+        verify_file(exec, &self.exec_sha256)
+    }
+}
+
+/// Unpack the zip archive into the directory
+fn unpack(archive: &Path, dir: &Path) -> Result<()> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = process::Command::new("tar");
+        cmd.arg("-xf").arg(archive).arg("-C").arg(dir);
+        cmd
+    } else {
+        let mut cmd = process::Command::new("unzip");
+        cmd.args(["-o", "-q"]).arg(archive).arg("-d").arg(dir);
+        cmd
+    };
+    cmd.stdin(process::Stdio::null());
+    info!("spawning command {:?}", cmd);
+    let status = cmd
+        .status()
+        .map_err(|e| err_other(format!("failed to spawn `{:?}`: {}", cmd, e)))?;
+    if !status.success() {
+        return Err(err_other(format!("failed to unpack {}", archive.display())));
+    }
+    Ok(())
+}
+
+/// Normalize the hex-encoded SHA-256 checksum
+pub(crate) fn parse_sha256(sha256: &str) -> Result<String> {
+    let sha256 = sha256.trim().to_ascii_lowercase();
+    if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(err_other(format!(
+            "`{}` is not a hex-encoded SHA-256 checksum",
+            sha256
+        )));
+    }
+    Ok(sha256)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn verify_file_fails_closed() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let path = temp_dir.path().join("flatc.zip");
+        fs::write(&path, b"")?;
+
+        verify_file(
+            &path,
+            "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855",
+        )?;
+        assert!(verify_file(
+            &path,
+            "0000000000000000000000000000000000000000000000000000000000000000"
+        )
+        .is_err());
+        assert!(verify_file(&path, "").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn offline_mode_forbids_downloads() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let destination = temp_dir.path().join("flatc.zip");
+
+        set_offline(true);
+        let result = fetch_verified(
+            "https://example.com/flatc.zip",
+            &destination,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        );
+        set_offline(false);
+
+        assert!(result.unwrap_err().to_string().contains("offline mode"));
+        assert!(!destination.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn install_reuses_verified_flatc() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let system_exec = crate::discovery::candidates()
+            .into_iter()
+            .next()
+            .expect("flatc in $PATH");
+        let version = Flatc::from_path(&system_exec).version()?;
+        let exec = temp_dir.path().join(FLATC_EXECUTABLE);
+        fs::copy(&system_exec, &exec)?;
+        let archive_sha256 = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        InstallRecord {
+            version: version.version().to_owned(),
+            archive_sha256: archive_sha256.to_owned(),
+            exec_sha256: sha256_hex(&fs::read(&exec)?),
+        }
+        .write(temp_dir.path())?;
+
+        assert_eq!(
+            install(version.version(), temp_dir.path(), archive_sha256)?,
+            exec
+        );
+
+        let error = install(
+            version.version(),
+            temp_dir.path(),
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("not 0000"));
+
+        fs::write(&exec, b"#!/bin/sh\n")?;
+        let error = install(version.version(), temp_dir.path(), archive_sha256).unwrap_err();
+        assert!(error.to_string().contains("checksum mismatch"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn release_asset_follows_release_layout() {
+        let linux = "x86_64-unknown-linux-gnu";
+        assert_eq!(release_asset("1.12.0", linux), None);
+        assert_eq!(
+            release_asset("2.0.0", linux),
+            Some("Linux.flatc.binary.clang++-9.zip")
+        );
+        assert_eq!(
+            release_asset("23.5.26", linux),
+            Some("Linux.flatc.binary.clang++-12.zip")
+        );
+        assert_eq!(
+            release_asset("24.3.25", linux),
+            Some("Linux.flatc.binary.clang++-15.zip")
+        );
+        assert_eq!(
+            release_asset("25.2.10", linux),
+            Some("Linux.flatc.binary.clang++-18.zip")
+        );
+        assert_eq!(
+            release_asset("24.3.25", "x86_64-unknown-linux-musl"),
+            Some("Linux.flatc.binary.clang++-15.zip")
+        );
+        assert_eq!(release_asset("24.3.25", "aarch64-unknown-linux-gnu"), None);
+        assert_eq!(release_asset("24.3.25", "aarch64-unknown-linux-musl"), None);
+
+        assert_eq!(
+            release_asset("2.0.0", "x86_64-apple-darwin"),
+            Some("Mac.flatc.binary.zip")
+        );
+        assert_eq!(
+            release_asset("23.5.26", "x86_64-apple-darwin"),
+            Some("MacIntel.flatc.binary.zip")
+        );
+        assert_eq!(
+            release_asset("23.5.26", "aarch64-apple-darwin"),
+            Some("Mac.flatc.binary.zip")
+        );
+        assert_eq!(
+            release_asset("23.5.26", "x86_64-pc-windows-msvc"),
+            Some("Windows.flatc.binary.zip")
+        );
+        assert_eq!(release_asset("v23.5.26", linux), None);
+    }
+
+    #[test]
+    fn release_url_prefers_resolver() -> Result<()> {
+        assert_eq!(
+            resolve_release_url("23.5.26", "x86_64-unknown-linux-gnu", None)?,
+            "https://github.com/google/flatbuffers/releases/download/v23.5.26/\
+             Linux.flatc.binary.clang++-12.zip"
+        );
+        assert!(resolve_release_url("24.3.25", "aarch64-unknown-linux-musl", None).is_err());
+
+        let resolver: ReleaseUrlResolver = |version, triple| {
+            if triple.starts_with("aarch64-unknown-linux") {
+                Some(format!(
+                    "https://mirror.example.com/flatc-{}-{}.zip",
+                    version, triple
+                ))
+            } else {
+                None
+            }
+        };
+        assert_eq!(
+            resolve_release_url("24.3.25", "aarch64-unknown-linux-musl", Some(resolver))?,
+            "https://mirror.example.com/flatc-24.3.25-aarch64-unknown-linux-musl.zip"
+        );
+        assert_eq!(
+            resolve_release_url("24.3.25", "aarch64-apple-darwin", Some(resolver))?,
+            "https://github.com/google/flatbuffers/releases/download/v24.3.25/Mac.flatc.binary.zip"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+//! Compilation of the schemas embedded into the binary (e.g. with `include_bytes!`) or generated
+//! in memory, rather than read from the file system.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! # fn try_main() -> flatc_rust::Result<()> {
+//! // Usually `include_bytes!("schemas/monster.fbs")` etc.
+//! const SCHEMAS: &[(&str, &[u8])] = &[
+//!     ("monster.fbs", b"include \"common/weapon.fbs\"; table Monster { weapon: Weapon; }"),
+//!     ("common/weapon.fbs", b"table Weapon { damage: int; }"),
+//! ];
+//!
+//! flatc_rust::Flatc::from_env_path().run_embedded(
+//!     SCHEMAS,
+//!     flatc_rust::Args {
+//!         out_dir: Path::new("target/flatbuffers/"),
+//!         ..Default::default()
+//!     },
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The schemas are written into a managed temporary directory under their names (relative
+//! paths), which is added to the include directories, so the `include` statements resolve like
+//! they do in the original tree.
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::temp::TempDir;
+use crate::{Args, Error, Flatc, Result};
+
+/// The embedded schemas written into a temporary directory, which is removed on drop
+pub struct SchemaTree {
+    dir: TempDir,
+    paths: Vec<PathBuf>,
+}
+
+impl SchemaTree {
+    /// Write the `(name, content)` pairs into a new temporary directory; the names are the
+    /// relative paths of the schemas (e.g. `common/weapon.fbs`)
+    pub fn new(schemas: &[(&str, &[u8])]) -> Result<SchemaTree> {
+        let dir = TempDir::new()?;
+        let mut paths = Vec::with_capacity(schemas.len());
+        for &(name, content) in schemas {
+            let is_relative = !name.is_empty()
+                && Path::new(name)
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_)));
+            if !is_relative {
+                return Err(Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "embedded schema name `{}` has to be a relative path inside the tree",
+                        name
+                    ),
+                ));
+            }
+            let path = dir.path().join(name);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, content)?;
+            paths.push(path);
+        }
+        Ok(SchemaTree { dir, paths })
+    }
+
+    /// The root of the tree, i.e. the include directory of the schemas
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// The paths of the written schemas in the order of the pairs
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+}
+
+impl Flatc {
+    /// Compile the embedded `(name, content)` schemas (see [`embedded`]): every schema is an
+    /// input (`args.inputs` are ignored), and the tree is searched for includes before
+    /// `args.includes`
+    ///
+    /// To compile only some of the schemas (including the others), use [`SchemaTree`] with
+    /// [`run`].
+    ///
+    /// [`embedded`]: embedded/index.html
+    /// [`SchemaTree`]: embedded/struct.SchemaTree.html
+    /// [`run`]: #method.run
+    pub fn run_embedded(&self, schemas: &[(&str, &[u8])], args: Args) -> Result<()> {
+        let tree = SchemaTree::new(schemas)?;
+        let inputs: Vec<&Path> = tree.paths().iter().map(PathBuf::as_path).collect();
+        let mut includes = vec![tree.root()];
+        includes.extend_from_slice(args.includes);
+        self.run(Args {
+            inputs: &inputs,
+            includes: &includes,
+            ..args
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn run_embedded_preserves_include_structure() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let schemas: &[(&str, &[u8])] = &[
+            (
+                "schemas/monster.fbs",
+                b"include \"common/weapon.fbs\";\ntable Monster { weapon: Weapon; }",
+            ),
+            ("common/weapon.fbs", b"table Weapon { damage: int; }"),
+        ];
+        let args = Args {
+            out_dir: temp_dir.path(),
+            quiet: true,
+            ..Default::default()
+        };
+
+        Flatc::from_env_path().run_embedded(schemas, args)?;
+        assert!(temp_dir.path().join("monster_generated.rs").is_file());
+        assert!(temp_dir.path().join("weapon_generated.rs").is_file());
+
+        let error = Flatc::from_env_path()
+            .run_embedded(&[("../escape.fbs", b"")], args)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        Ok(())
+    }
+}
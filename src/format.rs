@@ -0,0 +1,425 @@
+//! Formatting of `.fbs` schemas (`cargo flatc fmt`).
+//!
+//! ```
+//! let formatted = flatc_rust::format::format_schema(
+//!     "include \"b.fbs\";include \"a.fbs\";\ntable Monster{hp:short=100(required,id:1);}",
+//! )
+//! .expect("schema");
+//! assert_eq!(
+//!     formatted,
+//!     "include \"a.fbs\";\ninclude \"b.fbs\";\n\
+//!      table Monster {\n  hp: short = 100 (id: 1, required);\n}\n",
+//! );
+//! ```
+//!
+//! The formatting is built on the [`schema`] parser, so only the syntactically valid schemas are
+//! formatted:
+//!
+//! * Every statement, field and enum value is put on its own line, indented by two spaces, with
+//!   the single spaces between the tokens (`name: type = default (metadata)`, `enum Name : type`)
+//!   and at most one blank line kept between them.
+//! * The consecutive `include` statements are sorted by the path, the consecutive `attribute`
+//!   declarations by the name, and the metadata attributes of every declaration, field and enum
+//!   value by the name.
+//! * The comments stay with the statements they precede (or trail on the same line).
+//!
+//! [`schema`]: ../schema/index.html
+
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use crate::schema::{parse_statements, tokenize_with_comments, Spanned, Token};
+use crate::{err_other, Result};
+
+const INDENT: &str = "  ";
+
+/// Format the schema source
+pub fn format_schema(source: &str) -> Result<String> {
+    parse_statements(source)?;
+    let mut items = split_items(source, &tokenize_with_comments(source)?);
+    for run in items.chunk_by_mut(|a, b| a.kind == b.kind) {
+        if run[0].kind == ItemKind::Other {
+            continue;
+        }
+        let blank_before = run[0].blank_before;
+        run.sort_by(|a, b| a.key.cmp(&b.key));
+        for (index, item) in run.iter_mut().enumerate() {
+            item.blank_before = index == 0 && blank_before;
+        }
+    }
+
+    let mut printer = Printer::default();
+    for item in &items {
+        printer.item(item);
+    }
+    if !printer.out.is_empty() {
+        printer.out.push('\n');
+    }
+    Ok(printer.out)
+}
+
+/// Format the schema files in place, returns the files which were not formatted
+///
+/// With `check` the files are left intact, so the result lists the files which need formatting.
+pub fn format_files<P: AsRef<Path>>(paths: &[P], check: bool) -> Result<Vec<PathBuf>> {
+    let mut unformatted_files = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        let formatted =
+            format_schema(&source).map_err(|e| err_other(format!("{}: {}", path.display(), e)))?;
+        if formatted != source {
+            if !check {
+                fs::write(path, formatted)?;
+            }
+            unformatted_files.push(path.to_owned());
+        }
+    }
+    Ok(unformatted_files)
+}
+
+/// A token with its source text and the number of the line breaks before it
+struct Lexeme<'a> {
+    token: Token,
+    text: &'a str,
+    newlines_before: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ItemKind {
+    Include,
+    Attribute,
+    Other,
+}
+
+/// A top-level statement with its leading and trailing comments (or a standalone comment block)
+struct Item<'a> {
+    kind: ItemKind,
+    /// The included file or the declared attribute
+    key: String,
+    is_rpc_service: bool,
+    blank_before: bool,
+    lexemes: Vec<Lexeme<'a>>,
+}
+
+impl<'a> Item<'a> {
+    fn new(lexemes: Vec<Lexeme<'a>>) -> Item<'a> {
+        let mut statement = lexemes
+            .iter()
+            .skip_while(|lexeme| lexeme.token == Token::Comment);
+        let keyword = statement.next().map(|lexeme| &lexeme.token);
+        let kind = match keyword {
+            Some(Token::Word(word)) if word == "include" => ItemKind::Include,
+            Some(Token::Word(word)) if word == "attribute" => ItemKind::Attribute,
+            _ => ItemKind::Other,
+        };
+        let key = match statement.next().map(|lexeme| &lexeme.token) {
+            Some(Token::Word(key)) | Some(Token::Str(key)) => key.clone(),
+            _ => String::new(),
+        };
+        Item {
+            kind,
+            key,
+            is_rpc_service: keyword == Some(&Token::Word("rpc_service".to_owned())),
+            blank_before: lexemes[0].newlines_before > 1,
+            lexemes,
+        }
+    }
+}
+
+/// Split the tokens into the top-level items
+fn split_items<'a>(source: &'a str, tokens: &[Spanned]) -> Vec<Item<'a>> {
+    let mut previous_end = 0;
+    let mut lexemes = tokens
+        .iter()
+        .map(|spanned| {
+            let lexeme = Lexeme {
+                token: spanned.token.clone(),
+                text: source[spanned.span.clone()].trim_end(),
+                newlines_before: source[previous_end..spanned.span.start]
+                    .matches('\n')
+                    .count(),
+            };
+            previous_end = spanned.span.end;
+            lexeme
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .peekable();
+
+    let mut items = Vec::new();
+    let mut current = Vec::new();
+    let mut depth = 0;
+    while let Some(lexeme) = lexemes.next() {
+        let has_statement = current
+            .iter()
+            .any(|lexeme: &Lexeme| lexeme.token != Token::Comment);
+        if !has_statement {
+            if !current.is_empty() && lexeme.newlines_before > 1 {
+                // The comments separated by a blank line do not belong to the statement
+                items.push(Item::new(mem::take(&mut current)));
+            }
+            if lexeme.token == Token::Punct(';') {
+                continue;
+            }
+        }
+        match lexeme.token {
+            Token::Punct('{') => depth += 1,
+            Token::Punct('}') => depth -= 1,
+            _ => (),
+        }
+        let ends_statement = depth == 0 && matches!(lexeme.token, Token::Punct(';' | '}'));
+        current.push(lexeme);
+        if ends_statement {
+            while let Some(comment) = lexemes
+                .next_if(|lexeme| lexeme.token == Token::Comment && lexeme.newlines_before == 0)
+            {
+                current.push(comment);
+            }
+            items.push(Item::new(mem::take(&mut current)));
+        }
+    }
+    if !current.is_empty() {
+        items.push(Item::new(current));
+    }
+    items
+}
+
+/// The sorted metadata attributes up to the closing parenthesis and the number of the lexemes
+/// they take (none if there are comments inside)
+fn sorted_metadata(lexemes: &[Lexeme]) -> Option<(String, usize)> {
+    let end = lexemes
+        .iter()
+        .position(|lexeme| lexeme.token == Token::Punct(')'))?;
+    let inner = &lexemes[..end];
+    if inner.iter().any(|lexeme| lexeme.token == Token::Comment) {
+        return None;
+    }
+    let mut attributes: Vec<(&str, String)> = inner
+        .split(|lexeme| lexeme.token == Token::Punct(','))
+        .filter(|attribute| !attribute.is_empty())
+        .map(|attribute| {
+            let name = attribute[0].text;
+            match attribute.get(2) {
+                Some(value) => (name, format!("{}: {}", name, value.text)),
+                None => (name, name.to_owned()),
+            }
+        })
+        .collect();
+    attributes.sort_by(|a, b| a.0.cmp(b.0));
+    let attributes: Vec<String> = attributes
+        .into_iter()
+        .map(|(_, attribute)| attribute)
+        .collect();
+    Some((attributes.join(", "), end + 1))
+}
+
+#[derive(Default)]
+struct Printer {
+    out: String,
+    /// The open brackets
+    nesting: Vec<char>,
+    /// The next token starts a new line, unless it attaches to the previous one (e.g. `;`) or
+    /// is a trailing comment
+    line_break: bool,
+}
+
+impl Printer {
+    fn item(&mut self, item: &Item) {
+        self.line_break = !self.out.is_empty();
+        let mut previous: Option<&Token> = None;
+        // The parentheses seen in the current method of the RPC service
+        let mut method_parens = 0;
+        let mut index = 0;
+        while index < item.lexemes.len() {
+            let lexeme = &item.lexemes[index];
+            let blank_before = if index == 0 {
+                item.blank_before
+            } else {
+                lexeme.newlines_before > 1
+            };
+            index += 1;
+
+            match lexeme.token {
+                Token::Comment => {
+                    if previous.is_some() && lexeme.newlines_before == 0 {
+                        self.out.push(' ');
+                    } else {
+                        self.start_line(blank_before);
+                    }
+                    self.out.push_str(lexeme.text);
+                    if lexeme.text.starts_with("//") {
+                        self.line_break = true;
+                    }
+                }
+                Token::Punct('}') => {
+                    self.nesting.pop();
+                    if previous == Some(&Token::Punct('{')) {
+                        self.line_break = false;
+                    } else {
+                        self.start_line(false);
+                    }
+                    self.out.push('}');
+                    self.line_break = true;
+                }
+                Token::Punct(c @ (';' | ',' | ')' | ']')) => {
+                    if previous == Some(&Token::Comment) && self.line_break {
+                        self.start_line(false);
+                    }
+                    self.line_break = false;
+                    if c == ')' || c == ']' {
+                        self.nesting.pop();
+                    }
+                    self.out.push(c);
+                    let in_braces = self.nesting.last() == Some(&'{');
+                    self.line_break = c == ';' || (c == ',' && in_braces);
+                    if c == ';' && self.nesting.len() == 1 {
+                        method_parens = 0;
+                    }
+                }
+                ref token => {
+                    let is_method_parameter = item.is_rpc_service
+                        && self.nesting == ['{']
+                        && method_parens == 0
+                        && *token == Token::Punct('(');
+                    if self.line_break
+                        || (previous == Some(&Token::Comment) && lexeme.newlines_before > 0)
+                    {
+                        self.start_line(blank_before);
+                    } else if let Some(previous) = previous {
+                        if self.space_between(previous, token, is_method_parameter) {
+                            self.out.push(' ');
+                        }
+                    }
+                    match *token {
+                        Token::Punct('(') if !is_method_parameter => {
+                            if let Some((metadata, length)) =
+                                sorted_metadata(&item.lexemes[index..])
+                            {
+                                self.out.push('(');
+                                self.out.push_str(&metadata);
+                                self.out.push(')');
+                                index += length;
+                                previous = Some(&item.lexemes[index - 1].token);
+                                continue;
+                            }
+                            self.nesting.push('(');
+                        }
+                        Token::Punct(c @ ('{' | '(' | '[')) => {
+                            if c == '(' {
+                                method_parens += 1;
+                            }
+                            self.nesting.push(c);
+                            self.line_break = c == '{';
+                        }
+                        _ => (),
+                    }
+                    self.out.push_str(lexeme.text);
+                }
+            }
+            previous = Some(&lexeme.token);
+        }
+    }
+
+    /// Whether the tokens on the same line are separated by a space
+    fn space_between(&self, previous: &Token, next: &Token, is_method_parameter: bool) -> bool {
+        let innermost = self.nesting.last().copied();
+        match (previous, next) {
+            (Token::Punct('(' | '['), _) => false,
+            (Token::Punct(':'), _) => innermost != Some('['),
+            // Only the underlying types of the enums and unions are spaced, e.g. `Color : byte`
+            (_, Token::Punct(':')) => innermost.is_none(),
+            (_, Token::Punct('(')) => !is_method_parameter,
+            _ => true,
+        }
+    }
+
+    fn start_line(&mut self, blank_before: bool) {
+        self.line_break = false;
+        if self.out.is_empty() {
+            return;
+        }
+        if blank_before && !self.out.ends_with('{') {
+            self.out.push('\n');
+        }
+        self.out.push('\n');
+        let depth = self.nesting.iter().filter(|&&c| c == '{').count();
+        self.out.push_str(&INDENT.repeat(depth));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn format_schema_normalizes_layout_and_ordering() -> Result<()> {
+        let source = r#"// Header
+
+include "weapons.fbs"; include "common.fbs";  // trailing
+attribute "priority";
+namespace MyGame.Sample;
+enum Color:byte{Red=1,Green,Blue=3}
+/// The monster
+table Monster(priority:1){
+    pos:Vec3;
+
+
+    hp:short=100(required,id:1);
+    inventory:[ubyte:16] (id:2);   // bytes
+    path:[Vec3]=[];
+    empty:Empty;
+}
+table Empty {};
+rpc_service MonsterStorage {
+  Store(Monster):Monster(streaming:"none");
+}
+root_type Monster;
+"#;
+        let expected = r#"// Header
+
+include "common.fbs"; // trailing
+include "weapons.fbs";
+attribute "priority";
+namespace MyGame.Sample;
+enum Color : byte {
+  Red = 1,
+  Green,
+  Blue = 3
+}
+/// The monster
+table Monster (priority: 1) {
+  pos: Vec3;
+
+  hp: short = 100 (id: 1, required);
+  inventory: [ubyte:16] (id: 2); // bytes
+  path: [Vec3] = [];
+  empty: Empty;
+}
+table Empty {}
+rpc_service MonsterStorage {
+  Store(Monster): Monster (streaming: "none");
+}
+root_type Monster;
+"#;
+        assert_eq!(format_schema(source)?, expected);
+        assert_eq!(format_schema(expected)?, expected);
+        assert!(format_schema("table Monster { hp short; }").is_err());
+
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let formatted_path = temp_dir.path().join("formatted.fbs");
+        let unformatted_path = temp_dir.path().join("unformatted.fbs");
+        fs::write(&formatted_path, expected)?;
+        fs::write(&unformatted_path, source)?;
+        let paths = [&formatted_path, &unformatted_path];
+
+        assert_eq!(format_files(&paths, true)?, vec![unformatted_path.clone()]);
+        assert_eq!(fs::read_to_string(&unformatted_path)?, source);
+        assert_eq!(format_files(&paths, false)?, vec![unformatted_path.clone()]);
+        assert_eq!(fs::read_to_string(&unformatted_path)?, expected);
+        assert!(format_files(&paths, true)?.is_empty());
+
+        Ok(())
+    }
+}
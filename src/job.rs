@@ -0,0 +1,145 @@
+//! Windows job objects restricting the `flatc` processes (see [`limits`] and [`sandbox`]).
+//!
+//! [`limits`]: ../limits/index.html
+//! [`sandbox`]: ../sandbox/index.html
+#![allow(unsafe_code, non_snake_case, clippy::upper_case_acronyms)]
+
+use std::ffi::c_void;
+use std::io;
+use std::mem;
+use std::os::windows::io::AsRawHandle;
+use std::process;
+use std::ptr;
+
+type HANDLE = *mut c_void;
+type BOOL = i32;
+
+#[cfg(feature = "sandbox")]
+const JOB_OBJECT_BASIC_UI_RESTRICTIONS_CLASS: i32 = 4;
+const JOB_OBJECT_EXTENDED_LIMIT_INFORMATION_CLASS: i32 = 9;
+
+#[repr(C)]
+pub(crate) struct JOBOBJECT_BASIC_LIMIT_INFORMATION {
+    pub(crate) PerProcessUserTimeLimit: i64,
+    pub(crate) PerJobUserTimeLimit: i64,
+    pub(crate) LimitFlags: u32,
+    pub(crate) MinimumWorkingSetSize: usize,
+    pub(crate) MaximumWorkingSetSize: usize,
+    pub(crate) ActiveProcessLimit: u32,
+    pub(crate) Affinity: usize,
+    pub(crate) PriorityClass: u32,
+    pub(crate) SchedulingClass: u32,
+}
+
+#[repr(C)]
+pub(crate) struct IO_COUNTERS {
+    pub(crate) ReadOperationCount: u64,
+    pub(crate) WriteOperationCount: u64,
+    pub(crate) OtherOperationCount: u64,
+    pub(crate) ReadTransferCount: u64,
+    pub(crate) WriteTransferCount: u64,
+    pub(crate) OtherTransferCount: u64,
+}
+
+#[repr(C)]
+pub(crate) struct JOBOBJECT_EXTENDED_LIMIT_INFORMATION {
+    pub(crate) BasicLimitInformation: JOBOBJECT_BASIC_LIMIT_INFORMATION,
+    pub(crate) IoInfo: IO_COUNTERS,
+    pub(crate) ProcessMemoryLimit: usize,
+    pub(crate) JobMemoryLimit: usize,
+    pub(crate) PeakProcessMemoryUsed: usize,
+    pub(crate) PeakJobMemoryUsed: usize,
+}
+
+#[cfg(feature = "sandbox")]
+#[repr(C)]
+struct JOBOBJECT_BASIC_UI_RESTRICTIONS {
+    UIRestrictionsClass: u32,
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn CreateJobObjectW(attributes: *mut c_void, name: *const u16) -> HANDLE;
+    fn SetInformationJobObject(
+        job: HANDLE,
+        class: i32,
+        information: *const c_void,
+        length: u32,
+    ) -> BOOL;
+    fn AssignProcessToJobObject(job: HANDLE, process: HANDLE) -> BOOL;
+    fn CloseHandle(handle: HANDLE) -> BOOL;
+}
+
+/// An anonymous job object, which outlives its handle as long as a process assigned to it runs
+pub(crate) struct Job {
+    handle: HANDLE,
+}
+
+impl Job {
+    pub(crate) fn new() -> io::Result<Job> {
+        // SAFETY: no security attributes and no name
+        let handle = unsafe { CreateJobObjectW(ptr::null_mut(), ptr::null()) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Job { handle })
+    }
+
+    /// An empty `JOBOBJECT_EXTENDED_LIMIT_INFORMATION` to fill for [`Job::set_limits`]
+    pub(crate) fn no_limits() -> JOBOBJECT_EXTENDED_LIMIT_INFORMATION {
+        // SAFETY: the structure is plain integers, for which zeros mean no limits
+        unsafe { mem::zeroed() }
+    }
+
+    pub(crate) fn set_limits(
+        &self,
+        limits: &JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+    ) -> io::Result<()> {
+        self.set_information(JOB_OBJECT_EXTENDED_LIMIT_INFORMATION_CLASS, limits)
+    }
+
+    #[cfg(feature = "sandbox")]
+    /// Restrict the user interface access (`JOB_OBJECT_UILIMIT_*` flags)
+    pub(crate) fn set_ui_restrictions(&self, restrictions: u32) -> io::Result<()> {
+        self.set_information(
+            JOB_OBJECT_BASIC_UI_RESTRICTIONS_CLASS,
+            &JOBOBJECT_BASIC_UI_RESTRICTIONS {
+                UIRestrictionsClass: restrictions,
+            },
+        )
+    }
+
+    pub(crate) fn assign(&self, child: &process::Child) -> io::Result<()> {
+        // SAFETY: both handles are valid for the duration of the call
+        if unsafe { AssignProcessToJobObject(self.handle, child.as_raw_handle() as HANDLE) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn set_information<T>(&self, class: i32, information: &T) -> io::Result<()> {
+        // SAFETY: the information is the structure of the class, valid for the duration of the
+        // call
+        let result = unsafe {
+            SetInformationJobObject(
+                self.handle,
+                class,
+                information as *const T as *const c_void,
+                mem::size_of::<T>() as u32,
+            )
+        };
+        if result == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        // SAFETY: the handle is owned by the job
+        unsafe {
+            CloseHandle(self.handle);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Scheduling of the parallel `flatc` invocations (see [`Invocation::PerFile`]).
+//!
+//! At most [`Args::jobs`] invocations run at once (`NUM_JOBS` in build scripts, or as many as
+//! there are CPUs, by default). With `jobserver` feature, the runs inside a build script (or a
+//! `make` recipe) also take the tokens of the jobserver of Cargo (`CARGO_MAKEFLAGS`) or `make`
+//! (`MAKEFLAGS`), so they share the `-j` limit with `rustc` instead of oversubscribing the
+//! machine: the first invocation runs on the implicit token of the process, every other one
+//! waits for a token.
+//!
+//! [`Invocation::PerFile`]: ../enum.Invocation.html#variant.PerFile
+//! [`Args::jobs`]: ../struct.Args.html#structfield.jobs
+
+use std::sync::Mutex;
+use std::thread;
+
+/// Run `f` for every job in parallel, at most `limit` at once (as many as there are CPUs by
+/// default), and return the results in the order of the jobs
+pub(crate) fn run_parallel<J, T, F>(jobs: Vec<J>, limit: Option<usize>, f: F) -> Vec<T>
+where
+    J: Send,
+    T: Send + 'static,
+    F: Fn(J) -> T + Sync,
+{
+    let limit = limit
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from))
+        .max(1);
+    #[cfg(feature = "jobserver")]
+    if let Some(client) = jobserver_client() {
+        return run_with_tokens(client, jobs, limit, &f);
+    }
+    run_on_threads(jobs, limit, &f)
+}
+
+/// Run the jobs on `limit` threads
+fn run_on_threads<J, T, F>(jobs: Vec<J>, limit: usize, f: &F) -> Vec<T>
+where
+    J: Send,
+    T: Send,
+    F: Fn(J) -> T + Sync,
+{
+    let workers = limit.min(jobs.len());
+    let queue = Mutex::new(jobs.into_iter().enumerate());
+    let results = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let next = queue.lock().expect("job queue").next();
+                let Some((index, job)) = next else {
+                    break;
+                };
+                let result = f(job);
+                results.lock().expect("results").push((index, result));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().expect("results");
+    results.sort_by_key(|&(index, _)| index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// The jobserver the process has inherited, if any
+#[cfg(feature = "jobserver")]
+fn jobserver_client() -> Option<&'static jobserver::Client> {
+    static CLIENT: std::sync::OnceLock<Option<jobserver::Client>> = std::sync::OnceLock::new();
+    CLIENT
+        .get_or_init(|| {
+            // SAFETY: the inherited descriptors are checked to be pipes, and they are used by
+            // the jobserver client only
+            #[allow(unsafe_code)]
+            let from_env = unsafe { jobserver::Client::from_env_ext(true) };
+            from_env.client.ok()
+        })
+        .as_ref()
+}
+
+#[cfg(feature = "jobserver")]
+enum Event<T> {
+    Token(std::io::Result<jobserver::Acquired>),
+    /// The index and the result of the job, and the token it has run on (none for the implicit
+    /// one)
+    Done(usize, T, Option<jobserver::Acquired>),
+}
+
+/// Run the jobs on the implicit token of the process and the tokens of the jobserver, at most
+/// `limit` at once
+#[cfg(feature = "jobserver")]
+fn run_with_tokens<J, T, F>(client: &jobserver::Client, jobs: Vec<J>, limit: usize, f: &F) -> Vec<T>
+where
+    J: Send,
+    T: Send + 'static,
+    F: Fn(J) -> T + Sync,
+{
+    use std::sync::mpsc;
+
+    let job_count = jobs.len();
+    let (sender, receiver) = mpsc::channel();
+    let token_sender = sender.clone();
+    let helper = match client.clone().into_helper_thread(move |token| {
+        let _ = token_sender.send(Event::Token(token));
+    }) {
+        Ok(helper) => helper,
+        Err(e) => {
+            log::warn!("the jobserver is not usable, ignoring it: {}", e);
+            return run_on_threads(jobs, limit, f);
+        }
+    };
+    for _ in 1..limit.min(job_count) {
+        helper.request_token();
+    }
+
+    let mut jobs = jobs.into_iter().enumerate();
+    let mut results: Vec<Option<T>> = (0..job_count).map(|_| None).collect();
+    thread::scope(|scope| {
+        let mut has_implicit_token = true;
+        let mut idle_tokens = Vec::new();
+        let mut running = 0;
+        let mut done_count = 0;
+        loop {
+            while running < limit && jobs.len() > 0 {
+                let token = if has_implicit_token {
+                    has_implicit_token = false;
+                    None
+                } else if let Some(token) = idle_tokens.pop() {
+                    Some(token)
+                } else {
+                    break;
+                };
+                let (index, job) = jobs.next().expect("a job is left");
+                let sender = sender.clone();
+                scope.spawn(move || {
+                    let result = f(job);
+                    let _ = sender.send(Event::Done(index, result, token));
+                });
+                running += 1;
+            }
+            if jobs.len() == 0 {
+                // Release the tokens to the other processes as soon as they are not needed
+                idle_tokens.clear();
+            }
+            if done_count == job_count {
+                break;
+            }
+
+            match receiver.recv().expect("the sender is alive") {
+                Event::Token(Ok(token)) => idle_tokens.push(token),
+                // The jobs left wait for the other tokens (or the implicit one)
+                Event::Token(Err(e)) => log::warn!("failed to acquire a jobserver token: {}", e),
+                Event::Done(index, result, token) => {
+                    results[index] = Some(result);
+                    done_count += 1;
+                    running -= 1;
+                    match token {
+                        Some(token) => idle_tokens.push(token),
+                        None => has_implicit_token = true,
+                    }
+                }
+            }
+        }
+    });
+    drop(helper);
+
+    results
+        .into_iter()
+        .map(|result| result.expect("every job is done"))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Run 8 jobs with `run` and return the most jobs which have run at once
+    fn peak_running<R: FnOnce(&(dyn Fn(usize) -> usize + Sync)) -> Vec<usize>>(run: R) -> usize {
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let results = run(&|job| {
+            let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now_running, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            running.fetch_sub(1, Ordering::SeqCst);
+            job * 10
+        });
+        assert_eq!(results, (0..8).map(|job| job * 10).collect::<Vec<_>>());
+        peak.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn run_on_threads_respects_limit() {
+        assert_eq!(peak_running(|f| run_on_threads((0..8).collect(), 1, &f)), 1);
+        assert!(peak_running(|f| run_on_threads((0..8).collect(), 3, &f)) <= 3);
+    }
+
+    #[cfg(feature = "jobserver")]
+    #[test]
+    fn run_with_tokens_respects_jobserver_and_limit() -> std::io::Result<()> {
+        for (token_count, limit, max_running) in [(0, 8, 1), (2, 8, 3), (4, 2, 2)] {
+            let client = jobserver::Client::new(token_count)?;
+            let peak = peak_running(|f| run_with_tokens(&client, (0..8).collect(), limit, &f));
+            assert!(peak <= max_running);
+            assert_eq!(client.available()?, token_count);
+        }
+        Ok(())
+    }
+}
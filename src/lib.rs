@@ -6,7 +6,8 @@
 //! version [1.10.0+](https://github.com/google/flatbuffers/releases/tag/v1.10.0) installed (there
 //! are [windows binary releases](https://github.com/google/flatbuffers/releases), `flatbuffers`
 //! packages for [conda](https://anaconda.org/conda-forge/flatbuffers) [Windows, Linux, MacOS],
-//! [Arch Linux](https://www.archlinux.org/packages/community/x86_64/flatbuffers/)).
+//! [Arch Linux](https://www.archlinux.org/packages/community/x86_64/flatbuffers/)). `flatc` is
+//! looked up in `$PATH` unless `FLATC` environment variable points to the executable.
 //!
 //! # Examples
 //!
@@ -80,9 +81,64 @@
 //!     ```
 //! 5. Use the helpers like any regular Rust module ([example projects])
 //!
+//! Alternatively, [`compile_schemas`] generates the helpers into `OUT_DIR` and tracks the schemas
+//! (including the included ones) for rebuilds in one call.
+//!
 //! [build scripts in Cargo]: https://doc.rust-lang.org/cargo/reference/build-scripts.html
+//! [`compile_schemas`]: fn.compile_schemas.html
 //! [example projects]: https://github.com/frol/flatc-rust/tree/master/examples
 //!
+//! ## Cross-compilation
+//!
+//! `flatc` always runs on the host, so make sure the `flatc` you provide is built for the host
+//! (`HOST`) rather than for the target (`TARGET`) architecture. When cross-compiling,
+//! [`Flatc::locate`] skips the executables built for another architecture than the host (which
+//! would otherwise run under an emulator like qemu), and fails naming the one it has skipped if
+//! there is no other. When the output directory is
+//! shared between the targets, use [`build_script::per_target_out_dir`] (or set
+//! [`Args::out_dir_layout`]) to avoid clobbering the helpers generated for another target.
+//!
+//! [`Flatc::locate`]: struct.Flatc.html#method.locate
+//! [`build_script::per_target_out_dir`]: build_script/fn.per_target_out_dir.html
+//! [`Args::out_dir_layout`]: struct.Args.html#structfield.out_dir_layout
+//!
+//! ## Cargo features
+//!
+//! * `cli` builds `cargo flatc` subcommand (`cargo install flatc-rust --features cli`) to
+//!   regenerate (`cargo flatc`) or verify (`cargo flatc check`) the helpers described in the
+//!   config file or Cargo metadata, and to convert data (`cargo flatc convert`) from the
+//!   command line.
+//! * `config` enables [`run_from_config`](fn.run_from_config.html) to describe the compilation
+//!   in a [config file](config/index.html) instead of `build.rs` code.
+//! * `serde` implements `Serialize` for [`Args`](struct.Args.html) and `Serialize`/`Deserialize`
+//!   for [`ArgsOwned`](struct.ArgsOwned.html) to store the invocations in config files.
+//! * `download` enables [`download`](download/index.html) module to fetch prebuilt `flatc`
+//!   binaries.
+//! * `jobserver` makes the parallel runs
+//!   ([`Invocation::PerFile`](enum.Invocation.html#variant.PerFile)) take the tokens of the
+//!   jobserver of Cargo, so they respect `-j` alongside `rustc`.
+//! * `limits` enables
+//!   [`Flatc::with_resource_limits`](struct.Flatc.html#method.with_resource_limits) to cap the
+//!   memory and the CPU time of `flatc`, failing with
+//!   [`ResourceLimitExceeded`](limits/struct.ResourceLimitExceeded.html) when it exceeds them.
+//! * `priority` enables [`Flatc::with_low_priority`](struct.Flatc.html#method.with_low_priority)
+//!   to run `flatc` at a reduced CPU and IO priority.
+//! * `registry` enables [`registry`](registry/index.html) module to fetch checksum-pinned
+//!   schemas over HTTP(S) or from git refs (implies `download`).
+//! * `remote` enables [`Flatc::with_remote`](struct.Flatc.html#method.with_remote) to run `flatc`
+//!   on a remote host over SSH, staging the inputs there and fetching the outputs back.
+//! * `sandbox` enables [`Flatc::with_sandbox`](struct.Flatc.html#method.with_sandbox) to run
+//!   `flatc` without the network access and the filesystem access outside of the schemas and
+//!   `out_dir` (Linux), or in a job object that cannot start other processes (Windows).
+//! * `tonic` enables [`Args::tonic`](struct.Args.html#structfield.tonic) to append the
+//!   [tonic](tonic/index.html) servers and clients of the `rpc_service` declarations to the
+//!   generated Rust code.
+//! * `tracing` instruments the discovery, the spawning and the completion of `flatc` with
+//!   [`tracing`](https://docs.rs/tracing) spans and events (with the executable, the input count
+//!   and the durations as fields) in addition to the `log` records.
+//! * `verify` enables [`Args::verify_rust`](struct.Args.html#structfield.verify_rust) to check
+//!   that the generated Rust code parses right after the generation.
+//!
 //! ## Usage in external projects
 //!
 //! There is [a benchmark of FlatBuffers vs other serialization
@@ -91,13 +147,92 @@
 
 #![deny(missing_docs)]
 #![deny(unsafe_code)]
+#![allow(clippy::needless_doctest_main)]
 
-use std::ffi::OsString;
-use std::io;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::Arc;
+use std::time::Instant;
+
+use log::{log, Level};
+
+mod batch;
+pub mod build_script;
+pub mod bundle;
+pub mod cache;
+mod capabilities;
+mod color;
+mod command_line;
+pub mod compatibility;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod depfile;
+pub mod diff;
+mod discovery;
+#[cfg(feature = "download")]
+pub mod download;
+pub mod embedded;
+pub mod format;
+#[cfg(all(windows, any(feature = "limits", feature = "sandbox")))]
+mod job;
+mod jobs;
+#[cfg(feature = "limits")]
+pub mod limits;
+mod lock;
+pub mod matrix;
+mod normalize;
+mod outputs;
+mod owned;
+pub mod postprocess;
+#[cfg(feature = "priority")]
+pub mod priority;
+pub mod reflection;
+#[cfg(feature = "registry")]
+pub mod registry;
+#[cfg(feature = "remote")]
+pub mod remote;
+mod report;
+mod reproducible;
+mod requirement;
+mod retry;
+pub mod runtime;
+#[cfg(feature = "sandbox")]
+pub mod sandbox;
+pub mod schema;
+mod temp;
+pub mod testing;
+#[cfg(feature = "tonic")]
+pub mod tonic;
+mod trace;
+mod validation;
+#[cfg(feature = "verify")]
+mod verify;
+pub mod workspace;
+pub mod wsl;
 
-use log::info;
+pub use batch::{BatchError, InputFailure};
+use cache::CacheEntry;
+pub use color::ColorChoice;
+pub use discovery::{discover, which, CheckReport, FlatcNotFound, FlatcSource, FLATC_ENV};
+use lock::DirLock;
+use outputs::DirSnapshot;
+pub use outputs::NothingGenerated;
+pub use owned::ArgsOwned;
+pub use report::{InputReport, Manifest, Metrics, Report, RunOutput, REPORT_FILE_NAME};
+pub use retry::RetryPolicy;
+use temp::TempDir;
+pub use validation::{Problem, ValidationError};
+pub use workspace::compile_workspace;
+
+#[cfg(feature = "config")]
+pub use config::run_from_config;
+#[cfg(feature = "download")]
+pub use download::install;
+#[cfg(feature = "verify")]
+pub use verify::{UnparsableFile, VerificationError};
 
 /// The default Error type of the crate
 pub type Error = io::Error;
@@ -108,7 +243,7 @@ fn err_other<E>(error: E) -> Error
 where
     E: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
-    Error::new(io::ErrorKind::Other, error)
+    Error::other(error)
 }
 
 /// This structure represents the arguments passed to `flatc`
@@ -125,15 +260,35 @@ where
 ///     ..Default::default()
 /// };
 /// ```
+///
+/// With `serde` feature, `Args` are serializable (e.g. to log the invocations as structured data);
+/// use [`ArgsOwned`] to deserialize them.
+///
+/// To reuse an existing `flatc` command line, see [`ArgsOwned::parse_cli`].
+///
+/// [`ArgsOwned`]: struct.ArgsOwned.html
+/// [`ArgsOwned::parse_cli`]: struct.ArgsOwned.html#method.parse_cli
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Args<'a> {
     /// Specify the programming language (`rust` is the default)
     pub lang: &'a str,
     /// List of `.fbs` files to compile [required to be non-empty]
+    ///
+    /// The duplicates are skipped, and the schemas are passed to `flatc` sorted (unless data
+    /// files are converted), so the command line does not depend on the order of a glob.
     pub inputs: &'a [&'a Path],
     /// Output path for the generated helpers (`-o PATH` parameter) [required]
+    ///
+    /// `OUT_DIR` is already unique per target; an output directory shared between the targets
+    /// (e.g. `target/flatbuffers/`) needs [`build_script::per_target_out_dir`] (or
+    /// [`out_dir_layout`](#structfield.out_dir_layout)), or the builds for different targets
+    /// overwrite each other's helpers.
+    ///
+    /// [`build_script::per_target_out_dir`]: build_script/fn.per_target_out_dir.html
     pub out_dir: &'a Path,
-    /// Search for includes in the specified paths (`-I PATH` parameter)
+    /// Search for includes in the specified paths (`-I PATH` parameter); the duplicates are
+    /// skipped
     pub includes: &'a [&'a Path],
     /// Set the flatc '--binary' flag
     pub binary: bool,
@@ -141,8 +296,245 @@ pub struct Args<'a> {
     pub schema: bool,
     /// Set the flatc '--json' flag
     pub json: bool,
+    /// Also generate JSON Schema (`*.schema.json`) from the `.fbs` inputs (`--jsonschema` flag),
+    /// e.g. to publish validation schemas of REST APIs derived from the same source of truth
+    pub jsonschema: bool,
+    /// Embed the declaration filenames into the binary schemas relative to the specified root
+    /// (`--bfbs-filenames PATH` parameter)
+    pub bfbs_filenames: Option<&'a Path>,
+    /// Reject the schemas with fields lacking explicit ids (`--require-explicit-ids` flag)
+    pub require_explicit_ids: bool,
+    /// Fail unless the inputs are an evolution of the specified baseline schema (`--conform FILE`
+    /// parameter)
+    pub conform: Option<&'a Path>,
+    /// Search for the includes of the [`conform`] baseline schema in the specified paths
+    /// (`--conform-includes PATH` parameter), e.g. in a worktree of the last release
+    ///
+    /// [`conform`]: #structfield.conform
+    pub conform_includes: &'a [&'a Path],
+    /// Silence all `flatc` warnings, e.g. for vendored schemas (`--no-warnings` flag)
+    pub no_warnings: bool,
+    /// Pass non-UTF-8 strings through when converting JSON and binary data (`--allow-non-utf8`
+    /// flag)
+    pub allow_non_utf8: bool,
+    /// Skip the JSON fields missing from the schema when converting JSON to binary
+    /// (`--unknown-json` flag)
+    pub unknown_json: bool,
+    /// Generate every Rust namespace into its own file and tie them together with `mod.rs`
+    /// (`--rust-module-root-file` flag, requires `flatc` 2.0.5+), so the crate only has to
+    /// include `mod.rs`
+    pub rust_module_root_file: bool,
+    /// Implement `serde::Serialize` on the generated Rust types (`--rust-serialize` flag,
+    /// requires `flatc` 2.0.6+ and `serialize` feature of the `flatbuffers` crate)
+    pub rust_serialize: bool,
+    /// Generate the code encoding the FlatBuffers into JSON (`--gen-json-emit` flag); note that
+    /// `flatc` (as of 23.5.26) only implements it for Swift (`Encodable` conformances) and
+    /// generates the same code for the other languages, including C++
+    pub gen_json_emit: bool,
+    /// Generate the `Newtonsoft.Json` (de)serialization of the C# object API types
+    /// (`--cs-gen-json-serializer` flag), e.g. for Unity clients (add `--gen-object-api` to
+    /// [`extra`])
+    ///
+    /// [`extra`]: #structfield.extra
+    pub cs_gen_json_serializer: bool,
+    /// Create `out_dir` (with the missing parents) before the run (`true` by default)
+    pub create_out_dir: bool,
+    /// Remove the previously generated files (`*_generated.*`, other files are kept) from
+    /// `out_dir` before the run, so renamed or removed schemas do not leave stale helpers behind
+    pub clean_out_dir: bool,
+    /// Generate into a staging directory next to `out_dir` and move the generated files into
+    /// `out_dir` only after a successful run, so a failed run leaves `out_dir` untouched
+    pub atomic: bool,
+    /// Leave the files of `out_dir` whose content has not changed untouched (keeping their
+    /// modification times), so rerunning `flatc` on the same schemas does not trigger the
+    /// rebuilds of the dependent crates; the run is staged like an [`atomic`] one, and only the
+    /// rewritten files are reported as generated
+    ///
+    /// [`atomic`]: #structfield.atomic
+    pub skip_unchanged: bool,
+    /// Make the generated files read-only after the run, so the editors warn about the hand
+    /// edits the next run would overwrite; the read-only files of `out_dir` are made writable
+    /// again before the next run (and before [`clean_out_dir`] removes them)
+    ///
+    /// [`clean_out_dir`]: #structfield.clean_out_dir
+    pub read_only: bool,
+    /// Fail with [`NothingGenerated`] naming the probable cause if the run has not generated (or
+    /// updated) any file in `out_dir`, e.g. for the schemas which only include other schemas
+    /// without `--gen-all`
+    ///
+    /// It is off by default, as it takes a snapshot of the whole `out_dir` (recursively) before
+    /// and after the run, which is slow for a large or shared `out_dir`; the runs taking the
+    /// snapshot anyway (e.g. with a [`Report`] or a [`postprocess`]) only log a warning without
+    /// it.
+    ///
+    /// [`NothingGenerated`]: struct.NothingGenerated.html
+    /// [`Report`]: struct.Report.html
+    /// [`postprocess`]: #structfield.postprocess
+    pub require_generated: bool,
     /// Extra args to pass to flatc
     pub extra: &'a [&'a str],
+    /// Post-process every generated text file: the function receives the path and the content of
+    /// the file, and the returned content is written back (see [`postprocess`] for built-in
+    /// transformers)
+    ///
+    /// [`postprocess`]: postprocess/index.html
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub postprocess: Option<fn(&Path, String) -> String>,
+    /// Adapt the generated Rust code for `no_std` crates (using `core` and `alloc` instead of
+    /// `std`) and fail if it still depends on `std` (see [`postprocess::no_std`])
+    ///
+    /// [`postprocess::no_std`]: postprocess/fn.no_std.html
+    pub no_std: bool,
+    /// Rename every generated file (after the post-processing): the function receives the path
+    /// relative to `out_dir` and returns the new relative path (see [`postprocess`] for built-in
+    /// renamers)
+    ///
+    /// The renamed files are reported under their new names. [`clean_out_dir`] only recognizes
+    /// the files named like `*_generated.*`, and the references between the generated files
+    /// (e.g. the modules of [`rust_module_root_file`]) are not updated.
+    ///
+    /// [`postprocess`]: postprocess/index.html
+    /// [`clean_out_dir`]: #structfield.clean_out_dir
+    /// [`rust_module_root_file`]: #structfield.rust_module_root_file
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub rename: Option<fn(&Path) -> PathBuf>,
+    /// Prepend a comment to every generated source file recording the crate version, the `flatc`
+    /// version, the `flatc` arguments (without `-o`) and the SHA-256 of the inputs, so a stale
+    /// file tells how it was produced (the files without a known comment syntax, e.g. JSON, are
+    /// left as is)
+    pub provenance: bool,
+    /// Prepend the text (e.g. a license header or `@generated`) to every generated source file,
+    /// with every line turned into a comment of the language of the file (the files without a
+    /// known comment syntax are left as is, like with [`provenance`])
+    ///
+    /// [`provenance`]: #structfield.provenance
+    pub banner: Option<&'a str>,
+    /// Parse every generated `.rs` file with `syn` and fail with [`VerificationError`] listing
+    /// the files which do not parse, so a `flatc` incompatible with the toolchain is reported by
+    /// the build script rather than by the compilation of the crate (`verify` feature, the runs
+    /// fail with `io::ErrorKind::Unsupported` without it)
+    ///
+    /// [`VerificationError`]: struct.VerificationError.html
+    pub verify_rust: bool,
+    /// Append the tonic messages, server and client modules of the `rpc_service` declarations of
+    /// every schema to its generated Rust file, as `flatc` has no gRPC generator for Rust (see
+    /// [`tonic`]; `tonic` feature, the runs fail with `io::ErrorKind::Unsupported` without it)
+    ///
+    /// [`tonic`]: tonic/index.html
+    pub tonic: bool,
+    /// After a successful run, print `cargo:rustc-env=<NAME>=<absolute out_dir>` (see
+    /// [`build_script::DEFAULT_OUT_DIR_ENV`]), so the crate can
+    /// `include!(concat!(env!("<NAME>"), "/message_generated.rs"))` the helpers
+    ///
+    /// [`build_script::DEFAULT_OUT_DIR_ENV`]: build_script/constant.DEFAULT_OUT_DIR_ENV.html
+    pub rustc_env: Option<&'a str>,
+    /// Before the run, ask `flatc` for the full include closure of the inputs (`-M`) and print
+    /// `cargo:rerun-if-changed` for every schema (see [`depfile`])
+    ///
+    /// [`depfile`]: depfile/index.html
+    pub rerun_if_changed: bool,
+    /// Search for includes in the schema directories published by the dependencies too (every
+    /// `DEP_<LINKS>_SCHEMA_DIR`, see [`build_script::dependency_schema_dirs`]), and print
+    /// `cargo:rerun-if-changed` for them after the run
+    ///
+    /// [`build_script::dependency_schema_dirs`]: build_script/fn.dependency_schema_dirs.html
+    pub dependency_schemas: bool,
+    /// After a successful run, write the declared inputs and outputs of the run (the `flatc`
+    /// version, the inputs with their hashes, the include directories and the generated files)
+    /// into the JSON file (see [`Manifest`]), so the build systems like Bazel or Buck can declare
+    /// the outputs of the action precisely rather than scan `out_dir`
+    ///
+    /// [`Manifest`]: struct.Manifest.html
+    pub manifest: Option<&'a Path>,
+    /// Reuse the files generated by an earlier run with the same `flatc` version, arguments and
+    /// schemas (the inputs with the schemas they include) from the cache directory, e.g.
+    /// [`build_script::shared_cache_dir`] shared by the build scripts of a workspace, instead of
+    /// running `flatc` again; the runs populating the same entry are serialized (see [`cache`],
+    /// and [`Flatc::with_cache_backend`] to share the entries between the machines)
+    ///
+    /// The output of `flatc` is not cached: the [`Report`] of a run restored from the cache has
+    /// no warnings, and the [`RunOutput`] has a successful status and empty captured output.
+    ///
+    /// [`build_script::shared_cache_dir`]: build_script/fn.shared_cache_dir.html
+    /// [`cache`]: cache/index.html
+    /// [`Flatc::with_cache_backend`]: struct.Flatc.html#method.with_cache_backend
+    /// [`Report`]: struct.Report.html
+    /// [`RunOutput`]: struct.RunOutput.html
+    pub cache_dir: Option<&'a Path>,
+    /// Make the generated files byte-identical across the machines (e.g. to diff the artifacts
+    /// of the CI runs): fail unless the `flatc` version matches the requirement (see
+    /// [`Flatc::require_version`], e.g. `=23.5.26`), stage the [`atomic`] runs in a directory
+    /// with a fixed name, make the absolute paths relative in the generated sources (see
+    /// [`provenance`]) and use `\n` line endings; the inputs are always sorted when they are all
+    /// schemas
+    ///
+    /// [`Flatc::require_version`]: struct.Flatc.html#method.require_version
+    /// [`atomic`]: #structfield.atomic
+    /// [`provenance`]: #structfield.provenance
+    pub reproducible: Option<&'a str>,
+    /// Run `flatc` once for all the inputs ([`Invocation::Single`], the default) or once per
+    /// input in parallel ([`Invocation::PerFile`])
+    ///
+    /// [`Invocation::Single`]: enum.Invocation.html#variant.Single
+    /// [`Invocation::PerFile`]: enum.Invocation.html#variant.PerFile
+    pub invocation: Invocation,
+    /// The most `flatc` processes running at once with [`Invocation::PerFile`] (at least one);
+    /// `None` stands for `NUM_JOBS` Cargo sets for build scripts (see
+    /// [`build_script::num_jobs`]), or for the number of CPUs outside of build scripts
+    ///
+    /// [`Invocation::PerFile`]: enum.Invocation.html#variant.PerFile
+    /// [`build_script::num_jobs`]: build_script/fn.num_jobs.html
+    pub jobs: Option<usize>,
+    /// Swallow the output of a successful `flatc` run (it is still printed if `flatc` fails) and
+    /// log the progress of the crate at `debug` rather than `info` level, e.g. for large schema
+    /// sets dominating the build logs
+    pub quiet: bool,
+    /// Append the target triple (and the profile) to `out_dir` ([`OutDirLayout::Shared`] by
+    /// default), so the builds for different targets sharing a directory like
+    /// `target/flatbuffers/` do not overwrite each other's helpers
+    ///
+    /// [`OutDirLayout::Shared`]: enum.OutDirLayout.html#variant.Shared
+    pub out_dir_layout: OutDirLayout,
+}
+
+/// The layout of the output directory (see [`Args::out_dir_layout`])
+///
+/// The target triple and the profile are taken from `TARGET` and `PROFILE` environment
+/// variables Cargo sets for build scripts; the run fails if they are not set.
+///
+/// [`Args::out_dir_layout`]: struct.Args.html#structfield.out_dir_layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum OutDirLayout {
+    /// `out_dir` as is
+    #[default]
+    Shared,
+    /// `out_dir/<TARGET>`
+    PerTarget,
+    /// `out_dir/<TARGET>/<PROFILE>`
+    PerTargetAndProfile,
+}
+
+/// The strategy of invoking `flatc` for the inputs (see [`Args::invocation`])
+///
+/// [`Args::invocation`]: struct.Args.html#structfield.invocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Invocation {
+    /// A single `flatc` invocation with all the inputs, which is the fastest way and the only
+    /// one suitable for converting data (e.g. a schema followed by JSON files, or binary files
+    /// after `--`)
+    #[default]
+    Single,
+    /// A `flatc` invocation per input (running [`Args::jobs`] at once), which isolates the
+    /// failures of the schemas and reports all of them at once with [`BatchError`]; every input
+    /// has to be a schema which compiles on its own
+    ///
+    /// [`Args::jobs`]: struct.Args.html#structfield.jobs
+    /// [`BatchError`]: struct.BatchError.html
+    PerFile,
 }
 
 impl Default for Args<'_> {
@@ -155,11 +547,104 @@ impl Default for Args<'_> {
             binary: false,
             schema: false,
             json: false,
+            jsonschema: false,
+            bfbs_filenames: None,
+            require_explicit_ids: false,
+            conform: None,
+            conform_includes: &[],
+            no_warnings: false,
+            allow_non_utf8: false,
+            unknown_json: false,
+            rust_module_root_file: false,
+            rust_serialize: false,
+            gen_json_emit: false,
+            cs_gen_json_serializer: false,
+            create_out_dir: true,
+            clean_out_dir: false,
+            atomic: false,
+            skip_unchanged: false,
+            read_only: false,
+            require_generated: false,
             extra: &[],
+            postprocess: None,
+            no_std: false,
+            rename: None,
+            provenance: false,
+            banner: None,
+            verify_rust: false,
+            tonic: false,
+            rustc_env: None,
+            rerun_if_changed: false,
+            dependency_schemas: false,
+            manifest: None,
+            cache_dir: None,
+            reproducible: None,
+            invocation: Invocation::Single,
+            jobs: None,
+            quiet: false,
+            out_dir_layout: OutDirLayout::Shared,
+        }
+    }
+}
+
+/// Presets of the flag combinations for the common tasks, meant to be completed with the inputs
+/// and `out_dir` (and tweaked further if needed):
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// # fn try_main() -> flatc_rust::Result<()> {
+/// flatc_rust::run(flatc_rust::Args {
+///     inputs: &[Path::new("src/message.fbs")],
+///     out_dir: Path::new("target/flatbuffers/"),
+///     ..flatc_rust::Args::rust_object_api()
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+impl<'a> Args<'a> {
+    /// Rust helpers with the object API (`--gen-object-api`) and the mutation of the buffers in
+    /// place (`--gen-mutable`), which the object API users reach for sooner or later
+    pub fn rust_object_api() -> Args<'a> {
+        Args {
+            lang: "rust",
+            extra: &["--gen-object-api", "--gen-mutable"],
+            ..Default::default()
+        }
+    }
+
+    /// Binary schemas (`.bfbs`, `--binary --schema`) only, e.g. for [`reflection`] or for
+    /// [`conform`] checks
+    ///
+    /// [`reflection`]: reflection/index.html
+    /// [`conform`]: #structfield.conform
+    pub fn bfbs_only() -> Args<'a> {
+        Args {
+            lang: "binary",
+            schema: true,
+            ..Default::default()
+        }
+    }
+
+    /// Conversion of binary data into strict JSON (`--json --strict-json --raw-binary`); the
+    /// inputs are the schema followed by `--` and the binary files
+    pub fn json_conversion() -> Args<'a> {
+        Args {
+            lang: "json",
+            extra: &["--strict-json", "--raw-binary"],
+            ..Default::default()
         }
     }
 }
 
+/// Check whether the executable cannot be spawned because it is built for another architecture
+/// (e.g. a target-architecture `flatc` when cross-compiling)
+fn is_exec_format_error(error: &io::Error) -> bool {
+    // ENOEXEC on Unix, ERROR_BAD_EXE_FORMAT on Windows
+    let code = if cfg!(windows) { 193 } else { 8 };
+    error.raw_os_error() == Some(code)
+}
+
 /// Programmatic interface (API) for `flatc` command.
 ///
 /// NOTE: You may only need a small helper function [`run`].
@@ -167,19 +652,238 @@ impl Default for Args<'_> {
 /// [`run`]: fn.run.html
 pub struct Flatc {
     exec: PathBuf,
+    source: FlatcSource,
+    color: ColorChoice,
+    env_clear: bool,
+    env_allowlist: Vec<OsString>,
+    wsl: bool,
+    #[cfg(feature = "sandbox")]
+    sandbox: bool,
+    #[cfg(feature = "priority")]
+    low_priority: bool,
+    #[cfg(feature = "remote")]
+    remote: Option<remote::Remote>,
+    cache_backend: Option<Arc<dyn cache::CacheBackend>>,
+    retry: RetryPolicy,
+    #[cfg(feature = "limits")]
+    limits: limits::ResourceLimits,
 }
 
 impl Flatc {
-    /// New `flatc` command from `$PATH`
+    /// New `flatc` command from `$PATH` (or from [`FLATC_ENV`] environment variable, if set)
+    ///
+    /// [`FLATC_ENV`]: constant.FLATC_ENV.html
     pub fn from_env_path() -> Flatc {
+        let env_exec = std::env::var_os(FLATC_ENV).filter(|exec| !exec.is_empty());
         Flatc {
-            exec: PathBuf::from("flatc"),
+            source: if env_exec.is_some() {
+                FlatcSource::EnvVar
+            } else {
+                FlatcSource::Path
+            },
+            exec: env_exec.map_or_else(|| PathBuf::from("flatc"), PathBuf::from),
+            color: ColorChoice::Auto,
+            env_clear: false,
+            env_allowlist: Vec::new(),
+            wsl: false,
+            #[cfg(feature = "sandbox")]
+            sandbox: false,
+            #[cfg(feature = "priority")]
+            low_priority: false,
+            #[cfg(feature = "remote")]
+            remote: None,
+            cache_backend: None,
+            retry: RetryPolicy::default(),
+            #[cfg(feature = "limits")]
+            limits: limits::ResourceLimits::default(),
         }
     }
 
+    /// Find a working `flatc`: the one from [`FLATC_ENV`] environment variable if it is set,
+    /// otherwise the first working one from `$PATH` and the common installation locations (see
+    /// [`discover`])
+    ///
+    /// Fails with [`FlatcNotFound`] if there is none.
+    ///
+    /// [`FLATC_ENV`]: constant.FLATC_ENV.html
+    /// [`discover`]: fn.discover.html
+    /// [`FlatcNotFound`]: struct.FlatcNotFound.html
+    pub fn locate() -> Result<Flatc> {
+        discovery::locate()
+    }
+
     /// New `flatc` command from specified path
     pub fn from_path<P: std::convert::Into<PathBuf>>(path: P) -> Flatc {
-        Flatc { exec: path.into() }
+        Flatc {
+            exec: path.into(),
+            source: FlatcSource::Explicit,
+            color: ColorChoice::Auto,
+            env_clear: false,
+            env_allowlist: Vec::new(),
+            wsl: false,
+            #[cfg(feature = "sandbox")]
+            sandbox: false,
+            #[cfg(feature = "priority")]
+            low_priority: false,
+            #[cfg(feature = "remote")]
+            remote: None,
+            cache_backend: None,
+            retry: RetryPolicy::default(),
+            #[cfg(feature = "limits")]
+            limits: limits::ResourceLimits::default(),
+        }
+    }
+
+    /// Choose whether to color the diagnostics re-emitted after capturing the `flatc` output
+    /// (errors in red, `file:line` locations in bold); [`ColorChoice::Auto`] by default
+    ///
+    /// ```no_run
+    /// use flatc_rust::{ColorChoice, Flatc};
+    ///
+    /// let flatc = Flatc::from_env_path().with_color(ColorChoice::Never);
+    /// ```
+    ///
+    /// [`ColorChoice::Auto`]: enum.ColorChoice.html#variant.Auto
+    pub fn with_color(self, color: ColorChoice) -> Flatc {
+        Flatc { color, ..self }
+    }
+
+    /// Retry spawning `flatc` after the transient failures (e.g. the executable still busy
+    /// right after a download, or locked by an antivirus) according to the policy;
+    /// [`RetryPolicy::default`] by default
+    ///
+    /// ```no_run
+    /// use flatc_rust::{Flatc, RetryPolicy};
+    ///
+    /// let flatc = Flatc::from_env_path().with_retry(RetryPolicy::never());
+    /// ```
+    ///
+    /// [`RetryPolicy::default`]: struct.RetryPolicy.html#impl-Default-for-RetryPolicy
+    pub fn with_retry(self, retry: RetryPolicy) -> Flatc {
+        Flatc { retry, ..self }
+    }
+
+    /// Run every `flatc` process with an empty environment but the variables allowed with
+    /// [`env_allow`], rather than with the environment of the current process, so the stray
+    /// variables (the locale, `TMPDIR`, the injected wrappers) do not affect the hermetic builds
+    ///
+    /// ```no_run
+    /// let flatc = flatc_rust::Flatc::from_env_path()
+    ///     .env_clear()
+    ///     .env_allow("PATH");
+    /// ```
+    ///
+    /// NOTE: Windows programs may need `SystemRoot` to start.
+    ///
+    /// [`env_allow`]: #method.env_allow
+    pub fn env_clear(self) -> Flatc {
+        Flatc {
+            env_clear: true,
+            ..self
+        }
+    }
+
+    /// Pass the environment variable of the current process (if it is set) to `flatc` after
+    /// [`env_clear`]
+    ///
+    /// [`env_clear`]: #method.env_clear
+    pub fn env_allow<K: Into<OsString>>(mut self, name: K) -> Flatc {
+        self.env_allowlist.push(name.into());
+        self
+    }
+
+    /// Run `flatc` installed inside WSL (`wsl --exec flatc ...`), translating the Windows paths
+    /// of the arguments to their Linux forms and the paths in the captured output back (see
+    /// [`wsl`] for the details); the executable is then looked up in the `$PATH` of the default
+    /// distribution
+    ///
+    /// ```no_run
+    /// let flatc = flatc_rust::Flatc::from_path("flatc").with_wsl(true);
+    /// ```
+    ///
+    /// [`wsl`]: wsl/index.html
+    pub fn with_wsl(self, wsl: bool) -> Flatc {
+        Flatc { wsl, ..self }
+    }
+
+    /// Run `flatc` on the remote host over SSH, staging the files of every invocation there and
+    /// fetching the generated ones back (`remote` feature, see [`remote`] for the details); the
+    /// executable is then the path of `flatc` on the remote host
+    ///
+    /// ```no_run
+    /// use flatc_rust::remote::Remote;
+    ///
+    /// let flatc = flatc_rust::Flatc::from_path("flatc").with_remote(Remote::new("flatc-farm-1"));
+    /// ```
+    ///
+    /// [`remote`]: remote/index.html
+    #[cfg(feature = "remote")]
+    pub fn with_remote(self, remote: remote::Remote) -> Flatc {
+        Flatc {
+            remote: Some(remote),
+            ..self
+        }
+    }
+
+    /// Share the entries of the codegen cache ([`Args::cache_dir`]) between the machines through
+    /// the backend (see [`cache`] for the details)
+    ///
+    /// [`Args::cache_dir`]: struct.Args.html#structfield.cache_dir
+    /// [`cache`]: cache/index.html
+    pub fn with_cache_backend<B: cache::CacheBackend + 'static>(self, backend: B) -> Flatc {
+        Flatc {
+            cache_backend: Some(Arc::new(backend)),
+            ..self
+        }
+    }
+
+    /// Run every `flatc` process in a sandbox denying the network access and the filesystem
+    /// access outside of its inputs, include directories and `out_dir` (`sandbox` feature, see
+    /// [`sandbox`] for the details), e.g. to compile third-party schemas with least privilege
+    ///
+    /// The spawning fails on the platforms (and the kernels) sandboxing is not available on. On
+    /// Windows only the processes and the user interface of `flatc` are restricted.
+    ///
+    /// ```no_run
+    /// let flatc = flatc_rust::Flatc::from_env_path().with_sandbox(true);
+    /// ```
+    ///
+    /// [`sandbox`]: sandbox/index.html
+    #[cfg(feature = "sandbox")]
+    pub fn with_sandbox(self, sandbox: bool) -> Flatc {
+        Flatc { sandbox, ..self }
+    }
+
+    /// Spawn every `flatc` process at a reduced CPU and IO priority (`priority` feature, see
+    /// [`priority`] for the details), so the large regenerations do not starve the interactive
+    /// work
+    ///
+    /// ```no_run
+    /// let flatc = flatc_rust::Flatc::from_env_path().with_low_priority(true);
+    /// ```
+    ///
+    /// [`priority`]: priority/index.html
+    #[cfg(feature = "priority")]
+    pub fn with_low_priority(self, low_priority: bool) -> Flatc {
+        Flatc {
+            low_priority,
+            ..self
+        }
+    }
+
+    /// Cap the memory and the CPU time of every `flatc` process (`limits` feature, see
+    /// [`limits`] for the details), so a pathological schema cannot exhaust the build host; the
+    /// runs exceeding the limits fail with [`ResourceLimitExceeded`]
+    ///
+    /// [`limits`]: limits/index.html
+    /// [`ResourceLimitExceeded`]: limits/struct.ResourceLimitExceeded.html
+    #[cfg(feature = "limits")]
+    pub fn with_resource_limits(self, limits: limits::ResourceLimits) -> Flatc {
+        Flatc { limits, ..self }
+    }
+
+    pub(crate) fn with_source(self, source: FlatcSource) -> Flatc {
+        Flatc { source, ..self }
     }
 
     /// Check `flatc` command found and valid
@@ -187,21 +891,211 @@ impl Flatc {
         self.version().map(|_| ())
     }
 
-    fn spawn(&self, cmd: &mut process::Command) -> io::Result<process::Child> {
-        info!("spawning command {:?}", cmd);
+    /// Check `flatc` like [`check`], reporting the executable it resolves to, how it was chosen
+    /// and its version, and (with `probe`) the generators and the flags it lists in `--help`
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn try_main() -> flatc_rust::Result<()> {
+    /// // Paste this into the bug report
+    /// print!("{}", flatc_rust::Flatc::locate()?.check_report(true)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`check`]: #method.check
+    pub fn check_report(&self, probe: bool) -> Result<CheckReport> {
+        let version = self.version()?;
+        let (languages, flags) = if probe {
+            let (languages, flags) = discovery::parse_help(&self.help()?);
+            (Some(languages), Some(flags))
+        } else {
+            (None, None)
+        };
+        Ok(CheckReport {
+            exec: self.exec.clone(),
+            resolved_exec: discovery::resolve_exec(&self.exec),
+            source: self.source,
+            version,
+            languages,
+            flags,
+        })
+    }
+
+    /// The output of `flatc --help`
+    fn help(&self) -> Result<String> {
+        let child = self.spawn(
+            self.command(["--help"])
+                .stdin(process::Stdio::null())
+                .stdout(process::Stdio::piped())
+                .stderr(process::Stdio::piped()),
+            Level::Info,
+        )?;
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(err_other("flatc failed with error"));
+        }
+        String::from_utf8(output.stdout).map_err(err_other)
+    }
+
+    /// Fail unless the `flatc` version matches the requirement (see [`Version::matches`]), e.g.
+    /// to pin the exact version producing the committed helpers
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let flatc = flatc_rust::Flatc::from_env_path();
+    /// flatc.require_version("24.3.25").expect("flatc 24.3.25");
+    /// flatc.require_version(">=23.5.26, <25").expect("flatc 23.5.26+");
+    /// ```
+    ///
+    /// [`Version::matches`]: struct.Version.html#method.matches
+    pub fn require_version(&self, requirement: &str) -> Result<Version> {
+        let version = self.version()?;
+        if !version.matches(requirement)? {
+            return Err(err_other(format!(
+                "flatc {} ({}) does not match the required version `{}`",
+                version.version,
+                self.exec.display(),
+                requirement
+            )));
+        }
+        Ok(version)
+    }
+
+    /// Fail unless `flatc` is recent enough for the flags requested by the args (including
+    /// [`Args::extra`]), since the usage errors of `flatc` do not tell which flag is unsupported
+    ///
+    /// [`Args::extra`]: struct.Args.html#structfield.extra
+    fn check_flag_support(&self, args: &Args) -> Result<()> {
+        let required_versions = capabilities::required_versions(args.lang, &args.flatc_args()?);
+        if required_versions.is_empty() {
+            return Ok(());
+        }
+        let version = self.query_version(args.log_level())?;
+        for (flag, required_version) in required_versions {
+            if !version.matches(&format!(">={}", required_version))? {
+                return Err(err_other(format!(
+                    "flag {} needs flatc >= {}, found {} ({})",
+                    flag,
+                    required_version,
+                    version.version,
+                    self.exec.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn spawn(&self, cmd: &mut process::Command, log_level: Level) -> io::Result<process::Child> {
+        if self.env_clear {
+            cmd.env_clear();
+            for name in &self.env_allowlist {
+                if let Some(value) = std::env::var_os(name) {
+                    cmd.env(name, value);
+                }
+            }
+        }
+        log!(log_level, "spawning command {:?}", cmd);
+        #[cfg(feature = "sandbox")]
+        if self.sandbox {
+            sandbox::confine(cmd)?;
+        }
+        #[cfg(feature = "priority")]
+        if self.low_priority {
+            priority::lower(cmd);
+        }
+        #[cfg(feature = "limits")]
+        limits::apply(cmd, &self.limits);
 
-        cmd.spawn()
-            .map_err(|e| Error::new(e.kind(), format!("failed to spawn `{:?}`: {}", cmd, e)))
+        let mut retries = 0;
+        let child = loop {
+            match cmd.spawn() {
+                Err(ref e) if retries < self.retry.max_retries && retry::is_transient(e) => {
+                    let backoff = self.retry.backoff(retries);
+                    log::warn!(
+                        "failed to spawn `{:?}`: {}; retrying in {:?}",
+                        cmd,
+                        e,
+                        backoff
+                    );
+                    std::thread::sleep(backoff);
+                    retries += 1;
+                }
+                result => break result,
+            }
+        };
+        let child = child.map_err(|e| {
+            if e.kind() == io::ErrorKind::NotFound {
+                return Error::new(
+                    e.kind(),
+                    FlatcNotFound {
+                        exec: self.exec.clone(),
+                    },
+                );
+            }
+            if is_exec_format_error(&e) {
+                return Error::new(
+                    e.kind(),
+                    format!(
+                        "failed to spawn `{:?}`: {}; flatc has to be built for the host ({}), \
+                         not for the target ({})",
+                        cmd,
+                        e,
+                        build_script::host().as_deref().unwrap_or("unknown"),
+                        build_script::target().as_deref().unwrap_or("unknown"),
+                    ),
+                );
+            }
+            if retries > 0 {
+                return Error::new(
+                    e.kind(),
+                    format!(
+                        "failed to spawn `{:?}`: {} (after {} retries)",
+                        cmd, e, retries
+                    ),
+                );
+            }
+            Error::new(e.kind(), format!("failed to spawn `{:?}`: {}", cmd, e))
+        })?;
+        #[cfg(feature = "sandbox")]
+        if self.sandbox {
+            if let Err(e) = sandbox::assign(&child) {
+                let mut child = child;
+                let _ = child.kill();
+                return Err(Error::new(
+                    e.kind(),
+                    format!("failed to sandbox `{:?}`: {}", cmd, e),
+                ));
+            }
+        }
+        #[cfg(feature = "limits")]
+        if let Err(e) = limits::assign(&child, &self.limits) {
+            let mut child = child;
+            let _ = child.kill();
+            return Err(Error::new(
+                e.kind(),
+                format!("failed to limit the resources of `{:?}`: {}", cmd, e),
+            ));
+        }
+        trace::spawned(cmd, &child);
+        Ok(child)
     }
 
     /// Obtain `flatc` version
     pub fn version(&self) -> Result<Version> {
+        self.query_version(Level::Info)
+    }
+
+    fn query_version(&self, log_level: Level) -> Result<Version> {
         let child = self.spawn(
-            process::Command::new(&self.exec)
+            self.command(["--version"])
                 .stdin(process::Stdio::null())
                 .stdout(process::Stdio::piped())
-                .stderr(process::Stdio::piped())
-                .args(["--version"]),
+                .stderr(process::Stdio::piped()),
+            log_level,
         )?;
 
         let output = child.wait_with_output()?;
@@ -217,145 +1111,1748 @@ impl Flatc {
         if !output.starts_with(prefix) {
             return Err(err_other("output does not start with prefix"));
         }
-        let output = &output[prefix.len()..];
-        let first_char = output
-            .chars()
-            .next()
-            .ok_or_else(|| err_other("version is empty"))?;
-        if !first_char.is_ascii_digit() {
-            return Err(err_other("version does not start with digit"));
+        output[prefix.len()..].parse()
+    }
+
+    /// Render the command (the program and the args) as a shell-escaped string, e.g. to
+    /// reproduce the run locally
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    ///
+    /// # fn try_main() -> flatc_rust::Result<()> {
+    /// let flatc = flatc_rust::Flatc::from_env_path();
+    /// let args = flatc_rust::Args {
+    ///     inputs: &[Path::new("schemas/my message.fbs")],
+    ///     out_dir: Path::new("target/flatbuffers/"),
+    ///     ..Default::default()
+    /// };
+    /// if let Err(e) = flatc.run(args) {
+    ///     eprintln!("{}; reproduce with: {}", e, flatc.command_line(args)?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn command_line(&self, args: Args) -> Result<String> {
+        if let Some(out_dir) = args.laid_out_out_dir()? {
+            return self.command_line(args.with_out_dir(&out_dir));
         }
-        Ok(Version {
-            version: output.to_owned(),
-        })
+        Ok(self.render_command_line(&args.flatc_args()?))
+    }
+
+    fn render_command_line(&self, args: &[OsString]) -> String {
+        let cmd = self.command(args);
+        command_line::render(std::iter::once(cmd.get_program()).chain(cmd.get_args()))
+    }
+
+    /// The command running `flatc` with the args (inside WSL with [`with_wsl`])
+    ///
+    /// [`with_wsl`]: #method.with_wsl
+    fn command<I, S>(&self, args: I) -> process::Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        #[cfg(feature = "remote")]
+        if let Some(ref remote) = self.remote {
+            return remote.flatc_command(&self.exec, args);
+        }
+        if !self.wsl {
+            let mut cmd = process::Command::new(&self.exec);
+            cmd.args(args);
+            return cmd;
+        }
+        let mut cmd = process::Command::new(wsl::WSL_EXEC);
+        cmd.arg("--exec")
+            .arg(wsl::to_linux_arg(self.exec.as_os_str()))
+            .args(args.into_iter().map(|arg| wsl::to_linux_arg(arg.as_ref())));
+        cmd
     }
 
     /// Execute `flatc` command with given args, check it completed correctly.
     fn run_with_args(&self, args: Vec<OsString>) -> Result<()> {
-        let mut cmd = process::Command::new(&self.exec);
+        #[cfg(feature = "remote")]
+        if self.remote.is_some() {
+            // The output has to be captured to translate the staged paths
+            return self.run_with_args_captured(args, true).map(|_| ());
+        }
+        let command_line = self.render_command_line(&args);
+        let mut cmd = self.command(args);
         cmd.stdin(process::Stdio::null());
-        cmd.args(args);
 
-        let mut child = self.spawn(&mut cmd)?;
+        let started_at = Instant::now();
+        let mut child = self.spawn(&mut cmd, Level::Info)?;
+        let status = child.wait()?;
+        trace::exited(&self.exec, status, started_at.elapsed());
 
-        if !child.wait()?.success() {
+        if !status.success() {
+            #[cfg(feature = "limits")]
+            if let Some(e) = self.limit_exceeded(&command_line, status, &[]) {
+                return Err(e);
+            }
             return Err(err_other(format!(
-                "flatc ({:?}) exited with non-zero exit code",
-                cmd
+                "flatc (`{}`) exited with non-zero exit code",
+                command_line
             )));
         }
 
         Ok(())
     }
 
+    /// The [`ResourceLimitExceeded`] error of the failed `flatc`, if it has likely exceeded the
+    /// limits
+    ///
+    /// [`ResourceLimitExceeded`]: limits/struct.ResourceLimitExceeded.html
+    #[cfg(feature = "limits")]
+    fn limit_exceeded(
+        &self,
+        command_line: &str,
+        status: process::ExitStatus,
+        stderr: &[u8],
+    ) -> Option<Error> {
+        self.limits.exceeded(status, stderr).map(|resource| {
+            err_other(limits::ResourceLimitExceeded {
+                resource,
+                limits: self.limits,
+                command_line: command_line.to_owned(),
+            })
+        })
+    }
+
+    /// Execute `flatc` command with given args capturing its output, check it completed
+    /// correctly.
+    ///
+    /// If `forward` is set (or `flatc` fails), the captured output is forwarded to stdout/stderr
+    /// of the current process, so the logs look the same as if the output was not captured.
+    fn run_with_args_captured(
+        &self,
+        args: Vec<OsString>,
+        forward: bool,
+    ) -> Result<process::Output> {
+        let command_line = self.render_command_line(&args);
+        let output = self.output_with_args(args, forward)?;
+        if !output.status.success() {
+            #[cfg(feature = "limits")]
+            if let Some(e) = self.limit_exceeded(&command_line, output.status, &output.stderr) {
+                return Err(e);
+            }
+            return Err(err_other(format!(
+                "flatc (`{}`) exited with non-zero exit code",
+                command_line
+            )));
+        }
+
+        Ok(output)
+    }
+
+    /// Execute `flatc` command with given args capturing its output without checking the exit
+    /// status (the output is forwarded like in [`run_with_args_captured`], the spawning is logged
+    /// at `debug` level unless the output is forwarded)
+    ///
+    /// [`run_with_args_captured`]: #method.run_with_args_captured
+    fn output_with_args(&self, args: Vec<OsString>, forward: bool) -> Result<process::Output> {
+        #[cfg(feature = "remote")]
+        let staged = match self.remote {
+            Some(ref remote) => Some(remote::Staged::upload(remote, &args)?),
+            None => None,
+        };
+        #[cfg(feature = "remote")]
+        let args = match staged {
+            Some(ref staged) => staged.args.clone(),
+            None => args,
+        };
+        let mut cmd = self.command(args);
+        cmd.stdin(process::Stdio::null())
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped());
+
+        let log_level = if forward { Level::Info } else { Level::Debug };
+        let started_at = Instant::now();
+        let mut output = self.spawn(&mut cmd, log_level)?.wait_with_output()?;
+        trace::exited(&self.exec, output.status, started_at.elapsed());
+        #[cfg(feature = "remote")]
+        if let Some(ref staged) = staged {
+            staged.download(&mut output)?;
+        }
+        if self.wsl {
+            for stream in [&mut output.stdout, &mut output.stderr] {
+                if let Ok(text) = std::str::from_utf8(stream) {
+                    *stream = wsl::to_windows_paths(text).into_bytes();
+                }
+            }
+        }
+        if forward || !output.status.success() {
+            io::stdout().write_all(&output.stdout)?;
+            match std::str::from_utf8(&output.stderr) {
+                Ok(diagnostics) if self.color.is_enabled() => {
+                    io::stderr().write_all(color::colorize(diagnostics).as_bytes())?
+                }
+                _ => io::stderr().write_all(&output.stderr)?,
+            }
+        }
+        Ok(output)
+    }
+
+    /// Execute `flatc` with every given args list (one per input), running [`Args::jobs`]
+    /// invocations at once, and return the captured outputs in the order of the inputs
+    ///
+    /// Unless [`Args::quiet`] is set (or the invocation fails), the output of every invocation is
+    /// forwarded at once, so the outputs do not interleave. All the invocations run even if some
+    /// fail; the failures are reported together with [`BatchError`].
+    ///
+    /// [`Args::jobs`]: struct.Args.html#structfield.jobs
+    /// [`Args::quiet`]: struct.Args.html#structfield.quiet
+    /// [`BatchError`]: struct.BatchError.html
+    fn run_invocations(
+        &self,
+        args: &Args,
+        invocations: Vec<Vec<OsString>>,
+    ) -> Result<Vec<process::Output>> {
+        let limit = args.jobs.or_else(build_script::num_jobs);
+        let outputs = jobs::run_parallel(invocations, limit, |cmd_args| {
+            #[cfg(feature = "limits")]
+            let command_line = self.render_command_line(&cmd_args);
+            let output = self.output_with_args(cmd_args, !args.quiet)?;
+            #[cfg(feature = "limits")]
+            if let Some(e) = self.limit_exceeded(&command_line, output.status, &output.stderr) {
+                return Err(e);
+            }
+            Ok(output)
+        })
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+        let failures: Vec<InputFailure> = args
+            .inputs
+            .iter()
+            .zip(&outputs)
+            .filter(|(_, output)| !output.status.success())
+            .map(|(input, output)| {
+                let mut diagnostics = String::from_utf8_lossy(&output.stderr).into_owned();
+                diagnostics.push_str(&String::from_utf8_lossy(&output.stdout));
+                InputFailure {
+                    input: input.to_path_buf(),
+                    diagnostics: diagnostics.trim().to_owned(),
+                }
+            })
+            .collect();
+        if !failures.is_empty() {
+            return Err(err_other(BatchError {
+                failures,
+                input_count: args.inputs.len(),
+            }));
+        }
+        Ok(outputs)
+    }
+
+    /// Execute `flatc` with the raw command line arguments (e.g. a mode or an experimental flag
+    /// [`Args`] does not cover) and return its captured output
+    ///
+    /// The spawning errors are explained like for the other runs (see [`FlatcNotFound`]), and the
+    /// non-zero exit code is an error (the captured output is forwarded to stdout/stderr of the
+    /// current process then).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn try_main() -> flatc_rust::Result<()> {
+    /// let output = flatc_rust::Flatc::from_env_path().run_raw(&["--help"])?;
+    /// let usage = String::from_utf8_lossy(&output.stdout);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Args`]: struct.Args.html
+    /// [`FlatcNotFound`]: struct.FlatcNotFound.html
+    pub fn run_raw<S: AsRef<OsStr>>(&self, args: &[S]) -> Result<process::Output> {
+        self.run_with_args_captured(
+            args.iter().map(|arg| arg.as_ref().to_owned()).collect(),
+            false,
+        )
+    }
+
+    /// Ask `flatc` for the dependencies of the generated files (`-M`) without generating them
+    pub fn depfile(&self, args: Args) -> Result<depfile::Depfile> {
+        if let Some(out_dir) = args.laid_out_out_dir()? {
+            return self.depfile(args.with_out_dir(&out_dir));
+        }
+        let mut cmd_args = args.build_flatc_args()?;
+        // `-M` has to precede the input files
+        cmd_args.insert(1, "-M".into());
+        let output = self.run_with_args_captured(cmd_args, false)?;
+        depfile::Depfile::parse(&String::from_utf8(output.stdout).map_err(err_other)?)
+    }
+
     /// Execute configured `flatc` with given args
+    ///
+    /// The runs into the same `out_dir` are serialized with an advisory inter-process lock, so
+    /// the build scripts sharing the directory can safely run in parallel.
+    ///
+    /// The run fails if `flatc` exits successfully without generating (or with an empty) file
+    /// expected for the inputs, e.g. `<schema>_generated.rs` for `rust` (the outputs of the
+    /// generators naming them after the types, e.g. `python`, are not checked), which is the
+    /// symptom of a wrong lang and flags combination. A run which has not generated (or
+    /// updated) any file at all fails with [`NothingGenerated`] naming the probable cause if
+    /// [`Args::require_generated`] is set.
+    ///
+    /// [`NothingGenerated`]: struct.NothingGenerated.html
+    /// [`Args::require_generated`]: struct.Args.html#structfield.require_generated
     pub fn run(&self, args: Args) -> Result<()> {
-        let mut cmd_args: Vec<OsString> = Vec::new();
+        self.run_collecting(args, false).map(|_| ())
+    }
 
-        if args.out_dir.as_os_str().is_empty() {
-            return Err(err_other("out_dir is empty"));
+    /// Execute configured `flatc` with given args and collect a [`Report`] about the run
+    ///
+    /// The runs into the same `out_dir` are serialized like in [`run`].
+    ///
+    /// [`Report`]: struct.Report.html
+    /// [`run`]: #method.run
+    pub fn run_with_report(&self, args: Args) -> Result<Report> {
+        self.run_collecting(args, true).map(|(report, _)| report)
+    }
+
+    /// Execute configured `flatc` with given args and return the [`RunOutput`]: the captured
+    /// output of `flatc` (it is still forwarded unless [`quiet`] is set), the duration and the
+    /// generated files
+    ///
+    /// The runs into the same `out_dir` are serialized like in [`run`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    ///
+    /// # fn try_main() -> flatc_rust::Result<()> {
+    /// let output = flatc_rust::Flatc::from_env_path().run_with_output(flatc_rust::Args {
+    ///     inputs: &[Path::new("src/message.fbs")],
+    ///     out_dir: Path::new("target/flatbuffers/"),
+    ///     quiet: true,
+    ///     ..Default::default()
+    /// })?;
+    /// println!(
+    ///     "flatc generated {} file(s) in {:?}",
+    ///     output.generated_files.len(),
+    ///     output.duration
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`RunOutput`]: struct.RunOutput.html
+    /// [`quiet`]: struct.Args.html#structfield.quiet
+    /// [`run`]: #method.run
+    pub fn run_with_output(&self, args: Args) -> Result<RunOutput> {
+        let (report, outputs) = self.run_collecting(args, true)?;
+        let mut output = RunOutput {
+            // The output restored from the cache has not failed
+            status: outputs
+                .last()
+                .map(|output| output.status)
+                .unwrap_or_default(),
+            captured_stdout: Vec::new(),
+            captured_stderr: Vec::new(),
+            duration: report.duration,
+            generated_files: report.generated_files,
+        };
+        for invocation_output in outputs {
+            output.captured_stdout.extend(invocation_output.stdout);
+            output.captured_stderr.extend(invocation_output.stderr);
         }
+        Ok(output)
+    }
 
-        cmd_args.push({
-            let mut arg = OsString::with_capacity(args.lang.len() + 3);
-            arg.push("--");
-            arg.push(args.lang);
-            arg
-        });
+    /// Execute configured `flatc` with given args collecting a [`Report`] and the captured
+    /// outputs of the `flatc` invocations (in the order of the inputs, none if the files are
+    /// restored from the [`cache_dir`])
+    ///
+    /// Without `capture`, a single invocation inherits the output streams (unless [`quiet`] is
+    /// set), and the report is only good for the generated files.
+    ///
+    /// [`Report`]: struct.Report.html
+    /// [`cache_dir`]: struct.Args.html#structfield.cache_dir
+    /// [`quiet`]: struct.Args.html#structfield.quiet
+    fn run_collecting(&self, args: Args, capture: bool) -> Result<(Report, Vec<process::Output>)> {
+        if let Some(out_dir) = args.laid_out_out_dir()? {
+            return self.run_collecting(args.with_out_dir(&out_dir), capture);
+        }
+        let _lock = DirLock::acquire(args.out_dir)?;
+        if args.atomic || args.skip_unchanged {
+            let ((mut report, outputs), generated_files, declared_outputs) =
+                args.run_staged(|args| self.run_collecting(args, capture))?;
+            report.generated_files = generated_files;
+            self.write_manifest(&args, &declared_outputs)?;
+            return Ok((report, outputs));
+        }
+        self.check_flag_support(&args)?;
+        #[cfg(not(feature = "verify"))]
+        if args.verify_rust {
+            return Err(Error::new(
+                io::ErrorKind::Unsupported,
+                "verify_rust needs the `verify` feature of flatc-rust",
+            ));
+        }
+        #[cfg(not(feature = "tonic"))]
+        if args.tonic {
+            return Err(Error::new(
+                io::ErrorKind::Unsupported,
+                "tonic needs the `tonic` feature of flatc-rust",
+            ));
+        }
+        if let Some(requirement) = args.reproducible {
+            self.require_version(requirement)?;
+        }
+        let _span = trace::run(&self.exec, args.inputs.len(), args.out_dir);
 
-        if args.binary {
-            cmd_args.push("--binary".into());
+        if args.rerun_if_changed {
+            self.depfile(args)?.emit_rerun_if_changed();
         }
 
-        if args.schema {
-            cmd_args.push("--schema".into());
+        // The plain runs do not spawn `flatc --version` unless they need it
+        let version = if capture || args.provenance || args.cache_dir.is_some() {
+            Some(self.query_version(args.log_level())?.version)
+        } else {
+            None
+        };
+        let cmd_args = args.flatc_args()?;
+        let mut invocations = args.build_invocations()?;
+        let inputs = args
+            .inputs
+            .iter()
+            .filter(|input| input.as_os_str() != "--")
+            .map(|input| InputReport::from_path(input))
+            .collect::<Result<Vec<_>>>()?;
+        let cache_entry = match (args.cache_dir, version.as_deref()) {
+            (Some(cache_dir), Some(version)) => {
+                let entry = CacheEntry::lock(cache_dir, &cache::key(version, &args)?)?;
+                if let Some(ref backend) = self.cache_backend {
+                    if !entry.is_populated() && entry.fetch(backend.as_ref()) {
+                        log!(
+                            args.log_level(),
+                            "fetched the flatc output from the cache backend"
+                        );
+                    }
+                }
+                Some(entry)
+            }
+            _ => None,
+        };
+
+        args.prepare_out_dir(&[])?;
+        // Only the expected outputs are looked for in `out_dir` unless the generated files are
+        // needed
+        let needs_snapshot = capture
+            || args.require_generated
+            || args.processes_generated_files()
+            || args.read_only
+            || args.manifest.is_some()
+            || args.tonic
+            || cache_entry
+                .as_ref()
+                .is_some_and(|entry| !entry.is_populated());
+        let before = if needs_snapshot {
+            Some(DirSnapshot::take(args.out_dir)?)
+        } else {
+            None
+        };
+        let started_at = Instant::now();
+        let outputs = match args.invocation {
+            _ if cache_entry.as_ref().is_some_and(CacheEntry::is_populated) => {
+                log!(
+                    args.log_level(),
+                    "reusing the cached flatc output for {} input(s)",
+                    args.inputs.len()
+                );
+                cache_entry
+                    .as_ref()
+                    .expect("the entry")
+                    .restore(args.out_dir)?;
+                Vec::new()
+            }
+            Invocation::Single if capture || args.quiet => {
+                vec![self.run_with_args_captured(invocations.remove(0), !args.quiet)?]
+            }
+            Invocation::Single => {
+                self.run_with_args(invocations.remove(0))?;
+                Vec::new()
+            }
+            Invocation::PerFile => self.run_invocations(&args, invocations)?,
+        };
+        let duration = started_at.elapsed();
+        outputs::check_expected_files(&args)?;
+        let generated_files = outputs::generated_files(&args, before.as_ref())?;
+        if before.is_some() {
+            outputs::check_generated_files(&args, &generated_files)?;
         }
+        if let Some(entry) = cache_entry.filter(|entry| !entry.is_populated()) {
+            entry.store(
+                args.out_dir,
+                &generated_files,
+                self.cache_backend.as_deref(),
+            )?;
+        }
+        #[cfg(feature = "tonic")]
+        if args.tonic {
+            self.append_tonic_services(&args, &generated_files)?;
+        }
+        let provenance = match version {
+            Some(ref version) if args.provenance => {
+                Some(postprocess::provenance_lines(version, &cmd_args, &inputs))
+            }
+            _ => None,
+        };
+        let generated_files = if args.processes_generated_files() {
+            args.process_generated_files(&generated_files, provenance.as_deref())?
+        } else {
+            generated_files
+        };
+        if args.read_only {
+            outputs::set_read_only(&generated_files)?;
+        }
+        args.emit_cargo_directives()?;
+        self.write_manifest(&args, &generated_files)?;
 
-        if args.json {
-            cmd_args.push("--json".into());
+        let metrics = Metrics::collect(inputs.len(), args.includes.len(), &generated_files)?;
+        log!(
+            args.log_level(),
+            "flatc compiled {} input(s) in {:?}: {} generated file(s), {} bytes in total, the \
+             largest is {} bytes",
+            metrics.input_count,
+            duration,
+            metrics.generated_file_count,
+            metrics.output_bytes,
+            metrics.peak_output_bytes
+        );
+        trace::completed(duration, Some(metrics.generated_file_count));
+
+        let warnings = outputs
+            .iter()
+            .flat_map(|output| report::collect_warnings(&output.stderr))
+            .collect();
+        let report = Report {
+            flatc_version: version.unwrap_or_default(),
+            args: cmd_args
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            inputs,
+            generated_files,
+            duration,
+            warnings,
+            metrics,
+        };
+        Ok((report, outputs))
+    }
+
+    /// Write the [`manifest`] of the successful run declaring the outputs, if it is requested
+    ///
+    /// [`manifest`]: struct.Args.html#structfield.manifest
+    fn write_manifest(&self, args: &Args, outputs: &[PathBuf]) -> Result<()> {
+        let Some(path) = args.manifest else {
+            return Ok(());
+        };
+        let dependency_schema_dirs = if args.dependency_schemas {
+            build_script::dependency_schema_dirs()
+        } else {
+            Vec::new()
+        };
+        let mut includes = args.includes.to_vec();
+        includes.extend(dependency_schema_dirs.iter().map(PathBuf::as_path));
+        let mut outputs = outputs.to_vec();
+        outputs.sort();
+        let manifest = Manifest {
+            flatc_version: self.query_version(args.log_level())?.version,
+            args: args
+                .flatc_args()?
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            inputs: args
+                .inputs
+                .iter()
+                .filter(|input| input.as_os_str() != "--")
+                .map(|input| InputReport::from_path(input))
+                .collect::<Result<Vec<_>>>()?,
+            includes: normalize::includes(&includes)
+                .into_iter()
+                .map(Path::to_owned)
+                .collect(),
+            outputs,
+        };
+        manifest
+            .write_json(path)
+            .map_err(|e| err_other(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Compile a single schema into a managed temporary directory and return the generated
+    /// source
+    ///
+    /// `args.inputs` and `args.out_dir` are ignored. If `flatc` generates several files (e.g.
+    /// with `--gen-all`), the one named after the schema (e.g. `message_generated.rs` for
+    /// `message.fbs`) is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    ///
+    /// # fn try_main() -> flatc_rust::Result<()> {
+    /// let source = flatc_rust::Flatc::from_env_path()
+    ///     .generate_to_string(Path::new("src/message.fbs"), Default::default())?;
+    /// assert!(source.contains("pub struct Message"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn generate_to_string(&self, schema: &Path, args: Args) -> Result<String> {
+        let out_dir = TempDir::new()?;
+        let before = DirSnapshot::take(out_dir.path())?;
+        self.run(Args {
+            inputs: &[schema],
+            out_dir: out_dir.path(),
+            read_only: false,
+            manifest: None,
+            cache_dir: None,
+            ..args
+        })?;
+        let generated_files = DirSnapshot::take(out_dir.path())?.changed_since(&before);
+
+        let generated_file = match generated_files.as_slice() {
+            [] => return Err(err_other("flatc has not generated any files")),
+            [generated_file] => generated_file,
+            generated_files => {
+                let schema_stem = schema.file_stem().unwrap_or_default();
+                generated_files
+                    .iter()
+                    .find(|generated_file| {
+                        generated_file.file_stem().is_some_and(|stem| {
+                            stem.to_string_lossy()
+                                .strip_suffix("_generated")
+                                .is_some_and(|stem| stem == schema_stem)
+                        })
+                    })
+                    .ok_or_else(|| {
+                        err_other(format!(
+                            "flatc has generated several files ({:?}), none of which matches the \
+                             schema name",
+                            generated_files
+                        ))
+                    })?
+            }
+        };
+
+        std::fs::read_to_string(generated_file)
+    }
+
+    /// Check whether the helpers in `args.out_dir` are up to date: regenerate them into a managed
+    /// temporary directory and list the files of `args.out_dir` which are missing or differ
+    ///
+    /// Nothing is written to `args.out_dir`, so it is suitable for CI checks of committed
+    /// helpers.
+    pub fn outdated_files(&self, args: Args) -> Result<Vec<PathBuf>> {
+        if let Some(out_dir) = args.laid_out_out_dir()? {
+            return self.outdated_files(args.with_out_dir(&out_dir));
         }
+        let temp_dir = TempDir::new()?;
+        let before = DirSnapshot::take(temp_dir.path())?;
+        self.run(Args {
+            out_dir: temp_dir.path(),
+            read_only: false,
+            rustc_env: None,
+            rerun_if_changed: false,
+            manifest: None,
+            cache_dir: None,
+            ..args
+        })?;
 
-        for extra_arg in args.extra {
-            cmd_args.push(extra_arg.into());
+        let mut outdated_files = Vec::new();
+        for generated_file in DirSnapshot::take(temp_dir.path())?.changed_since(&before) {
+            let relative = generated_file
+                .strip_prefix(temp_dir.path())
+                .map_err(err_other)?;
+            let existing_file = args.out_dir.join(relative);
+            let is_up_to_date = match std::fs::read(&existing_file) {
+                Ok(existing) => existing == std::fs::read(&generated_file)?,
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => false,
+                Err(e) => return Err(e),
+            };
+            if !is_up_to_date {
+                outdated_files.push(existing_file);
+            }
         }
+        Ok(outdated_files)
+    }
 
-        if args.lang.is_empty() {
-            return Err(err_other("lang is empty"));
+    /// Update the committed helpers in `args.out_dir` (e.g. `src/generated/`) in place: regenerate
+    /// them, rewrite only the files whose content has changed (see [`skip_unchanged`]) and remove
+    /// the stale generated files (see [`clean_out_dir`]), and return the updated and removed
+    /// files
+    ///
+    /// It is the fix for what [`outdated_files`] reports, meant for developers (or a bot) after
+    /// the schema changes. Nothing is written if `flatc` fails.
+    ///
+    /// [`skip_unchanged`]: struct.Args.html#structfield.skip_unchanged
+    /// [`clean_out_dir`]: struct.Args.html#structfield.clean_out_dir
+    /// [`outdated_files`]: #method.outdated_files
+    pub fn sync(&self, args: Args) -> Result<Vec<PathBuf>> {
+        if let Some(out_dir) = args.laid_out_out_dir()? {
+            return self.sync(args.with_out_dir(&out_dir));
+        }
+        let before = DirSnapshot::take(args.out_dir)?.generated_files();
+        let report = self.run_with_report(Args {
+            skip_unchanged: true,
+            clean_out_dir: true,
+            rustc_env: None,
+            rerun_if_changed: false,
+            ..args
+        })?;
+
+        let mut updated_files = report.generated_files;
+        updated_files.extend(before.into_iter().filter(|file| !file.exists()));
+        updated_files.sort();
+        Ok(updated_files)
+    }
+
+    /// Run `flatc` if it is available, otherwise copy the pre-generated helpers committed to
+    /// `committed_dir` into `args.out_dir` (printing a `cargo:warning`), so the crate still
+    /// builds where `flatc` is not installed (e.g. docs.rs)
+    ///
+    /// Only a missing `flatc` ([`FlatcNotFound`]) triggers the fallback; the failures of the
+    /// run are reported as usual. Keep `committed_dir` up to date with [`outdated_files`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    ///
+    /// # fn try_main() -> flatc_rust::Result<()> {
+    /// flatc_rust::Flatc::from_env_path().run_or_fallback(
+    ///     flatc_rust::Args {
+    ///         inputs: &[Path::new("src/message.fbs")],
+    ///         out_dir: Path::new("target/flatbuffers/"),
+    ///         ..Default::default()
+    ///     },
+    ///     Path::new("src/generated/"),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`FlatcNotFound`]: struct.FlatcNotFound.html
+    /// [`outdated_files`]: #method.outdated_files
+    pub fn run_or_fallback(&self, args: Args, committed_dir: &Path) -> Result<()> {
+        let e = match self.check() {
+            Ok(()) => return self.run(args),
+            Err(e) => e,
+        };
+        if !e.get_ref().is_some_and(|inner| inner.is::<FlatcNotFound>()) {
+            return Err(e);
+        }
+        if let Some(out_dir) = args.laid_out_out_dir()? {
+            return self.run_or_fallback(args.with_out_dir(&out_dir), committed_dir);
+        }
+        if !committed_dir.is_dir() {
+            return Err(err_other(format!(
+                "{}, and there are no pre-generated helpers in {}",
+                e,
+                committed_dir.display()
+            )));
+        }
+
+        log!(
+            Level::Warn,
+            "flatc is not available, copying the pre-generated helpers from {}",
+            committed_dir.display()
+        );
+        build_script::warning(&format!(
+            "flatc is not found, using the pre-generated helpers from {}",
+            committed_dir.display()
+        ));
+        args.prepare_out_dir(&[])?;
+        let copied_files = outputs::copy_files(committed_dir, args.out_dir)?;
+        for copied_file in &copied_files {
+            log!(
+                args.log_level(),
+                "copied pre-generated file {:?}",
+                copied_file
+            );
+        }
+        if args.read_only {
+            outputs::set_read_only(&copied_files)?;
+        }
+        args.emit_cargo_directives()
+    }
+
+    /// Annotate the binary FlatBuffers (`--annotate`) with the layout described by the schema to
+    /// debug malformed buffers, and return the paths of the annotated dumps
+    ///
+    /// `flatc` writes the dump of every binary next to it, replacing the extension with `.afb`
+    /// (e.g. `payload.bin` is annotated into `payload.afb`); the existing dumps are overwritten.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    ///
+    /// # fn try_main() -> flatc_rust::Result<()> {
+    /// let dumps = flatc_rust::Flatc::from_env_path().annotate(
+    ///     Path::new("src/message.fbs"),
+    ///     &[],
+    ///     &[Path::new("corrupted/payload.bin")],
+    /// )?;
+    /// assert_eq!(dumps, vec![Path::new("corrupted/payload.afb")]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn annotate(
+        &self,
+        schema: &Path,
+        includes: &[&Path],
+        binaries: &[&Path],
+    ) -> Result<Vec<PathBuf>> {
+        if binaries.is_empty() {
+            return Err(err_other("no binary files to annotate"));
+        }
+        let annotated_files: Vec<PathBuf> = binaries
+            .iter()
+            .map(|binary| binary.with_extension("afb"))
+            .collect();
+        // `flatc` only warns about the binaries it fails to annotate, so the stale dumps must not
+        // be mistaken for the fresh ones
+        for annotated_file in &annotated_files {
+            match std::fs::remove_file(annotated_file) {
+                Ok(()) => (),
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+                Err(e) => return Err(e),
+            }
         }
 
-        for include in args.includes.iter() {
+        let mut cmd_args: Vec<OsString> = vec!["--annotate".into(), schema.into()];
+        for include in includes {
             cmd_args.push("-I".into());
             cmd_args.push(include.into());
         }
+        cmd_args.extend(binaries.iter().map(|&binary| binary.into()));
+        self.run_with_args(cmd_args)?;
 
-        cmd_args.push("-o".into());
-        cmd_args.push(
-            args.out_dir
-                .to_str()
-                .ok_or_else(|| {
-                    Error::new(
-                        io::ErrorKind::Other,
-                        "only UTF-8 convertable paths are supported",
-                    )
-                })?
-                .into(),
-        );
+        for (binary, annotated_file) in binaries.iter().zip(&annotated_files) {
+            if !annotated_file.is_file() {
+                return Err(err_other(format!(
+                    "flatc has not annotated {}",
+                    binary.display()
+                )));
+            }
+        }
+        Ok(annotated_files)
+    }
 
-        if args.inputs.is_empty() {
-            return Err(err_other("input is empty"));
+    /// Load the binary schema of the `.fbs` schema (compiled into a managed temporary directory)
+    /// or of the existing `.bfbs` file (see [`reflection`])
+    ///
+    /// [`reflection`]: reflection/index.html
+    pub fn binary_schema(&self, schema: &Path, includes: &[&Path]) -> Result<reflection::Schema> {
+        if schema
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("bfbs"))
+        {
+            return reflection::Schema::from_path(schema);
         }
+        self.compile_binary_schema(schema, includes, &[])
+    }
 
-        cmd_args.extend(args.inputs.iter().map(|input| input.into()));
+    /// Compile the binary schema of the `.fbs` schema with the extra flags (e.g.
+    /// `--bfbs-comments`) in a managed temporary directory and load it
+    fn compile_binary_schema(
+        &self,
+        schema: &Path,
+        includes: &[&Path],
+        extra: &[&str],
+    ) -> Result<reflection::Schema> {
+        let out_dir = TempDir::new()?;
+        self.run(Args {
+            inputs: &[schema],
+            includes,
+            out_dir: out_dir.path(),
+            extra,
+            ..Args::bfbs_only()
+        })?;
+        let bfbs_name = Path::new(schema.file_stem().unwrap_or_default()).with_extension("bfbs");
+        reflection::Schema::from_path(out_dir.path().join(bfbs_name))
+    }
 
-        self.run_with_args(cmd_args)
+    /// Append the tonic modules of the services declared by every schema to its generated Rust
+    /// file (see [`Args::tonic`])
+    ///
+    /// [`Args::tonic`]: struct.Args.html#structfield.tonic
+    #[cfg(feature = "tonic")]
+    fn append_tonic_services(&self, args: &Args, generated_files: &[PathBuf]) -> Result<()> {
+        let dependency_schema_dirs = if args.dependency_schemas {
+            build_script::dependency_schema_dirs()
+        } else {
+            Vec::new()
+        };
+        let mut includes = args.includes.to_vec();
+        includes.extend(dependency_schema_dirs.iter().map(PathBuf::as_path));
+        for &input in args
+            .inputs
+            .iter()
+            .take_while(|input| input.as_os_str() != "--")
+        {
+            if input.extension().is_none_or(|extension| extension != "fbs") {
+                continue;
+            }
+            let declared_services: Vec<String> = schema::Schema::from_path(input)?
+                .declarations
+                .iter()
+                .filter(|declaration| declaration.kind == schema::DeclarationKind::RpcService)
+                .map(schema::Declaration::qualified_name)
+                .collect();
+            if declared_services.is_empty() {
+                continue;
+            }
+            let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+            let generated_file = args.out_dir.join(format!("{}_generated.rs", stem));
+            if !generated_files.contains(&generated_file) {
+                return Err(err_other(format!(
+                    "tonic needs the Rust helpers of {} in {}, but flatc has not generated them",
+                    input.display(),
+                    generated_file.display()
+                )));
+            }
+            let mut services = self
+                .compile_binary_schema(input, &includes, &["--bfbs-comments", "--bfbs-builtins"])?
+                .services;
+            services.retain(|service| declared_services.contains(&service.name));
+            let mut code = std::fs::read_to_string(&generated_file)?;
+            code.push_str(&tonic::generate(&services)?);
+            std::fs::write(&generated_file, code)?;
+        }
+        Ok(())
     }
-}
 
-/// Execute `flatc` found in `$PATH` with given args
-///
-/// # Examples
-///
-/// Please, refer to [the root crate documentation](index.html#examples).
-pub fn run(args: Args) -> Result<()> {
-    let flatc = Flatc::from_env_path();
+    /// Convert the JSON into a binary FlatBuffer (its `root_type`) with the schema, staging the
+    /// files in a managed temporary directory
+    ///
+    /// The includes of the schema are looked up relative to it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    ///
+    /// # fn try_main() -> flatc_rust::Result<()> {
+    /// let flatc = flatc_rust::Flatc::from_env_path();
+    /// let schema = Path::new("src/message.fbs");
+    /// let binary = flatc.json_to_binary_bytes(schema, r#"{"text": "hello"}"#)?;
+    /// assert!(flatc.binary_to_json_string(schema, &binary)?.contains("hello"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn json_to_binary_bytes(&self, schema: &Path, json: &str) -> Result<Vec<u8>> {
+        let temp_dir = TempDir::new()?;
+        let json_path = temp_dir.path().join("data.json");
+        std::fs::write(&json_path, json)?;
+        let out_dir = temp_dir.path().join("out");
+        self.run(Args {
+            lang: "binary",
+            inputs: &[schema, &json_path],
+            out_dir: &out_dir,
+            quiet: true,
+            ..Default::default()
+        })?;
+        std::fs::read(out_dir.join("data.bin"))
+    }
 
-    // First check with have good `flatc`
-    flatc.check()?;
+    /// Convert the binary FlatBuffer (its `root_type`) into strict JSON with the schema (see
+    /// [`Args::json_conversion`]), staging the files in a managed temporary directory
+    ///
+    /// The includes of the schema are looked up relative to it.
+    ///
+    /// [`Args::json_conversion`]: struct.Args.html#method.json_conversion
+    pub fn binary_to_json_string(&self, schema: &Path, binary: &[u8]) -> Result<String> {
+        let temp_dir = TempDir::new()?;
+        let binary_path = temp_dir.path().join("data.bin");
+        std::fs::write(&binary_path, binary)?;
+        let out_dir = temp_dir.path().join("out");
+        self.run(Args {
+            inputs: &[schema, Path::new("--"), &binary_path],
+            out_dir: &out_dir,
+            quiet: true,
+            ..Args::json_conversion()
+        })?;
+        std::fs::read_to_string(out_dir.join("data.json"))
+    }
 
-    flatc.run(args)
-}
+    /// Compare two versions of the schema (`.fbs` or `.bfbs` files) at the FlatBuffers level
+    /// (see [`diff`])
+    ///
+    /// The includes of `.fbs` schemas are looked up relative to the schemas.
+    ///
+    /// [`diff`]: diff/index.html
+    pub fn diff(&self, old_schema: &Path, new_schema: &Path) -> Result<diff::SchemaDiff> {
+        Ok(diff::SchemaDiff::between(
+            &self.binary_schema(old_schema, &[])?,
+            &self.binary_schema(new_schema, &[])?,
+        ))
+    }
 
-/// FlatBuffers (flatc) version.
-pub struct Version {
-    version: String,
+    /// Check that the new version of the schema (`.fbs` or `.bfbs` file) keeps the compatibility
+    /// with the old one, reporting all the violations at once with [`CompatibilityError`]
+    ///
+    /// [`CompatibilityError`]: compatibility/struct.CompatibilityError.html
+    pub fn check_compatibility(
+        &self,
+        old_schema: &Path,
+        new_schema: &Path,
+        policy: compatibility::Policy,
+    ) -> Result<()> {
+        let violations = compatibility::violations(&self.diff(old_schema, new_schema)?, policy);
+        if violations.is_empty() {
+            return Ok(());
+        }
+        Err(Error::new(
+            io::ErrorKind::InvalidData,
+            compatibility::CompatibilityError { policy, violations },
+        ))
+    }
 }
 
-impl Version {
-    /// Version getter
-    pub fn version(&self) -> &str {
-        &self.version
+impl<'a> Args<'a> {
+    /// Check that the input files and the include directories exist and the inputs have plausible
+    /// extensions (done before every `flatc` run)
+    ///
+    /// All the problems are reported at once with [`ValidationError`].
+    ///
+    /// [`ValidationError`]: struct.ValidationError.html
+    pub fn validate(&self) -> Result<()> {
+        validation::validate(self)
     }
-}
 
-#[cfg(test)]
-mod test {
-    use tempfile;
+    /// Prepare `out_dir` for the run according to the args (the `keep` files are not cleaned)
+    fn prepare_out_dir(&self, keep: &[PathBuf]) -> Result<()> {
+        if self.create_out_dir {
+            std::fs::create_dir_all(self.out_dir)?;
+        }
+        if self.read_only {
+            outputs::make_writable(self.out_dir)?;
+        }
+        if self.clean_out_dir {
+            for removed_file in outputs::clean_generated_files(self.out_dir, keep)? {
+                log!(
+                    self.log_level(),
+                    "removed stale generated file {:?}",
+                    removed_file
+                );
+            }
+        }
+        Ok(())
+    }
 
-    use super::*;
+    /// Run `f` with `out_dir` replaced by a staging directory next to it (on the same file
+    /// system), and move the generated files into `out_dir` only once `f` succeeds (skipping the
+    /// unchanged ones with [`skip_unchanged`]); return the result of `f`, the moved files and
+    /// all the files `f` has generated (under `out_dir`)
+    ///
+    /// [`skip_unchanged`]: #structfield.skip_unchanged
+    fn run_staged<T, F: FnOnce(Args) -> Result<T>>(
+        &self,
+        f: F,
+    ) -> Result<(T, Vec<PathBuf>, Vec<PathBuf>)> {
+        // Fail early on invalid args, the staging directory hides the empty `out_dir`
+        self.build_flatc_args()?;
 
-    #[test]
-    fn version() {
-        Flatc::from_env_path().version().expect("version");
-    }
+        let parent = match self.out_dir.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        std::fs::create_dir_all(parent)?;
+        let staging_dir = match self.out_dir.file_name() {
+            // The runs into `out_dir` are serialized, so the name is not shared
+            Some(name) if self.reproducible.is_some() => {
+                TempDir::new_named_in(parent, &name.to_string_lossy())?
+            }
+            _ => TempDir::new_in(parent)?,
+        };
+        let result = f(Args {
+            out_dir: staging_dir.path(),
+            create_out_dir: false,
+            clean_out_dir: false,
+            atomic: false,
+            skip_unchanged: false,
+            read_only: false,
+            rustc_env: None,
+            manifest: None,
+            ..*self
+        })?;
 
-    #[test]
-    fn run_can_produce_output() -> io::Result<()> {
-        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
-        let input_path = temp_dir.path().join("test.fbs");
-        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")
-            .expect("test input fbs file could not be written");
+        let staged_files = DirSnapshot::take(staging_dir.path())?
+            .files()
+            .into_iter()
+            .map(|staged_file| {
+                let generated_file = self.out_dir.join(
+                    staged_file
+                        .strip_prefix(staging_dir.path())
+                        .map_err(err_other)?,
+                );
+                Ok((staged_file, generated_file))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if self.skip_unchanged {
+            let regenerated_files: Vec<PathBuf> = staged_files
+                .iter()
+                .map(|(_, generated_file)| generated_file.clone())
+                .collect();
+            self.prepare_out_dir(&regenerated_files)?;
+        } else {
+            self.prepare_out_dir(&[])?;
+        }
+        let outputs: Vec<PathBuf> = staged_files
+            .iter()
+            .map(|(_, generated_file)| generated_file.clone())
+            .collect();
+        let mut generated_files = Vec::new();
+        for (staged_file, generated_file) in staged_files {
+            if self.skip_unchanged && outputs::have_same_content(&staged_file, &generated_file)? {
+                log!(
+                    Level::Debug,
+                    "{:?} has not changed, leaving it untouched",
+                    generated_file
+                );
+                continue;
+            }
+            if let Some(generated_dir) = generated_file.parent() {
+                std::fs::create_dir_all(generated_dir)?;
+            }
+            std::fs::rename(&staged_file, &generated_file)?;
+            generated_files.push(generated_file);
+        }
+        if self.read_only {
+            outputs::set_read_only(&outputs)?;
+        }
+        // `f` has printed the `rerun-if-changed` directives, only `out_dir` has changed
+        self.emit_rustc_env()?;
 
-        run(Args {
+        Ok((result, generated_files, outputs))
+    }
+
+    /// `out_dir` according to [`out_dir_layout`], or `None` if `out_dir` is used as is
+    ///
+    /// [`out_dir_layout`]: #structfield.out_dir_layout
+    fn laid_out_out_dir(&self) -> Result<Option<PathBuf>> {
+        self.laid_out_out_dir_with(|name| std::env::var(name).ok())
+    }
+
+    /// [`laid_out_out_dir`](#method.laid_out_out_dir) with the environment variables looked up
+    /// by `lookup`
+    fn laid_out_out_dir_with<F: Fn(&str) -> Option<String>>(
+        &self,
+        lookup: F,
+    ) -> Result<Option<PathBuf>> {
+        let var = |name: &str| {
+            lookup(name).ok_or_else(|| {
+                err_other(format!(
+                    "{} is not set (is it run from a build script?), but out_dir_layout is {:?}",
+                    name, self.out_dir_layout
+                ))
+            })
+        };
+        Ok(match self.out_dir_layout {
+            OutDirLayout::Shared => None,
+            OutDirLayout::PerTarget => Some(self.out_dir.join(var("TARGET")?)),
+            OutDirLayout::PerTargetAndProfile => {
+                Some(self.out_dir.join(var("TARGET")?).join(var("PROFILE")?))
+            }
+        })
+    }
+
+    /// The args with `out_dir` replaced (and used as is)
+    fn with_out_dir<'b>(&self, out_dir: &'b Path) -> Args<'b>
+    where
+        'a: 'b,
+    {
+        Args {
+            out_dir,
+            out_dir_layout: OutDirLayout::Shared,
+            ..*self
+        }
+    }
+
+    /// The level of the progress logging (`debug` in [`quiet`] mode)
+    ///
+    /// [`quiet`]: #structfield.quiet
+    fn log_level(&self) -> Level {
+        if self.quiet {
+            Level::Debug
+        } else {
+            Level::Info
+        }
+    }
+
+    /// Whether the generated files have to be processed after `flatc` run
+    fn processes_generated_files(&self) -> bool {
+        self.postprocess.is_some()
+            || self.verify_rust
+            || self.reproducible.is_some()
+            || self.no_std
+            || self.rename.is_some()
+            || self.provenance
+            || self.banner.is_some()
+    }
+
+    /// Process the generated files according to the args (`provenance` holds the lines of the
+    /// [`provenance`] comment) and return their final paths
+    ///
+    /// [`provenance`]: #structfield.provenance
+    fn process_generated_files(
+        &self,
+        generated_files: &[PathBuf],
+        provenance: Option<&[String]>,
+    ) -> Result<Vec<PathBuf>> {
+        if let Some(postprocess) = self.postprocess {
+            postprocess::apply(postprocess, generated_files)?;
+        }
+        if self.no_std {
+            let rust_files: Vec<PathBuf> = generated_files
+                .iter()
+                .filter(|file| file.extension().is_some_and(|extension| extension == "rs"))
+                .cloned()
+                .collect();
+            postprocess::apply(postprocess::no_std, &rust_files)?;
+            postprocess::check_no_std_files(&rust_files)?;
+        }
+        if let Some(lines) = provenance {
+            postprocess::apply(
+                |path, content| postprocess::prepend_comment(path, content, lines),
+                generated_files,
+            )?;
+        }
+        if let Some(banner) = self.banner {
+            let lines: Vec<String> = banner.lines().map(str::to_owned).collect();
+            postprocess::apply(
+                |path, content| postprocess::prepend_comment(path, content, &lines),
+                generated_files,
+            )?;
+        }
+        if self.reproducible.is_some() {
+            let roots = reproducible::path_roots(self);
+            postprocess::apply(
+                |_, content| reproducible::normalize(content, &roots),
+                generated_files,
+            )?;
+        }
+        #[cfg(feature = "verify")]
+        if self.verify_rust {
+            verify::verify_rust_files(generated_files)?;
+        }
+        match self.rename {
+            Some(rename) => self.rename_generated_files(rename, generated_files),
+            None => Ok(generated_files.to_vec()),
+        }
+    }
+
+    /// Rename the generated files inside `out_dir` (see [`rename`])
+    ///
+    /// [`rename`]: #structfield.rename
+    fn rename_generated_files(
+        &self,
+        rename: fn(&Path) -> PathBuf,
+        generated_files: &[PathBuf],
+    ) -> Result<Vec<PathBuf>> {
+        let mut renamed_files: Vec<PathBuf> = Vec::with_capacity(generated_files.len());
+        for generated_file in generated_files {
+            let relative = generated_file
+                .strip_prefix(self.out_dir)
+                .map_err(err_other)?;
+            let renamed = rename(relative);
+            if renamed.is_absolute()
+                || renamed
+                    .components()
+                    .any(|component| component == std::path::Component::ParentDir)
+            {
+                return Err(err_other(format!(
+                    "{} is renamed to {} outside of out_dir",
+                    relative.display(),
+                    renamed.display()
+                )));
+            }
+            let renamed_file = self.out_dir.join(renamed);
+            if renamed_files.contains(&renamed_file) {
+                return Err(err_other(format!(
+                    "several generated files are renamed to {}",
+                    renamed_file.display()
+                )));
+            }
+            if renamed_file != *generated_file {
+                if let Some(renamed_dir) = renamed_file.parent() {
+                    std::fs::create_dir_all(renamed_dir)?;
+                }
+                std::fs::rename(generated_file, &renamed_file)?;
+            }
+            renamed_files.push(renamed_file);
+        }
+        Ok(renamed_files)
+    }
+
+    /// Print the requested build script directives for Cargo after a successful run
+    fn emit_cargo_directives(&self) -> Result<()> {
+        self.emit_rustc_env()?;
+        if self.dependency_schemas {
+            for schema_dir in build_script::dependency_schema_dirs() {
+                build_script::rerun_if_changed(&schema_dir);
+            }
+        }
+        Ok(())
+    }
+
+    /// Print the [`rustc_env`] directive, if it is requested
+    ///
+    /// [`rustc_env`]: #structfield.rustc_env
+    fn emit_rustc_env(&self) -> Result<()> {
+        if let Some(name) = self.rustc_env {
+            build_script::rustc_env(name, &self.out_dir.canonicalize()?);
+        }
+        Ok(())
+    }
+
+    /// Convert the args into the `flatc` command line arguments of every invocation (see
+    /// [`Invocation`]) after validating them.
+    fn build_invocations(&self) -> Result<Vec<Vec<OsString>>> {
+        match self.invocation {
+            Invocation::Single => Ok(vec![self.build_flatc_args()?]),
+            Invocation::PerFile => {
+                self.validate()?;
+                if self.inputs.iter().any(|input| input.as_os_str() == "--") {
+                    return Err(err_other(
+                        "per-file invocation cannot convert binary files (following `--`)",
+                    ));
+                }
+                self.inputs
+                    .iter()
+                    .map(|input| {
+                        Args {
+                            inputs: std::slice::from_ref(input),
+                            ..*self
+                        }
+                        .resolved_flatc_args()
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Convert the args into the `flatc` command line arguments with absolute paths after
+    /// validating them.
+    fn build_flatc_args(&self) -> Result<Vec<OsString>> {
+        let cmd_args = self.resolved_flatc_args()?;
+        self.validate()?;
+        Ok(cmd_args)
+    }
+
+    /// Convert the args into the `flatc` command line arguments with the paths resolved against
+    /// the working directory, so `flatc` diagnostics do not depend on where it runs
+    ///
+    /// The paths are made absolute lexically (unlike `canonicalize`, the symlinks are kept and
+    /// Windows gets no `\\?\` prefixes `flatc` does not understand).
+    fn resolved_flatc_args(&self) -> Result<Vec<OsString>> {
+        self.flatc_args_with(|path| {
+            std::path::absolute(path).map_err(|e| {
+                Error::new(
+                    e.kind(),
+                    format!(
+                        "cannot resolve {} against the working directory: {}",
+                        path.display(),
+                        e
+                    ),
+                )
+            })
+        })
+    }
+
+    /// Convert the args into the `flatc` command line arguments (without looking at the file
+    /// system).
+    fn flatc_args(&self) -> Result<Vec<OsString>> {
+        self.flatc_args_with(|path| Ok(path.to_path_buf()))
+    }
+
+    /// Convert the args into the `flatc` command line arguments, mapping the paths with `resolve`
+    fn flatc_args_with<F: Fn(&Path) -> Result<PathBuf>>(
+        &self,
+        resolve: F,
+    ) -> Result<Vec<OsString>> {
+        let mut cmd_args: Vec<OsString> = Vec::new();
+
+        if self.out_dir.as_os_str().is_empty() {
+            return Err(err_other("out_dir is empty"));
+        }
+
+        cmd_args.push({
+            let mut arg = OsString::with_capacity(self.lang.len() + 3);
+            arg.push("--");
+            arg.push(self.lang);
+            arg
+        });
+
+        if self.binary {
+            cmd_args.push("--binary".into());
+        }
+
+        if self.schema {
+            cmd_args.push("--schema".into());
+        }
+
+        if self.json {
+            cmd_args.push("--json".into());
+        }
+
+        if self.jsonschema {
+            cmd_args.push("--jsonschema".into());
+        }
+
+        if let Some(bfbs_filenames) = self.bfbs_filenames {
+            cmd_args.push("--bfbs-filenames".into());
+            cmd_args.push(resolve(bfbs_filenames)?.into());
+        }
+
+        if self.require_explicit_ids {
+            cmd_args.push("--require-explicit-ids".into());
+        }
+
+        if let Some(conform) = self.conform {
+            cmd_args.push("--conform".into());
+            cmd_args.push(resolve(conform)?.into());
+        }
+
+        for conform_include in self.conform_includes {
+            cmd_args.push("--conform-includes".into());
+            cmd_args.push(resolve(conform_include)?.into());
+        }
+
+        if self.no_warnings {
+            cmd_args.push("--no-warnings".into());
+        }
+
+        if self.allow_non_utf8 {
+            cmd_args.push("--allow-non-utf8".into());
+        }
+
+        if self.unknown_json {
+            cmd_args.push("--unknown-json".into());
+        }
+
+        if self.rust_module_root_file {
+            cmd_args.push("--rust-module-root-file".into());
+        }
+
+        if self.rust_serialize {
+            cmd_args.push("--rust-serialize".into());
+        }
+
+        if self.gen_json_emit {
+            cmd_args.push("--gen-json-emit".into());
+        }
+
+        if self.cs_gen_json_serializer {
+            cmd_args.push("--cs-gen-json-serializer".into());
+        }
+
+        for extra_arg in self.extra {
+            cmd_args.push(extra_arg.into());
+        }
+
+        if self.lang.is_empty() {
+            return Err(err_other("lang is empty"));
+        }
+
+        let dependency_schema_dirs = if self.dependency_schemas {
+            build_script::dependency_schema_dirs()
+        } else {
+            Vec::new()
+        };
+        let mut includes = self.includes.to_vec();
+        includes.extend(dependency_schema_dirs.iter().map(PathBuf::as_path));
+        for include in normalize::includes(&includes) {
+            cmd_args.push("-I".into());
+            cmd_args.push(resolve(include)?.into());
+        }
+
+        cmd_args.push("-o".into());
+        cmd_args.push(
+            resolve(self.out_dir)?
+                .into_os_string()
+                .into_string()
+                .map_err(|_| err_other("only UTF-8 convertable paths are supported"))?
+                .into(),
+        );
+
+        if self.inputs.is_empty() {
+            return Err(err_other("input is empty"));
+        }
+
+        for input in normalize::inputs(self.inputs) {
+            if input.as_os_str() == "--" {
+                cmd_args.push(input.into());
+            } else {
+                cmd_args.push(resolve(input)?.into());
+            }
+        }
+
+        Ok(cmd_args)
+    }
+}
+
+/// Execute `flatc` found in `$PATH` with given args
+///
+/// If there is no `flatc` in `$PATH` (and [`FLATC_ENV`] is not set), the one found in the common
+/// installation locations is used (see [`Flatc::locate`]), e.g. in the activated conda
+/// environment when the build does not inherit its `$PATH`; the same goes for the rest of the
+/// functions running `flatc` found in `$PATH`.
+///
+/// # Examples
+///
+/// Please, refer to [the root crate documentation](index.html#examples).
+///
+/// [`FLATC_ENV`]: constant.FLATC_ENV.html
+/// [`Flatc::locate`]: struct.Flatc.html#method.locate
+pub fn run(args: Args) -> Result<()> {
+    run_with(args, &discovery::process_env)
+}
+
+/// [`run`] with the environment variables looked up by `lookup`
+///
+/// [`run`]: fn.run.html
+fn run_with(args: Args, lookup: discovery::Lookup) -> Result<()> {
+    let flatc = discovery::default_flatc_with(lookup)?;
+
+    // First check with have good `flatc`
+    flatc.check()?;
+
+    flatc.run(args)
+}
+
+/// Execute `flatc` found in `$PATH` with given args, or copy the pre-generated helpers from
+/// `committed_dir` if there is no `flatc` (see [`Flatc::run_or_fallback`])
+///
+/// [`Flatc::run_or_fallback`]: struct.Flatc.html#method.run_or_fallback
+pub fn run_or_fallback(args: Args, committed_dir: &Path) -> Result<()> {
+    discovery::default_flatc()
+        .unwrap_or_else(|_| Flatc::from_env_path())
+        .run_or_fallback(args, committed_dir)
+}
+
+/// Execute `flatc` found in `$PATH` with given args and collect a [`Report`] about the run
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// # fn try_main() -> flatc_rust::Result<()> {
+/// let out_dir = Path::new("target/flatbuffers/");
+/// let report = flatc_rust::run_with_report(flatc_rust::Args {
+///     inputs: &[Path::new("src/message.fbs")],
+///     out_dir,
+///     ..Default::default()
+/// })?;
+/// // Archive the codegen provenance next to the generated helpers
+/// report.write_to_dir(out_dir)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Report`]: struct.Report.html
+pub fn run_with_report(args: Args) -> Result<Report> {
+    let flatc = discovery::default_flatc()?;
+
+    // First check with have good `flatc`
+    flatc.check()?;
+
+    flatc.run_with_report(args)
+}
+
+/// Execute `flatc` found in `$PATH` with given args and return the [`RunOutput`] (see
+/// [`Flatc::run_with_output`])
+///
+/// [`RunOutput`]: struct.RunOutput.html
+/// [`Flatc::run_with_output`]: struct.Flatc.html#method.run_with_output
+pub fn run_with_output(args: Args) -> Result<RunOutput> {
+    let flatc = discovery::default_flatc()?;
+
+    // First check with have good `flatc`
+    flatc.check()?;
+
+    flatc.run_with_output(args)
+}
+
+/// Compare two versions of the schema (`.fbs` or `.bfbs` files) with `flatc` found in `$PATH`
+///
+/// See [`Flatc::diff`] and [the `diff` module](diff/index.html).
+///
+/// [`Flatc::diff`]: struct.Flatc.html#method.diff
+pub fn diff<P: AsRef<Path>, Q: AsRef<Path>>(
+    old_schema: P,
+    new_schema: Q,
+) -> Result<diff::SchemaDiff> {
+    let flatc = discovery::default_flatc()?;
+
+    // First check with have good `flatc`
+    flatc.check()?;
+
+    flatc.diff(old_schema.as_ref(), new_schema.as_ref())
+}
+
+/// Compile the schemas into Rust helpers in `OUT_DIR` with `flatc` found in `$PATH` (a build
+/// script is expected), in the spirit of `prost_build::compile_protos`
+///
+/// `cargo:rerun-if-changed` is printed for the schemas and everything they include (see
+/// [`Args::rerun_if_changed`]).
+///
+/// # Examples
+///
+/// ```no_run
+/// // build.rs
+/// flatc_rust::compile_schemas(&["schemas/message.fbs"], &["schemas/"]).expect("flatc");
+/// ```
+///
+/// ```ignore
+/// // src/lib.rs
+/// include!(concat!(env!("OUT_DIR"), "/message_generated.rs"));
+/// ```
+///
+/// [`Args::rerun_if_changed`]: struct.Args.html#structfield.rerun_if_changed
+pub fn compile_schemas<P: AsRef<Path>, I: AsRef<Path>>(
+    schemas: &[P],
+    includes: &[I],
+) -> Result<()> {
+    compile_schemas_with(schemas, includes, |name| std::env::var_os(name))
+}
+
+/// [`compile_schemas`] with the environment variables looked up by `lookup`
+///
+/// [`compile_schemas`]: fn.compile_schemas.html
+fn compile_schemas_with<P: AsRef<Path>, I: AsRef<Path>, F: Fn(&str) -> Option<OsString>>(
+    schemas: &[P],
+    includes: &[I],
+    lookup: F,
+) -> Result<()> {
+    let out_dir = lookup("OUT_DIR").ok_or_else(|| err_other("OUT_DIR is not set"))?;
+    let inputs: Vec<&Path> = schemas.iter().map(AsRef::as_ref).collect();
+    let includes: Vec<&Path> = includes.iter().map(AsRef::as_ref).collect();
+    run_with(
+        Args {
+            lang: "rust",
+            inputs: &inputs,
+            includes: &includes,
+            out_dir: Path::new(&out_dir),
+            rerun_if_changed: true,
+            ..Default::default()
+        },
+        &lookup,
+    )
+}
+
+/// FlatBuffers (flatc) version.
+///
+/// ```
+/// let version: flatc_rust::Version = "24.3.25".parse().expect("version");
+/// assert_eq!((version.major(), version.minor(), version.patch()), (24, 3, 25));
+/// assert_eq!(version.to_string(), "24.3.25");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    version: String,
+    components: (u64, u64, u64),
+}
+
+impl Version {
+    /// Version getter
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The major version, e.g. `24` of `24.3.25`
+    pub fn major(&self) -> u64 {
+        self.components.0
+    }
+
+    /// The minor version (zero if missing), e.g. `3` of `24.3.25`
+    pub fn minor(&self) -> u64 {
+        self.components.1
+    }
+
+    /// The patch version (zero if missing), e.g. `25` of `24.3.25`
+    pub fn patch(&self) -> u64 {
+        self.components.2
+    }
+
+    /// Check whether the version matches the requirement: comma-separated comparators (`=`, `>`,
+    /// `>=`, `<`, `<=`, `^`, `~` followed by a version, or `*`) which all have to match
+    ///
+    /// A bare version matches exactly, up to its precision (`24.3` matches any `24.3.x`).
+    pub fn matches(&self, requirement: &str) -> Result<bool> {
+        requirement::matches(&self.version, requirement)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.version)
+    }
+}
+
+impl std::str::FromStr for Version {
+    type Err = Error;
+
+    /// Parse the version as printed by `flatc --version` (without the `flatc version ` prefix)
+    fn from_str(version: &str) -> Result<Version> {
+        let first_char = version
+            .chars()
+            .next()
+            .ok_or_else(|| err_other("version is empty"))?;
+        if !first_char.is_ascii_digit() {
+            return Err(err_other("version does not start with digit"));
+        }
+        let components = requirement::parse_components(version)
+            .ok_or_else(|| err_other(format!("`{}` is not a version", version)))?;
+        Ok(Version {
+            version: version.to_owned(),
+            components,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn version() {
+        Flatc::from_env_path().version().expect("version");
+    }
+
+    #[test]
+    fn version_has_numeric_components() -> Result<()> {
+        let old: Version = "1.9".parse()?;
+        let new: Version = "1.12.0".parse()?;
+        assert_eq!((old.major(), old.minor(), old.patch()), (1, 9, 0));
+        assert_eq!((new.major(), new.minor(), new.patch()), (1, 12, 0));
+        // The string comparison would order them the other way around
+        assert!(old.version() > new.version());
+        assert!((old.major(), old.minor()) < (new.major(), new.minor()));
+        assert_eq!(old.to_string(), "1.9");
+        assert_eq!(old, "1.9".parse()?);
+        assert!("v22.9".parse::<Version>().is_err());
+        assert!("".parse::<Version>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_produce_output() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")
+            .expect("test input fbs file could not be written");
+
+        run(Args {
             lang: "rust",
             inputs: &[&input_path],
             out_dir: temp_dir.path(),
@@ -369,4 +2866,1515 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn run_with_report_lists_inputs_and_generated_files() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+
+        let report = run_with_report(Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            ..Default::default()
+        })
+        .expect("run_with_report");
+
+        assert_eq!(report.inputs.len(), 1);
+        assert_eq!(report.inputs[0].path, input_path);
+        assert_eq!(
+            report.generated_files,
+            vec![temp_dir.path().join("test_generated.rs")]
+        );
+        assert_eq!(report.metrics.input_count, 1);
+        assert_eq!(report.metrics.generated_file_count, 1);
+        assert_ne!(report.metrics.peak_output_bytes, 0);
+        assert_eq!(
+            report.metrics.output_bytes,
+            report.metrics.peak_output_bytes
+        );
+
+        let report_path = report.write_to_dir(temp_dir.path())?;
+        assert!(std::fs::read_to_string(report_path)?.contains(&report.flatc_version));
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_report_lists_binary_inputs_after_separator() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let schema_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&schema_path, "table Test { text: string; } root_type Test;")?;
+        let binary_path = temp_dir.path().join("data.bin");
+        std::fs::write(
+            &binary_path,
+            Flatc::from_env_path().json_to_binary_bytes(&schema_path, r#"{"text": "hello"}"#)?,
+        )?;
+        let json_dir = temp_dir.path().join("json");
+
+        let report = run_with_report(Args {
+            inputs: &[&schema_path, Path::new("--"), &binary_path],
+            out_dir: &json_dir,
+            ..Args::json_conversion()
+        })
+        .expect("run_with_report");
+
+        let input_paths: Vec<&Path> = report.inputs.iter().map(|input| &*input.path).collect();
+        assert_eq!(input_paths, vec![&*schema_path, &*binary_path]);
+        assert_eq!(report.generated_files, vec![json_dir.join("data.json")]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_embeds_bfbs_filenames() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+
+        run(Args {
+            lang: "binary",
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            schema: true,
+            bfbs_filenames: Some(temp_dir.path()),
+            ..Default::default()
+        })
+        .expect("run");
+
+        let bfbs = std::fs::read(temp_dir.path().join("test.bfbs"))?;
+        assert!(bfbs.windows(10).any(|window| window == b"//test.fbs"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_require_explicit_ids() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let implicit_path = temp_dir.path().join("implicit.fbs");
+        std::fs::write(
+            &implicit_path,
+            "table Test { text: string; } root_type Test;",
+        )?;
+        let explicit_path = temp_dir.path().join("explicit.fbs");
+        std::fs::write(
+            &explicit_path,
+            "table Test { text: string (id: 0); } root_type Test;",
+        )?;
+
+        let args = Args {
+            out_dir: temp_dir.path(),
+            require_explicit_ids: true,
+            ..Default::default()
+        };
+        assert!(run(Args {
+            inputs: &[&implicit_path],
+            ..args
+        })
+        .is_err());
+        run(Args {
+            inputs: &[&explicit_path],
+            ..args
+        })
+        .expect("run");
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_checks_conformance_with_baseline_includes() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let baseline_dir = temp_dir.path().join("baseline");
+        std::fs::create_dir(&baseline_dir)?;
+        std::fs::write(baseline_dir.join("common.fbs"), "table Common { id: int; }")?;
+        let baseline_path = baseline_dir.join("test.fbs");
+        std::fs::write(
+            &baseline_path,
+            "include \"common.fbs\";\ntable Test { a: int; b: int; } root_type Test;",
+        )?;
+        let evolved_path = temp_dir.path().join("evolved.fbs");
+        std::fs::write(
+            &evolved_path,
+            "table Test { a: int; b: int; c: int; } root_type Test;",
+        )?;
+        let broken_path = temp_dir.path().join("broken.fbs");
+        std::fs::write(
+            &broken_path,
+            "table Test { b: int; a: int; } root_type Test;",
+        )?;
+
+        let args = Args {
+            out_dir: temp_dir.path(),
+            conform: Some(&baseline_path),
+            conform_includes: &[&baseline_dir],
+            ..Default::default()
+        };
+        run(Args {
+            inputs: &[&evolved_path],
+            ..args
+        })
+        .expect("run");
+        assert!(run(Args {
+            inputs: &[&broken_path],
+            ..args
+        })
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_report_can_silence_warnings() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { Text: string; } root_type Test;")?;
+
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            ..Default::default()
+        };
+        assert!(!run_with_report(args).expect("run").warnings.is_empty());
+        let report = run_with_report(Args {
+            no_warnings: true,
+            ..args
+        })
+        .expect("run");
+        assert!(report.warnings.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn quiet_run_still_collects_warnings() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { Text: string; } root_type Test;")?;
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            quiet: true,
+            ..Default::default()
+        };
+
+        run(args)?;
+        let report = run_with_report(args)?;
+        assert!(!report.warnings.is_empty());
+        assert!(run(Args {
+            inputs: &[&temp_dir.path().join("missing.fbs")],
+            ..args
+        })
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_convert_non_utf8_json() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let schema_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&schema_path, "table Test { text: string; } root_type Test;")?;
+        let data_path = temp_dir.path().join("data.json");
+        std::fs::write(&data_path, b"{ \"text\": \"a\xffb\" }")?;
+
+        let args = Args {
+            lang: "binary",
+            inputs: &[&schema_path, &data_path],
+            out_dir: temp_dir.path(),
+            ..Default::default()
+        };
+        assert!(run(args).is_err());
+        run(Args {
+            allow_non_utf8: true,
+            ..args
+        })
+        .expect("run");
+        assert!(temp_dir.path().join("data.bin").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_skip_unknown_json_fields() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let schema_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&schema_path, "table Test { text: string; } root_type Test;")?;
+        let data_path = temp_dir.path().join("data.json");
+        std::fs::write(&data_path, "{ \"text\": \"a\", \"_comment\": \"fixture\" }")?;
+
+        let args = Args {
+            lang: "binary",
+            inputs: &[&schema_path, &data_path],
+            out_dir: temp_dir.path(),
+            ..Default::default()
+        };
+        assert!(run(args).is_err());
+        run(Args {
+            unknown_json: true,
+            ..args
+        })
+        .expect("run");
+        assert!(temp_dir.path().join("data.bin").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_creates_out_dir() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        // Invalid schema, so `flatc` fails and does not create the directory itself
+        std::fs::write(&input_path, "table Test { text: string }")?;
+
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: &temp_dir.path().join("manual"),
+            create_out_dir: false,
+            ..Default::default()
+        };
+        assert!(run(args).is_err());
+        assert!(!args.out_dir.exists());
+
+        let out_dir = temp_dir.path().join("target").join("flatbuffers");
+        assert!(run(Args {
+            out_dir: &out_dir,
+            create_out_dir: true,
+            ..args
+        })
+        .is_err());
+        assert!(out_dir.is_dir());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_cleans_stale_generated_files() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        let out_dir = temp_dir.path().join("out");
+        std::fs::create_dir(&out_dir)?;
+        std::fs::write(out_dir.join("renamed_generated.rs"), "")?;
+        std::fs::write(out_dir.join("mod.rs"), "")?;
+
+        run(Args {
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            clean_out_dir: true,
+            ..Default::default()
+        })
+        .expect("run");
+
+        assert!(!out_dir.join("renamed_generated.rs").exists());
+        assert!(out_dir.join("mod.rs").exists());
+        assert!(out_dir.join("test_generated.rs").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn atomic_run_keeps_out_dir_on_failure() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string }")?;
+        let out_dir = temp_dir.path().join("out");
+        std::fs::create_dir(&out_dir)?;
+        let generated_path = out_dir.join("test_generated.rs");
+        std::fs::write(&generated_path, "// previous helpers")?;
+
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            clean_out_dir: true,
+            atomic: true,
+            ..Default::default()
+        };
+        assert!(run(args).is_err());
+        assert_eq!(
+            std::fs::read_to_string(&generated_path)?,
+            "// previous helpers"
+        );
+
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        let report = run_with_report(args).expect("run_with_report");
+        assert_eq!(report.generated_files, vec![generated_path.clone()]);
+        assert!(std::fs::read_to_string(&generated_path)?.contains("pub struct Test"));
+        // Only the schema and the output directory are left, the staging one is removed
+        assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_output_captures_flatc_output() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let first_path = temp_dir.path().join("first.fbs");
+        std::fs::write(
+            &first_path,
+            "table First { Text: string; } root_type First;",
+        )?;
+        let second_path = temp_dir.path().join("second.fbs");
+        std::fs::write(&second_path, "table Second { Id: int; } root_type Second;")?;
+        let out_dir = temp_dir.path().join("out");
+
+        let output = run_with_output(Args {
+            inputs: &[&first_path, &second_path],
+            out_dir: &out_dir,
+            invocation: Invocation::PerFile,
+            atomic: true,
+            quiet: true,
+            ..Default::default()
+        })
+        .expect("run_with_output");
+
+        assert!(output.status.success());
+        let stderr = String::from_utf8_lossy(&output.captured_stderr);
+        let first_warning = stderr.find("got: Text").expect("warning about Text");
+        let second_warning = stderr.find("got: Id").expect("warning about Id");
+        assert!(first_warning < second_warning);
+        assert_eq!(
+            output.generated_files,
+            vec![
+                out_dir.join("first_generated.rs"),
+                out_dir.join("second_generated.rs"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_skip_unchanged_files() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let first_path = temp_dir.path().join("first.fbs");
+        std::fs::write(
+            &first_path,
+            "table First { text: string; } root_type First;",
+        )?;
+        let second_path = temp_dir.path().join("second.fbs");
+        std::fs::write(
+            &second_path,
+            "table Second { text: string; } root_type Second;",
+        )?;
+        let out_dir = temp_dir.path().join("out");
+        let args = Args {
+            inputs: &[&first_path, &second_path],
+            out_dir: &out_dir,
+            clean_out_dir: true,
+            skip_unchanged: true,
+            ..Default::default()
+        };
+        let first_generated = out_dir.join("first_generated.rs");
+        let second_generated = out_dir.join("second_generated.rs");
+
+        let report = run_with_report(args).expect("run_with_report");
+        assert_eq!(
+            report.generated_files,
+            vec![first_generated.clone(), second_generated.clone()]
+        );
+        let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified());
+        let first_modified = modified(&first_generated)?;
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        std::fs::write(
+            &second_path,
+            "table Second { text: string; id: int; } root_type Second;",
+        )?;
+        let report = run_with_report(args).expect("rerun");
+        assert_eq!(report.generated_files, vec![second_generated.clone()]);
+        assert_eq!(modified(&first_generated)?, first_modified);
+        assert!(std::fs::read_to_string(&second_generated)?.contains("fn id("));
+        // Only the schemas and the output directory are left, the staging one is removed
+        assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_make_generated_files_read_only() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("monster.fbs");
+        std::fs::write(&input_path, "table Monster { hp: short; }")?;
+        let out_dir = temp_dir.path().join("out");
+        let generated_file = out_dir.join("monster_generated.rs");
+        let is_read_only =
+            |path: &Path| std::fs::metadata(path).map(|metadata| metadata.permissions().readonly());
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            read_only: true,
+            ..Default::default()
+        };
+
+        run(args).expect("run");
+        assert!(is_read_only(&generated_file)?);
+        std::fs::write(&input_path, "table Monster { hp: short; mana: short; }")?;
+        run(args).expect("rerun");
+        assert!(is_read_only(&generated_file)?);
+        assert!(std::fs::read_to_string(&generated_file)?.contains("fn mana("));
+
+        std::fs::write(&input_path, "table Monster { hp: short; }")?;
+        run(Args {
+            skip_unchanged: true,
+            ..args
+        })
+        .expect("staged run");
+        assert!(is_read_only(&generated_file)?);
+        assert!(!std::fs::read_to_string(&generated_file)?.contains("fn mana("));
+
+        let old_file = out_dir.join("old_generated.rs");
+        std::fs::write(&old_file, "")?;
+        outputs::set_read_only(std::slice::from_ref(&old_file))?;
+        run(Args {
+            clean_out_dir: true,
+            read_only: false,
+            ..args
+        })
+        .expect("cleaning run");
+        assert!(!old_file.exists());
+        assert!(!is_read_only(&generated_file)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn compile_schemas_generates_into_out_dir() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let include_dir = temp_dir.path().join("include");
+        std::fs::create_dir(&include_dir)?;
+        std::fs::write(include_dir.join("common.fbs"), "table Common { id: int; }")?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(
+            &input_path,
+            "include \"common.fbs\";\ntable Test { common: Common; } root_type Test;",
+        )?;
+        let out_dir = temp_dir.path().join("out");
+
+        compile_schemas_with(&[&input_path], &[&include_dir], |name| {
+            (name == "OUT_DIR").then(|| out_dir.clone().into_os_string())
+        })
+        .expect("compile_schemas");
+
+        assert!(out_dir.join("test_generated.rs").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn out_dir_layout_appends_target_and_profile() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        let lookup = |name: &str| match name {
+            "TARGET" => Some("wasm32-unknown-unknown".to_owned()),
+            "PROFILE" => Some("release".to_owned()),
+            _ => None,
+        };
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            out_dir_layout: OutDirLayout::PerTargetAndProfile,
+            ..Default::default()
+        };
+
+        let out_dir = args.laid_out_out_dir_with(lookup)?.expect("laid out");
+        assert_eq!(
+            out_dir,
+            temp_dir
+                .path()
+                .join("wasm32-unknown-unknown")
+                .join("release")
+        );
+        run(args.with_out_dir(&out_dir))?;
+        assert!(out_dir.join("test_generated.rs").is_file());
+        let per_target = Args {
+            out_dir: Path::new("out"),
+            out_dir_layout: OutDirLayout::PerTarget,
+            ..args
+        };
+        assert_eq!(
+            per_target.laid_out_out_dir_with(lookup)?,
+            Some(Path::new("out").join("wasm32-unknown-unknown"))
+        );
+        let error = per_target.laid_out_out_dir_with(|_| None).unwrap_err();
+        assert!(error.to_string().starts_with("TARGET is not set"));
+        assert_eq!(
+            Args {
+                out_dir_layout: OutDirLayout::Shared,
+                ..args
+            }
+            .laid_out_out_dir_with(|_| None)?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn command_line_is_shell_escaped() -> io::Result<()> {
+        let command_line = Flatc::from_path("/opt/flatc/bin/flatc").command_line(Args {
+            inputs: &[Path::new("schemas/my message.fbs")],
+            out_dir: Path::new("target/flatbuffers/"),
+            extra: &["--gen-object-api"],
+            ..Default::default()
+        })?;
+
+        assert_eq!(
+            command_line,
+            "/opt/flatc/bin/flatc --rust --gen-object-api -o target/flatbuffers/ \
+             'schemas/my message.fbs'"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn require_version_pins_flatc() -> io::Result<()> {
+        let flatc = Flatc::from_env_path();
+        let version = flatc.version()?;
+
+        assert_eq!(
+            flatc.require_version(version.version())?.version(),
+            version.version()
+        );
+        flatc.require_version(">=1.10")?;
+        let error = flatc.require_version("<1.10").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("does not match the required version"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_generate_json_schema() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+
+        let report = Flatc::from_env_path().run_with_report(Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            jsonschema: true,
+            ..Default::default()
+        })?;
+
+        assert!(report.args.contains(&"--jsonschema".to_owned()));
+        let json_schema: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(
+            temp_dir.path().join("test.schema.json"),
+        )?)?;
+        assert_eq!(json_schema["$ref"], "#/definitions/Test");
+        assert!(temp_dir.path().join("test_generated.rs").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_generate_rust_module_root_file() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(
+            &input_path,
+            "namespace my.ns; table Test { text: string; } root_type Test;",
+        )?;
+
+        run(Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            rust_module_root_file: true,
+            ..Default::default()
+        })?;
+
+        let mod_rs = std::fs::read_to_string(temp_dir.path().join("mod.rs"))?;
+        assert!(mod_rs.contains("pub mod ns"));
+        assert!(temp_dir
+            .path()
+            .join("my")
+            .join("ns")
+            .join("test_generated.rs")
+            .is_file());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_implement_serialize() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+
+        run(Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            rust_serialize: true,
+            ..Default::default()
+        })?;
+
+        let output = std::fs::read_to_string(temp_dir.path().join("test_generated.rs"))?;
+        assert!(output.contains("impl Serialize for Test<'_>"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_generate_json_encoding() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+
+        run(Args {
+            lang: "swift",
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            gen_json_emit: true,
+            ..Default::default()
+        })?;
+
+        let output = std::fs::read_to_string(temp_dir.path().join("test_generated.swift"))?;
+        assert!(output.contains("extension Test: Encodable"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_generate_csharp_json_serializer() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+
+        run(Args {
+            lang: "csharp",
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            cs_gen_json_serializer: true,
+            extra: &["--gen-object-api"],
+            ..Default::default()
+        })?;
+
+        let output = std::fs::read_to_string(temp_dir.path().join("Test.cs"))?;
+        assert!(output.contains("public string SerializeToJson()"));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_rejects_flags_unsupported_by_flatc() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        let flatc_path = temp_dir.path().join("flatc");
+        std::fs::write(&flatc_path, "#!/bin/sh\necho 'flatc version 1.12.0'\n")?;
+        std::fs::set_permissions(&flatc_path, std::fs::Permissions::from_mode(0o755))?;
+
+        let error = Flatc::from_path(flatc_path)
+            .run(Args {
+                inputs: &[&input_path],
+                out_dir: temp_dir.path(),
+                rust_module_root_file: true,
+                ..Default::default()
+            })
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("flag --rust-module-root-file needs flatc >= 2.0.5, found 1.12.0"));
+        let error = Flatc::from_path(temp_dir.path().join("flatc"))
+            .run_with_report(Args {
+                inputs: &[&input_path],
+                out_dir: temp_dir.path(),
+                rust_serialize: true,
+                ..Default::default()
+            })
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("flag --rust-serialize needs flatc >= 2.0.6, found 1.12.0"));
+        let error = Flatc::from_path(temp_dir.path().join("flatc"))
+            .run(Args {
+                inputs: &[&input_path],
+                out_dir: temp_dir.path(),
+                extra: &["--gen-object-api"],
+                ..Default::default()
+            })
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("flag --gen-object-api needs flatc >= 2.0.0, found 1.12.0"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn per_file_invocation_compiles_every_input() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let first_path = temp_dir.path().join("first.fbs");
+        std::fs::write(
+            &first_path,
+            "table First { text: string; } root_type First;",
+        )?;
+        let second_path = temp_dir.path().join("second.fbs");
+        std::fs::write(&second_path, "table Second { id: int; } root_type Second;")?;
+        let args = Args {
+            inputs: &[&first_path, &second_path],
+            out_dir: temp_dir.path(),
+            invocation: Invocation::PerFile,
+            ..Default::default()
+        };
+
+        let report = Flatc::from_env_path().run_with_report(args)?;
+
+        assert_eq!(
+            report.generated_files,
+            vec![
+                temp_dir.path().join("first_generated.rs"),
+                temp_dir.path().join("second_generated.rs"),
+            ]
+        );
+        assert!(run(Args {
+            inputs: &[&first_path, Path::new("--"), &second_path],
+            ..args
+        })
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn per_file_invocation_reports_every_failing_input() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let first_path = temp_dir.path().join("first.fbs");
+        std::fs::write(&first_path, "table First { text: string }")?;
+        let valid_path = temp_dir.path().join("valid.fbs");
+        std::fs::write(&valid_path, "table Valid { id: int; } root_type Valid;")?;
+        let second_path = temp_dir.path().join("second.fbs");
+        std::fs::write(&second_path, "table Second { id: unknown; }")?;
+
+        let error = run(Args {
+            inputs: &[&first_path, &valid_path, &second_path],
+            out_dir: temp_dir.path(),
+            invocation: Invocation::PerFile,
+            ..Default::default()
+        })
+        .unwrap_err();
+
+        let error = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<BatchError>())
+            .expect("BatchError");
+        assert_eq!(error.input_count, 3);
+        assert_eq!(
+            error
+                .failures
+                .iter()
+                .map(|failure| &failure.input)
+                .collect::<Vec<_>>(),
+            vec![&first_path, &second_path]
+        );
+        assert!(error.failures[1].diagnostics.contains("unknown"));
+        assert!(temp_dir.path().join("valid_generated.rs").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn annotate_dumps_binary_layout() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let schema_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&schema_path, "table Test { text: string; } root_type Test;")?;
+        let json_path = temp_dir.path().join("payload.json");
+        std::fs::write(&json_path, r#"{"text": "hello"}"#)?;
+        run(Args {
+            lang: "binary",
+            inputs: &[&schema_path, &json_path],
+            out_dir: temp_dir.path(),
+            ..Default::default()
+        })?;
+        let binary_path = temp_dir.path().join("payload.bin");
+
+        let flatc = Flatc::from_env_path();
+        let annotated_files = flatc.annotate(&schema_path, &[], &[&binary_path])?;
+
+        assert_eq!(annotated_files, vec![temp_dir.path().join("payload.afb")]);
+        let dump = std::fs::read_to_string(&annotated_files[0])?;
+        assert!(dump.contains("hello"));
+        assert!(flatc
+            .annotate(&schema_path, &[], &[&temp_dir.path().join("missing.bin")])
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_raw_returns_captured_output() -> io::Result<()> {
+        let flatc = Flatc::from_env_path();
+
+        let output = flatc.run_raw(&[OsString::from("--version")])?;
+        assert!(String::from_utf8_lossy(&output.stdout).starts_with("flatc version "));
+        let error = flatc.run_raw(&["--no-such-flag"]).unwrap_err();
+        assert!(error.to_string().contains("--no-such-flag"));
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn env_clear_keeps_allowed_variables_only() -> io::Result<()> {
+        // Set by Cargo for the test binaries
+        let output = Flatc::from_path("env")
+            .env_clear()
+            .env_allow("CARGO_PKG_NAME")
+            .env_allow("FLATC_RUST_TEST_UNSET")
+            .run_raw::<&str>(&[])?;
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!("CARGO_PKG_NAME={}\n", env!("CARGO_PKG_NAME"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sync_updates_changed_and_stale_files_only() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let kept_path = temp_dir.path().join("kept.fbs");
+        std::fs::write(&kept_path, "table Kept { id: int; } root_type Kept;")?;
+        let changed_path = temp_dir.path().join("changed.fbs");
+        std::fs::write(
+            &changed_path,
+            "table Changed { id: int; } root_type Changed;",
+        )?;
+        let generated_dir = temp_dir.path().join("generated");
+        std::fs::create_dir(&generated_dir)?;
+        std::fs::write(generated_dir.join("removed_generated.rs"), "// stale")?;
+        std::fs::write(generated_dir.join("mod.rs"), "// hand-written")?;
+        let flatc = Flatc::from_env_path();
+        let args = Args {
+            inputs: &[&kept_path, &changed_path],
+            out_dir: &generated_dir,
+            quiet: true,
+            ..Default::default()
+        };
+
+        let updated_files = flatc.sync(args)?;
+        assert_eq!(
+            updated_files,
+            vec![
+                generated_dir.join("changed_generated.rs"),
+                generated_dir.join("kept_generated.rs"),
+                generated_dir.join("removed_generated.rs"),
+            ]
+        );
+        assert!(generated_dir.join("mod.rs").is_file());
+        assert!(flatc.outdated_files(args)?.is_empty());
+
+        std::fs::write(
+            &changed_path,
+            "table Changed { name: string; } root_type Changed;",
+        )?;
+        assert_eq!(
+            flatc.sync(args)?,
+            vec![generated_dir.join("changed_generated.rs")]
+        );
+        assert!(flatc.outdated_files(args)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_checks_expected_outputs() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let schema_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&schema_path, "table Test { id: int; } root_type Test;")?;
+        let json_path = temp_dir.path().join("data.json");
+        std::fs::write(&json_path, "{ id: 1 }")?;
+        let out_dir = temp_dir.path().join("out");
+        let args = Args {
+            inputs: &[&schema_path, &json_path],
+            out_dir: &out_dir,
+            binary: true,
+            jsonschema: true,
+            ..Default::default()
+        };
+
+        Flatc::from_env_path().run(args)?;
+        assert_eq!(
+            DirSnapshot::take(&out_dir)?.files(),
+            vec![
+                out_dir.join("data.bin"),
+                out_dir.join("test.schema.json"),
+                out_dir.join("test_generated.rs"),
+            ]
+        );
+
+        #[cfg(unix)]
+        {
+            // Exits successfully without generating anything
+            let error = Flatc::from_path("true")
+                .run(Args {
+                    out_dir: &temp_dir.path().join("nothing"),
+                    ..args
+                })
+                .unwrap_err();
+            assert!(error
+                .to_string()
+                .starts_with("flatc has exited successfully, but has not generated "));
+            assert!(error.to_string().contains("test_generated.rs"));
+            assert!(error.to_string().contains("data.bin"));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_counts_unchanged_expected_outputs_as_generated() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { id: int; } root_type Test;")?;
+        let out_dir = temp_dir.path().join("out");
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            ..Default::default()
+        };
+        Flatc::from_env_path().run(args)?;
+
+        // Leaves the file as is, like a rewrite with the same length within the resolution of
+        // a coarse mtime
+        Flatc::from_path("true").run(args)?;
+        assert!(out_dir.join("test_generated.rs").is_file());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_reports_nothing_generated_with_probable_cause() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        std::fs::write(
+            temp_dir.path().join("included.fbs"),
+            "namespace included; table Included { id: int; }",
+        )?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "include \"included.fbs\";")?;
+        let out_dir = temp_dir.path().join("out");
+        let args = Args {
+            lang: "python",
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            ..Default::default()
+        };
+
+        // Not checked unless required
+        Flatc::from_env_path().run(args)?;
+        let args = Args {
+            require_generated: true,
+            ..args
+        };
+        let error = Flatc::from_env_path().run(args).unwrap_err();
+        let nothing_generated = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<NothingGenerated>())
+            .expect("NothingGenerated");
+        assert_eq!(nothing_generated.out_dir, out_dir);
+        assert!(nothing_generated
+            .probable_cause
+            .as_deref()
+            .expect("probable cause")
+            .contains("--gen-all"));
+
+        Flatc::from_env_path().run(Args {
+            extra: &["--gen-all"],
+            ..args
+        })?;
+        assert!(out_dir.join("included").join("Included.py").is_file());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_include_dependency_schemas() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let schema_dir = temp_dir.path().join("dependency");
+        std::fs::create_dir(&schema_dir)?;
+        std::fs::write(schema_dir.join("shared.fbs"), "table Shared { id: int; }")?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(
+            &input_path,
+            "include \"shared.fbs\";\ntable Test { shared: Shared; } root_type Test;",
+        )?;
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            quiet: true,
+            ..Default::default()
+        };
+
+        let dependency_schema_dirs = build_script::dependency_schema_dirs_in([(
+            OsString::from("DEP_FLATC_RUST_TEST_SCHEMA_DIR"),
+            schema_dir.clone().into_os_string(),
+        )]);
+        assert_eq!(dependency_schema_dirs, vec![schema_dir.clone()]);
+        assert!(Flatc::from_env_path().run(args).is_err());
+        Flatc::from_env_path().run(Args {
+            includes: &[&dependency_schema_dirs[0]],
+            ..args
+        })?;
+        assert!(temp_dir.path().join("test_generated.rs").is_file());
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_or_fallback_copies_committed_helpers_without_flatc() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        let committed_dir = temp_dir.path().join("committed");
+        std::fs::create_dir_all(committed_dir.join("nested"))?;
+        std::fs::write(committed_dir.join("test_generated.rs"), "// committed")?;
+        std::fs::write(committed_dir.join("nested").join("mod.rs"), "// nested")?;
+        let out_dir = temp_dir.path().join("out");
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            ..Default::default()
+        };
+
+        Flatc::from_env_path().run_or_fallback(args, &committed_dir)?;
+        assert_ne!(
+            std::fs::read_to_string(out_dir.join("test_generated.rs"))?,
+            "// committed"
+        );
+
+        let missing_flatc = Flatc::from_path(temp_dir.path().join("flatc"));
+        missing_flatc.run_or_fallback(args, &committed_dir)?;
+        assert_eq!(
+            std::fs::read_to_string(out_dir.join("test_generated.rs"))?,
+            "// committed"
+        );
+        assert!(out_dir.join("nested").join("mod.rs").is_file());
+
+        let error = missing_flatc
+            .run_or_fallback(args, &temp_dir.path().join("missing"))
+            .unwrap_err();
+        assert!(error.to_string().contains("no pre-generated helpers"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn presets_combine_flags() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let schema_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&schema_path, "table Test { text: string; } root_type Test;")?;
+
+        run(Args {
+            inputs: &[&schema_path],
+            out_dir: temp_dir.path(),
+            ..Args::rust_object_api()
+        })?;
+        let output = std::fs::read_to_string(temp_dir.path().join("test_generated.rs"))?;
+        assert!(output.contains("pub struct TestT"));
+
+        run(Args {
+            inputs: &[&schema_path],
+            out_dir: temp_dir.path(),
+            ..Args::bfbs_only()
+        })?;
+        assert!(temp_dir.path().join("test.bfbs").is_file());
+
+        let json_path = temp_dir.path().join("data.json");
+        std::fs::write(&json_path, r#"{"text": "hello"}"#)?;
+        run(Args {
+            lang: "binary",
+            inputs: &[&schema_path, &json_path],
+            out_dir: temp_dir.path(),
+            ..Default::default()
+        })?;
+        let json_dir = temp_dir.path().join("json");
+        run(Args {
+            inputs: &[
+                &schema_path,
+                Path::new("--"),
+                &temp_dir.path().join("data.bin"),
+            ],
+            out_dir: &json_dir,
+            ..Args::json_conversion()
+        })?;
+        let json = std::fs::read_to_string(json_dir.join("data.json"))?;
+        assert!(json.contains(r#""text": "hello""#));
+
+        Ok(())
+    }
+
+    #[test]
+    fn json_converts_in_memory() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let schema_path = temp_dir.path().join("test.fbs");
+        std::fs::write(
+            &schema_path,
+            "table Test { text: string; id: int; } root_type Test;",
+        )?;
+        let flatc = Flatc::from_env_path();
+
+        let binary = flatc.json_to_binary_bytes(&schema_path, r#"{"text": "hello", "id": 7}"#)?;
+        assert!(!binary.is_empty());
+        let json = flatc.binary_to_json_string(&schema_path, &binary)?;
+        assert!(json.contains(r#""text": "hello""#));
+        assert!(json.contains(r#""id": 7"#));
+
+        assert!(flatc
+            .json_to_binary_bytes(&schema_path, r#"{"unknown": 1}"#)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn depfile_lists_include_closure() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let include_dir = temp_dir.path().join("include");
+        std::fs::create_dir(&include_dir)?;
+        std::fs::write(include_dir.join("common.fbs"), "table Common { id: int; }")?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(
+            &input_path,
+            "include \"common.fbs\";\ntable Test { common: Common; } root_type Test;",
+        )?;
+        let out_dir = temp_dir.path().join("out");
+
+        let depfile = Flatc::from_env_path().depfile(Args {
+            inputs: &[&input_path],
+            includes: &[&include_dir],
+            out_dir: &out_dir,
+            ..Default::default()
+        })?;
+
+        assert_eq!(depfile.rules.len(), 1);
+        assert_eq!(
+            depfile.rules[0].targets,
+            vec![out_dir.join("test_generated.rs")]
+        );
+        let mut dependencies = depfile.dependencies();
+        dependencies.sort();
+        assert_eq!(
+            dependencies,
+            vec![
+                include_dir.join("common.fbs").as_path(),
+                input_path.as_path()
+            ]
+        );
+        assert!(!out_dir.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn generate_to_string_returns_generated_source() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+
+        let source = Flatc::from_env_path().generate_to_string(&input_path, Default::default())?;
+
+        assert!(source.contains("pub struct Test<'a>"));
+        assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_applies_postprocess_to_generated_files() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+
+        run(Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            postprocess: Some(|_path, content| format!("// post-processed\n{}", content)),
+            ..Default::default()
+        })
+        .expect("run");
+
+        let output = std::fs::read_to_string(temp_dir.path().join("test_generated.rs"))?;
+        assert!(output.starts_with("// post-processed\n"));
+        let input = std::fs::read_to_string(&input_path)?;
+        assert!(!input.contains("post-processed"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_stamp_provenance() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        let schema = "table Test { text: string; } root_type Test;";
+        std::fs::write(&input_path, schema)?;
+        let out_dir = temp_dir.path().join("out");
+        let args = Args {
+            lang: "python",
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            atomic: true,
+            provenance: true,
+            ..Default::default()
+        };
+
+        run(args).expect("run");
+
+        let output = std::fs::read_to_string(out_dir.join("Test.py"))?;
+        let mut lines = output.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                format!(
+                    "# Generated by flatc-rust {} with flatc {}",
+                    env!("CARGO_PKG_VERSION"),
+                    Flatc::from_env_path().version()?.version()
+                )
+                .as_str()
+            )
+        );
+        assert_eq!(
+            lines.next(),
+            Some(format!("# flatc args: --python {}", input_path.display()).as_str())
+        );
+        assert_eq!(
+            lines.next(),
+            Some(
+                format!(
+                    "# {} SHA-256: {}",
+                    input_path.display(),
+                    report::sha256_hex(schema.as_bytes())
+                )
+                .as_str()
+            )
+        );
+        assert!(lines
+            .next()
+            .is_some_and(|line| line.contains("automatically generated")));
+
+        run(args).expect("rerun");
+        assert_eq!(std::fs::read_to_string(out_dir.join("Test.py"))?, output);
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_write_manifest_declaring_outputs() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        let out_dir = temp_dir.path().join("out");
+        let manifest_path = temp_dir.path().join("manifest.json");
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            includes: &[temp_dir.path()],
+            skip_unchanged: true,
+            manifest: Some(&manifest_path),
+            ..Default::default()
+        };
+
+        for _ in 0..2 {
+            run(args).expect("run");
+            // The unchanged files of the rerun are declared too
+            let manifest: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&manifest_path)?)?;
+            assert_eq!(
+                manifest["flatc_version"],
+                Flatc::from_env_path().version()?.version()
+            );
+            assert_eq!(
+                manifest["inputs"][0]["path"],
+                input_path.to_string_lossy().as_ref()
+            );
+            assert_eq!(
+                manifest["includes"],
+                serde_json::json!([temp_dir.path().to_string_lossy()])
+            );
+            assert_eq!(
+                manifest["outputs"],
+                serde_json::json!([out_dir.join("test_generated.rs").to_string_lossy()])
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_rename_generated_files() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        let out_dir = temp_dir.path().join("out");
+
+        let report = run_with_report(Args {
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            rename: Some(postprocess::strip_generated_suffix),
+            ..Default::default()
+        })
+        .expect("run_with_report");
+        assert_eq!(report.generated_files, vec![out_dir.join("test.rs")]);
+        assert!(!out_dir.join("test_generated.rs").exists());
+
+        let error = run(Args {
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            rename: Some(|_path| PathBuf::from("../escaped.rs")),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("outside of out_dir"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_can_prepend_banner() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+
+        run(Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            provenance: true,
+            banner: Some("Copyright Example Corp.\n\n@generated"),
+            ..Default::default()
+        })
+        .expect("run");
+
+        let output = std::fs::read_to_string(temp_dir.path().join("test_generated.rs"))?;
+        assert!(output.starts_with(
+            "// Copyright Example Corp.\n//\n// @generated\n// Generated by flatc-rust "
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "verify"))]
+    #[test]
+    fn run_rejects_verify_rust_without_verify_feature() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+
+        let error = run(Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            verify_rust: true,
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        assert!(!temp_dir.path().join("test_generated.rs").exists());
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "tonic"))]
+    #[test]
+    fn run_rejects_tonic_without_tonic_feature() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+
+        let error = run(Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            tonic: true,
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        assert!(!temp_dir.path().join("test_generated.rs").exists());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    fn run_can_verify_generated_rust() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            verify_rust: true,
+            ..Default::default()
+        };
+
+        run(args)?;
+
+        let error = run(Args {
+            postprocess: Some(|_path, content| content.replacen("pub struct", "pub struct {", 1)),
+            ..args
+        })
+        .unwrap_err();
+        let error = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<VerificationError>())
+            .expect("VerificationError");
+        assert_eq!(
+            error.files[0].path,
+            temp_dir.path().join("test_generated.rs")
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn spawn_explains_foreign_architecture_executables() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let flatc_path = temp_dir.path().join("flatc");
+        // A truncated ELF header is as good as an executable for another architecture
+        std::fs::write(&flatc_path, b"\x7fELF\x02\x01\x01")?;
+        std::fs::set_permissions(&flatc_path, std::fs::Permissions::from_mode(0o755))?;
+
+        let error = Flatc::from_path(flatc_path).check().unwrap_err();
+        assert!(error.to_string().contains("has to be built for the host"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn outdated_files_lists_missing_and_changed_helpers() -> io::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        let flatc = Flatc::from_env_path();
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            ..Default::default()
+        };
+        let output_path = temp_dir.path().join("test_generated.rs");
+
+        assert_eq!(flatc.outdated_files(args)?, vec![output_path.clone()]);
+        flatc.run(args)?;
+        assert!(flatc.outdated_files(args)?.is_empty());
+        std::fs::write(&output_path, "// edited by hand")?;
+        assert_eq!(flatc.outdated_files(args)?, vec![output_path.clone()]);
+
+        // The check writes nothing the run would write
+        let manifest_path = temp_dir.path().join("manifest.json");
+        flatc.outdated_files(Args {
+            manifest: Some(&manifest_path),
+            read_only: true,
+            ..args
+        })?;
+        assert!(!manifest_path.exists());
+        assert_eq!(std::fs::read_to_string(&output_path)?, "// edited by hand");
+
+        Ok(())
+    }
 }
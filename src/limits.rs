@@ -0,0 +1,319 @@
+//! Resource limits of the `flatc` processes (`limits` feature, see
+//! [`Flatc::with_resource_limits`]).
+//!
+//! On Unix the child sets its limits right before `exec` (`RLIMIT_AS` for the memory, and
+//! `RLIMIT_CPU` for the CPU time, which delivers `SIGXCPU`, and `SIGKILL` a second later); on
+//! Windows the child is assigned to a job object with the per-process memory and user-mode time
+//! limits right after it is spawned.
+//!
+//! A run killed by a limit fails with [`ResourceLimitExceeded`] rather than with the generic
+//! error of a failed `flatc`. The exhausted memory is recognized by the way `flatc` fails to
+//! allocate it (aborting on `std::bad_alloc`, or failing to map a shared library), so a crash
+//! unrelated to the memory limit may be attributed to it.
+//!
+//! [`Flatc::with_resource_limits`]: ../struct.Flatc.html#method.with_resource_limits
+//! [`ResourceLimitExceeded`]: struct.ResourceLimitExceeded.html
+
+use std::error;
+use std::fmt;
+use std::process;
+use std::time::Duration;
+
+/// The limits of every `flatc` process (see [`Flatc::with_resource_limits`])
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use flatc_rust::limits::ResourceLimits;
+///
+/// let flatc = flatc_rust::Flatc::from_env_path().with_resource_limits(ResourceLimits {
+///     memory: Some(1 << 30),
+///     cpu_time: Some(Duration::from_secs(60)),
+/// });
+/// ```
+///
+/// [`Flatc::with_resource_limits`]: ../struct.Flatc.html#method.with_resource_limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResourceLimits {
+    /// The most memory (the address space on Unix) in bytes
+    pub memory: Option<u64>,
+    /// The most CPU time (rounded up to whole seconds on Unix)
+    pub cpu_time: Option<Duration>,
+}
+
+/// The resource a `flatc` process has exhausted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    /// [`ResourceLimits::memory`](struct.ResourceLimits.html#structfield.memory)
+    Memory,
+    /// [`ResourceLimits::cpu_time`](struct.ResourceLimits.html#structfield.cpu_time)
+    CpuTime,
+}
+
+/// `flatc` was killed for exceeding its resource limits (see [`limits`])
+///
+/// It is the inner error of the returned [`Error`]:
+///
+/// ```no_run
+/// # let flatc = flatc_rust::Flatc::from_env_path();
+/// # let args = flatc_rust::Args::default();
+/// if let Err(e) = flatc.run(args) {
+///     if let Some(e) = e
+///         .get_ref()
+///         .and_then(|e| e.downcast_ref::<flatc_rust::limits::ResourceLimitExceeded>())
+///     {
+///         eprintln!("flatc has exhausted {:?}", e.resource);
+///     }
+/// }
+/// ```
+///
+/// [`limits`]: index.html
+/// [`Error`]: ../type.Error.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceLimitExceeded {
+    /// The exhausted resource
+    pub resource: Resource,
+    /// The limits `flatc` ran with
+    pub limits: ResourceLimits,
+    /// The command line of the killed `flatc`
+    pub command_line: String,
+}
+
+impl fmt::Display for ResourceLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "flatc (`{}`) has exceeded its ", self.command_line)?;
+        match (self.resource, self.limits) {
+            (
+                Resource::Memory,
+                ResourceLimits {
+                    memory: Some(memory),
+                    ..
+                },
+            ) => write!(f, "memory limit ({} bytes)", memory),
+            (
+                Resource::CpuTime,
+                ResourceLimits {
+                    cpu_time: Some(cpu_time),
+                    ..
+                },
+            ) => write!(f, "CPU time limit ({:?})", cpu_time),
+            (Resource::Memory, _) => write!(f, "memory limit"),
+            (Resource::CpuTime, _) => write!(f, "CPU time limit"),
+        }
+    }
+}
+
+impl error::Error for ResourceLimitExceeded {}
+
+impl ResourceLimits {
+    fn is_empty(&self) -> bool {
+        self.memory.is_none() && self.cpu_time.is_none()
+    }
+
+    /// The resource the failed process has likely exhausted (`stderr` is its captured output, if
+    /// any)
+    pub(crate) fn exceeded(&self, status: process::ExitStatus, stderr: &[u8]) -> Option<Resource> {
+        if self.is_empty() || status.success() {
+            return None;
+        }
+        let stderr = String::from_utf8_lossy(stderr);
+        let out_of_memory = [
+            "bad_alloc",
+            "failed to map segment",
+            "Cannot allocate memory",
+        ]
+        .iter()
+        .any(|message| stderr.contains(message));
+        let (cpu_time_exceeded, aborted) = platform::termination(status);
+        if self.cpu_time.is_some() && cpu_time_exceeded {
+            Some(Resource::CpuTime)
+        } else if self.memory.is_some() && (out_of_memory || aborted) {
+            Some(Resource::Memory)
+        } else {
+            None
+        }
+    }
+}
+
+/// Make the command spawn its process with the limits
+pub(crate) fn apply(cmd: &mut process::Command, limits: &ResourceLimits) {
+    if !limits.is_empty() {
+        platform::apply(cmd, limits);
+    }
+}
+
+/// Put the spawned process under the limits (the ones not set by [`apply`] before `exec`)
+pub(crate) fn assign(child: &process::Child, limits: &ResourceLimits) -> std::io::Result<()> {
+    if limits.is_empty() {
+        return Ok(());
+    }
+    platform::assign(child, limits)
+}
+
+#[cfg(unix)]
+#[allow(unsafe_code)]
+mod platform {
+    use std::convert::TryFrom;
+    use std::io;
+    use std::os::unix::process::{CommandExt, ExitStatusExt};
+    use std::process;
+
+    use super::ResourceLimits;
+
+    pub(super) fn apply(cmd: &mut process::Command, limits: &ResourceLimits) {
+        let memory = limits
+            .memory
+            .map(|memory| libc::rlim_t::try_from(memory).unwrap_or(libc::RLIM_INFINITY));
+        let cpu_time = limits.cpu_time.map(|cpu_time| {
+            let secs = cpu_time.as_secs() + u64::from(cpu_time.subsec_nanos() > 0);
+            libc::rlim_t::try_from(secs.max(1)).unwrap_or(libc::RLIM_INFINITY)
+        });
+        // SAFETY: the closure only makes system calls, which is safe between `fork` and `exec`
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(memory) = memory {
+                    let limit = libc::rlimit {
+                        rlim_cur: memory,
+                        rlim_max: memory,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                if let Some(cpu_time) = cpu_time {
+                    // `SIGXCPU` at the soft limit, `SIGKILL` at the hard one
+                    let limit = libc::rlimit {
+                        rlim_cur: cpu_time,
+                        rlim_max: cpu_time.saturating_add(1),
+                    };
+                    if libc::setrlimit(libc::RLIMIT_CPU, &limit) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+
+    pub(super) fn assign(_child: &process::Child, _limits: &ResourceLimits) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Whether the process was killed for its CPU time, and whether it has aborted or crashed
+    pub(super) fn termination(status: process::ExitStatus) -> (bool, bool) {
+        match status.signal() {
+            Some(libc::SIGXCPU) | Some(libc::SIGKILL) => (true, false),
+            Some(libc::SIGABRT) | Some(libc::SIGSEGV) => (false, true),
+            _ => (false, false),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::convert::TryFrom;
+    use std::io;
+    use std::process;
+
+    use super::ResourceLimits;
+    use crate::job::Job;
+
+    const JOB_OBJECT_LIMIT_PROCESS_TIME: u32 = 0x0000_0002;
+    const JOB_OBJECT_LIMIT_PROCESS_MEMORY: u32 = 0x0000_0100;
+    /// The exit code of the processes terminated for their user-mode time
+    const ERROR_NOT_ENOUGH_QUOTA: u32 = 1816;
+    const STATUS_NO_MEMORY: u32 = 0xC000_0017;
+    /// The exit code of `abort()`
+    const ABORT_EXIT_CODE: u32 = 3;
+
+    pub(super) fn apply(_cmd: &mut process::Command, _limits: &ResourceLimits) {}
+
+    pub(super) fn assign(child: &process::Child, limits: &ResourceLimits) -> io::Result<()> {
+        let mut information = Job::no_limits();
+        if let Some(memory) = limits.memory {
+            information.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+            information.ProcessMemoryLimit = usize::try_from(memory).unwrap_or(usize::MAX);
+        }
+        if let Some(cpu_time) = limits.cpu_time {
+            information.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_TIME;
+            // In 100-nanosecond intervals
+            information.BasicLimitInformation.PerProcessUserTimeLimit =
+                i64::try_from(cpu_time.as_nanos() / 100).unwrap_or(i64::MAX);
+        }
+        let job = Job::new()?;
+        job.set_limits(&information)?;
+        job.assign(child)
+    }
+
+    /// Whether the process was killed for its CPU time, and whether it has aborted or crashed
+    pub(super) fn termination(status: process::ExitStatus) -> (bool, bool) {
+        match status.code().map(|code| code as u32) {
+            Some(ERROR_NOT_ENOUGH_QUOTA) => (true, false),
+            Some(STATUS_NO_MEMORY) | Some(ABORT_EXIT_CODE) => (false, true),
+            _ => (false, false),
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::io;
+    use std::process;
+
+    use super::ResourceLimits;
+
+    pub(super) fn apply(_cmd: &mut process::Command, _limits: &ResourceLimits) {}
+
+    pub(super) fn assign(_child: &process::Child, _limits: &ResourceLimits) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "limiting the resources of flatc is only supported on Unix and Windows",
+        ))
+    }
+
+    pub(super) fn termination(_status: process::ExitStatus) -> (bool, bool) {
+        (false, false)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+    use crate::{Args, Flatc};
+
+    #[test]
+    fn run_fails_with_resource_limit_exceeded() -> crate::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        // A "flatc" stuck in a busy loop, like on a pathological schema
+        let flatc_path = temp_dir.path().join("flatc");
+        fs::write(&flatc_path, "#!/bin/sh\nwhile :; do :; done\n")?;
+        fs::set_permissions(&flatc_path, fs::Permissions::from_mode(0o755))?;
+        let input_path = temp_dir.path().join("monster.fbs");
+        fs::write(&input_path, "table Monster { hp: short; }")?;
+        let limits = ResourceLimits {
+            cpu_time: Some(Duration::from_millis(500)),
+            ..Default::default()
+        };
+
+        let error = Flatc::from_path(&flatc_path)
+            .with_resource_limits(limits)
+            .run(Args {
+                inputs: &[&input_path],
+                out_dir: &temp_dir.path().join("out"),
+                ..Default::default()
+            })
+            .unwrap_err();
+        let exceeded = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<ResourceLimitExceeded>())
+            .expect("ResourceLimitExceeded");
+        assert_eq!(exceeded.resource, Resource::CpuTime);
+        assert_eq!(exceeded.limits, limits);
+        assert!(error.to_string().contains("CPU time limit (500ms)"));
+
+        Ok(())
+    }
+}
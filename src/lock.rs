@@ -0,0 +1,126 @@
+//! Advisory inter-process locks of the output directories.
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::path::{self, Path, PathBuf};
+
+use fs4::{FileExt, TryLockError};
+use log::info;
+
+use crate::report::sha256_hex;
+use crate::Result;
+
+/// An exclusive advisory lock of a directory, e.g. the `out_dir` shared by several build
+/// scripts of a workspace, which is released on drop.
+///
+/// The lock file lives in the system temporary directory (named after the absolute path of the
+/// directory), so nothing is added to the locked directory itself. It is removed on release, so
+/// the lock files do not pile up there.
+pub(crate) struct DirLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl DirLock {
+    /// Block until the lock of the directory is acquired.
+    pub(crate) fn acquire(dir: &Path) -> Result<DirLock> {
+        // An empty path is rejected later with a better error message
+        let dir = if dir.as_os_str().is_empty() {
+            env::current_dir()?
+        } else {
+            path::absolute(dir)?
+        };
+        let path = lock_path(&dir);
+        loop {
+            let file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&path)?;
+            match FileExt::try_lock(&file) {
+                Ok(()) => (),
+                Err(TryLockError::WouldBlock) => {
+                    info!("waiting for another flatc run into {:?}", dir);
+                    FileExt::lock(&file)?;
+                }
+                Err(TryLockError::Error(e)) => return Err(e),
+            }
+            // The holder we have waited for may have removed the file, which someone else could
+            // have recreated and locked since
+            if is_locked_file(&file, &path) {
+                return Ok(DirLock { file, path });
+            }
+        }
+    }
+}
+
+impl Drop for DirLock {
+    fn drop(&mut self) {
+        // Still locked, so the waiters notice the removal (on Windows the file is only removed
+        // if nobody else has it open)
+        let _ = fs::remove_file(&self.path);
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// The lock file of the absolute directory
+fn lock_path(dir: &Path) -> PathBuf {
+    env::temp_dir().join(format!(
+        "flatc-rust-{}.lock",
+        &sha256_hex(dir.to_string_lossy().as_bytes())[..16]
+    ))
+}
+
+/// Whether the locked file is still the one at the path
+#[cfg(unix)]
+fn is_locked_file(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (file.metadata(), fs::metadata(path)) {
+        (Ok(locked), Ok(current)) => locked.dev() == current.dev() && locked.ino() == current.ino(),
+        _ => false,
+    }
+}
+
+/// Whether the locked file is still the one at the path
+#[cfg(not(unix))]
+fn is_locked_file(_file: &File, _path: &Path) -> bool {
+    // The files opened by the waiters cannot be removed
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn acquire_waits_for_release() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let dir = temp_dir.path().join("out");
+
+        let lock = DirLock::acquire(&dir)?;
+        let acquired = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let dir = dir.clone();
+            let acquired = Arc::clone(&acquired);
+            thread::spawn(move || {
+                let _lock = DirLock::acquire(&dir).expect("lock");
+                acquired.store(true, Ordering::SeqCst);
+            })
+        };
+
+        thread::sleep(Duration::from_millis(100));
+        assert!(!acquired.load(Ordering::SeqCst));
+        drop(lock);
+        waiter.join().expect("waiter");
+        assert!(acquired.load(Ordering::SeqCst));
+        assert!(!lock_path(&path::absolute(&dir)?).exists());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+//! Running the same args against several `flatc` versions.
+//!
+//! It is useful to keep the schemas compatible with several `flatc` versions (e.g. in CI):
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! let report = flatc_rust::matrix::run_matrix(
+//!     &[Path::new("/opt/flatc-2.0.8/bin/flatc"), Path::new("/opt/flatc-24.3.25/bin/flatc")],
+//!     flatc_rust::Args {
+//!         inputs: &[Path::new("src/message.fbs")],
+//!         ..Default::default()
+//!     },
+//! );
+//! for entry in &report.entries {
+//!     println!("{:?} ({:?}): {:?}", entry.exec, entry.version, entry.result);
+//! }
+//! for diff in report.diffs() {
+//!     println!("{}", diff);
+//! }
+//! assert!(report.all_succeeded());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::outputs::DirSnapshot;
+use crate::temp::TempDir;
+use crate::{Args, Flatc, Result};
+
+/// The outcome of running the args against a single `flatc`
+#[derive(Debug, Clone)]
+pub struct MatrixEntry {
+    /// The `flatc` executable
+    pub exec: PathBuf,
+    /// The version reported by `flatc --version` (`None` if it could not be obtained)
+    pub version: Option<String>,
+    /// Whether the run succeeded (the error message otherwise)
+    pub result: std::result::Result<(), String>,
+    /// The content of the generated files by their paths relative to the output directory
+    pub outputs: BTreeMap<PathBuf, String>,
+}
+
+/// The outcomes of running the args against every `flatc`
+#[derive(Debug, Clone)]
+pub struct MatrixReport {
+    /// The outcomes in the order of the executables
+    pub entries: Vec<MatrixEntry>,
+}
+
+/// A difference between the outputs of two `flatc` versions
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputDiff {
+    /// The `flatc` executable the outputs are compared against (the first successful one)
+    pub baseline: PathBuf,
+    /// The `flatc` executable with the different outputs
+    pub other: PathBuf,
+    /// The generated file path relative to the output directory
+    pub file: PathBuf,
+    /// The kind of the difference
+    pub kind: DiffKind,
+}
+
+/// The kind of [`OutputDiff`]
+///
+/// [`OutputDiff`]: struct.OutputDiff.html
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffKind {
+    /// The file is generated by the baseline `flatc` only
+    OnlyInBaseline,
+    /// The file is generated by the other `flatc` only
+    OnlyInOther,
+    /// The file content differs starting from the line (1-based)
+    Changed {
+        /// The first different line number
+        line: usize,
+        /// The line generated by the baseline `flatc` (empty if the file is shorter)
+        baseline_line: String,
+        /// The line generated by the other `flatc` (empty if the file is shorter)
+        other_line: String,
+    },
+}
+
+impl fmt::Display for OutputDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let file = self.file.display();
+        match self.kind {
+            DiffKind::OnlyInBaseline => write!(
+                f,
+                "{} is generated by {:?} but not by {:?}",
+                file, self.baseline, self.other
+            ),
+            DiffKind::OnlyInOther => write!(
+                f,
+                "{} is generated by {:?} but not by {:?}",
+                file, self.other, self.baseline
+            ),
+            DiffKind::Changed {
+                line,
+                ref baseline_line,
+                ref other_line,
+            } => write!(
+                f,
+                "{}:{} differs: {:?} generates `{}`, {:?} generates `{}`",
+                file, line, self.baseline, baseline_line, self.other, other_line
+            ),
+        }
+    }
+}
+
+impl MatrixReport {
+    /// Check whether the run succeeded with every `flatc`
+    pub fn all_succeeded(&self) -> bool {
+        self.entries.iter().all(|entry| entry.result.is_ok())
+    }
+
+    /// The differences of the outputs of every successful run compared to the first successful
+    /// one
+    pub fn diffs(&self) -> Vec<OutputDiff> {
+        let mut successful = self.entries.iter().filter(|entry| entry.result.is_ok());
+        let baseline = match successful.next() {
+            Some(baseline) => baseline,
+            None => return Vec::new(),
+        };
+
+        let mut diffs = Vec::new();
+        for other in successful {
+            let diff = |file: &Path, kind| OutputDiff {
+                baseline: baseline.exec.clone(),
+                other: other.exec.clone(),
+                file: file.to_owned(),
+                kind,
+            };
+            for (file, baseline_content) in &baseline.outputs {
+                match other.outputs.get(file) {
+                    None => diffs.push(diff(file, DiffKind::OnlyInBaseline)),
+                    Some(other_content) => {
+                        if let Some(kind) = compare(baseline_content, other_content) {
+                            diffs.push(diff(file, kind));
+                        }
+                    }
+                }
+            }
+            for file in other.outputs.keys() {
+                if !baseline.outputs.contains_key(file) {
+                    diffs.push(diff(file, DiffKind::OnlyInOther));
+                }
+            }
+        }
+        diffs
+    }
+}
+
+fn compare(baseline: &str, other: &str) -> Option<DiffKind> {
+    if baseline == other {
+        return None;
+    }
+    let mut baseline_lines = baseline.lines();
+    let mut other_lines = other.lines();
+    let mut line = 0;
+    loop {
+        line += 1;
+        match (baseline_lines.next(), other_lines.next()) {
+            (Some(baseline_line), Some(other_line)) if baseline_line == other_line => continue,
+            (None, None) => {
+                // The content differs in line endings only
+                return Some(DiffKind::Changed {
+                    line,
+                    baseline_line: String::new(),
+                    other_line: String::new(),
+                });
+            }
+            (baseline_line, other_line) => {
+                return Some(DiffKind::Changed {
+                    line,
+                    baseline_line: baseline_line.unwrap_or_default().to_owned(),
+                    other_line: other_line.unwrap_or_default().to_owned(),
+                })
+            }
+        }
+    }
+}
+
+/// Run the args against every `flatc` executable
+///
+/// Every run generates the files into its own temporary directory, so `args.out_dir` is
+/// ignored.
+pub fn run_matrix(executables: &[&Path], args: Args) -> MatrixReport {
+    MatrixReport {
+        entries: executables
+            .iter()
+            .map(|exec| run_entry(exec, args))
+            .collect(),
+    }
+}
+
+fn run_entry(exec: &Path, args: Args) -> MatrixEntry {
+    let flatc = Flatc::from_path(exec);
+    let mut entry = MatrixEntry {
+        exec: exec.to_owned(),
+        version: flatc.version().ok().map(|version| version.version),
+        result: Ok(()),
+        outputs: BTreeMap::new(),
+    };
+    match generate(&flatc, args) {
+        Ok(outputs) => entry.outputs = outputs,
+        Err(e) => entry.result = Err(e.to_string()),
+    }
+    entry
+}
+
+fn generate(flatc: &Flatc, args: Args) -> Result<BTreeMap<PathBuf, String>> {
+    let out_dir = TempDir::new()?;
+    let before = DirSnapshot::take(out_dir.path())?;
+    flatc.run(Args {
+        out_dir: out_dir.path(),
+        ..args
+    })?;
+
+    let mut outputs = BTreeMap::new();
+    for file in DirSnapshot::take(out_dir.path())?.changed_since(&before) {
+        let content = String::from_utf8_lossy(&fs::read(&file)?).into_owned();
+        let relative = file
+            .strip_prefix(out_dir.path())
+            .unwrap_or(&file)
+            .to_owned();
+        outputs.insert(relative, content);
+    }
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn run_matrix_reports_failures_and_diffs() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+
+        let report = run_matrix(
+            &[Path::new("flatc"), &temp_dir.path().join("missing-flatc")],
+            Args {
+                inputs: &[&input_path],
+                ..Default::default()
+            },
+        );
+
+        assert!(!report.all_succeeded());
+        assert!(report.entries[0].version.is_some());
+        assert!(report.entries[0]
+            .outputs
+            .contains_key(Path::new("test_generated.rs")));
+        assert!(report.entries[1].result.is_err());
+        assert!(report.diffs().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn diffs_point_at_first_different_line() {
+        let entry = |exec: &str, outputs: &[(&str, &str)]| MatrixEntry {
+            exec: PathBuf::from(exec),
+            version: None,
+            result: Ok(()),
+            outputs: outputs
+                .iter()
+                .map(|(file, content)| (PathBuf::from(file), content.to_string()))
+                .collect(),
+        };
+        let report = MatrixReport {
+            entries: vec![
+                entry("old", &[("a.rs", "same\nold\n"), ("b.rs", "")]),
+                entry("new", &[("a.rs", "same\nnew\n"), ("c.rs", "")]),
+            ],
+        };
+
+        let diffs = report.diffs();
+
+        assert_eq!(diffs.len(), 3);
+        assert_eq!(
+            diffs[0].kind,
+            DiffKind::Changed {
+                line: 2,
+                baseline_line: "old".to_owned(),
+                other_line: "new".to_owned(),
+            }
+        );
+        assert_eq!(diffs[1].kind, DiffKind::OnlyInBaseline);
+        assert_eq!(diffs[2].kind, DiffKind::OnlyInOther);
+        assert_eq!(
+            diffs[0].to_string(),
+            "a.rs:2 differs: \"old\" generates `old`, \"new\" generates `new`"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Normalization of the input and include paths, so the command lines are reproducible
+//! regardless of the shell expansions and the path forms.
+
+use std::collections::hash_map::{Entry, HashMap};
+use std::collections::HashSet;
+use std::path::{self, Component, Path, PathBuf};
+
+/// The extensions of the schemas, which are compiled independently of each other
+const SCHEMA_EXTENSIONS: &[&str] = &["fbs", "proto"];
+
+/// The form-independent key of the path: absolute and without `.` components
+fn path_key(path: &Path) -> PathBuf {
+    path::absolute(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+fn is_schema(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        SCHEMA_EXTENSIONS
+            .iter()
+            .any(|expected| extension.eq_ignore_ascii_case(expected))
+    })
+}
+
+/// Deduplicate the paths keeping the first occurrences (the paths differing in form only, e.g.
+/// `./a.fbs` and `a.fbs`, are duplicates as well)
+fn dedup<'a>(paths: &[&'a Path]) -> Vec<&'a Path> {
+    let mut seen = HashSet::new();
+    paths
+        .iter()
+        .copied()
+        .filter(|path| seen.insert(path_key(path)))
+        .collect()
+}
+
+/// Deduplicate the inputs and sort them if they are all schemas (the order of the data files
+/// and the schemas they follow matters for conversions)
+pub(crate) fn inputs<'a>(inputs: &[&'a Path]) -> Vec<&'a Path> {
+    let mut inputs = dedup(inputs);
+    if inputs.iter().all(|input| is_schema(input)) {
+        inputs.sort();
+    }
+    inputs
+}
+
+/// Deduplicate the include paths, keeping their order (the first include path containing a
+/// file wins)
+pub(crate) fn includes<'a>(includes: &[&'a Path]) -> Vec<&'a Path> {
+    dedup(includes)
+}
+
+/// The duplicates differing in form only from the first occurrences (paired with them), which
+/// likely point at inconsistent paths in the build configuration
+pub(crate) fn form_duplicates<'a>(paths: &[&'a Path]) -> Vec<(&'a Path, &'a Path)> {
+    let mut seen: HashMap<PathBuf, &Path> = HashMap::new();
+    let mut duplicates = Vec::new();
+    for &path in paths {
+        match seen.entry(path_key(path)) {
+            Entry::Occupied(first) if *first.get() != path => {
+                duplicates.push((*first.get(), path));
+            }
+            Entry::Occupied(_) => (),
+            Entry::Vacant(entry) => {
+                entry.insert(path);
+            }
+        }
+    }
+    duplicates
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn inputs_are_deduplicated_and_sorted() {
+        let b = Path::new("schemas/b.fbs");
+        let a = Path::new("schemas/a.fbs");
+        let dotted_a = Path::new("./schemas/a.fbs");
+        assert_eq!(inputs(&[b, a, dotted_a, b]), vec![a, b]);
+        assert_eq!(form_duplicates(&[b, a, dotted_a, b]), vec![(a, dotted_a)]);
+
+        let data = Path::new("data.json");
+        assert_eq!(inputs(&[b, data, data]), vec![b, data]);
+
+        let include = Path::new("include");
+        let other = Path::new("other");
+        assert_eq!(
+            includes(&[other, include, Path::new("./other/")]),
+            vec![other, include]
+        );
+    }
+}
@@ -0,0 +1,387 @@
+//! Tracking of the files generated by `flatc`.
+
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use log::warn;
+
+use crate::schema::Schema;
+use crate::{err_other, Args, Result};
+
+/// A snapshot of the files (recursively) found in a directory.
+///
+/// `flatc` does not report which files it has (re)generated, so we compare the state of the
+/// output directory before and after the run.
+pub(crate) struct DirSnapshot {
+    files: BTreeMap<PathBuf, (u64, Option<SystemTime>)>,
+}
+
+impl DirSnapshot {
+    /// Take a snapshot of the directory; a missing directory is treated as an empty one.
+    pub(crate) fn take(dir: &Path) -> Result<DirSnapshot> {
+        let mut files = BTreeMap::new();
+        match fs::metadata(dir) {
+            Ok(ref metadata) if metadata.is_dir() => collect_files(dir, &mut files)?,
+            Ok(_) => (),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e),
+        }
+        Ok(DirSnapshot { files })
+    }
+
+    /// List the files which were created or modified since the `earlier` snapshot.
+    pub(crate) fn changed_since(&self, earlier: &DirSnapshot) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .filter(|(path, state)| earlier.files.get(*path) != Some(state))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// List all the files of the snapshot.
+    pub(crate) fn files(&self) -> Vec<PathBuf> {
+        self.files.keys().cloned().collect()
+    }
+
+    /// List the files which look generated by `flatc` (`*_generated.*`, e.g.
+    /// `message_generated.rs`).
+    pub(crate) fn generated_files(&self) -> Vec<PathBuf> {
+        self.files
+            .keys()
+            .filter(|path| {
+                path.file_stem()
+                    .is_some_and(|stem| stem.to_string_lossy().ends_with(GENERATED_SUFFIX))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// The suffix `flatc` appends to the stem of the schema for the generated file names
+const GENERATED_SUFFIX: &str = "_generated";
+
+/// Remove the previously generated files (see [`DirSnapshot::generated_files`]) from the
+/// directory, leaving the other files (and the `keep` ones) intact.
+pub(crate) fn clean_generated_files(dir: &Path, keep: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut generated_files = DirSnapshot::take(dir)?.generated_files();
+    generated_files.retain(|generated_file| !keep.contains(generated_file));
+    for generated_file in &generated_files {
+        fs::remove_file(generated_file)?;
+    }
+    Ok(generated_files)
+}
+
+/// Whether both files exist and have the same content
+pub(crate) fn have_same_content(file: &Path, other_file: &Path) -> Result<bool> {
+    let other_metadata = match fs::metadata(other_file) {
+        Ok(metadata) => metadata,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    if !other_metadata.is_file() || other_metadata.len() != fs::metadata(file)?.len() {
+        return Ok(false);
+    }
+    Ok(fs::read(file)? == fs::read(other_file)?)
+}
+
+/// The generators naming their output after the schema (`<stem>_generated.<ext>`) with the
+/// extensions
+const GENERATED_EXTENSIONS: &[(&str, &str)] = &[
+    ("cpp", "h"),
+    ("lobster", "lobster"),
+    ("rust", "rs"),
+    ("swift", "swift"),
+];
+
+/// The `flatc` flags of the modes which generate nothing into `out_dir`
+const NO_OUTPUT_FLAGS: &[&str] = &["-M", "--annotate", "--file-names-only"];
+
+/// The `flatc` flags changing how the generated files are named, which make the outputs
+/// unpredictable
+const RENAMING_FLAGS: &[&str] = &[
+    "--filename-ext",
+    "--filename-suffix",
+    "--rust-module-root-file",
+];
+
+/// The files `flatc` has to generate into `args.out_dir` for the generators whose outputs are
+/// named after the inputs: the helpers of the schemas (see [`GENERATED_EXTENSIONS`]), the binary
+/// schemas (`--binary --schema`), the JSON schemas (`--jsonschema`), the binaries converted from
+/// JSON (`--binary` without `--schema`) and the JSON converted from binaries (`--json`)
+pub(crate) fn expected_files(args: &Args) -> Vec<PathBuf> {
+    if args.rust_module_root_file
+        || args
+            .extra
+            .iter()
+            .any(|flag| NO_OUTPUT_FLAGS.contains(flag) || RENAMING_FLAGS.contains(flag))
+    {
+        return Vec::new();
+    }
+    let binary = args.binary || args.lang == "binary";
+    let json = args.json || args.lang == "json";
+    let jsonschema = args.jsonschema || args.lang == "jsonschema";
+    let generated_extension = GENERATED_EXTENSIONS
+        .iter()
+        .find(|(lang, _)| *lang == args.lang)
+        .map(|(_, extension)| extension);
+
+    let mut expected_files = Vec::new();
+    let mut binary_inputs = false;
+    for input in args.inputs {
+        if input.as_os_str() == "--" {
+            binary_inputs = true;
+            continue;
+        }
+        let (Some(stem), Some(extension)) = (input.file_stem(), input.extension()) else {
+            continue;
+        };
+        let stem = stem.to_string_lossy();
+        let named = |suffix: &str| args.out_dir.join(format!("{}{}", stem, suffix));
+        if binary_inputs {
+            if json {
+                expected_files.push(named(".json"));
+            }
+        } else if extension == "fbs" {
+            if let Some(extension) = generated_extension {
+                expected_files.push(named(&format!("_generated.{}", extension)));
+            }
+            if binary && args.schema {
+                expected_files.push(named(".bfbs"));
+            }
+            if jsonschema {
+                expected_files.push(named(".schema.json"));
+            }
+        } else if (extension == "json" || extension == "json5") && binary && !args.schema {
+            expected_files.push(named(".bin"));
+        }
+    }
+    expected_files
+}
+
+/// List the files the run has generated into `args.out_dir` since the `before` snapshot: the
+/// created or modified files, and the expected files (see [`expected_files`]), which `flatc` may
+/// have rewritten with the same length within the timestamp resolution of the filesystem
+///
+/// Without the snapshot, only the expected files are listed.
+pub(crate) fn generated_files(args: &Args, before: Option<&DirSnapshot>) -> Result<Vec<PathBuf>> {
+    let mut generated_files = match before {
+        Some(before) => DirSnapshot::take(args.out_dir)?.changed_since(before),
+        None => Vec::new(),
+    };
+    for expected_file in expected_files(args) {
+        if !generated_files.contains(&expected_file) && expected_file.is_file() {
+            generated_files.push(expected_file);
+        }
+    }
+    generated_files.sort();
+    Ok(generated_files)
+}
+
+/// Fail unless `flatc` has generated every expected file (see [`expected_files`]) and none of
+/// them is empty
+pub(crate) fn check_expected_files(args: &Args) -> Result<()> {
+    let mut missing_files = Vec::new();
+    for expected_file in expected_files(args) {
+        match fs::metadata(&expected_file) {
+            Ok(metadata) if metadata.len() > 0 => (),
+            Ok(_) => missing_files.push(format!("{} (empty)", expected_file.display())),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+                missing_files.push(expected_file.display().to_string())
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    if missing_files.is_empty() {
+        return Ok(());
+    }
+    Err(err_other(format!(
+        "flatc has exited successfully, but has not generated {} (check the lang and the flags)",
+        missing_files.join(", ")
+    )))
+}
+
+/// The error of a run after which `flatc` has exited successfully without generating (or
+/// updating) any file in `out_dir`, with the probable cause, if it is known (see
+/// [`Args::require_generated`])
+///
+/// It is returned wrapped into [`Error`] of `Other` kind:
+///
+/// ```no_run
+/// # let args = flatc_rust::Args::default();
+/// let args = flatc_rust::Args {
+///     require_generated: true,
+///     ..args
+/// };
+/// if let Err(e) = flatc_rust::run(args) {
+///     if let Some(e) = e
+///         .get_ref()
+///         .and_then(|e| e.downcast_ref::<flatc_rust::NothingGenerated>())
+///     {
+///         eprintln!("nothing generated in {}", e.out_dir.display());
+///     }
+/// }
+/// ```
+///
+/// [`Args::require_generated`]: struct.Args.html#structfield.require_generated
+/// [`Error`]: type.Error.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct NothingGenerated {
+    /// The output directory of the run
+    pub out_dir: PathBuf,
+    /// The probable cause, e.g. the schemas which only include other schemas without
+    /// `--gen-all`
+    pub probable_cause: Option<String>,
+}
+
+impl fmt::Display for NothingGenerated {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "flatc has exited successfully, but has not generated any files in {}",
+            self.out_dir.display()
+        )?;
+        if let Some(ref probable_cause) = self.probable_cause {
+            write!(f, "; probably {}", probable_cause)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for NothingGenerated {}
+
+/// Fail with [`NothingGenerated`] if the run has not generated any files (except in the modes
+/// generating nothing into `out_dir`, e.g. `-M`) and [`Args::require_generated`] is set, only
+/// log a warning otherwise
+///
+/// [`Args::require_generated`]: ../struct.Args.html#structfield.require_generated
+pub(crate) fn check_generated_files(args: &Args, generated_files: &[PathBuf]) -> Result<()> {
+    if !generated_files.is_empty() || args.extra.iter().any(|flag| NO_OUTPUT_FLAGS.contains(flag)) {
+        return Ok(());
+    }
+    let nothing_generated = NothingGenerated {
+        out_dir: args.out_dir.to_owned(),
+        probable_cause: probable_cause(args),
+    };
+    if args.require_generated {
+        return Err(err_other(nothing_generated));
+    }
+    warn!("{}", nothing_generated);
+    Ok(())
+}
+
+/// Guess why `flatc` has generated nothing for the args
+fn probable_cause(args: &Args) -> Option<String> {
+    let mut binary_inputs = false;
+    let mut schemas = Vec::new();
+    let mut json_inputs = false;
+    for input in args.inputs {
+        if input.as_os_str() == "--" {
+            binary_inputs = true;
+        } else if input
+            .extension()
+            .is_some_and(|extension| extension == "fbs")
+        {
+            schemas.push(Schema::from_path(input).ok()?);
+        } else if input
+            .extension()
+            .is_some_and(|extension| extension == "json" || extension == "json5")
+        {
+            json_inputs = true;
+        }
+    }
+
+    let binary = args.binary || args.lang == "binary";
+    let json = args.json || args.lang == "json";
+    if binary_inputs && !json {
+        return Some("the binary files after `--` are converted to JSON only with `json`".into());
+    }
+    if json_inputs && !binary {
+        return Some("the JSON files are converted to binaries only with `binary`".into());
+    }
+    if schemas.is_empty() || schemas.iter().any(|schema| !schema.declarations.is_empty()) {
+        return None;
+    }
+    if schemas.iter().all(|schema| schema.includes.is_empty()) {
+        Some("the schemas declare no types".into())
+    } else if !args.extra.contains(&"--gen-all") {
+        Some(
+            "the schemas only include other schemas, whose code flatc generates only with \
+             `--gen-all`"
+                .into(),
+        )
+    } else {
+        None
+    }
+}
+
+/// Copy the files of the directory (recursively) into the other one, and return the copies
+pub(crate) fn copy_files(from: &Path, to: &Path) -> Result<Vec<PathBuf>> {
+    let mut copies = Vec::new();
+    for file in DirSnapshot::take(from)?.files() {
+        let copy = to.join(file.strip_prefix(from).map_err(err_other)?);
+        if let Some(parent) = copy.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&file, &copy)?;
+        copies.push(copy);
+    }
+    Ok(copies)
+}
+
+/// Make the files read-only (see [`Args::read_only`])
+pub(crate) fn set_read_only(files: &[PathBuf]) -> Result<()> {
+    for file in files {
+        set_writable(file, false)?;
+    }
+    Ok(())
+}
+
+/// Make the read-only files of the directory writable (for the owner)
+pub(crate) fn make_writable(dir: &Path) -> Result<()> {
+    let mut read_only_files = DirSnapshot::take(dir)?.files();
+    read_only_files
+        .retain(|file| fs::metadata(file).is_ok_and(|metadata| metadata.permissions().readonly()));
+    for file in &read_only_files {
+        set_writable(file, true)?;
+    }
+    Ok(())
+}
+
+fn set_writable(file: &Path, writable: bool) -> Result<()> {
+    let mut permissions = fs::metadata(file)?.permissions();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = permissions.mode();
+        permissions.set_mode(if writable {
+            mode | 0o200
+        } else {
+            mode & !0o222
+        });
+    }
+    #[cfg(not(unix))]
+    permissions.set_readonly(!writable);
+    fs::set_permissions(file, permissions)
+}
+
+fn collect_files(
+    dir: &Path,
+    files: &mut BTreeMap<PathBuf, (u64, Option<SystemTime>)>,
+) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            collect_files(&entry.path(), files)?;
+        } else {
+            files.insert(entry.path(), (metadata.len(), metadata.modified().ok()));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,286 @@
+//! Owned counterpart of the args.
+
+use std::path::{Path, PathBuf};
+
+use crate::{command_line, Args, Invocation, OutDirLayout, Result};
+
+/// The owned version of [`Args`] to store the invocations (e.g. load them from config files with
+/// `serde` feature)
+///
+/// The fields have the same meaning as the ones of [`Args`]; the missing fields get the defaults
+/// of [`Args`] on deserialization. [`Args::postprocess`] and [`Args::rename`] are not
+/// (de)serialized.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn try_main() -> flatc_rust::Result<()> {
+/// let args: flatc_rust::ArgsOwned = Default::default();
+/// let flatc = flatc_rust::Flatc::from_env_path();
+/// args.with_args(|args| flatc.run(args))?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Args`]: struct.Args.html
+/// [`Args::postprocess`]: struct.Args.html#structfield.postprocess
+/// [`Args::rename`]: struct.Args.html#structfield.rename
+/// [`Invocation::PerFile`]: enum.Invocation.html#variant.PerFile
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ArgsOwned {
+    /// The programming language (`rust` is the default)
+    pub lang: String,
+    /// The `.fbs` files to compile
+    pub inputs: Vec<PathBuf>,
+    /// The output path for the generated helpers (`-o PATH` parameter)
+    pub out_dir: PathBuf,
+    /// The paths to search for includes in (`-I PATH` parameter)
+    pub includes: Vec<PathBuf>,
+    /// Set the flatc `--binary` flag
+    pub binary: bool,
+    /// Set the flatc `--schema` flag
+    pub schema: bool,
+    /// Set the flatc `--json` flag
+    pub json: bool,
+    /// Also generate JSON Schema from the `.fbs` inputs (`--jsonschema` flag)
+    pub jsonschema: bool,
+    /// The root to embed the declaration filenames into the binary schemas relative to
+    pub bfbs_filenames: Option<PathBuf>,
+    /// Reject the schemas with fields lacking explicit ids
+    pub require_explicit_ids: bool,
+    /// The baseline schema the inputs must be an evolution of (`--conform FILE`)
+    pub conform: Option<PathBuf>,
+    /// The paths to search for the includes of the `conform` baseline schema in
+    pub conform_includes: Vec<PathBuf>,
+    /// Silence all `flatc` warnings (`--no-warnings` flag)
+    pub no_warnings: bool,
+    /// Pass non-UTF-8 strings through when converting JSON and binary data
+    pub allow_non_utf8: bool,
+    /// Skip the JSON fields missing from the schema when converting JSON to binary
+    pub unknown_json: bool,
+    /// Generate every Rust namespace into its own file tied together with `mod.rs`
+    pub rust_module_root_file: bool,
+    /// Implement `serde::Serialize` on the generated Rust types
+    pub rust_serialize: bool,
+    /// Generate the code encoding the FlatBuffers into JSON
+    pub gen_json_emit: bool,
+    /// Generate the `Newtonsoft.Json` (de)serialization of the C# object API types
+    pub cs_gen_json_serializer: bool,
+    /// Create `out_dir` (with the missing parents) before the run
+    pub create_out_dir: bool,
+    /// Remove the previously generated files from `out_dir` before the run
+    pub clean_out_dir: bool,
+    /// Generate into a staging directory and move the generated files into `out_dir`
+    pub atomic: bool,
+    /// Leave the files of `out_dir` whose content has not changed untouched
+    pub skip_unchanged: bool,
+    /// Make the generated files read-only after the run
+    pub read_only: bool,
+    /// Fail if the run has not generated (or updated) any file
+    pub require_generated: bool,
+    /// Extra args to pass to flatc
+    pub extra: Vec<String>,
+    /// Post-process every generated text file (not (de)serialized)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub postprocess: Option<fn(&Path, String) -> String>,
+    /// Adapt the generated Rust code for `no_std` crates
+    pub no_std: bool,
+    /// Rename every generated file (not (de)serialized)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub rename: Option<fn(&Path) -> PathBuf>,
+    /// Prepend a comment recording the provenance to every generated source file
+    pub provenance: bool,
+    /// The text to prepend to every generated source file
+    pub banner: Option<String>,
+    /// Parse every generated `.rs` file with `syn` (`verify` feature)
+    pub verify_rust: bool,
+    /// Append the tonic servers and clients of the `rpc_service` declarations (`tonic` feature)
+    pub tonic: bool,
+    /// The name of the `cargo:rustc-env` variable to set to the absolute `out_dir`
+    pub rustc_env: Option<String>,
+    /// Print `cargo:rerun-if-changed` for the full include closure of the inputs
+    pub rerun_if_changed: bool,
+    /// Search for includes in the schema directories published by the dependencies
+    pub dependency_schemas: bool,
+    /// The path to write the declared inputs and outputs of the run to
+    pub manifest: Option<PathBuf>,
+    /// The directory to cache the generated files in
+    pub cache_dir: Option<PathBuf>,
+    /// Make the generated files byte-identical across the machines
+    pub reproducible: Option<String>,
+    /// Run `flatc` once for all the inputs or once per input
+    pub invocation: Invocation,
+    /// The most `flatc` processes running at once with [`Invocation::PerFile`]
+    pub jobs: Option<usize>,
+    /// Swallow the output of a successful `flatc` run
+    pub quiet: bool,
+    /// Append the target triple (and the profile) to `out_dir`
+    pub out_dir_layout: OutDirLayout,
+}
+
+impl Default for ArgsOwned {
+    fn default() -> Self {
+        Args::default().into()
+    }
+}
+
+impl From<Args<'_>> for ArgsOwned {
+    fn from(args: Args) -> Self {
+        ArgsOwned {
+            lang: args.lang.to_owned(),
+            inputs: args.inputs.iter().map(|&path| path.to_owned()).collect(),
+            out_dir: args.out_dir.to_owned(),
+            includes: args.includes.iter().map(|&path| path.to_owned()).collect(),
+            binary: args.binary,
+            schema: args.schema,
+            json: args.json,
+            jsonschema: args.jsonschema,
+            bfbs_filenames: args.bfbs_filenames.map(Path::to_owned),
+            require_explicit_ids: args.require_explicit_ids,
+            conform: args.conform.map(Path::to_owned),
+            conform_includes: args
+                .conform_includes
+                .iter()
+                .map(|&path| path.to_owned())
+                .collect(),
+            no_warnings: args.no_warnings,
+            allow_non_utf8: args.allow_non_utf8,
+            unknown_json: args.unknown_json,
+            rust_module_root_file: args.rust_module_root_file,
+            rust_serialize: args.rust_serialize,
+            gen_json_emit: args.gen_json_emit,
+            cs_gen_json_serializer: args.cs_gen_json_serializer,
+            create_out_dir: args.create_out_dir,
+            clean_out_dir: args.clean_out_dir,
+            atomic: args.atomic,
+            skip_unchanged: args.skip_unchanged,
+            read_only: args.read_only,
+            require_generated: args.require_generated,
+            extra: args.extra.iter().map(|&arg| arg.to_owned()).collect(),
+            postprocess: args.postprocess,
+            no_std: args.no_std,
+            rename: args.rename,
+            provenance: args.provenance,
+            banner: args.banner.map(str::to_owned),
+            verify_rust: args.verify_rust,
+            tonic: args.tonic,
+            rustc_env: args.rustc_env.map(str::to_owned),
+            rerun_if_changed: args.rerun_if_changed,
+            dependency_schemas: args.dependency_schemas,
+            manifest: args.manifest.map(Path::to_owned),
+            cache_dir: args.cache_dir.map(Path::to_owned),
+            reproducible: args.reproducible.map(str::to_owned),
+            invocation: args.invocation,
+            jobs: args.jobs,
+            quiet: args.quiet,
+            out_dir_layout: args.out_dir_layout,
+        }
+    }
+}
+
+impl ArgsOwned {
+    /// Parse a raw `flatc` command line, e.g. copied from a Makefile
+    ///
+    /// The words are split like a POSIX shell does (without expansions); the leading `flatc`
+    /// program is optional. The first generator flag (e.g. `--rust`) becomes [`lang`] (the
+    /// following `--binary`, `--json` and `--jsonschema` set the corresponding flags), `-o`
+    /// defaults to the current directory, and the options not covered by the fields are kept in
+    /// [`extra`].
+    ///
+    /// ```
+    /// # fn try_main() -> flatc_rust::Result<()> {
+    /// let args = flatc_rust::ArgsOwned::parse_cli("flatc --rust -o out -I inc foo.fbs")?;
+    /// assert_eq!(args.lang, "rust");
+    /// assert_eq!(args.inputs, vec![std::path::PathBuf::from("foo.fbs")]);
+    /// # Ok(())
+    /// # }
+    /// # try_main().unwrap();
+    /// ```
+    ///
+    /// [`lang`]: #structfield.lang
+    /// [`extra`]: #structfield.extra
+    pub fn parse_cli(command_line: &str) -> Result<ArgsOwned> {
+        command_line::parse(command_line)
+    }
+
+    /// Call `f` with [`Args`] borrowing from `self`
+    ///
+    /// [`Args`]: struct.Args.html
+    pub fn with_args<T, F: FnOnce(Args) -> T>(&self, f: F) -> T {
+        let inputs: Vec<&Path> = self.inputs.iter().map(PathBuf::as_path).collect();
+        let includes: Vec<&Path> = self.includes.iter().map(PathBuf::as_path).collect();
+        let conform_includes: Vec<&Path> =
+            self.conform_includes.iter().map(PathBuf::as_path).collect();
+        let extra: Vec<&str> = self.extra.iter().map(String::as_str).collect();
+        f(Args {
+            lang: &self.lang,
+            inputs: &inputs,
+            out_dir: &self.out_dir,
+            includes: &includes,
+            binary: self.binary,
+            schema: self.schema,
+            json: self.json,
+            jsonschema: self.jsonschema,
+            bfbs_filenames: self.bfbs_filenames.as_deref(),
+            require_explicit_ids: self.require_explicit_ids,
+            conform: self.conform.as_deref(),
+            conform_includes: &conform_includes,
+            no_warnings: self.no_warnings,
+            allow_non_utf8: self.allow_non_utf8,
+            unknown_json: self.unknown_json,
+            rust_module_root_file: self.rust_module_root_file,
+            rust_serialize: self.rust_serialize,
+            gen_json_emit: self.gen_json_emit,
+            cs_gen_json_serializer: self.cs_gen_json_serializer,
+            create_out_dir: self.create_out_dir,
+            clean_out_dir: self.clean_out_dir,
+            atomic: self.atomic,
+            skip_unchanged: self.skip_unchanged,
+            read_only: self.read_only,
+            require_generated: self.require_generated,
+            extra: &extra,
+            postprocess: self.postprocess,
+            no_std: self.no_std,
+            rename: self.rename,
+            provenance: self.provenance,
+            banner: self.banner.as_deref(),
+            verify_rust: self.verify_rust,
+            tonic: self.tonic,
+            rustc_env: self.rustc_env.as_deref(),
+            rerun_if_changed: self.rerun_if_changed,
+            dependency_schemas: self.dependency_schemas,
+            manifest: self.manifest.as_deref(),
+            cache_dir: self.cache_dir.as_deref(),
+            reproducible: self.reproducible.as_deref(),
+            invocation: self.invocation,
+            jobs: self.jobs,
+            quiet: self.quiet,
+            out_dir_layout: self.out_dir_layout,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deserialize_uses_args_defaults() {
+        let args: ArgsOwned = serde_json::from_str(
+            r#"{"inputs": ["schemas/message.fbs"], "out_dir": "target/flatbuffers"}"#,
+        )
+        .expect("deserialize");
+
+        args.with_args(|args| {
+            assert_eq!(args.lang, "rust");
+            assert_eq!(args.inputs, &[Path::new("schemas/message.fbs")]);
+            assert!(args.create_out_dir);
+        });
+
+        let json = serde_json::to_value(&args).expect("serialize");
+        assert_eq!(json["out_dir"], "target/flatbuffers");
+        assert!(json.get("postprocess").is_none());
+    }
+}
@@ -0,0 +1,607 @@
+//! Post-processing of the generated files.
+//!
+//! A post-processor is a plain function which receives the path and the content of a generated
+//! file and returns the new content; it is set via [`Args::postprocess`]. Similarly, a renamer
+//! receives the path of a generated file relative to `out_dir` and returns the new one; it is set
+//! via [`Args::rename`].
+//!
+//! [`Args::postprocess`]: ../struct.Args.html#structfield.postprocess
+//! [`Args::rename`]: ../struct.Args.html#structfield.rename
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::report::InputReport;
+use crate::{command_line, err_other, Result};
+
+/// Apply the post-processor to every file, rewriting only the files whose content has changed.
+///
+/// Files which are not valid UTF-8 (e.g. binary schemas) are left untouched.
+pub(crate) fn apply<F: Fn(&Path, String) -> String>(
+    postprocess: F,
+    files: &[PathBuf],
+) -> Result<()> {
+    for file in files {
+        let content = match String::from_utf8(fs::read(file)?) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let processed = postprocess(file, content.clone());
+        if processed != content {
+            fs::write(file, processed)?;
+        }
+    }
+    Ok(())
+}
+
+/// Drop the `_generated` suffix `flatc` appends to the file stems (e.g. `message_generated.rs`
+/// becomes `message.rs`)
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// flatc_rust::run(flatc_rust::Args {
+///     inputs: &[Path::new("src/message.fbs")],
+///     out_dir: Path::new("target/flatbuffers/"),
+///     rename: Some(flatc_rust::postprocess::strip_generated_suffix),
+///     ..Default::default()
+/// })
+/// .expect("flatc");
+/// ```
+pub fn strip_generated_suffix(path: &Path) -> PathBuf {
+    let file_name = match path.file_name().and_then(|file_name| file_name.to_str()) {
+        Some(file_name) => file_name,
+        None => return path.to_path_buf(),
+    };
+    let (stem, extension) = match file_name.find('.') {
+        Some(index) => file_name.split_at(index),
+        None => (file_name, ""),
+    };
+    match stem.strip_suffix("_generated") {
+        Some(stem) if !stem.is_empty() => path.with_file_name(format!("{}{}", stem, extension)),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Lowercase the file names (the directories are kept as is)
+pub fn lowercase_file_name(path: &Path) -> PathBuf {
+    match path.file_name().and_then(|file_name| file_name.to_str()) {
+        Some(file_name) => path.with_file_name(file_name.to_lowercase()),
+        None => path.to_path_buf(),
+    }
+}
+
+/// The lines of the provenance comment (see [`Args::provenance`])
+///
+/// `-o` is left out of the arguments, so the comment does not depend on the staging directory of
+/// [`Args::atomic`] runs.
+///
+/// [`Args::provenance`]: ../struct.Args.html#structfield.provenance
+/// [`Args::atomic`]: ../struct.Args.html#structfield.atomic
+pub(crate) fn provenance_lines(
+    flatc_version: &str,
+    args: &[OsString],
+    inputs: &[InputReport],
+) -> Vec<String> {
+    let mut args_without_out_dir = Vec::with_capacity(args.len());
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "-o" {
+            args.next();
+        } else {
+            args_without_out_dir.push(arg);
+        }
+    }
+    let mut lines = vec![
+        format!(
+            "Generated by flatc-rust {} with flatc {}",
+            env!("CARGO_PKG_VERSION"),
+            flatc_version
+        ),
+        format!("flatc args: {}", command_line::render(args_without_out_dir)),
+    ];
+    lines.extend(
+        inputs
+            .iter()
+            .map(|input| format!("{} SHA-256: {}", input.path.display(), input.sha256)),
+    );
+    lines
+}
+
+/// The line comment syntax of the generated file (by its extension)
+fn line_comment(path: &Path) -> Option<&'static str> {
+    Some(match path.extension()?.to_str()? {
+        "rs" | "h" | "hpp" | "cpp" | "cc" | "c" | "cs" | "java" | "kt" | "go" | "ts" | "js"
+        | "swift" | "dart" | "fbs" | "proto" => "//",
+        "py" | "pyi" | "nim" => "#",
+        "lua" => "--",
+        _ => return None,
+    })
+}
+
+/// Prepend the lines as a comment to the content of the file (if its comment syntax is known)
+pub(crate) fn prepend_comment(path: &Path, content: String, lines: &[String]) -> String {
+    let comment = match line_comment(path) {
+        Some(comment) => comment,
+        None => return content,
+    };
+    let mut processed = String::with_capacity(content.len());
+    for line in lines {
+        processed.push_str(comment);
+        if !line.is_empty() {
+            processed.push(' ');
+            processed.push_str(line);
+        }
+        processed.push('\n');
+    }
+    processed.push_str(&content);
+    processed
+}
+
+/// The attribute injected by [`serde_derive`]
+///
+/// [`serde_derive`]: fn.serde_derive.html
+pub const SERDE_DERIVE_ATTRIBUTE: &str =
+    "#[cfg_attr(feature = \"serde\", derive(serde::Serialize, serde::Deserialize))]";
+
+/// Add `serde` derives (enabled by the `serde` feature of the crate including the generated
+/// code) to the object API types (`--gen-object-api`) and the enums they use
+///
+/// The generated Rust code uses `serde::Serialize` and `serde::Deserialize` paths, so `serde`
+/// (with `derive` feature) has to be an (optional) dependency of the crate. Bit flags enums are not
+/// supported.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// flatc_rust::run(flatc_rust::Args {
+///     inputs: &[Path::new("src/message.fbs")],
+///     out_dir: Path::new("target/flatbuffers/"),
+///     extra: &["--gen-object-api"],
+///     postprocess: Some(flatc_rust::postprocess::serde_derive),
+///     ..Default::default()
+/// })
+/// .expect("flatc");
+/// ```
+pub fn serde_derive(_path: &Path, content: String) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut processed = String::with_capacity(content.len());
+    for (index, line) in lines.iter().enumerate() {
+        let item = line.trim_start();
+        if is_object_api_type(item, &lines[..index]) || is_enum_type(item) {
+            processed.push_str(&line[..line.len() - item.len()]);
+            processed.push_str(SERDE_DERIVE_ATTRIBUTE);
+            processed.push('\n');
+        }
+        processed.push_str(line);
+        processed.push('\n');
+    }
+    processed
+}
+
+/// The attributes preceding the item (in the reverse order)
+fn attributes<'a>(preceding_lines: &'a [&'a str]) -> impl Iterator<Item = &'a str> + 'a {
+    preceding_lines
+        .iter()
+        .rev()
+        .map(|line| line.trim())
+        .take_while(|line| line.starts_with("#["))
+}
+
+/// Object API types are the only generated structs and enums without generics and with a body
+fn is_object_api_type(item: &str, preceding_lines: &[&str]) -> bool {
+    let name = match item
+        .strip_prefix("pub struct ")
+        .or_else(|| item.strip_prefix("pub enum "))
+        .and_then(|item| item.strip_suffix(" {"))
+    {
+        Some(name) => name,
+        None => return false,
+    };
+    name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && attributes(preceding_lines).any(|attribute| {
+            attribute.starts_with("#[derive(") && attribute.contains("Debug, Clone, PartialEq")
+        })
+}
+
+/// Enums are generated as transparent wrappers of integers
+fn is_enum_type(item: &str) -> bool {
+    let repr = match item
+        .strip_prefix("pub struct ")
+        .and_then(|item| item.split_once("(pub "))
+        .and_then(|(_, repr)| repr.strip_suffix(");"))
+    {
+        Some(repr) => repr,
+        None => return false,
+    };
+    matches!(
+        repr,
+        "i8" | "u8" | "i16" | "u16" | "i32" | "u32" | "i64" | "u64"
+    )
+}
+
+/// The `std` modules which are re-exports of the `core` ones
+const CORE_MODULES: &[&str] = &[
+    "any", "cell", "char", "clone", "cmp", "convert", "default", "fmt", "hash", "iter", "marker",
+    "mem", "num", "ops", "option", "ptr", "result", "slice", "str",
+];
+
+/// The `std` modules which are re-exports of the `alloc` ones
+const ALLOC_MODULES: &[&str] = &["borrow", "boxed", "string", "vec"];
+
+/// The lines injected by [`no_std`] to bring the `alloc` items of the `std` prelude into scope
+///
+/// [`no_std`]: fn.no_std.html
+pub const NO_STD_ALLOC_IMPORTS: &[&str] = &[
+    "extern crate alloc;",
+    "#[allow(unused_imports)]",
+    "use self::alloc::{boxed::Box, string::{String, ToString}, vec::Vec};",
+];
+
+/// Adapt the generated Rust code for `no_std` crates
+///
+/// `std::` paths are rewritten to their `core::` or `alloc::` counterparts and the `alloc` items
+/// of the `std` prelude (`Box`, `String`, `ToString`, `Vec`) used by the object API are imported
+/// next to `extern crate flatbuffers;`. The code may still depend on `std` (use [`check_no_std`]
+/// to find the offending constructs); `flatbuffers` has to be used with `default-features =
+/// false`.
+///
+/// [`check_no_std`]: fn.check_no_std.html
+pub fn no_std(_path: &Path, content: String) -> String {
+    let mut content = content;
+    for module in CORE_MODULES {
+        content = replace_path(
+            &content,
+            &format!("std::{}", module),
+            &format!("core::{}", module),
+        );
+    }
+    for module in ALLOC_MODULES {
+        content = replace_path(
+            &content,
+            &format!("std::{}", module),
+            &format!("alloc::{}", module),
+        );
+    }
+    if content.contains("extern crate alloc;") {
+        return content;
+    }
+
+    let mut processed = String::with_capacity(content.len());
+    for line in content.lines() {
+        processed.push_str(line);
+        processed.push('\n');
+        let item = line.trim_start();
+        if item == "extern crate flatbuffers;" {
+            for import in NO_STD_ALLOC_IMPORTS {
+                processed.push_str(&line[..line.len() - item.len()]);
+                processed.push_str(import);
+                processed.push('\n');
+            }
+        }
+    }
+    processed
+}
+
+/// Replace the `from` path (which has to start at an identifier boundary) with `to`
+fn replace_path(content: &str, from: &str, to: &str) -> String {
+    let mut replaced = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(position) = find_path(rest, from) {
+        replaced.push_str(&rest[..position]);
+        replaced.push_str(to);
+        rest = &rest[position + from.len()..];
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+/// Find the position of the `path` which is not a part of a longer path (`::std` is fine)
+fn find_path(content: &str, path: &str) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut offset = 0;
+    while let Some(position) = content[offset..].find(path) {
+        let start = offset + position;
+        let end = start + path.len();
+        let before = content[..start].trim_end_matches("::");
+        let preceded_by_ident = content[..start].ends_with(is_ident)
+            || (before.len() != start && before.ends_with(is_ident));
+        if !preceded_by_ident && !content[end..].starts_with(is_ident) {
+            return Some(start);
+        }
+        offset = end;
+    }
+    None
+}
+
+/// Find the constructs of the generated Rust code which depend on `std`
+///
+/// Every found issue is described as `line N: <the line>`.
+pub fn check_no_std(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let code = line.split("//").next().unwrap_or_default();
+            find_path(code, "std").is_some_and(|position| code[position + 3..].starts_with("::"))
+                || ["println!", "eprintln!", "HashMap", "HashSet"]
+                    .iter()
+                    .any(|construct| code.contains(construct))
+        })
+        .map(|(index, line)| format!("line {}: {}", index + 1, line.trim()))
+        .collect()
+}
+
+/// Check the generated Rust files with [`check_no_std`] and fail listing all the issues
+///
+/// [`check_no_std`]: fn.check_no_std.html
+pub(crate) fn check_no_std_files(files: &[PathBuf]) -> Result<()> {
+    let mut issues = Vec::new();
+    for file in files {
+        for issue in check_no_std(&fs::read_to_string(file)?) {
+            issues.push(format!("{}: {}", file.display(), issue));
+        }
+    }
+    if !issues.is_empty() {
+        return Err(err_other(format!(
+            "the generated code is not `no_std` compatible:\n{}",
+            issues.join("\n")
+        )));
+    }
+    Ok(())
+}
+
+/// Gate the modules of the FlatBuffers namespaces behind Cargo features: `features` maps a
+/// namespace (e.g. `MyGame.Weapons`) to the feature of the crate including the generated code
+///
+/// The `#[cfg(feature = "...")]` attribute is added to every `pub mod` of the namespace, in the
+/// single generated file as well as in the `mod.rs` of [`Args::rust_module_root_file`], so the
+/// nested namespaces are gated too. Post-processors are plain functions, so wrap it with the
+/// mapping:
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// fn gate_namespaces(_path: &Path, content: String) -> String {
+///     flatc_rust::postprocess::feature_gate_namespaces(
+///         content,
+///         &[("MyGame.Weapons", "weapons"), ("MyGame.Spells", "spells")],
+///     )
+/// }
+///
+/// flatc_rust::run(flatc_rust::Args {
+///     inputs: &[Path::new("schemas/game.fbs")],
+///     out_dir: Path::new("target/flatbuffers/"),
+///     postprocess: Some(gate_namespaces),
+///     ..Default::default()
+/// })
+/// .expect("flatc");
+/// ```
+///
+/// [`Args::rust_module_root_file`]: ../struct.Args.html#structfield.rust_module_root_file
+pub fn feature_gate_namespaces(content: String, features: &[(&str, &str)]) -> String {
+    let features: Vec<(Vec<String>, &str)> = features
+        .iter()
+        .map(|&(namespace, feature)| (namespace.split('.').map(camel_to_snake).collect(), feature))
+        .collect();
+    let mut modules: Vec<&str> = Vec::new();
+    let mut processed = String::with_capacity(content.len());
+    for line in content.lines() {
+        let item = line.trim_start();
+        if let Some(module) = item
+            .strip_prefix("pub mod ")
+            .and_then(|rest| rest.strip_suffix(" {"))
+        {
+            modules.push(module);
+            for (namespace, feature) in &features {
+                if namespace
+                    .iter()
+                    .map(String::as_str)
+                    .eq(modules.iter().copied())
+                {
+                    processed.push_str(&line[..line.len() - item.len()]);
+                    processed.push_str(&format!("#[cfg(feature = \"{}\")]\n", feature));
+                }
+            }
+        } else if let Some(trailer) = item.strip_prefix('}') {
+            // `}  // pub mod MyGame` in the generated files, `} // my_game` in `mod.rs`; the rest
+            // of the closing braces (even the commented ones) do not close a module
+            let closed = match trailer.trim_start().strip_prefix("//").map(str::trim) {
+                Some(comment) => match comment.strip_prefix("pub mod ") {
+                    Some(namespace) => Some(camel_to_snake(namespace)),
+                    None => Some(comment.to_owned()),
+                },
+                None => None,
+            };
+            if closed.is_some() && closed.as_deref() == modules.last().copied() {
+                modules.pop();
+            }
+        }
+        processed.push_str(line);
+        processed.push('\n');
+    }
+    processed
+}
+
+/// Convert the namespace component to the module name like `flatc` does (`MyGame` becomes
+/// `my_game`, `Example2` becomes `example_2`)
+fn camel_to_snake(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if let Some(previous) = previous {
+            if c != '_'
+                && !c.is_ascii_lowercase()
+                && (previous.is_ascii_lowercase()
+                    || (previous.is_ascii_digit() && !c.is_ascii_digit()))
+            {
+                snake.push('_');
+            }
+        }
+        snake.push(c.to_ascii_lowercase());
+        previous = Some(c);
+    }
+    snake
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn serde_derive_annotates_object_api_types_and_enums() {
+        let generated = "\
+pub mod sample {
+  #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+  #[repr(transparent)]
+  pub struct Color(pub i8);
+  #[repr(transparent)]
+  #[derive(Clone, Copy, PartialEq)]
+  pub struct Vec3(pub [u8; 12]);
+  pub enum MonsterOffset {}
+  #[derive(Copy, Clone, PartialEq)]
+  pub struct Monster<'a> {
+    pub _tab: flatbuffers::Table<'a>,
+  }
+  #[non_exhaustive]
+  #[derive(Debug, Clone, PartialEq)]
+  pub struct MonsterT {
+    pub color: Color,
+  }
+}
+";
+
+        let processed = serde_derive(Path::new("sample_generated.rs"), generated.to_owned());
+
+        assert_eq!(processed.matches(SERDE_DERIVE_ATTRIBUTE).count(), 2);
+        assert!(processed.contains(&format!(
+            "  {}\n  pub struct Color(pub i8);",
+            SERDE_DERIVE_ATTRIBUTE
+        )));
+        assert!(processed.contains(&format!(
+            "  {}\n  pub struct MonsterT {{",
+            SERDE_DERIVE_ATTRIBUTE
+        )));
+    }
+
+    #[test]
+    fn no_std_rewrites_std_paths_and_imports_alloc() {
+        let generated = "\
+use std::mem;
+use std::cmp::Ordering;
+extern crate flatbuffers;
+pub mod sample {
+  extern crate flatbuffers;
+  impl ::std::fmt::Debug for Color {}
+  pub struct MonsterT {
+    pub name: Option<std::string::String>,
+  }
+}
+";
+
+        let processed = no_std(Path::new("sample_generated.rs"), generated.to_owned());
+
+        assert!(processed.starts_with("use core::mem;\nuse core::cmp::Ordering;\n"));
+        assert!(processed.contains("impl ::core::fmt::Debug for Color {}"));
+        assert!(processed.contains("Option<alloc::string::String>"));
+        assert_eq!(processed.matches(NO_STD_ALLOC_IMPORTS[2]).count(), 2);
+        assert!(check_no_std(&processed).is_empty());
+        assert_eq!(
+            no_std(Path::new("sample_generated.rs"), processed.clone()),
+            processed
+        );
+    }
+
+    #[test]
+    fn check_no_std_reports_offending_constructs() {
+        let generated = "\
+use core::mem;
+// std::io is mentioned in a comment only
+use std::collections::HashMap;
+fn dump() { println!(\"dump\"); }
+";
+
+        assert_eq!(
+            check_no_std(generated),
+            vec![
+                "line 3: use std::collections::HashMap;".to_owned(),
+                "line 4: fn dump() { println!(\"dump\"); }".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn renamers_change_file_names_only() {
+        assert_eq!(
+            strip_generated_suffix(Path::new("game/monster_generated.rs")),
+            PathBuf::from("game/monster.rs")
+        );
+        assert_eq!(
+            strip_generated_suffix(Path::new("monster_generated.d.ts")),
+            PathBuf::from("monster.d.ts")
+        );
+        assert_eq!(
+            strip_generated_suffix(Path::new("_generated.rs")),
+            PathBuf::from("_generated.rs")
+        );
+        assert_eq!(
+            lowercase_file_name(Path::new("MyGame/Monster.py")),
+            PathBuf::from("MyGame/monster.py")
+        );
+    }
+
+    #[test]
+    fn feature_gate_namespaces_gates_mapped_modules() {
+        let generated = "\
+#[allow(unused_imports, dead_code)]
+pub mod my_game {
+  use core::mem;
+#[allow(unused_imports, dead_code)]
+pub mod sample {
+  pub struct Monster {}
+  impl Monster {
+    pub fn new() -> Self {
+      Monster {}
+    }  // new
+  }  // impl Monster
+}  // pub mod Sample
+#[allow(unused_imports, dead_code)]
+pub mod weapons_2 {
+  pub struct Sword {}
+}  // pub mod Weapons2
+}  // pub mod MyGame
+pub mod weapons_2 {
+}  // pub mod Weapons2
+";
+
+        let processed = feature_gate_namespaces(
+            generated.to_owned(),
+            &[("MyGame.Weapons2", "weapons"), ("MyGame", "game")],
+        );
+
+        assert_eq!(processed.matches("#[cfg(feature = ").count(), 2);
+        assert!(processed.starts_with(
+            "#[allow(unused_imports, dead_code)]\n#[cfg(feature = \"game\")]\npub mod my_game {"
+        ));
+        assert!(processed.contains("#[cfg(feature = \"weapons\")]\npub mod weapons_2 {\n  pub"));
+        assert!(processed
+            .ends_with("}  // pub mod MyGame\npub mod weapons_2 {\n}  // pub mod Weapons2\n"));
+
+        let module_root = "\
+pub mod my_game {
+  use super::*;
+  pub mod weapons_2 {
+    mod sword_generated;
+  } // weapons_2
+} // my_game
+";
+        let processed =
+            feature_gate_namespaces(module_root.to_owned(), &[("MyGame.Weapons2", "weapons")]);
+        assert!(processed.contains("  #[cfg(feature = \"weapons\")]\n  pub mod weapons_2 {"));
+    }
+}
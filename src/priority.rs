@@ -0,0 +1,84 @@
+//! Lowering of the CPU and IO priority of the `flatc` processes (`priority` feature, see
+//! [`Flatc::with_low_priority`]).
+//!
+//! On Unix the child raises its niceness by 10 right before `exec` (and switches to the idle IO
+//! class on Linux, like `nice -n 10 ionice -c 3`); on Windows it is created with
+//! `BELOW_NORMAL_PRIORITY_CLASS`. Lowering the priority is best effort: the failures are
+//! ignored.
+//!
+//! [`Flatc::with_low_priority`]: ../struct.Flatc.html#method.with_low_priority
+
+use std::process;
+
+/// The niceness added to the `flatc` processes
+#[cfg(unix)]
+const NICENESS_INCREMENT: i32 = 10;
+
+/// `IOPRIO_WHO_PROCESS` of `ioprio_set`
+#[cfg(target_os = "linux")]
+const IOPRIO_WHO_PROCESS: i32 = 1;
+/// `IOPRIO_PRIO_VALUE(IOPRIO_CLASS_IDLE, 0)`
+#[cfg(target_os = "linux")]
+const IOPRIO_IDLE: i32 = 3 << 13;
+
+/// Make the command spawn its process at a reduced priority
+#[cfg(unix)]
+#[allow(unsafe_code)]
+pub(crate) fn lower(cmd: &mut process::Command) {
+    use std::os::unix::process::CommandExt;
+
+    // SAFETY: the closure only makes system calls, which is safe between `fork` and `exec`
+    unsafe {
+        cmd.pre_exec(|| {
+            libc::nice(NICENESS_INCREMENT);
+            #[cfg(target_os = "linux")]
+            libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, IOPRIO_IDLE);
+            Ok(())
+        });
+    }
+}
+
+/// Make the command spawn its process at a reduced priority
+#[cfg(windows)]
+pub(crate) fn lower(cmd: &mut process::Command) {
+    use std::os::windows::process::CommandExt;
+
+    const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+    cmd.creation_flags(BELOW_NORMAL_PRIORITY_CLASS);
+}
+
+/// Make the command spawn its process at a reduced priority (not supported on this platform)
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn lower(cmd: &mut process::Command) {
+    let _ = cmd;
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use std::process;
+
+    /// The niceness of the process from `/proc/<pid>/stat`, read by the process itself
+    fn niceness(low_priority: bool) -> i32 {
+        let mut cmd = process::Command::new("cat");
+        cmd.arg("/proc/self/stat");
+        if low_priority {
+            super::lower(&mut cmd);
+        }
+        let output = cmd.output().expect("cat");
+        let stat = String::from_utf8(output.stdout).expect("UTF-8 stat");
+        // The fields after the command name, which starts at the third one
+        let fields: Vec<&str> = stat[stat.rfind(')').expect("command name") + 1..]
+            .split_whitespace()
+            .collect();
+        fields[16].parse().expect("niceness")
+    }
+
+    #[test]
+    fn lower_raises_niceness() {
+        let niceness_before = niceness(false);
+        assert_eq!(
+            niceness(true),
+            (niceness_before + super::NICENESS_INCREMENT).min(19)
+        );
+    }
+}
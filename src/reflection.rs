@@ -0,0 +1,840 @@
+//! Loading of the binary schemas (`.bfbs`) generated by `flatc --binary --schema`.
+//!
+//! The binary schemas are FlatBuffers of the [reflection schema], so they describe the objects
+//! (tables and structs), the fields, the enums and the attributes exactly as `flatc` has resolved
+//! them (unlike the [`schema`] parser), which is what the runtime tooling (dynamic readers,
+//! validators, etc.) needs:
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! let out_dir = Path::new("target/flatbuffers/");
+//! flatc_rust::run(flatc_rust::Args {
+//!     inputs: &[Path::new("schemas/monster.fbs")],
+//!     out_dir,
+//!     ..flatc_rust::Args::bfbs_only()
+//! })
+//! .expect("flatc");
+//!
+//! let schema = flatc_rust::reflection::Schema::from_path(out_dir.join("monster.bfbs"))
+//!     .expect("binary schema");
+//! let monster = schema.root_object().expect("root table");
+//! for field in &monster.fields {
+//!     println!("{} (id {}): {:?}", field.name, field.id, field.field_type.base_type);
+//! }
+//! ```
+//!
+//! The attributes and the documentation are included by `flatc` only if they are requested
+//! (`--reflect-names` is the default, `--bfbs-comments` and `--bfbs-builtins` are not).
+//!
+//! [reflection schema]: https://github.com/google/flatbuffers/blob/master/reflection/reflection.fbs
+//! [`schema`]: ../schema/index.html
+
+use std::convert::{TryFrom, TryInto};
+use std::fs;
+use std::path::Path;
+
+use crate::schema::Attribute;
+use crate::{Error, Result};
+
+/// The file identifier of the binary schemas
+pub const FILE_IDENTIFIER: &[u8; 4] = b"BFBS";
+
+/// The type of a field, a vector element or an enum value (`reflection.BaseType`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseType {
+    /// `None`
+    None,
+    /// The type field of a union
+    UType,
+    /// `bool`
+    Bool,
+    /// `byte`
+    Byte,
+    /// `ubyte`
+    UByte,
+    /// `short`
+    Short,
+    /// `ushort`
+    UShort,
+    /// `int`
+    Int,
+    /// `uint`
+    UInt,
+    /// `long`
+    Long,
+    /// `ulong`
+    ULong,
+    /// `float`
+    Float,
+    /// `double`
+    Double,
+    /// `string`
+    String,
+    /// A vector (`[T]`)
+    Vector,
+    /// A table or a struct
+    Obj,
+    /// A union
+    Union,
+    /// A fixed-length array of a struct (`[T:N]`)
+    Array,
+    /// A vector with 64-bit offsets
+    Vector64,
+}
+
+impl BaseType {
+    fn from_byte(value: i8) -> Result<BaseType> {
+        const BASE_TYPES: &[BaseType] = &[
+            BaseType::None,
+            BaseType::UType,
+            BaseType::Bool,
+            BaseType::Byte,
+            BaseType::UByte,
+            BaseType::Short,
+            BaseType::UShort,
+            BaseType::Int,
+            BaseType::UInt,
+            BaseType::Long,
+            BaseType::ULong,
+            BaseType::Float,
+            BaseType::Double,
+            BaseType::String,
+            BaseType::Vector,
+            BaseType::Obj,
+            BaseType::Union,
+            BaseType::Array,
+            BaseType::Vector64,
+        ];
+        usize::try_from(value)
+            .ok()
+            .and_then(|index| BASE_TYPES.get(index))
+            .copied()
+            .ok_or_else(|| invalid(format!("unknown base type {}", value)))
+    }
+
+    /// The name of the type in the schemas
+    fn name(self) -> &'static str {
+        match self {
+            BaseType::None => "none",
+            BaseType::UType => "utype",
+            BaseType::Bool => "bool",
+            BaseType::Byte => "byte",
+            BaseType::UByte => "ubyte",
+            BaseType::Short => "short",
+            BaseType::UShort => "ushort",
+            BaseType::Int => "int",
+            BaseType::UInt => "uint",
+            BaseType::Long => "long",
+            BaseType::ULong => "ulong",
+            BaseType::Float => "float",
+            BaseType::Double => "double",
+            BaseType::String => "string",
+            BaseType::Vector => "vector",
+            BaseType::Obj => "object",
+            BaseType::Union => "union",
+            BaseType::Array => "array",
+            BaseType::Vector64 => "vector64",
+        }
+    }
+
+    /// Whether the type is a scalar (the union type fields included)
+    pub fn is_scalar(self) -> bool {
+        matches!(
+            self,
+            BaseType::UType
+                | BaseType::Bool
+                | BaseType::Byte
+                | BaseType::UByte
+                | BaseType::Short
+                | BaseType::UShort
+                | BaseType::Int
+                | BaseType::UInt
+                | BaseType::Long
+                | BaseType::ULong
+                | BaseType::Float
+                | BaseType::Double
+        )
+    }
+}
+
+/// A resolved type (`reflection.Type`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Type {
+    /// The base type
+    pub base_type: BaseType,
+    /// The element type of the vectors and the arrays
+    pub element: BaseType,
+    /// The index into [`Schema::objects`] (for the objects) or [`Schema::enums`] (for the
+    /// unions and the enum-typed scalars), -1 if none
+    ///
+    /// [`Schema::objects`]: struct.Schema.html#structfield.objects
+    /// [`Schema::enums`]: struct.Schema.html#structfield.enums
+    pub index: i32,
+    /// The length of the arrays
+    pub fixed_length: u16,
+    /// The size of the type in bytes
+    pub base_size: u32,
+    /// The size of the element of the vectors and the arrays in bytes
+    pub element_size: u32,
+}
+
+/// A field of an object (`reflection.Field`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    /// The field name
+    pub name: String,
+    /// The field type
+    pub field_type: Type,
+    /// The field id
+    pub id: u16,
+    /// The offset of the field in the vtable (for the tables) or in the struct
+    pub offset: u16,
+    /// The default value of the integer fields
+    pub default_integer: i64,
+    /// The default value of the floating-point fields
+    pub default_real: f64,
+    /// Whether the field is deprecated
+    pub deprecated: bool,
+    /// Whether the field is required
+    pub required: bool,
+    /// Whether the field is the key of the table
+    pub key: bool,
+    /// Whether the scalar field is optional (`= null`)
+    pub optional: bool,
+    /// The padding of the struct field in bytes
+    pub padding: u16,
+    /// Whether the field uses 64-bit offsets
+    pub offset64: bool,
+    /// The attributes
+    pub attributes: Vec<Attribute>,
+    /// The documentation comment lines
+    pub documentation: Vec<String>,
+}
+
+/// A table or a struct (`reflection.Object`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    /// The name qualified with the namespace
+    pub name: String,
+    /// The fields sorted by name
+    pub fields: Vec<Field>,
+    /// Whether the object is a struct
+    pub is_struct: bool,
+    /// The alignment in bytes
+    pub minalign: i32,
+    /// The size of the struct in bytes
+    pub bytesize: i32,
+    /// The attributes
+    pub attributes: Vec<Attribute>,
+    /// The documentation comment lines
+    pub documentation: Vec<String>,
+    /// The schema file declaring the object (with `--bfbs-filenames`)
+    pub declaration_file: Option<String>,
+}
+
+/// A value of an enum or a union (`reflection.EnumVal`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumVal {
+    /// The value name
+    pub name: String,
+    /// The value
+    pub value: i64,
+    /// The member type of the unions
+    pub union_type: Option<Type>,
+    /// The attributes
+    pub attributes: Vec<Attribute>,
+    /// The documentation comment lines
+    pub documentation: Vec<String>,
+}
+
+/// An enum or a union (`reflection.Enum`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum {
+    /// The name qualified with the namespace
+    pub name: String,
+    /// The values in the order of the values
+    pub values: Vec<EnumVal>,
+    /// Whether it is a union
+    pub is_union: bool,
+    /// The underlying integer type
+    pub underlying_type: Type,
+    /// The attributes
+    pub attributes: Vec<Attribute>,
+    /// The documentation comment lines
+    pub documentation: Vec<String>,
+    /// The schema file declaring the enum (with `--bfbs-filenames`)
+    pub declaration_file: Option<String>,
+}
+
+/// A method of an RPC service (`reflection.RPCCall`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcCall {
+    /// The method name
+    pub name: String,
+    /// The name of the request table
+    pub request: String,
+    /// The name of the response table
+    pub response: String,
+    /// The attributes
+    pub attributes: Vec<Attribute>,
+    /// The documentation comment lines
+    pub documentation: Vec<String>,
+}
+
+/// An RPC service (`reflection.Service`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Service {
+    /// The name qualified with the namespace
+    pub name: String,
+    /// The methods
+    pub calls: Vec<RpcCall>,
+    /// The attributes
+    pub attributes: Vec<Attribute>,
+    /// The documentation comment lines
+    pub documentation: Vec<String>,
+    /// The schema file declaring the service (with `--bfbs-filenames`)
+    pub declaration_file: Option<String>,
+}
+
+/// A schema file with its includes (`reflection.SchemaFile`, with `--bfbs-filenames`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaFile {
+    /// The file name relative to the `--bfbs-filenames` directory
+    pub filename: String,
+    /// The included files
+    pub included_filenames: Vec<String>,
+}
+
+/// A loaded binary schema (`reflection.Schema`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    /// The tables and the structs sorted by name
+    pub objects: Vec<Object>,
+    /// The enums and the unions sorted by name
+    pub enums: Vec<Enum>,
+    /// The `file_identifier`
+    pub file_ident: Option<String>,
+    /// The `file_extension`
+    pub file_ext: Option<String>,
+    /// The name of the `root_type` object
+    pub root_table: Option<String>,
+    /// The RPC services sorted by name
+    pub services: Vec<Service>,
+    /// The advanced features used by the schema (`reflection.AdvancedFeatures` bit flags)
+    pub advanced_features: u64,
+    /// The schema files (with `--bfbs-filenames`)
+    pub fbs_files: Vec<SchemaFile>,
+}
+
+impl Schema {
+    /// Load the binary schema (the `BFBS` file identifier is checked if present)
+    pub fn parse(bytes: &[u8]) -> Result<Schema> {
+        if bytes.get(4..8).is_some_and(|identifier| {
+            identifier != FILE_IDENTIFIER && identifier.iter().all(u8::is_ascii_alphanumeric)
+        }) {
+            return Err(invalid(
+                "not a binary schema (unexpected file identifier)".into(),
+            ));
+        }
+        let root = Table::at(bytes, indirect(bytes, 0)?)?;
+        Ok(Schema {
+            objects: root.tables(0)?.iter().map(object).collect::<Result<_>>()?,
+            enums: root
+                .tables(1)?
+                .iter()
+                .map(enumeration)
+                .collect::<Result<_>>()?,
+            file_ident: root.string(2)?,
+            file_ext: root.string(3)?,
+            root_table: root
+                .table(4)?
+                .map(|root_table| root_table.required_string(0, "Object.name"))
+                .transpose()?,
+            services: root.tables(5)?.iter().map(service).collect::<Result<_>>()?,
+            advanced_features: root.scalar(6, 0u64)?,
+            fbs_files: root
+                .tables(7)?
+                .iter()
+                .map(|file| {
+                    Ok(SchemaFile {
+                        filename: file.required_string(0, "SchemaFile.filename")?,
+                        included_filenames: file.strings(1)?,
+                    })
+                })
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Read and load the binary schema file
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Schema> {
+        let path = path.as_ref();
+        Schema::parse(&fs::read(path)?)
+            .map_err(|e| Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+    }
+
+    /// The object with the qualified name
+    pub fn object(&self, name: &str) -> Option<&Object> {
+        self.objects.iter().find(|object| object.name == name)
+    }
+
+    /// The enum or the union with the qualified name
+    pub fn enumeration(&self, name: &str) -> Option<&Enum> {
+        self.enums
+            .iter()
+            .find(|enumeration| enumeration.name == name)
+    }
+
+    /// The object of the `root_type`
+    pub fn root_object(&self) -> Option<&Object> {
+        self.root_table
+            .as_deref()
+            .and_then(|root_table| self.object(root_table))
+    }
+
+    /// The object the type refers to (for `Obj` types, and the vectors and arrays of them)
+    pub fn object_of(&self, type_: &Type) -> Option<&Object> {
+        if type_.base_type == BaseType::Obj || type_.element == BaseType::Obj {
+            usize::try_from(type_.index)
+                .ok()
+                .and_then(|index| self.objects.get(index))
+        } else {
+            None
+        }
+    }
+
+    /// The enum the type refers to (for the unions and the enum-typed scalars, and the vectors
+    /// and arrays of them)
+    pub fn enumeration_of(&self, type_: &Type) -> Option<&Enum> {
+        if type_.base_type == BaseType::Obj || type_.element == BaseType::Obj {
+            None
+        } else {
+            usize::try_from(type_.index)
+                .ok()
+                .and_then(|index| self.enums.get(index))
+        }
+    }
+
+    /// The type as written in the schemas (with the qualified names), e.g. `short`,
+    /// `Game.Color`, `[Game.Monster]` or `[ubyte:16]`
+    pub fn type_name(&self, type_: &Type) -> String {
+        match type_.base_type {
+            BaseType::Vector | BaseType::Vector64 => {
+                format!("[{}]", self.named_type(type_.element, type_))
+            }
+            BaseType::Array => format!(
+                "[{}:{}]",
+                self.named_type(type_.element, type_),
+                type_.fixed_length
+            ),
+            base_type => self.named_type(base_type, type_),
+        }
+    }
+
+    fn named_type(&self, base_type: BaseType, type_: &Type) -> String {
+        let name = match base_type {
+            BaseType::Obj => self.object_of(type_).map(|object| object.name.as_str()),
+            _ => self
+                .enumeration_of(type_)
+                .map(|enumeration| enumeration.name.as_str()),
+        };
+        match name {
+            Some(name) if base_type == BaseType::UType => format!("{}_type", name),
+            Some(name) => name.to_owned(),
+            None => base_type.name().to_owned(),
+        }
+    }
+}
+
+impl Object {
+    /// The field with the name
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// The fields in the order of their ids
+    pub fn fields_by_id(&self) -> Vec<&Field> {
+        let mut fields: Vec<&Field> = self.fields.iter().collect();
+        fields.sort_by_key(|field| field.id);
+        fields
+    }
+
+    /// The value of the attribute (`Some("")` for the attributes without values)
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        attribute(&self.attributes, name)
+    }
+}
+
+impl Field {
+    /// The value of the attribute (`Some("")` for the attributes without values)
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        attribute(&self.attributes, name)
+    }
+}
+
+impl Enum {
+    /// The value with the name
+    pub fn value(&self, name: &str) -> Option<&EnumVal> {
+        self.values.iter().find(|value| value.name == name)
+    }
+
+    /// The value of the attribute (`Some("")` for the attributes without values)
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        attribute(&self.attributes, name)
+    }
+}
+
+fn attribute<'a>(attributes: &'a [Attribute], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name == name)
+        .map(|attribute| attribute.value.as_deref().unwrap_or(""))
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("invalid binary schema: {}", message),
+    )
+}
+
+fn bytes<const N: usize>(buf: &[u8], position: usize) -> Result<[u8; N]> {
+    buf.get(position..position.saturating_add(N))
+        .map(|bytes| bytes.try_into().expect("slice of N bytes"))
+        .ok_or_else(|| invalid(format!("offset {} is out of bounds", position)))
+}
+
+fn read_u32(buf: &[u8], position: usize) -> Result<usize> {
+    Ok(u32::from_le_bytes(bytes(buf, position)?) as usize)
+}
+
+/// Follow the unsigned offset stored at the position
+fn indirect(buf: &[u8], position: usize) -> Result<usize> {
+    Ok(position.saturating_add(read_u32(buf, position)?))
+}
+
+fn string_at(buf: &[u8], position: usize) -> Result<String> {
+    let length = read_u32(buf, position)?;
+    let start = position + 4;
+    let bytes = buf
+        .get(start..start.saturating_add(length))
+        .ok_or_else(|| invalid(format!("string at {} is out of bounds", position)))?;
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| invalid(format!("string at {} is not UTF-8", position)))
+}
+
+/// A scalar stored in a table
+trait Scalar: Sized {
+    fn read(buf: &[u8], position: usize) -> Result<Self>;
+}
+
+macro_rules! scalar {
+    ($($type:ty),*) => {
+        $(impl Scalar for $type {
+            fn read(buf: &[u8], position: usize) -> Result<Self> {
+                Ok(<$type>::from_le_bytes(bytes(buf, position)?))
+            }
+        })*
+    };
+}
+
+scalar!(i8, u16, i32, u32, i64, u64, f64);
+
+impl Scalar for bool {
+    fn read(buf: &[u8], position: usize) -> Result<Self> {
+        Ok(bytes::<1>(buf, position)?[0] != 0)
+    }
+}
+
+/// A table of the buffer with its vtable
+#[derive(Clone, Copy)]
+struct Table<'b> {
+    buf: &'b [u8],
+    position: usize,
+    vtable: usize,
+    vtable_size: usize,
+}
+
+impl<'b> Table<'b> {
+    fn at(buf: &'b [u8], position: usize) -> Result<Table<'b>> {
+        let vtable_offset = i32::read(buf, position)? as isize;
+        let vtable = (position as isize)
+            .checked_sub(vtable_offset)
+            .and_then(|vtable| usize::try_from(vtable).ok())
+            .ok_or_else(|| invalid(format!("vtable of table at {} is out of bounds", position)))?;
+        Ok(Table {
+            buf,
+            position,
+            vtable,
+            vtable_size: u16::read(buf, vtable)? as usize,
+        })
+    }
+
+    /// The position of the field in the buffer (`None` if the field is absent)
+    fn field(&self, slot: usize) -> Result<Option<usize>> {
+        let entry = 4 + 2 * slot;
+        if entry + 2 > self.vtable_size {
+            return Ok(None);
+        }
+        let offset = u16::read(self.buf, self.vtable + entry)? as usize;
+        Ok(Some(self.position + offset).filter(|_| offset != 0))
+    }
+
+    fn scalar<T: Scalar>(&self, slot: usize, default: T) -> Result<T> {
+        match self.field(slot)? {
+            Some(position) => T::read(self.buf, position),
+            None => Ok(default),
+        }
+    }
+
+    fn string(&self, slot: usize) -> Result<Option<String>> {
+        self.field(slot)?
+            .map(|position| string_at(self.buf, indirect(self.buf, position)?))
+            .transpose()
+    }
+
+    fn required_string(&self, slot: usize, name: &str) -> Result<String> {
+        self.string(slot)?
+            .ok_or_else(|| invalid(format!("required {} is missing", name)))
+    }
+
+    fn table(&self, slot: usize) -> Result<Option<Table<'b>>> {
+        self.field(slot)?
+            .map(|position| Table::at(self.buf, indirect(self.buf, position)?))
+            .transpose()
+    }
+
+    fn required_table(&self, slot: usize, name: &str) -> Result<Table<'b>> {
+        self.table(slot)?
+            .ok_or_else(|| invalid(format!("required {} is missing", name)))
+    }
+
+    /// The positions of the offsets stored in the vector (empty if the vector is absent)
+    fn offsets(&self, slot: usize) -> Result<Vec<usize>> {
+        let Some(position) = self.field(slot)? else {
+            return Ok(Vec::new());
+        };
+        let vector = indirect(self.buf, position)?;
+        let length = read_u32(self.buf, vector)?;
+        if length > self.buf.len() / 4 {
+            return Err(invalid(format!("vector at {} is out of bounds", vector)));
+        }
+        (0..length)
+            .map(|index| indirect(self.buf, vector + 4 + 4 * index))
+            .collect()
+    }
+
+    fn tables(&self, slot: usize) -> Result<Vec<Table<'b>>> {
+        self.offsets(slot)?
+            .into_iter()
+            .map(|position| Table::at(self.buf, position))
+            .collect()
+    }
+
+    fn strings(&self, slot: usize) -> Result<Vec<String>> {
+        self.offsets(slot)?
+            .into_iter()
+            .map(|position| string_at(self.buf, position))
+            .collect()
+    }
+
+    fn attributes(&self, slot: usize) -> Result<Vec<Attribute>> {
+        self.tables(slot)?
+            .iter()
+            .map(|key_value| {
+                Ok(Attribute {
+                    name: key_value.required_string(0, "KeyValue.key")?,
+                    value: key_value.string(1)?,
+                })
+            })
+            .collect()
+    }
+}
+
+fn type_(table: Table) -> Result<Type> {
+    Ok(Type {
+        base_type: BaseType::from_byte(table.scalar(0, 0i8)?)?,
+        element: BaseType::from_byte(table.scalar(1, 0i8)?)?,
+        index: table.scalar(2, -1i32)?,
+        fixed_length: table.scalar(3, 0u16)?,
+        base_size: table.scalar(4, 4u32)?,
+        element_size: table.scalar(5, 0u32)?,
+    })
+}
+
+fn field(table: &Table) -> Result<Field> {
+    Ok(Field {
+        name: table.required_string(0, "Field.name")?,
+        field_type: type_(table.required_table(1, "Field.type")?)?,
+        id: table.scalar(2, 0u16)?,
+        offset: table.scalar(3, 0u16)?,
+        default_integer: table.scalar(4, 0i64)?,
+        default_real: table.scalar(5, 0f64)?,
+        deprecated: table.scalar(6, false)?,
+        required: table.scalar(7, false)?,
+        key: table.scalar(8, false)?,
+        attributes: table.attributes(9)?,
+        documentation: table.strings(10)?,
+        optional: table.scalar(11, false)?,
+        padding: table.scalar(12, 0u16)?,
+        offset64: table.scalar(13, false)?,
+    })
+}
+
+fn object(table: &Table) -> Result<Object> {
+    Ok(Object {
+        name: table.required_string(0, "Object.name")?,
+        fields: table.tables(1)?.iter().map(field).collect::<Result<_>>()?,
+        is_struct: table.scalar(2, false)?,
+        minalign: table.scalar(3, 0i32)?,
+        bytesize: table.scalar(4, 0i32)?,
+        attributes: table.attributes(5)?,
+        documentation: table.strings(6)?,
+        declaration_file: table.string(7)?,
+    })
+}
+
+fn enumeration(table: &Table) -> Result<Enum> {
+    Ok(Enum {
+        name: table.required_string(0, "Enum.name")?,
+        values: table
+            .tables(1)?
+            .iter()
+            .map(|value| {
+                Ok(EnumVal {
+                    name: value.required_string(0, "EnumVal.name")?,
+                    value: value.scalar(1, 0i64)?,
+                    union_type: value.table(3)?.map(type_).transpose()?,
+                    documentation: value.strings(4)?,
+                    attributes: value.attributes(5)?,
+                })
+            })
+            .collect::<Result<_>>()?,
+        is_union: table.scalar(2, false)?,
+        underlying_type: type_(table.required_table(3, "Enum.underlying_type")?)?,
+        attributes: table.attributes(4)?,
+        documentation: table.strings(5)?,
+        declaration_file: table.string(6)?,
+    })
+}
+
+fn service(table: &Table) -> Result<Service> {
+    Ok(Service {
+        name: table.required_string(0, "Service.name")?,
+        calls: table
+            .tables(1)?
+            .iter()
+            .map(|call| {
+                Ok(RpcCall {
+                    name: call.required_string(0, "RPCCall.name")?,
+                    request: call
+                        .required_table(1, "RPCCall.request")?
+                        .required_string(0, "Object.name")?,
+                    response: call
+                        .required_table(2, "RPCCall.response")?
+                        .required_string(0, "Object.name")?,
+                    attributes: call.attributes(3)?,
+                    documentation: call.strings(4)?,
+                })
+            })
+            .collect::<Result<_>>()?,
+        attributes: table.attributes(2)?,
+        documentation: table.strings(3)?,
+        declaration_file: table.string(4)?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{run, Args};
+
+    #[test]
+    fn schema_describes_compiled_objects() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("monster.fbs");
+        fs::write(
+            &input_path,
+            "namespace Game;\n\
+             enum Color : ubyte { Red, Green = 2 }\n\
+             struct Vec3 { x: float; y: float; z: float; }\n\
+             /// A monster\n\
+             table Monster {\n\
+               name: string (required);\n\
+               hp: short = 100 (deprecated);\n\
+               pos: Vec3;\n\
+               color: Color = Green;\n\
+               friends: [Monster];\n\
+             }\n\
+             rpc_service Arena { Fight(Monster): Monster; }\n\
+             root_type Monster;\n\
+             file_identifier \"MONS\";\n",
+        )?;
+
+        run(Args {
+            inputs: &[&input_path],
+            out_dir: temp_dir.path(),
+            extra: &["--bfbs-comments"],
+            ..Args::bfbs_only()
+        })?;
+        let schema = Schema::from_path(temp_dir.path().join("monster.bfbs"))?;
+
+        assert_eq!(schema.file_ident.as_deref(), Some("MONS"));
+        assert_eq!(schema.services[0].name, "Game.Arena");
+        assert_eq!(schema.services[0].calls[0].request, "Game.Monster");
+
+        let monster = schema.root_object().expect("root table");
+        assert_eq!(monster.name, "Game.Monster");
+        assert!(!monster.is_struct);
+        assert_eq!(monster.documentation, vec![" A monster"]);
+        let ids: Vec<_> = monster
+            .fields_by_id()
+            .iter()
+            .map(|field| (field.name.as_str(), field.id))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                ("name", 0),
+                ("hp", 1),
+                ("pos", 2),
+                ("color", 3),
+                ("friends", 4)
+            ]
+        );
+
+        let name = monster.field("name").expect("name");
+        assert!(name.required);
+        assert_eq!(name.field_type.base_type, BaseType::String);
+        assert!(monster.field("hp").expect("hp").deprecated);
+        assert_eq!(monster.field("hp").expect("hp").default_integer, 100);
+
+        let pos = monster.field("pos").expect("pos");
+        let vec3 = schema.object_of(&pos.field_type).expect("Vec3");
+        assert!(vec3.is_struct);
+        assert_eq!(vec3.bytesize, 12);
+
+        let color = monster.field("color").expect("color");
+        let color_enum = schema.enumeration_of(&color.field_type).expect("Color");
+        assert_eq!(color_enum.name, "Game.Color");
+        assert_eq!(color_enum.value("Green").expect("Green").value, 2);
+        assert_eq!(color.default_integer, 2);
+
+        let friends = monster.field("friends").expect("friends");
+        assert_eq!(friends.field_type.base_type, BaseType::Vector);
+        assert_eq!(schema.type_name(&friends.field_type), "[Game.Monster]");
+        assert_eq!(schema.type_name(&color.field_type), "Game.Color");
+        assert_eq!(
+            schema
+                .object_of(&friends.field_type)
+                .map(|object| &object.name),
+            Some(&monster.name)
+        );
+
+        let error = Schema::parse(b"\x08\x00\x00\x00MONS").unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(Schema::parse(b"\xff\x00\x00\x00").is_err());
+
+        Ok(())
+    }
+}
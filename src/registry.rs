@@ -0,0 +1,346 @@
+//! Fetching of the schemas from a remote registry (requires `registry` feature).
+//!
+//! The schemas are downloaded over HTTP(S) or taken from a git ref into a cache directory, which
+//! then serves as the include directory of the compilation. Like the [`download`] of `flatc`
+//! binaries, every schema is pinned with its SHA-256 checksum:
+//!
+//! ```no_run
+//! // build.rs
+//! use std::path::Path;
+//!
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! let schemas = flatc_rust::registry::Registry::new(Path::new(&out_dir).join("contracts"))
+//!     .git(
+//!         "https://github.com/example/contracts.git",
+//!         "v1.4.0",
+//!         "events/event.fbs",
+//!         "<SHA-256 of events/event.fbs>",
+//!     )
+//!     .http(
+//!         "https://contracts.example.com/common.fbs",
+//!         "common.fbs",
+//!         "<SHA-256 of common.fbs>",
+//!     )
+//!     .fetch()
+//!     .expect("schemas");
+//!
+//! flatc_rust::run(flatc_rust::Args {
+//!     inputs: &schemas.inputs(),
+//!     includes: &[&schemas.dir],
+//!     out_dir: Path::new(&out_dir),
+//!     ..Default::default()
+//! })
+//! .expect("flatc");
+//! ```
+//!
+//! The cached schemas whose checksums match are reused without touching the network, so the
+//! builds work in the [offline mode] once the cache is populated.
+//!
+//! [`download`]: ../download/index.html
+//! [offline mode]: ../download/index.html#offline-mode
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use log::info;
+
+use crate::download::{fetch_verified, is_offline, parse_sha256, verify_file};
+use crate::report::sha256_hex;
+use crate::{err_other, Result};
+
+/// Where a schema is fetched from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// An HTTP(S) (or any other `curl`-supported) URL
+    Http {
+        /// The URL of the schema
+        url: String,
+    },
+    /// A file of a git repository at a ref
+    Git {
+        /// The repository URL (anything `git fetch` accepts)
+        repository: String,
+        /// The branch, the tag or the commit
+        reference: String,
+        /// The path of the schema in the repository
+        path: String,
+    },
+}
+
+/// A pinned remote schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteSchema {
+    /// Where the schema is fetched from
+    pub source: Source,
+    /// The path of the schema inside the cache directory (the includes of the schemas are
+    /// resolved against the cache directory, so keep the layout they expect)
+    pub file_name: PathBuf,
+    /// The hex-encoded SHA-256 checksum of the schema
+    pub sha256: String,
+}
+
+/// The schemas fetched into the cache directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchedSchemas {
+    /// The cache directory (to be used as an include directory)
+    pub dir: PathBuf,
+    /// The fetched schemas in the order of declaration
+    pub files: Vec<PathBuf>,
+}
+
+impl FetchedSchemas {
+    /// The fetched schemas as [`Args::inputs`]
+    ///
+    /// [`Args::inputs`]: ../struct.Args.html#structfield.inputs
+    pub fn inputs(&self) -> Vec<&Path> {
+        self.files.iter().map(PathBuf::as_path).collect()
+    }
+}
+
+/// The set of pinned remote schemas and their cache directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Registry {
+    cache_dir: PathBuf,
+    schemas: Vec<RemoteSchema>,
+}
+
+impl Registry {
+    /// A registry caching the schemas in the directory (e.g. inside `OUT_DIR`)
+    pub fn new<P: Into<PathBuf>>(cache_dir: P) -> Registry {
+        Registry {
+            cache_dir: cache_dir.into(),
+            schemas: Vec::new(),
+        }
+    }
+
+    /// Add the schema
+    pub fn schema(mut self, schema: RemoteSchema) -> Registry {
+        self.schemas.push(schema);
+        self
+    }
+
+    /// Add the schema downloaded from the URL into `file_name` inside the cache directory
+    pub fn http<P: Into<PathBuf>>(self, url: &str, file_name: P, sha256: &str) -> Registry {
+        self.schema(RemoteSchema {
+            source: Source::Http { url: url.into() },
+            file_name: file_name.into(),
+            sha256: sha256.into(),
+        })
+    }
+
+    /// Add the schema at `path` of the git repository at the ref (it keeps its path inside the
+    /// cache directory)
+    pub fn git(self, repository: &str, reference: &str, path: &str, sha256: &str) -> Registry {
+        self.schema(RemoteSchema {
+            source: Source::Git {
+                repository: repository.into(),
+                reference: reference.into(),
+                path: path.into(),
+            },
+            file_name: path.into(),
+            sha256: sha256.into(),
+        })
+    }
+
+    /// Fetch the schemas which are not cached yet (or whose cached checksums do not match)
+    pub fn fetch(&self) -> Result<FetchedSchemas> {
+        let mut files = Vec::new();
+        for schema in &self.schemas {
+            let destination = self.cache_dir.join(&schema.file_name);
+            if schema.file_name.is_absolute()
+                || schema
+                    .file_name
+                    .components()
+                    .any(|component| component == std::path::Component::ParentDir)
+            {
+                return Err(err_other(format!(
+                    "schema file name {} is outside of the cache directory",
+                    schema.file_name.display()
+                )));
+            }
+            let sha256 = parse_sha256(&schema.sha256)?;
+            if !(destination.is_file() && verify_file(&destination, &sha256).is_ok()) {
+                if let Some(parent) = destination.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                match &schema.source {
+                    Source::Http { url } => fetch_verified(url, &destination, &sha256)?,
+                    Source::Git {
+                        repository,
+                        reference,
+                        path,
+                    } => self.fetch_git(repository, reference, path, &destination, &sha256)?,
+                }
+            }
+            files.push(destination);
+        }
+        Ok(FetchedSchemas {
+            dir: self.cache_dir.clone(),
+            files,
+        })
+    }
+
+    /// Fetch the ref into a bare repository inside the cache directory (shared by the schemas of
+    /// the same repository) and extract the file if its checksum matches
+    fn fetch_git(
+        &self,
+        repository: &str,
+        reference: &str,
+        path: &str,
+        destination: &Path,
+        sha256: &str,
+    ) -> Result<()> {
+        if is_offline() {
+            return Err(err_other(format!(
+                "refusing to fetch {} from {} in the offline mode",
+                reference, repository
+            )));
+        }
+        let git_dir = self
+            .cache_dir
+            .join(format!(".git-{}", &sha256_hex(repository.as_bytes())[..16]));
+        if !git_dir.is_dir() {
+            git(&[
+                "init".into(),
+                "--quiet".into(),
+                "--bare".into(),
+                git_dir.clone().into(),
+            ])?;
+        }
+        let mut git_dir_arg = OsString::from("--git-dir=");
+        git_dir_arg.push(&git_dir);
+        git(&[
+            git_dir_arg.clone(),
+            "fetch".into(),
+            "--quiet".into(),
+            "--depth=1".into(),
+            repository.into(),
+            reference.into(),
+        ])?;
+        let content = git(&[
+            git_dir_arg,
+            "show".into(),
+            format!("FETCH_HEAD:{}", path).into(),
+        ])?;
+
+        let actual = sha256_hex(&content);
+        if actual != sha256 {
+            return Err(err_other(format!(
+                "checksum mismatch for {} at {} of {}: expected SHA-256 {}, got {}",
+                path, reference, repository, sha256, actual
+            )));
+        }
+        let mut partial = OsString::from(destination.as_os_str());
+        partial.push(".part");
+        fs::write(&partial, content)?;
+        fs::rename(&partial, destination)
+    }
+}
+
+/// Run `git` returning its stdout
+fn git(args: &[OsString]) -> Result<Vec<u8>> {
+    let mut cmd = process::Command::new("git");
+    cmd.args(args).stdin(process::Stdio::null());
+    info!("spawning command {:?}", cmd);
+    let output = cmd
+        .output()
+        .map_err(|e| err_other(format!("failed to spawn `{:?}`: {}", cmd, e)))?;
+    if !output.status.success() {
+        return Err(err_other(format!(
+            "`{:?}` failed: {}",
+            cmd,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::download::set_offline;
+
+    const SCHEMA: &str = "table Event { name: string; } root_type Event;\n";
+
+    fn run_git(repository: &Path, args: &[&str]) -> Result<()> {
+        let mut cmd_args: Vec<OsString> = vec!["-C".into(), repository.into()];
+        cmd_args.extend(args.iter().map(OsString::from));
+        git(&cmd_args).map(drop)
+    }
+
+    #[test]
+    fn fetch_pins_checksums_and_reuses_cache() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let repository = temp_dir.path().join("contracts");
+        fs::create_dir_all(repository.join("events"))?;
+        fs::write(repository.join("events/event.fbs"), SCHEMA)?;
+        run_git(&repository, &["init", "--quiet"])?;
+        run_git(&repository, &["add", "."])?;
+        run_git(
+            &repository,
+            &[
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                "schemas",
+            ],
+        )?;
+        run_git(&repository, &["tag", "v1"])?;
+        let http_source = temp_dir.path().join("common.fbs");
+        fs::write(&http_source, SCHEMA)?;
+
+        let sha256 = sha256_hex(SCHEMA.as_bytes());
+        let cache_dir = temp_dir.path().join("cache");
+        let registry = Registry::new(&cache_dir)
+            .git(
+                repository.to_str().expect("UTF-8 path"),
+                "v1",
+                "events/event.fbs",
+                &sha256,
+            )
+            .http(
+                &format!("file://{}", http_source.display()),
+                "common.fbs",
+                &sha256,
+            );
+        let fetched = registry.fetch()?;
+        assert_eq!(
+            fetched.inputs(),
+            vec![
+                cache_dir.join("events/event.fbs").as_path(),
+                cache_dir.join("common.fbs").as_path(),
+            ]
+        );
+        assert_eq!(fs::read_to_string(&fetched.files[0])?, SCHEMA);
+
+        set_offline(true);
+        let cached = registry.fetch();
+        set_offline(false);
+        assert_eq!(cached?, fetched);
+
+        let mismatch = Registry::new(temp_dir.path().join("other"))
+            .git(
+                repository.to_str().expect("UTF-8 path"),
+                "v1",
+                "events/event.fbs",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+            )
+            .fetch()
+            .unwrap_err();
+        assert!(mismatch.to_string().contains("checksum mismatch"));
+        assert!(!temp_dir.path().join("other/events/event.fbs").exists());
+
+        assert!(Registry::new(&cache_dir)
+            .http("https://example.com/a.fbs", "../a.fbs", &sha256)
+            .fetch()
+            .is_err());
+
+        Ok(())
+    }
+}
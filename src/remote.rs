@@ -0,0 +1,353 @@
+//! Running `flatc` on a remote host over SSH (`remote` feature, see [`Flatc::with_remote`]).
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use flatc_rust::remote::Remote;
+//!
+//! # fn try_main() -> flatc_rust::Result<()> {
+//! let mut remote = Remote::new("builder@flatc-farm-1");
+//! remote.ssh_args = vec!["-o".into(), "ControlMaster=auto".into()];
+//! flatc_rust::Flatc::from_path("/opt/flatbuffers-24.3.25/bin/flatc")
+//!     .with_remote(remote)
+//!     .run(flatc_rust::Args {
+//!         inputs: &[Path::new("schemas/monster.fbs")],
+//!         out_dir: Path::new("target/flatbuffers/"),
+//!         ..Default::default()
+//!     })?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Every invocation stages its files in a new temporary directory of the remote host: the
+//! arguments naming the existing files and directories (the inputs with the schemas they include
+//! from their directories, the include directories) are uploaded with `tar` piped through `ssh`,
+//! keeping their absolute paths under the staging root, so the relative includes resolve like
+//! they do locally. `flatc` runs with the staged paths, its output directory (`-o`) is fetched
+//! back into the local one when it succeeds, and the staged paths in its output are translated
+//! back to the local ones. The staging directory is removed afterwards.
+//!
+//! The executable is the path of `flatc` on the remote host. Every invocation opens a few SSH
+//! connections, so consider sharing one with `ControlMaster`. Both hosts need `tar` and a POSIX
+//! shell (the remote commands are shell-escaped).
+//!
+//! [`Flatc::with_remote`]: ../struct.Flatc.html#method.with_remote
+
+use std::collections::BTreeSet;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::{command_line, err_other, schema};
+
+/// The SSH destination running `flatc`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Remote {
+    /// The destination, e.g. `builder@flatc-farm-1`
+    pub host: String,
+    /// The `ssh` executable (`ssh` from `$PATH` by default)
+    pub ssh: PathBuf,
+    /// The options passed to every `ssh` call, e.g. `-p`, `2222`
+    pub ssh_args: Vec<OsString>,
+}
+
+impl Remote {
+    /// The destination with the default `ssh` options
+    pub fn new<H: Into<String>>(host: H) -> Remote {
+        Remote {
+            host: host.into(),
+            ssh: PathBuf::from("ssh"),
+            ssh_args: Vec::new(),
+        }
+    }
+
+    /// The `ssh` command running the shell command on the host
+    pub(crate) fn command(&self, remote_command: &str) -> process::Command {
+        let mut cmd = process::Command::new(&self.ssh);
+        cmd.args(&self.ssh_args).arg(&self.host).arg(remote_command);
+        cmd
+    }
+
+    /// The `ssh` command running `flatc` with the args on the host
+    pub(crate) fn flatc_command<I, S>(&self, exec: &Path, args: I) -> process::Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let args: Vec<OsString> = args.into_iter().map(|arg| arg.as_ref().into()).collect();
+        let remote_command = command_line::render(
+            std::iter::once(exec.as_os_str()).chain(args.iter().map(OsString::as_os_str)),
+        );
+        self.command(&remote_command)
+    }
+
+    fn check_status(&self, status: process::ExitStatus, action: &str) -> io::Result<()> {
+        if status.success() {
+            return Ok(());
+        }
+        Err(err_other(format!(
+            "failed to {} on {} ({})",
+            action, self.host, status
+        )))
+    }
+}
+
+/// The files of an invocation staged on the remote host, which are removed on drop
+pub(crate) struct Staged<'a> {
+    remote: &'a Remote,
+    /// The remote directory the local absolute paths are staged under
+    root: String,
+    /// The args with the local paths replaced by the staged ones
+    pub(crate) args: Vec<OsString>,
+    /// The local output directories with their staged paths
+    out_dirs: Vec<(PathBuf, String)>,
+}
+
+impl<'a> Staged<'a> {
+    /// Upload the files and the directories the args name into a new staging directory
+    pub(crate) fn upload(remote: &'a Remote, args: &[OsString]) -> io::Result<Staged<'a>> {
+        let output = remote
+            .command("mktemp -d")
+            .stdin(process::Stdio::null())
+            .stderr(process::Stdio::inherit())
+            .output()?;
+        remote.check_status(output.status, "create the staging directory")?;
+        let dir = String::from_utf8(output.stdout).map_err(err_other)?;
+        let mut staged = Staged {
+            remote,
+            root: format!("{}/root", dir.trim()),
+            args: Vec::with_capacity(args.len()),
+            out_dirs: Vec::new(),
+        };
+
+        let mut uploads = BTreeSet::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            staged.args.push(arg.clone());
+            if arg == "-o" {
+                if let Some(out_dir) = args.next() {
+                    let out_dir = std::path::absolute(out_dir)?;
+                    let staged_out_dir = staged.staged_path(&out_dir)?;
+                    staged.args.push(staged_out_dir.clone().into());
+                    staged.out_dirs.push((out_dir, staged_out_dir));
+                }
+                continue;
+            }
+            let path = Path::new(arg);
+            if !path.exists() || arg.to_str().is_some_and(|arg| arg.starts_with('-')) {
+                continue;
+            }
+            let path = std::path::absolute(path)?;
+            *staged.args.last_mut().expect("the arg") = staged.staged_path(&path)?.into();
+            if path.is_file() {
+                add_with_includes(&mut uploads, path);
+            } else {
+                uploads.insert(path);
+            }
+        }
+        staged.upload_paths(&uploads)?;
+        Ok(staged)
+    }
+
+    /// Fetch the output directories if `flatc` has succeeded, and translate the staged paths in
+    /// its output back to the local ones
+    pub(crate) fn download(&self, output: &mut process::Output) -> io::Result<()> {
+        for stream in [&mut output.stdout, &mut output.stderr] {
+            if let Ok(text) = std::str::from_utf8(stream) {
+                *stream = text.replace(&self.root, "").into_bytes();
+            }
+        }
+        if !output.status.success() {
+            return Ok(());
+        }
+        for (out_dir, staged_out_dir) in &self.out_dirs {
+            fs::create_dir_all(out_dir)?;
+            let mut remote_tar = self
+                .remote
+                .command(&command_line::render(
+                    ["tar", "-cf", "-", "-C", staged_out_dir, "."].map(OsStr::new),
+                ))
+                .stdin(process::Stdio::null())
+                .stdout(process::Stdio::piped())
+                .spawn()?;
+            let status = process::Command::new("tar")
+                .args(["-xf", "-", "-C"])
+                .arg(out_dir)
+                .stdin(remote_tar.stdout.take().expect("piped stdout"))
+                .status()?;
+            self.remote
+                .check_status(remote_tar.wait()?, "fetch the generated files")?;
+            if !status.success() {
+                return Err(err_other(format!(
+                    "failed to extract the generated files into {} ({})",
+                    out_dir.display(),
+                    status
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The staged path of the local absolute path
+    fn staged_path(&self, path: &Path) -> io::Result<String> {
+        let path = path
+            .to_str()
+            .ok_or_else(|| err_other(format!("{} is not UTF-8", path.display())))?;
+        Ok(format!("{}{}", self.root, path))
+    }
+
+    /// Upload the local absolute paths, and create the staged output directories
+    fn upload_paths(&self, paths: &BTreeSet<PathBuf>) -> io::Result<()> {
+        let mut remote_command = command_line::render(
+            ["mkdir", "-p", &self.root]
+                .iter()
+                .copied()
+                .chain(self.out_dirs.iter().map(|(_, dir)| dir.as_str())),
+        );
+        if paths.is_empty() {
+            let status = self
+                .remote
+                .command(&remote_command)
+                .stdin(process::Stdio::null())
+                .status()?;
+            return self.remote.check_status(status, "stage the inputs");
+        }
+        remote_command.push_str(" && ");
+        remote_command.push_str(&command_line::render(
+            ["tar", "-xf", "-", "-C", &self.root].map(OsStr::new),
+        ));
+
+        let mut local_tar = process::Command::new("tar")
+            .args(["-cf", "-", "-C", "/"])
+            .args(
+                paths
+                    .iter()
+                    .map(|path| path.strip_prefix("/").unwrap_or(path)),
+            )
+            .stdout(process::Stdio::piped())
+            .spawn()?;
+        let status = self
+            .remote
+            .command(&remote_command)
+            .stdin(local_tar.stdout.take().expect("piped stdout"))
+            .status()?;
+        let tar_status = local_tar.wait()?;
+        if !tar_status.success() {
+            return Err(err_other(format!(
+                "failed to archive the inputs ({})",
+                tar_status
+            )));
+        }
+        self.remote.check_status(status, "stage the inputs")
+    }
+}
+
+impl Drop for Staged<'_> {
+    fn drop(&mut self) {
+        let dir = self.root.trim_end_matches("/root");
+        let status = self
+            .remote
+            .command(&command_line::render(["rm", "-rf", dir].map(OsStr::new)))
+            .stdin(process::Stdio::null())
+            .status();
+        if !status.as_ref().is_ok_and(process::ExitStatus::success) {
+            log::warn!(
+                "failed to remove the staging directory {} on {}",
+                dir,
+                self.remote.host
+            );
+        }
+    }
+}
+
+/// Add the file with the schemas it includes relative to its directory (transitively)
+fn add_with_includes(uploads: &mut BTreeSet<PathBuf>, path: PathBuf) {
+    if uploads.contains(&path) {
+        return;
+    }
+    let includes = if path.extension().is_some_and(|extension| extension == "fbs") {
+        schema::Schema::from_path(&path)
+            .map(|schema| schema.includes)
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let dir = path.parent().map(Path::to_owned);
+    uploads.insert(path);
+    for include in includes {
+        let Some(ref dir) = dir else {
+            continue;
+        };
+        // The includes found in the include directories are staged with them
+        if let Ok(included) = std::path::absolute(dir.join(include)) {
+            if included.is_file() {
+                add_with_includes(uploads, included);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+    use crate::{Args, Flatc};
+
+    #[test]
+    fn remote_flatc_compiles_staged_inputs() -> crate::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        // The "remote host" is the local one, running the commands in a fresh directory
+        let ssh = temp_dir.path().join("ssh");
+        fs::write(&ssh, "#!/bin/sh\nshift\ncd /\nexec sh -c \"$*\"\n")?;
+        fs::set_permissions(&ssh, fs::Permissions::from_mode(0o755))?;
+        let mut remote = Remote::new("flatc-farm");
+        remote.ssh = ssh;
+
+        let schemas_dir = temp_dir.path().join("schemas");
+        fs::create_dir_all(schemas_dir.join("common"))?;
+        fs::write(
+            schemas_dir.join("monster.fbs"),
+            "include \"common/weapon.fbs\";\ntable Monster { weapon: Weapon; }",
+        )?;
+        fs::write(
+            schemas_dir.join("common/weapon.fbs"),
+            "table Weapon { damage: int; }",
+        )?;
+        let out_dir = temp_dir.path().join("out");
+        let args = Args {
+            inputs: &[&schemas_dir.join("monster.fbs")],
+            out_dir: &out_dir,
+            ..Default::default()
+        };
+
+        let flatc = Flatc::from_path("flatc").with_remote(remote.clone());
+        assert_eq!(flatc.version()?, Flatc::from_env_path().version()?);
+        flatc.run(args)?;
+        assert!(out_dir.join("monster_generated.rs").is_file());
+
+        fs::write(schemas_dir.join("bad.fbs"), "table Bad { a: int }")?;
+        let error = flatc
+            .run(Args {
+                inputs: &[&schemas_dir.join("bad.fbs")],
+                invocation: crate::Invocation::PerFile,
+                quiet: true,
+                ..args
+            })
+            .unwrap_err();
+        // The diagnostics name the local path
+        assert!(error
+            .to_string()
+            .contains(&format!("{}:1", schemas_dir.join("bad.fbs").display())));
+
+        let staged = Staged::upload(&remote, &[OsString::from("--version")])?;
+        let staging_dir = PathBuf::from(staged.root.trim_end_matches("/root"));
+        assert!(staging_dir.is_dir());
+        drop(staged);
+        assert!(!staging_dir.exists());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+//! Machine-readable reports about `flatc` runs.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+use crate::Result;
+
+/// The default file name used by [`Report::write_to_dir`].
+///
+/// [`Report::write_to_dir`]: struct.Report.html#method.write_to_dir
+pub const REPORT_FILE_NAME: &str = "flatc-report.json";
+
+/// The outcome of a successful `flatc` run (see [`Flatc::run_with_output`])
+///
+/// With [`Invocation::PerFile`], the outputs of the invocations are concatenated in the order of
+/// the inputs.
+///
+/// [`Flatc::run_with_output`]: struct.Flatc.html#method.run_with_output
+/// [`Invocation::PerFile`]: enum.Invocation.html#variant.PerFile
+#[derive(Debug, Clone)]
+pub struct RunOutput {
+    /// The exit status of `flatc` (of the last invocation)
+    pub status: ExitStatus,
+    /// The captured stdout of `flatc`
+    pub captured_stdout: Vec<u8>,
+    /// The captured stderr of `flatc` (e.g. the warnings)
+    pub captured_stderr: Vec<u8>,
+    /// Wall-clock duration of the `flatc` execution
+    pub duration: Duration,
+    /// The files created or modified in the output directory by the run
+    pub generated_files: Vec<PathBuf>,
+}
+
+/// A report about a single `flatc` run (codegen provenance)
+///
+/// Use [`run_with_report`] or [`Flatc::run_with_report`] to obtain it.
+///
+/// [`run_with_report`]: fn.run_with_report.html
+/// [`Flatc::run_with_report`]: struct.Flatc.html#method.run_with_report
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// The version reported by `flatc --version`
+    pub flatc_version: String,
+    /// The command line arguments `flatc` was executed with (the ones of the equivalent single
+    /// invocation with [`Invocation::PerFile`])
+    ///
+    /// [`Invocation::PerFile`]: enum.Invocation.html#variant.PerFile
+    pub args: Vec<String>,
+    /// The input files with their hashes
+    pub inputs: Vec<InputReport>,
+    /// The files created or modified in the output directory by the run
+    pub generated_files: Vec<PathBuf>,
+    /// Wall-clock duration of the `flatc` execution
+    pub duration: Duration,
+    /// The warnings printed by `flatc`
+    pub warnings: Vec<String>,
+    /// The size metrics of the run
+    pub metrics: Metrics,
+}
+
+/// The size metrics of a `flatc` run
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Metrics {
+    /// The number of input files
+    pub input_count: usize,
+    /// The number of include paths
+    pub include_count: usize,
+    /// The number of generated files
+    pub generated_file_count: usize,
+    /// The total size of the generated files in bytes
+    pub output_bytes: u64,
+    /// The size of the largest generated file in bytes
+    pub peak_output_bytes: u64,
+}
+
+impl Metrics {
+    pub(crate) fn collect(
+        input_count: usize,
+        include_count: usize,
+        generated_files: &[PathBuf],
+    ) -> Result<Metrics> {
+        let mut metrics = Metrics {
+            input_count,
+            include_count,
+            generated_file_count: generated_files.len(),
+            ..Default::default()
+        };
+        for generated_file in generated_files {
+            let size = fs::metadata(generated_file)?.len();
+            metrics.output_bytes += size;
+            metrics.peak_output_bytes = metrics.peak_output_bytes.max(size);
+        }
+        Ok(metrics)
+    }
+}
+
+/// An input file of a `flatc` run
+#[derive(Debug, Clone)]
+pub struct InputReport {
+    /// The input path as it was passed to `flatc`
+    pub path: PathBuf,
+    /// Hex-encoded SHA-256 hash of the file content
+    pub sha256: String,
+}
+
+impl InputReport {
+    pub(crate) fn from_path(path: &Path) -> Result<InputReport> {
+        Ok(InputReport {
+            path: path.to_owned(),
+            sha256: sha256_hex(&fs::read(path)?),
+        })
+    }
+}
+
+impl Report {
+    /// Render the report as a pretty-printed JSON document
+    pub fn to_json(&self) -> String {
+        let report = serde_json::json!({
+            "flatc_version": self.flatc_version,
+            "args": self.args,
+            "inputs": self
+                .inputs
+                .iter()
+                .map(|input| serde_json::json!({
+                    "path": input.path.to_string_lossy(),
+                    "sha256": input.sha256,
+                }))
+                .collect::<Vec<_>>(),
+            "generated_files": self
+                .generated_files
+                .iter()
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>(),
+            "duration_secs": self.duration.as_secs_f64(),
+            "warnings": self.warnings,
+            "metrics": {
+                "input_count": self.metrics.input_count,
+                "include_count": self.metrics.include_count,
+                "generated_file_count": self.metrics.generated_file_count,
+                "output_bytes": self.metrics.output_bytes,
+                "peak_output_bytes": self.metrics.peak_output_bytes,
+            },
+        });
+        serde_json::to_string_pretty(&report).expect("JSON values are always serializable")
+    }
+
+    /// Write the JSON report into the specified file
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_json())
+    }
+
+    /// Write the JSON report as [`REPORT_FILE_NAME`] into the specified directory (e.g. the
+    /// `out_dir` of the run) and return the path of the written file
+    ///
+    /// [`REPORT_FILE_NAME`]: constant.REPORT_FILE_NAME.html
+    pub fn write_to_dir<P: AsRef<Path>>(&self, dir: P) -> Result<PathBuf> {
+        let path = dir.as_ref().join(REPORT_FILE_NAME);
+        self.write_json(&path)?;
+        Ok(path)
+    }
+}
+
+/// The declared inputs and outputs of a successful `flatc` run (see [`Args::manifest`]), for the
+/// build systems caching the actions by their declared files (e.g. Bazel, Buck)
+///
+/// [`Args::manifest`]: struct.Args.html#structfield.manifest
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    /// The version reported by `flatc --version`
+    pub flatc_version: String,
+    /// The command line arguments of the equivalent single `flatc` invocation
+    pub args: Vec<String>,
+    /// The input files with their hashes
+    pub inputs: Vec<InputReport>,
+    /// The include directories searched by `flatc` (including the schema directories of the
+    /// dependencies)
+    pub includes: Vec<PathBuf>,
+    /// All the files the run has generated in the output directory (sorted), including the ones
+    /// left untouched by [`Args::skip_unchanged`]
+    ///
+    /// [`Args::skip_unchanged`]: struct.Args.html#structfield.skip_unchanged
+    pub outputs: Vec<PathBuf>,
+}
+
+impl Manifest {
+    /// Render the manifest as a pretty-printed JSON document
+    pub fn to_json(&self) -> String {
+        let manifest = serde_json::json!({
+            "flatc_version": self.flatc_version,
+            "args": self.args,
+            "inputs": self
+                .inputs
+                .iter()
+                .map(|input| serde_json::json!({
+                    "path": input.path.to_string_lossy(),
+                    "sha256": input.sha256,
+                }))
+                .collect::<Vec<_>>(),
+            "includes": self
+                .includes
+                .iter()
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>(),
+            "outputs": self
+                .outputs
+                .iter()
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>(),
+        });
+        serde_json::to_string_pretty(&manifest).expect("JSON values are always serializable")
+    }
+
+    /// Write the JSON manifest into the specified file
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_json())
+    }
+}
+
+/// Hex-encoded SHA-256 hash of the data
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Extract the warning lines from `flatc` output
+pub(crate) fn collect_warnings(output: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(output)
+        .lines()
+        .filter(|line| line.contains("warning:"))
+        .map(|line| line.trim().to_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sha256_hex_of_empty_input() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn collect_warnings_skips_other_lines() {
+        let output = b"flatc: warning: test.fbs(1, 7): field names should be lowercase\n\
+                       some other output\n";
+        assert_eq!(
+            collect_warnings(output),
+            vec!["flatc: warning: test.fbs(1, 7): field names should be lowercase".to_owned()]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Normalization of the generated files for the reproducible mode (see [`Args::reproducible`]).
+//!
+//! [`Args::reproducible`]: struct.Args.html#structfield.reproducible
+
+use std::env;
+use std::path::{self, PathBuf, MAIN_SEPARATOR};
+
+use crate::Args;
+
+/// The absolute directories of the run whose paths are made relative in the generated files
+/// (the longest first): `out_dir`, the current and the package directories, and the input and
+/// include directories outside of them
+pub(crate) fn path_roots(args: &Args) -> Vec<String> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+    let mut add_root = |dir: PathBuf| {
+        if !roots.contains(&dir) {
+            roots.push(dir);
+        }
+    };
+    for dir in std::iter::once(args.out_dir.to_owned())
+        .chain(env::current_dir().ok())
+        .chain(env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from))
+    {
+        if let Ok(canonical) = dir.canonicalize() {
+            add_root(canonical);
+        }
+        if let Ok(absolute) = path::absolute(&dir) {
+            add_root(absolute);
+        }
+    }
+    let base_count = roots.len();
+    let schema_dirs: Vec<PathBuf> = args
+        .inputs
+        .iter()
+        .filter_map(|input| input.parent())
+        .chain(args.includes.iter().copied())
+        .filter_map(|dir| path::absolute(dir).ok())
+        .collect();
+    for dir in schema_dirs {
+        if !roots[..base_count].iter().any(|root| dir.starts_with(root)) && !roots.contains(&dir) {
+            roots.push(dir);
+        }
+    }
+
+    let mut roots: Vec<String> = roots
+        .into_iter()
+        .filter(|root| root.parent().is_some())
+        .filter_map(|root| root.to_str().map(str::to_owned))
+        .collect();
+    roots.sort_by_key(|root| std::cmp::Reverse(root.len()));
+    roots
+}
+
+/// The content with the paths under the roots made relative (the roots themselves become `.`)
+/// and with `\n` line endings
+pub(crate) fn normalize(content: String, roots: &[String]) -> String {
+    let mut content = content.replace("\r\n", "\n");
+    for root in roots {
+        let root = root.trim_end_matches(MAIN_SEPARATOR);
+        if !content.contains(root) {
+            continue;
+        }
+        let mut normalized = String::with_capacity(content.len());
+        let mut rest = content.as_str();
+        while let Some(index) = rest.find(root) {
+            normalized.push_str(&rest[..index]);
+            rest = &rest[index + root.len()..];
+            match rest.chars().next() {
+                Some(MAIN_SEPARATOR) => rest = &rest[MAIN_SEPARATOR.len_utf8()..],
+                // Another path starting with the same characters
+                Some(c) if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') => {
+                    normalized.push_str(root);
+                }
+                _ => normalized.push('.'),
+            }
+        }
+        normalized.push_str(rest);
+        content = normalized;
+    }
+    content
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::Flatc;
+
+    #[test]
+    fn reproducible_runs_are_byte_identical() -> crate::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let version = Flatc::from_env_path().version()?;
+        let pin = format!("={}", version.version());
+        let run_on_machine = |machine: &str| -> crate::Result<String> {
+            let schemas_dir = temp_dir.path().join(machine).join("schemas");
+            fs::create_dir_all(&schemas_dir)?;
+            let input_path = schemas_dir.join("monster.fbs");
+            fs::write(&input_path, "table Monster { hp: short; }")?;
+            let out_dir = temp_dir.path().join(machine).join("out");
+            Flatc::from_env_path().run(Args {
+                inputs: &[&input_path],
+                out_dir: &out_dir,
+                includes: &[&schemas_dir],
+                atomic: true,
+                provenance: true,
+                reproducible: Some(&pin),
+                ..Default::default()
+            })?;
+            fs::read_to_string(out_dir.join("monster_generated.rs"))
+        };
+
+        let generated = run_on_machine("runner-1")?;
+        assert_eq!(run_on_machine("runner-2")?, generated);
+        assert!(generated.contains("// flatc args: --rust -I . monster.fbs\n"));
+        assert!(generated.contains("// monster.fbs SHA-256: "));
+        assert!(!generated.contains(&*temp_dir.path().to_string_lossy()));
+
+        let error = Flatc::from_env_path()
+            .run(Args {
+                inputs: &[&temp_dir.path().join("runner-1/schemas/monster.fbs")],
+                out_dir: &temp_dir.path().join("runner-1/out"),
+                reproducible: Some("=0.0.1"),
+                ..Default::default()
+            })
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("does not match the required version `=0.0.1`"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+//! Version requirements (a small subset of Cargo's semver requirements).
+
+use crate::{err_other, Result};
+
+/// Parse the leading `major.minor.patch` components of the version (the missing ones are zeros)
+pub(crate) fn parse_components(version: &str) -> Option<(u64, u64, u64)> {
+    let mut components = version
+        .trim()
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|component| component.parse::<u64>().ok());
+    let major = components.next()??;
+    let minor = components.next().flatten().unwrap_or(0);
+    let patch = components.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+/// Check whether the version matches every comma-separated comparator of the requirement
+///
+/// The comparators are `=`, `>`, `>=`, `<`, `<=`, `^`, `~` followed by a version, and `*`; a bare
+/// version has to match exactly (`24.3` matches any `24.3.x` though).
+pub(crate) fn matches(version: &str, requirement: &str) -> Result<bool> {
+    let version = parse_components(version)
+        .ok_or_else(|| err_other(format!("`{}` is not a version", version)))?;
+    for comparator in requirement.split(',').map(str::trim) {
+        if !matches_comparator(version, comparator)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn matches_comparator(version: (u64, u64, u64), comparator: &str) -> Result<bool> {
+    if comparator == "*" {
+        return Ok(true);
+    }
+    let operator_len = comparator
+        .find(|c: char| c.is_ascii_digit())
+        .ok_or_else(|| err_other(format!("`{}` is not a version requirement", comparator)))?;
+    let (operator, expected) = comparator.split_at(operator_len);
+    let precision = expected.split('.').count();
+    let expected = parse_components(expected)
+        .ok_or_else(|| err_other(format!("`{}` is not a version requirement", comparator)))?;
+    let same_prefix = |precision: usize| match precision {
+        1 => version.0 == expected.0,
+        2 => (version.0, version.1) == (expected.0, expected.1),
+        _ => version == expected,
+    };
+    Ok(match operator.trim() {
+        "" | "=" => same_prefix(precision),
+        ">" => version > expected,
+        ">=" => version >= expected,
+        "<" => version < expected,
+        "<=" => version <= expected,
+        "^" if expected.0 > 0 => version.0 == expected.0 && version >= expected,
+        "^" => same_prefix(2) && version >= expected,
+        "~" => same_prefix(precision.min(2)) && version >= expected,
+        operator => {
+            return Err(err_other(format!(
+                "unknown version requirement operator `{}`",
+                operator
+            )))
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_supports_comparators() -> Result<()> {
+        assert!(matches("24.3.25", "24.3.25")?);
+        assert!(!matches("24.3.7", "24.3.25")?);
+        assert!(matches("24.3.7", "24.3")?);
+        assert!(matches("24.3.25", ">=23.5.26, <25")?);
+        assert!(!matches("25.1.21", ">=23.5.26, <25")?);
+        assert!(matches("24.12.23", "^24.3")?);
+        assert!(!matches("24.12.23", "~24.3")?);
+        assert!(matches("1.12.1", "~1.12.0")?);
+        assert!(matches("2.0.0", "*")?);
+        assert!(matches("24.3.25", "?24").is_err());
+        assert!(matches("24.3.25", "").is_err());
+        Ok(())
+    }
+}
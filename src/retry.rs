@@ -0,0 +1,112 @@
+//! Retries of the transient `flatc` spawn failures.
+
+use std::io;
+use std::time::Duration;
+
+/// How to retry spawning `flatc` after the transient failures (see [`Flatc::with_retry`])
+///
+/// Only the spawn errors which are known to clear up by themselves are retried: the executable
+/// still open for writing (`ETXTBSY`, e.g. right after a download), the sharing and the access
+/// violations on Windows (e.g. an antivirus scanning the fresh executable), the stale NFS
+/// handles and the interrupted calls. A missing executable and the failures of `flatc` itself
+/// (a non-zero exit status) are never retried.
+///
+/// [`Flatc::with_retry`]: struct.Flatc.html#method.with_retry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The most retries after the first attempt (`0` disables the retries)
+    pub max_retries: u32,
+    /// The delay before the first retry, which doubles with every next one
+    pub initial_backoff: Duration,
+    /// The longest delay between the retries
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// 4 retries within about 1.5 seconds
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 4,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// No retries at all
+    pub fn never() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// The delay before the retry (counting from zero)
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .checked_mul(1 << retry.min(16))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+}
+
+/// Whether the spawn error is likely to clear up by itself
+pub(crate) fn is_transient(error: &io::Error) -> bool {
+    if matches!(
+        error.kind(),
+        io::ErrorKind::ExecutableFileBusy
+            | io::ErrorKind::StaleNetworkFileHandle
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+    ) {
+        return true;
+    }
+    // ERROR_ACCESS_DENIED, ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+    cfg!(windows) && matches!(error.raw_os_error(), Some(5 | 32 | 33))
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::thread;
+
+    use super::*;
+    use crate::Flatc;
+
+    #[test]
+    fn spawn_retries_busy_executable() -> crate::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let flatc_path = temp_dir.path().join("flatc");
+        let file = fs::File::create(&flatc_path)?;
+        fs::set_permissions(&flatc_path, fs::Permissions::from_mode(0o755))?;
+        fs::write(&flatc_path, "#!/bin/sh\necho 'flatc version 23.5.26'\n")?;
+        // The executable is busy while the file is open for writing
+        let error = Flatc::from_path(&flatc_path)
+            .with_retry(RetryPolicy::never())
+            .check()
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::ExecutableFileBusy);
+
+        let closing = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            drop(file);
+        });
+        Flatc::from_path(&flatc_path)
+            .with_retry(RetryPolicy {
+                max_retries: 20,
+                initial_backoff: Duration::from_millis(20),
+                max_backoff: Duration::from_millis(50),
+            })
+            .check()?;
+        closing.join().expect("closing thread");
+
+        assert_eq!(
+            RetryPolicy::default().backoff(5),
+            RetryPolicy::default().max_backoff
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+//! Compatibility of `flatc` with the `flatbuffers` runtime crate.
+//!
+//! The code generated by `flatc` only compiles against the matching `flatbuffers` crate versions,
+//! and the mismatches surface as baffling errors in the generated code. Since 2.0.0, `flatc` and
+//! the crate share the version numbers, and the generated code expects the runtime of the same
+//! `major.minor` version.
+//!
+//! ```no_run
+//! // build.rs
+//! let flatc = flatc_rust::Flatc::from_env_path();
+//! if let Err(e) = flatc_rust::runtime::check_flatc(&flatc) {
+//!     println!("cargo:warning={}", e);
+//! }
+//! ```
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use crate::requirement::parse_components;
+use crate::{err_other, Flatc, Result};
+
+/// The name of the runtime crate
+pub const RUNTIME_CRATE: &str = "flatbuffers";
+
+/// The `flatbuffers` crate versions (as a human-readable requirement) compatible with the code
+/// generated by the `flatc` version
+pub fn compatible_runtime(flatc_version: &str) -> Option<String> {
+    let (major, minor, _) = parse_components(flatc_version)?;
+    Some(match (major, minor) {
+        (1, 10) => "0.5.x".to_owned(),
+        (1, 11) => "0.6.x".to_owned(),
+        (1, 12) => ">=0.6, <0.9".to_owned(),
+        (2, 0) => ">=2.0, <2.2".to_owned(),
+        (major, minor) if major >= 22 => format!("{}.{}.x", major, minor),
+        _ => return None,
+    })
+}
+
+/// Check whether the `flatbuffers` crate version is compatible with the code generated by the
+/// `flatc` version
+pub fn is_compatible(flatc_version: &str, runtime_version: &str) -> Option<bool> {
+    let (flatc_major, flatc_minor, _) = parse_components(flatc_version)?;
+    let (major, minor, _) = parse_components(runtime_version)?;
+    Some(match (flatc_major, flatc_minor) {
+        (1, 10) => (major, minor) == (0, 5),
+        (1, 11) => (major, minor) == (0, 6),
+        (1, 12) => major == 0 && (6..9).contains(&minor),
+        (2, 0) => major == 2 && minor < 2,
+        (flatc_major, flatc_minor) if flatc_major >= 22 => {
+            (major, minor) == (flatc_major, flatc_minor)
+        }
+        _ => return None,
+    })
+}
+
+/// Fail if the `flatbuffers` crate version is known to be incompatible with the code generated by
+/// the `flatc` version (versions missing from the table are assumed compatible)
+pub fn check(flatc_version: &str, runtime_version: &str) -> Result<()> {
+    if is_compatible(flatc_version, runtime_version) == Some(false) {
+        return Err(err_other(format!(
+            "flatc {} generates code for {} {}, but {} {} is used",
+            flatc_version,
+            RUNTIME_CRATE,
+            compatible_runtime(flatc_version).unwrap_or_default(),
+            RUNTIME_CRATE,
+            runtime_version
+        )));
+    }
+    Ok(())
+}
+
+/// Find the `flatbuffers` crate version in the `Cargo.lock` of the package (looking up the
+/// directory tree for the workspace one)
+///
+/// Returns `None` if there is no lock file or it does not mention the crate; if several versions
+/// are locked the highest one is returned.
+pub fn detect_runtime_version(manifest_dir: &Path) -> Result<Option<String>> {
+    let lock_file = match manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lock_file| lock_file.is_file())
+    {
+        Some(lock_file) => lock_file,
+        None => return Ok(None),
+    };
+    Ok(locked_version(
+        &fs::read_to_string(lock_file)?,
+        RUNTIME_CRATE,
+    ))
+}
+
+fn locked_version(lock_file: &str, package: &str) -> Option<String> {
+    let name_line = format!("name = \"{}\"", package);
+    let mut lines = lock_file.lines().map(str::trim);
+    let mut versions = Vec::new();
+    while let Some(line) = lines.next() {
+        if line != name_line {
+            continue;
+        }
+        if let Some(version) = lines
+            .next()
+            .and_then(|line| line.strip_prefix("version = \""))
+            .and_then(|version| version.strip_suffix('"'))
+        {
+            versions.push(version.to_owned());
+        }
+    }
+    versions
+        .into_iter()
+        .max_by_key(|version| parse_components(version))
+}
+
+/// Check the `flatc` against the `flatbuffers` crate used by the package being built (a build
+/// script is expected, so `CARGO_MANIFEST_DIR` is used to find `Cargo.lock`)
+pub fn check_flatc(flatc: &Flatc) -> Result<()> {
+    let manifest_dir = env::var_os("CARGO_MANIFEST_DIR")
+        .ok_or_else(|| err_other("CARGO_MANIFEST_DIR is not set"))?;
+    let runtime_version = match detect_runtime_version(Path::new(&manifest_dir))? {
+        Some(runtime_version) => runtime_version,
+        None => return Ok(()),
+    };
+    check(flatc.version()?.version(), &runtime_version)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn compatibility_follows_flatc_versions() {
+        assert_eq!(is_compatible("23.5.26", "23.5.26"), Some(true));
+        assert_eq!(is_compatible("23.5.26", "23.5.9"), Some(true));
+        assert_eq!(is_compatible("24.3.25", "23.5.26"), Some(false));
+        assert_eq!(is_compatible("1.12.0", "0.8.4"), Some(true));
+        assert_eq!(is_compatible("1.12.0", "2.1.2"), Some(false));
+        assert_eq!(is_compatible("1.9.0", "0.4.0"), None);
+
+        assert!(check("22.9.29", "22.9.29").is_ok());
+        assert_eq!(
+            check("24.3.25", "2.1.2").unwrap_err().to_string(),
+            "flatc 24.3.25 generates code for flatbuffers 24.3.x, but flatbuffers 2.1.2 is used"
+        );
+    }
+
+    #[test]
+    fn locked_version_picks_highest_runtime() {
+        let lock_file = r#"
+[[package]]
+name = "flatbuffers"
+version = "2.1.2"
+
+[[package]]
+name = "flatbuffers"
+version = "23.5.26"
+
+[[package]]
+name = "flatc-rust"
+version = "0.2.0"
+"#;
+        assert_eq!(
+            locked_version(lock_file, "flatbuffers"),
+            Some("23.5.26".to_owned())
+        );
+        assert_eq!(locked_version(lock_file, "serde"), None);
+    }
+}
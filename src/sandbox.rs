@@ -0,0 +1,409 @@
+//! Confinement of the `flatc` processes (`sandbox` feature, see [`Flatc::with_sandbox`]).
+//!
+//! On Linux (x86_64 and aarch64) the child restricts itself right before `exec`: Landlock
+//! (Linux 5.13+) limits the filesystem access to reading the system libraries, `flatc` itself
+//! and the paths found in its arguments (with the directories of the input files, where `flatc`
+//! looks up the includes), and to writing into the output directory (`-o`); a seccomp filter
+//! makes `socket()` and `io_uring_setup()` fail with `EPERM`, so `flatc` has no network access.
+//!
+//! On Windows the child is assigned to a restricted job object right after it is spawned (like
+//! with the [`limits`]): it cannot start other processes (so it cannot reach the network through
+//! a helper), and it has no access to the desktop, the clipboard, the global atoms, the handles
+//! of the other processes' user objects and the system settings. Windows has no job-level
+//! filesystem or network rules, so the direct file and socket access of `flatc` itself is not
+//! restricted there.
+//!
+//! The other platforms are not supported yet: spawning fails with `Unsupported` error rather
+//! than running `flatc` unconfined, like on the kernels without Landlock.
+//!
+//! [`limits`]: ../limits/index.html
+//! [`Flatc::with_sandbox`]: ../struct.Flatc.html#method.with_sandbox
+
+use std::io;
+use std::process;
+
+/// Make the command confine the process it spawns
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+pub(crate) fn confine(cmd: &mut process::Command) -> io::Result<()> {
+    linux::confine(cmd)
+}
+
+/// Make the command confine the process it spawns (the job object is assigned by [`assign`])
+#[cfg(windows)]
+pub(crate) fn confine(cmd: &mut process::Command) -> io::Result<()> {
+    let _ = cmd;
+    Ok(())
+}
+
+/// Make the command confine the process it spawns
+#[cfg(not(any(
+    windows,
+    all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    )
+)))]
+pub(crate) fn confine(cmd: &mut process::Command) -> io::Result<()> {
+    let _ = cmd;
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "sandboxing flatc is only supported on Linux (x86_64 and aarch64) and Windows",
+    ))
+}
+
+/// Confine the spawned process (the restrictions not set by [`confine`] before `exec`)
+#[cfg(not(windows))]
+pub(crate) fn assign(child: &process::Child) -> io::Result<()> {
+    let _ = child;
+    Ok(())
+}
+
+/// Confine the spawned process (the restrictions not set by [`confine`] before `exec`)
+#[cfg(windows)]
+pub(crate) fn assign(child: &process::Child) -> io::Result<()> {
+    use crate::job::Job;
+
+    const JOB_OBJECT_LIMIT_ACTIVE_PROCESS: u32 = 0x0000_0008;
+    const JOB_OBJECT_LIMIT_DIE_ON_UNHANDLED_EXCEPTION: u32 = 0x0000_0400;
+    /// `JOB_OBJECT_UILIMIT_HANDLES` to `JOB_OBJECT_UILIMIT_EXITWINDOWS`
+    const JOB_OBJECT_UILIMIT_ALL: u32 = 0x0000_00ff;
+
+    let mut limits = Job::no_limits();
+    limits.BasicLimitInformation.LimitFlags =
+        JOB_OBJECT_LIMIT_ACTIVE_PROCESS | JOB_OBJECT_LIMIT_DIE_ON_UNHANDLED_EXCEPTION;
+    limits.BasicLimitInformation.ActiveProcessLimit = 1;
+    let job = Job::new()?;
+    job.set_limits(&limits)?;
+    job.set_ui_restrictions(JOB_OBJECT_UILIMIT_ALL)?;
+    job.assign(child)
+}
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+#[allow(unsafe_code)]
+mod linux {
+    use std::fs;
+    use std::io;
+    use std::mem;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::process::CommandExt;
+    use std::path::{Path, PathBuf};
+    use std::process;
+    use std::ptr;
+
+    use crate::discovery::resolve_exec;
+
+    const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1;
+    const LANDLOCK_RULE_PATH_BENEATH: u32 = 1;
+
+    const ACCESS_FS_EXECUTE: u64 = 1 << 0;
+    const ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+    const ACCESS_FS_READ_FILE: u64 = 1 << 2;
+    const ACCESS_FS_READ_DIR: u64 = 1 << 3;
+    /// All the rights of the first Landlock ABI (`EXECUTE` to `MAKE_SYM`)
+    const ACCESS_FS_V1: u64 = (1 << 13) - 1;
+    const ACCESS_FS_REFER: u64 = 1 << 13;
+    const ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+    const ACCESS_FS_IOCTL_DEV: u64 = 1 << 15;
+    /// The rights applicable to the files (rather than to the directories)
+    const ACCESS_FS_FILE: u64 = ACCESS_FS_EXECUTE
+        | ACCESS_FS_WRITE_FILE
+        | ACCESS_FS_READ_FILE
+        | ACCESS_FS_TRUNCATE
+        | ACCESS_FS_IOCTL_DEV;
+
+    const ACCESS_READ: u64 = ACCESS_FS_READ_FILE | ACCESS_FS_READ_DIR;
+    const ACCESS_EXECUTE: u64 = ACCESS_READ | ACCESS_FS_EXECUTE;
+
+    /// The system paths the dynamically linked `flatc` needs
+    const SYSTEM_PATHS: &[&str] = &["/usr", "/lib", "/lib64", "/etc/ld.so.cache"];
+
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xc000_003e;
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xc000_00b7;
+    /// The flag of the x32 system calls, which bypass the checks of the x86_64 numbers
+    const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+    }
+
+    #[repr(C, packed)]
+    struct PathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: i32,
+    }
+
+    pub(super) fn confine(cmd: &mut process::Command) -> io::Result<()> {
+        let ruleset = create_ruleset(cmd)?;
+        let filter = network_filter();
+        // SAFETY: the closure only makes system calls (no allocations or locks), which is safe
+        // between `fork` and `exec`; the ruleset and the filter outlive the call
+        unsafe {
+            cmd.pre_exec(move || restrict_self(&ruleset, &filter));
+        }
+        Ok(())
+    }
+
+    /// The Landlock ruleset for the command, created in the parent, so the failures to open the
+    /// paths are reported as usual
+    fn create_ruleset(cmd: &process::Command) -> io::Result<OwnedFd> {
+        // SAFETY: querying the ABI version takes no attributes
+        let abi = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                ptr::null::<RulesetAttr>(),
+                0usize,
+                LANDLOCK_CREATE_RULESET_VERSION,
+            )
+        };
+        if abi < 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "sandboxing flatc needs Landlock (Linux 5.13+): {}",
+                    io::Error::last_os_error()
+                ),
+            ));
+        }
+        let mut handled_access = ACCESS_FS_V1;
+        if abi >= 2 {
+            handled_access |= ACCESS_FS_REFER;
+        }
+        if abi >= 3 {
+            handled_access |= ACCESS_FS_TRUNCATE;
+        }
+        if abi >= 5 {
+            handled_access |= ACCESS_FS_IOCTL_DEV;
+        }
+
+        let attr = RulesetAttr {
+            handled_access_fs: handled_access,
+        };
+        // SAFETY: the attributes are valid for the duration of the call
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                &attr as *const RulesetAttr,
+                mem::size_of::<RulesetAttr>(),
+                0u32,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: the descriptor has just been created and is owned by nobody else
+        let ruleset = unsafe { OwnedFd::from_raw_fd(fd as i32) };
+
+        for (path, access) in allowed_paths(cmd) {
+            add_rule(&ruleset, &path, access & handled_access)?;
+        }
+        Ok(ruleset)
+    }
+
+    /// The paths the command may access with their Landlock rights
+    fn allowed_paths(cmd: &process::Command) -> Vec<(PathBuf, u64)> {
+        let mut paths: Vec<(PathBuf, u64)> = SYSTEM_PATHS
+            .iter()
+            .map(|path| (PathBuf::from(path), ACCESS_EXECUTE))
+            .collect();
+        if let Some(exec) = resolve_exec(Path::new(cmd.get_program())) {
+            paths.push((exec, ACCESS_EXECUTE));
+        }
+
+        let mut args = cmd.get_args();
+        while let Some(arg) = args.next() {
+            if arg == "-o" {
+                if let Some(out_dir) = args.next() {
+                    paths.push((PathBuf::from(out_dir), u64::MAX));
+                }
+                continue;
+            }
+            let path = Path::new(arg);
+            if path.is_file() {
+                let dir = match path.parent() {
+                    Some(dir) if dir != Path::new("") => dir,
+                    _ => Path::new("."),
+                };
+                paths.push((dir.to_owned(), ACCESS_READ));
+            } else if path.is_dir() {
+                paths.push((path.to_owned(), ACCESS_READ));
+            }
+        }
+        paths
+            .into_iter()
+            .filter(|(path, _)| path.exists())
+            .collect()
+    }
+
+    fn add_rule(ruleset: &OwnedFd, path: &Path, access: u64) -> io::Result<()> {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_PATH)
+            .open(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        let access = if file.metadata()?.is_dir() {
+            access
+        } else {
+            access & ACCESS_FS_FILE
+        };
+        let attr = PathBeneathAttr {
+            allowed_access: access,
+            parent_fd: file.as_raw_fd(),
+        };
+        // SAFETY: the attributes and the descriptors are valid for the duration of the call
+        let result = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_add_rule,
+                ruleset.as_raw_fd(),
+                LANDLOCK_RULE_PATH_BENEATH,
+                &attr as *const PathBeneathAttr,
+                0u32,
+            )
+        };
+        if result < 0 {
+            let e = io::Error::last_os_error();
+            return Err(io::Error::new(
+                e.kind(),
+                format!("failed to allow access to {}: {}", path.display(), e),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The seccomp filter failing the system calls creating sockets (and `io_uring` instances,
+    /// which can create them too) with `EPERM`, and killing the process on a foreign ABI
+    fn network_filter() -> Vec<libc::sock_filter> {
+        fn statement(code: u32, k: u32) -> libc::sock_filter {
+            libc::sock_filter {
+                code: code as u16,
+                jt: 0,
+                jf: 0,
+                k,
+            }
+        }
+        fn jump(code: u32, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+            libc::sock_filter {
+                code: (libc::BPF_JMP | code | libc::BPF_K) as u16,
+                jt,
+                jf,
+                k,
+            }
+        }
+        let load = libc::BPF_LD | libc::BPF_W | libc::BPF_ABS;
+        let ret = libc::BPF_RET | libc::BPF_K;
+        vec![
+            statement(load, mem::offset_of!(libc::seccomp_data, arch) as u32),
+            jump(libc::BPF_JEQ, AUDIT_ARCH, 1, 0),
+            statement(ret, libc::SECCOMP_RET_KILL_PROCESS),
+            statement(load, mem::offset_of!(libc::seccomp_data, nr) as u32),
+            jump(libc::BPF_JGE, X32_SYSCALL_BIT, 2, 0),
+            jump(libc::BPF_JEQ, libc::SYS_socket as u32, 1, 0),
+            jump(libc::BPF_JEQ, libc::SYS_io_uring_setup as u32, 0, 1),
+            statement(ret, libc::SECCOMP_RET_ERRNO | libc::EPERM as u32),
+            statement(ret, libc::SECCOMP_RET_ALLOW),
+        ]
+    }
+
+    /// Apply the ruleset and the filter to the current process (in the child, before `exec`)
+    fn restrict_self(ruleset: &OwnedFd, filter: &[libc::sock_filter]) -> io::Result<()> {
+        let program = libc::sock_fprog {
+            len: filter.len() as u16,
+            filter: filter.as_ptr() as *mut libc::sock_filter,
+        };
+        // SAFETY: plain system calls with the arguments valid for their duration
+        unsafe {
+            if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::syscall(libc::SYS_landlock_restrict_self, ruleset.as_raw_fd(), 0u32) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::prctl(
+                libc::PR_SET_SECCOMP,
+                libc::SECCOMP_MODE_FILTER,
+                &program as *const libc::sock_fprog,
+            ) != 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::{Args, Flatc, Invocation};
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn sandboxed_flatc_only_reads_the_tree() -> crate::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let schemas_dir = temp_dir.path().join("schemas");
+        let private_dir = temp_dir.path().join("private");
+        fs::create_dir(&schemas_dir)?;
+        fs::create_dir(&private_dir)?;
+        fs::write(
+            private_dir.join("private.fbs"),
+            "table Private { id: int; }",
+        )?;
+        let input_path = schemas_dir.join("test.fbs");
+        fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        let out_dir = temp_dir.path().join("out");
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            invocation: Invocation::PerFile,
+            quiet: true,
+            ..Default::default()
+        };
+
+        Flatc::from_env_path().with_sandbox(true).run(args)?;
+        assert!(out_dir.join("test_generated.rs").is_file());
+
+        fs::write(
+            &input_path,
+            "include \"../private/private.fbs\";\ntable Test { private: Private; } root_type Test;",
+        )?;
+        Flatc::from_env_path().run(args)?;
+        let error = Flatc::from_env_path()
+            .with_sandbox(true)
+            .run(args)
+            .expect_err("private schema is readable");
+        assert!(error.to_string().contains("unable to load include file"));
+
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn sandboxed_flatc_generates_code() -> crate::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        let out_dir = temp_dir.path().join("out");
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            invocation: Invocation::PerFile,
+            quiet: true,
+            ..Default::default()
+        };
+
+        Flatc::from_env_path().with_sandbox(true).run(args)?;
+        assert!(out_dir.join("test_generated.rs").is_file());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,837 @@
+//! Lightweight parsing of `.fbs` schemas.
+//!
+//! The parser extracts the declarations (includes, namespaces, tables, structs, enums, unions,
+//! `root_type`, `file_identifier`) without running `flatc`, which is enough for the tooling
+//! inspecting the schemas (e.g. dependency tracking or linting):
+//!
+//! ```
+//! let schema = flatc_rust::schema::Schema::parse(
+//!     r#"
+//!     include "common.fbs";
+//!     namespace MyGame.Sample;
+//!     table Monster { name: string; hp: short = 100 (id: 1); }
+//!     root_type Monster;
+//!     file_identifier "MONS";
+//!     "#,
+//! )
+//! .expect("schema");
+//! assert_eq!(schema.includes, vec!["common.fbs"]);
+//! assert_eq!(schema.root_type.as_deref(), Some("Monster"));
+//! let monster = schema.declaration("MyGame.Sample.Monster").expect("Monster");
+//! assert_eq!(monster.fields[1].id(), Some(1));
+//! ```
+//!
+//! The schemas are not validated beyond the syntax: the types are not resolved and the JSON
+//! objects of the schema files are skipped. Use `flatc` (e.g. [`Flatc::run`] with
+//! [`Args::bfbs_only`]) for the full validation.
+//!
+//! [`Flatc::run`]: ../struct.Flatc.html#method.run
+//! [`Args::bfbs_only`]: ../struct.Args.html#method.bfbs_only
+
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::{err_other, Result};
+
+/// The kind of [`Declaration`]
+///
+/// [`Declaration`]: struct.Declaration.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeclarationKind {
+    /// `table`
+    Table,
+    /// `struct`
+    Struct,
+    /// `enum`
+    Enum,
+    /// `union`
+    Union,
+    /// `rpc_service`
+    RpcService,
+}
+
+/// An attribute of the metadata list, e.g. `id: 1` or `deprecated`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    /// The attribute name
+    pub name: String,
+    /// The attribute value (without the quotes for the string values)
+    pub value: Option<String>,
+}
+
+/// A field of a table or a struct
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    /// The field name
+    pub name: String,
+    /// The field type as written, e.g. `short`, `[Monster]` or `[ubyte:16]`
+    pub type_name: String,
+    /// The default value as written (without the quotes for the string values)
+    pub default: Option<String>,
+    /// The metadata attributes
+    pub attributes: Vec<Attribute>,
+    /// The line of the declaration (1-based)
+    pub line: usize,
+}
+
+impl Field {
+    /// The value of the attribute (`Some("")` for the attributes without values)
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        attribute(&self.attributes, name)
+    }
+
+    /// The explicit field id (`id` attribute)
+    pub fn id(&self) -> Option<u64> {
+        self.attribute("id").and_then(|id| id.parse().ok())
+    }
+
+    /// Whether the field is deprecated (`deprecated` attribute)
+    pub fn is_deprecated(&self) -> bool {
+        self.attribute("deprecated").is_some()
+    }
+}
+
+/// A value of an enum or a union
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumValue {
+    /// The value name (the type name for the union members without aliases)
+    pub name: String,
+    /// The explicit value as written
+    pub value: Option<String>,
+    /// The member type of the aliased union members, e.g. `Monster` of `Hero: Monster`
+    pub union_type: Option<String>,
+    /// The metadata attributes
+    pub attributes: Vec<Attribute>,
+    /// The line of the declaration (1-based)
+    pub line: usize,
+}
+
+/// A type (or an RPC service) declared by the schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    /// The declaration kind
+    pub kind: DeclarationKind,
+    /// The name as written
+    pub name: String,
+    /// The namespace the type is declared in (empty if none)
+    pub namespace: String,
+    /// The underlying type of the enums (and the unions declaring it), e.g. `byte`
+    pub underlying_type: Option<String>,
+    /// The metadata attributes
+    pub attributes: Vec<Attribute>,
+    /// The fields of the tables and the structs (the methods of the RPC services are not
+    /// listed)
+    pub fields: Vec<Field>,
+    /// The values of the enums and the unions
+    pub values: Vec<EnumValue>,
+    /// The line of the declaration (1-based)
+    pub line: usize,
+}
+
+impl Declaration {
+    /// The name qualified with the namespace, e.g. `MyGame.Sample.Monster`
+    pub fn qualified_name(&self) -> String {
+        if self.namespace.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.namespace, self.name)
+        }
+    }
+
+    /// The value of the attribute (`Some("")` for the attributes without values)
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        attribute(&self.attributes, name)
+    }
+
+    /// The field of a table or a struct
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+/// The declarations of a parsed `.fbs` schema
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    /// The included schemas as written
+    pub includes: Vec<String>,
+    /// The `native_include` files as written
+    pub native_includes: Vec<String>,
+    /// The namespaces in the order of appearance (without duplicates)
+    pub namespaces: Vec<String>,
+    /// The declarations in the order of appearance
+    pub declarations: Vec<Declaration>,
+    /// The custom attributes declared with `attribute`
+    pub attributes: Vec<String>,
+    /// The `root_type` as written
+    pub root_type: Option<String>,
+    /// The `file_identifier`
+    pub file_identifier: Option<String>,
+    /// The `file_extension`
+    pub file_extension: Option<String>,
+}
+
+impl Schema {
+    /// Parse the schema source
+    pub fn parse(source: &str) -> Result<Schema> {
+        parse_statements(source).map(|(schema, _)| schema)
+    }
+
+    /// Read and parse the schema file
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Schema> {
+        let path = path.as_ref();
+        Schema::parse(&fs::read_to_string(path)?)
+            .map_err(|e| err_other(format!("{}: {}", path.display(), e)))
+    }
+
+    /// The declaration with the name, either qualified with the namespace or as written
+    pub fn declaration(&self, name: &str) -> Option<&Declaration> {
+        self.declarations
+            .iter()
+            .find(|declaration| declaration.qualified_name() == name)
+            .or_else(|| {
+                self.declarations
+                    .iter()
+                    .find(|declaration| declaration.name == name)
+            })
+    }
+
+    /// The declarations of the kind
+    pub fn declarations_of(&self, kind: DeclarationKind) -> impl Iterator<Item = &Declaration> {
+        self.declarations
+            .iter()
+            .filter(move |declaration| declaration.kind == kind)
+    }
+
+    /// The declaration of the `root_type`
+    pub fn root_declaration(&self) -> Option<&Declaration> {
+        self.root_type
+            .as_deref()
+            .and_then(|root_type| self.declaration(root_type))
+    }
+}
+
+/// The kind of a top-level statement of the schema
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum StatementKind {
+    /// `include` of the file
+    Include(String),
+    /// `root_type`, `file_identifier` or `file_extension`, which only apply to the root schema
+    RootDeclaration,
+    /// `attribute` declaration of the attribute
+    Attribute(String),
+    /// The type (or RPC service) declaration with the qualified name
+    Declaration(String),
+    /// Any other statement (namespaces, native includes, JSON objects, etc.)
+    Other,
+}
+
+/// A top-level statement with its byte range in the source
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Statement {
+    pub(crate) kind: StatementKind,
+    pub(crate) span: Range<usize>,
+}
+
+/// Parse the schema source, listing its top-level statements as well
+pub(crate) fn parse_statements(source: &str) -> Result<(Schema, Vec<Statement>)> {
+    Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+    }
+    .schema()
+}
+
+fn attribute<'a>(attributes: &'a [Attribute], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name == name)
+        .map(|attribute| attribute.value.as_deref().unwrap_or(""))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    /// An identifier (possibly qualified with dots), a keyword or a number
+    Word(String),
+    /// A string literal (unescaped)
+    Str(String),
+    /// A punctuation character
+    Punct(char),
+    /// A line or a block comment (only with `tokenize_with_comments`)
+    Comment,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(word) => format!("`{}`", word),
+            Token::Str(string) => format!("string {:?}", string),
+            Token::Punct(c) => format!("`{}`", c),
+            Token::Comment => "comment".to_owned(),
+        }
+    }
+}
+
+fn syntax_error(line: usize, message: String) -> crate::Error {
+    err_other(format!("schema syntax error at line {}: {}", line, message))
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.'
+}
+
+/// A token with its line (1-based) and its byte range in the source
+pub(crate) struct Spanned {
+    pub(crate) token: Token,
+    pub(crate) line: usize,
+    pub(crate) span: Range<usize>,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Spanned>> {
+    lex(source, false)
+}
+
+/// Split the source into the tokens, keeping the comments as `Token::Comment`
+pub(crate) fn tokenize_with_comments(source: &str) -> Result<Vec<Spanned>> {
+    lex(source, true)
+}
+
+fn lex(source: &str, keep_comments: bool) -> Result<Vec<Spanned>> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut chars = source.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                None
+            }
+            c if c.is_whitespace() => None,
+            '/' if source[start + 1..].starts_with('/') => {
+                // The newline is left for the next iteration
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+                keep_comments.then_some((Token::Comment, line))
+            }
+            '/' if source[start + 1..].starts_with('*') => {
+                chars.next();
+                let start_line = line;
+                let mut previous = '\0';
+                loop {
+                    match chars.next() {
+                        Some((_, '/')) if previous == '*' => break,
+                        Some((_, c)) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            previous = c;
+                        }
+                        None => {
+                            return Err(syntax_error(start_line, "unterminated comment".into()))
+                        }
+                    }
+                }
+                keep_comments.then_some((Token::Comment, start_line))
+            }
+            '"' | '\'' => {
+                let quote = c;
+                let start_line = line;
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, c)) if c == quote => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => string.push('\n'),
+                            Some((_, 't')) => string.push('\t'),
+                            Some((_, c)) => string.push(c),
+                            None => (),
+                        },
+                        Some((_, c)) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            string.push(c);
+                        }
+                        None => return Err(syntax_error(start_line, "unterminated string".into())),
+                    }
+                }
+                Some((Token::Str(string), start_line))
+            }
+            c if is_word_char(c) || c == '-' || c == '+' => {
+                let numeric = !c.is_ascii_alphabetic() && c != '_';
+                let mut word = String::from(c);
+                while let Some(&(_, next)) = chars.peek() {
+                    let exponent_sign = numeric
+                        && (next == '-' || next == '+')
+                        && word.ends_with(['e', 'E'])
+                        && !word.starts_with("0x");
+                    if !is_word_char(next) && !exponent_sign {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                Some((Token::Word(word), line))
+            }
+            c => Some((Token::Punct(c), line)),
+        };
+        if let Some((token, line)) = token {
+            let end = chars.peek().map_or(source.len(), |&(index, _)| index);
+            tokens.push(Spanned {
+                token,
+                line,
+                span: start..end,
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|spanned| &spanned.token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |spanned| spanned.line)
+    }
+
+    fn next(&mut self, expected: &str) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|spanned| spanned.token.clone())
+            .ok_or_else(|| {
+                syntax_error(
+                    self.line(),
+                    format!("expected {}, found end of file", expected),
+                )
+            })?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn unexpected<T>(&self, token: &Token, expected: &str) -> Result<T> {
+        Err(syntax_error(
+            self.tokens[self.position - 1].line,
+            format!("expected {}, found {}", expected, token.describe()),
+        ))
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    /// Consume the punctuation character if it is the next token
+    fn eat_punct(&mut self, c: char) -> bool {
+        let is_punct = self.is_punct(c);
+        if is_punct {
+            self.position += 1;
+        }
+        is_punct
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<()> {
+        let expected = format!("`{}`", c);
+        match self.next(&expected)? {
+            Token::Punct(found) if found == c => Ok(()),
+            token => self.unexpected(&token, &expected),
+        }
+    }
+
+    fn expect_word(&mut self, expected: &str) -> Result<String> {
+        match self.next(expected)? {
+            Token::Word(word) => Ok(word),
+            token => self.unexpected(&token, expected),
+        }
+    }
+
+    fn expect_string(&mut self, expected: &str) -> Result<String> {
+        match self.next(expected)? {
+            Token::Str(string) => Ok(string),
+            token => self.unexpected(&token, expected),
+        }
+    }
+
+    /// A scalar or a string value
+    fn value(&mut self, expected: &str) -> Result<String> {
+        match self.next(expected)? {
+            Token::Word(word) | Token::Str(word) => Ok(word),
+            token => self.unexpected(&token, expected),
+        }
+    }
+
+    fn schema(mut self) -> Result<(Schema, Vec<Statement>)> {
+        let mut schema = Schema::default();
+        let mut statements = Vec::new();
+        let mut namespace = String::new();
+        while let Some(token) = self.peek().cloned() {
+            let line = self.line();
+            let start = self.tokens[self.position].span.start;
+            let keyword = match token {
+                Token::Word(keyword) => keyword,
+                Token::Punct('{') => {
+                    self.skip_braces()?;
+                    statements.push(self.statement(StatementKind::Other, start));
+                    continue;
+                }
+                Token::Punct(';') => {
+                    self.position += 1;
+                    continue;
+                }
+                token => {
+                    self.position += 1;
+                    return self.unexpected(&token, "a declaration");
+                }
+            };
+            self.position += 1;
+            let kind = match keyword.as_str() {
+                "include" => {
+                    let include = self.expect_string("included file")?;
+                    self.expect_punct(';')?;
+                    schema.includes.push(include.clone());
+                    StatementKind::Include(include)
+                }
+                "native_include" => {
+                    schema
+                        .native_includes
+                        .push(self.expect_string("included file")?);
+                    self.expect_punct(';')?;
+                    StatementKind::Other
+                }
+                "namespace" => {
+                    namespace = if self.is_punct(';') {
+                        String::new()
+                    } else {
+                        self.expect_word("namespace name")?
+                    };
+                    self.expect_punct(';')?;
+                    if !namespace.is_empty() && !schema.namespaces.contains(&namespace) {
+                        schema.namespaces.push(namespace.clone());
+                    }
+                    StatementKind::Other
+                }
+                "attribute" => {
+                    let attribute = self.value("attribute name")?;
+                    self.expect_punct(';')?;
+                    schema.attributes.push(attribute.clone());
+                    StatementKind::Attribute(attribute)
+                }
+                "root_type" => {
+                    schema.root_type = Some(self.expect_word("root type name")?);
+                    self.expect_punct(';')?;
+                    StatementKind::RootDeclaration
+                }
+                "file_identifier" => {
+                    schema.file_identifier = Some(self.expect_string("file identifier")?);
+                    self.expect_punct(';')?;
+                    StatementKind::RootDeclaration
+                }
+                "file_extension" => {
+                    schema.file_extension = Some(self.expect_string("file extension")?);
+                    self.expect_punct(';')?;
+                    StatementKind::RootDeclaration
+                }
+                "table" | "struct" => {
+                    let kind = if keyword == "table" {
+                        DeclarationKind::Table
+                    } else {
+                        DeclarationKind::Struct
+                    };
+                    let mut declaration = self.declaration_head(kind, &namespace, line)?;
+                    let qualified_name = declaration.qualified_name();
+                    self.expect_punct('{')?;
+                    while !self.eat_punct('}') {
+                        declaration.fields.push(self.field()?);
+                    }
+                    schema.declarations.push(declaration);
+                    StatementKind::Declaration(qualified_name)
+                }
+                "enum" | "union" => {
+                    let kind = if keyword == "enum" {
+                        DeclarationKind::Enum
+                    } else {
+                        DeclarationKind::Union
+                    };
+                    let mut declaration = self.declaration_head(kind, &namespace, line)?;
+                    let qualified_name = declaration.qualified_name();
+                    self.expect_punct('{')?;
+                    while !self.eat_punct('}') {
+                        declaration.values.push(self.enum_value()?);
+                        if !self.eat_punct(',') {
+                            self.expect_punct('}')?;
+                            break;
+                        }
+                    }
+                    schema.declarations.push(declaration);
+                    StatementKind::Declaration(qualified_name)
+                }
+                "rpc_service" => {
+                    let declaration =
+                        self.declaration_head(DeclarationKind::RpcService, &namespace, line)?;
+                    let qualified_name = declaration.qualified_name();
+                    self.skip_braces()?;
+                    schema.declarations.push(declaration);
+                    StatementKind::Declaration(qualified_name)
+                }
+                _ => {
+                    self.position -= 1;
+                    return self.unexpected(&Token::Word(keyword), "a declaration");
+                }
+            };
+            statements.push(self.statement(kind, start));
+        }
+        Ok((schema, statements))
+    }
+
+    /// The statement starting at the byte offset and ending with the last consumed token
+    fn statement(&self, kind: StatementKind, start: usize) -> Statement {
+        Statement {
+            kind,
+            span: start..self.tokens[self.position - 1].span.end,
+        }
+    }
+
+    /// The name, the enum underlying type and the metadata of the declaration
+    fn declaration_head(
+        &mut self,
+        kind: DeclarationKind,
+        namespace: &str,
+        line: usize,
+    ) -> Result<Declaration> {
+        let name = self.expect_word("type name")?;
+        let underlying_type = if kind == DeclarationKind::Enum {
+            self.expect_punct(':')?;
+            Some(self.expect_word("enum underlying type")?)
+        } else if kind == DeclarationKind::Union && self.eat_punct(':') {
+            Some(self.expect_word("union underlying type")?)
+        } else {
+            None
+        };
+        Ok(Declaration {
+            kind,
+            name,
+            namespace: namespace.to_owned(),
+            underlying_type,
+            attributes: self.metadata()?,
+            fields: Vec::new(),
+            values: Vec::new(),
+            line,
+        })
+    }
+
+    fn field(&mut self) -> Result<Field> {
+        let line = self.line();
+        let name = self.expect_word("field name")?;
+        self.expect_punct(':')?;
+        let type_name = self.type_name()?;
+        let default = if self.eat_punct('=') {
+            if self.eat_punct('[') {
+                self.expect_punct(']')?;
+                Some("[]".to_owned())
+            } else {
+                Some(self.value("default value")?)
+            }
+        } else {
+            None
+        };
+        let attributes = self.metadata()?;
+        self.expect_punct(';')?;
+        Ok(Field {
+            name,
+            type_name,
+            default,
+            attributes,
+            line,
+        })
+    }
+
+    fn type_name(&mut self) -> Result<String> {
+        if !self.eat_punct('[') {
+            return self.expect_word("field type");
+        }
+        let element = self.type_name()?;
+        let type_name = if self.eat_punct(':') {
+            format!("[{}:{}]", element, self.expect_word("array length")?)
+        } else {
+            format!("[{}]", element)
+        };
+        self.expect_punct(']')?;
+        Ok(type_name)
+    }
+
+    fn enum_value(&mut self) -> Result<EnumValue> {
+        let line = self.line();
+        let name = self.expect_word("enum value name")?;
+        let union_type = if self.eat_punct(':') {
+            Some(self.expect_word("union member type")?)
+        } else {
+            None
+        };
+        let value = if self.eat_punct('=') {
+            Some(self.value("enum value")?)
+        } else {
+            None
+        };
+        Ok(EnumValue {
+            name,
+            value,
+            union_type,
+            attributes: self.metadata()?,
+            line,
+        })
+    }
+
+    /// The optional `(name: value, ...)` list
+    fn metadata(&mut self) -> Result<Vec<Attribute>> {
+        let mut attributes = Vec::new();
+        if !self.eat_punct('(') {
+            return Ok(attributes);
+        }
+        while !self.eat_punct(')') {
+            let name = self.value("attribute name")?;
+            let value = if self.eat_punct(':') {
+                Some(self.value("attribute value")?)
+            } else {
+                None
+            };
+            attributes.push(Attribute { name, value });
+            if !self.eat_punct(',') {
+                self.expect_punct(')')?;
+                break;
+            }
+        }
+        Ok(attributes)
+    }
+
+    /// Skip the (nested) braces, e.g. of the JSON objects or the RPC service bodies
+    fn skip_braces(&mut self) -> Result<()> {
+        self.expect_punct('{')?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next("`}`")? {
+                Token::Punct('{') => depth += 1,
+                Token::Punct('}') => depth -= 1,
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_extracts_declarations() -> Result<()> {
+        let schema = Schema::parse(
+            r#"// Example schema
+            include "common.fbs";
+            native_include 'native.h';
+            attribute "priority";
+
+            namespace MyGame.Sample;
+
+            enum Color : byte { Red = 1, Green, Blue = 3, }
+            union Equipment : ubyte { Weapon, Shield: Armor }
+
+            /* Multi-line
+               comment */
+            struct Vec3 { x: float; y: float = -1.5e-3; pos: [ubyte:16]; }
+
+            table Monster (priority: 1) {
+                name: string (id: 0, required);
+                hp: short = 100 (id: 1);
+                friendly: bool = false (id: 2, deprecated);
+                inventory: [ubyte] (id: 3);
+                color: Color = Blue (id: 4);
+                path: [Vec3] = [];
+            }
+
+            rpc_service MonsterStorage {
+                Store(Monster): Monster (streaming: "none");
+            }
+
+            root_type Monster;
+            file_identifier "MONS";
+            file_extension "mon";
+            { name: "ignored" }
+            "#,
+        )?;
+
+        assert_eq!(schema.includes, vec!["common.fbs"]);
+        assert_eq!(schema.native_includes, vec!["native.h"]);
+        assert_eq!(schema.namespaces, vec!["MyGame.Sample"]);
+        assert_eq!(schema.attributes, vec!["priority"]);
+        assert_eq!(schema.file_identifier.as_deref(), Some("MONS"));
+        assert_eq!(schema.file_extension.as_deref(), Some("mon"));
+        assert_eq!(schema.declarations.len(), 5);
+        assert_eq!(schema.declarations_of(DeclarationKind::Table).count(), 1);
+
+        let color = schema.declaration("Color").expect("Color");
+        assert_eq!(color.kind, DeclarationKind::Enum);
+        assert_eq!(color.underlying_type.as_deref(), Some("byte"));
+        let values: Vec<_> = color
+            .values
+            .iter()
+            .map(|value| (value.name.as_str(), value.value.as_deref()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![("Red", Some("1")), ("Green", None), ("Blue", Some("3"))]
+        );
+
+        let equipment = schema.declaration("Equipment").expect("Equipment");
+        assert_eq!(equipment.underlying_type.as_deref(), Some("ubyte"));
+        assert_eq!(equipment.values[1].name, "Shield");
+        assert_eq!(equipment.values[1].union_type.as_deref(), Some("Armor"));
+
+        let vec3 = schema.declaration("MyGame.Sample.Vec3").expect("Vec3");
+        assert_eq!(vec3.line, 13);
+        assert_eq!(vec3.fields[1].default.as_deref(), Some("-1.5e-3"));
+        assert_eq!(vec3.fields[2].type_name, "[ubyte:16]");
+
+        let monster = schema.root_declaration().expect("Monster");
+        assert_eq!(monster.qualified_name(), "MyGame.Sample.Monster");
+        assert_eq!(monster.attribute("priority"), Some("1"));
+        assert_eq!(monster.fields.len(), 6);
+        let name = monster.field("name").expect("name");
+        assert_eq!(name.id(), Some(0));
+        assert_eq!(name.attribute("required"), Some(""));
+        assert!(monster.field("friendly").expect("friendly").is_deprecated());
+        assert_eq!(
+            monster.field("inventory").expect("inventory").type_name,
+            "[ubyte]"
+        );
+        assert_eq!(
+            monster.field("color").expect("color").default.as_deref(),
+            Some("Blue")
+        );
+        assert_eq!(
+            monster.field("path").expect("path").default.as_deref(),
+            Some("[]")
+        );
+
+        let service = schema
+            .declaration("MonsterStorage")
+            .expect("MonsterStorage");
+        assert_eq!(service.kind, DeclarationKind::RpcService);
+
+        let error = Schema::parse("table Monster {\n  name string;\n}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "schema syntax error at line 2: expected `:`, found `string`"
+        );
+        assert!(Schema::parse("table Monster { name: string;").is_err());
+
+        Ok(())
+    }
+}
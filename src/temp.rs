@@ -0,0 +1,75 @@
+//! Managed temporary directories.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::Result;
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A uniquely named directory (inside the system temporary directory by default), which is
+/// removed (with all its content) on drop.
+pub(crate) struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub(crate) fn new() -> Result<TempDir> {
+        TempDir::create(&env::temp_dir(), "flatc-rust")
+    }
+
+    /// A hidden directory inside `dir`, e.g. to stay on the same file system as `dir`, so the
+    /// files can be renamed into it atomically.
+    pub(crate) fn new_in(dir: &Path) -> Result<TempDir> {
+        TempDir::create(dir, ".flatc-rust")
+    }
+
+    /// A hidden directory inside `dir` with the fixed name (the leftover of an interrupted run is
+    /// removed first), e.g. for the paths which have to be the same in every run.
+    pub(crate) fn new_named_in(dir: &Path, name: &str) -> Result<TempDir> {
+        let path = dir.join(format!(".flatc-rust-{}", name));
+        match fs::remove_dir_all(&path) {
+            Ok(()) => (),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e),
+        }
+        fs::create_dir(&path)?;
+        Ok(TempDir { path })
+    }
+
+    fn create(dir: &Path, prefix: &str) -> Result<TempDir> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.subsec_nanos())
+            .unwrap_or(0);
+        loop {
+            let path = dir.join(format!(
+                "{}-{}-{}-{}",
+                prefix,
+                process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed),
+                nanos
+            ));
+            match fs::create_dir(&path) {
+                Ok(()) => return Ok(TempDir { path }),
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
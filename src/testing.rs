@@ -0,0 +1,109 @@
+//! Helpers for guarding the schemas with plain `cargo test`, without build scripts.
+//!
+//! ```no_run
+//! #[test]
+//! fn schemas_compile() {
+//!     flatc_rust::assert_schema_compiles!("schemas/monster.fbs", "schemas/weapon.fbs");
+//!     flatc_rust::assert_schema_compiles!("schemas/game.fbs"; includes = ["schemas/include/"]);
+//! }
+//! ```
+//!
+//! `cargo test` runs the tests in the directory of the package, so the relative paths are
+//! resolved against it. `flatc` is found in `$PATH` (or [`FLATC_ENV`] environment variable, if
+//! set).
+//!
+//! [`FLATC_ENV`]: ../constant.FLATC_ENV.html
+
+use std::path::Path;
+
+use crate::temp::TempDir;
+use crate::{Args, Flatc, Invocation, Result};
+
+/// Compile every schema on its own into a temporary directory with `flatc` found in `$PATH`
+///
+/// The error is a [`BatchError`] listing every schema which failed to compile with the
+/// diagnostics of `flatc`.
+///
+/// [`BatchError`]: ../struct.BatchError.html
+pub fn compile_schemas(schemas: &[&Path], includes: &[&Path]) -> Result<()> {
+    let flatc = Flatc::from_env_path();
+
+    // First check with have good `flatc`
+    flatc.check()?;
+
+    let out_dir = TempDir::new()?;
+    flatc.run(Args {
+        inputs: schemas,
+        includes,
+        out_dir: out_dir.path(),
+        invocation: Invocation::PerFile,
+        quiet: true,
+        ..Default::default()
+    })
+}
+
+/// Panic with the diagnostics of `flatc` unless every schema compiles (see
+/// [`compile_schemas`]); it is what [`assert_schema_compiles!`] expands to
+///
+/// [`compile_schemas`]: fn.compile_schemas.html
+/// [`assert_schema_compiles!`]: ../macro.assert_schema_compiles.html
+#[track_caller]
+pub fn assert_schemas_compile(schemas: &[&Path], includes: &[&Path]) {
+    if let Err(e) = compile_schemas(schemas, includes) {
+        panic!("the schemas do not compile: {}", e);
+    }
+}
+
+/// Assert that the schemas compile, panicking with the diagnostics of `flatc` otherwise (see
+/// [`testing`])
+///
+/// The include directories follow `; includes = [...]`.
+///
+/// [`testing`]: testing/index.html
+#[macro_export]
+macro_rules! assert_schema_compiles {
+    ($($schema:expr),+ $(,)? ; includes = [$($include:expr),* $(,)?]) => {
+        $crate::testing::assert_schemas_compile(
+            &[$(::std::path::Path::new($schema)),+],
+            &[$(::std::path::Path::new($include)),*],
+        )
+    };
+    ($($schema:expr),+ $(,)?) => {
+        $crate::testing::assert_schemas_compile(&[$(::std::path::Path::new($schema)),+], &[])
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::panic;
+
+    #[test]
+    fn assert_schema_compiles_reports_diagnostics() -> crate::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let include_dir = temp_dir.path().join("include");
+        fs::create_dir(&include_dir)?;
+        fs::write(include_dir.join("common.fbs"), "table Common { id: int; }")?;
+        let good_path = temp_dir.path().join("good.fbs");
+        fs::write(
+            &good_path,
+            "include \"common.fbs\";\ntable Good { common: Common; } root_type Good;",
+        )?;
+        let bad_path = temp_dir.path().join("bad.fbs");
+        fs::write(&bad_path, "table Bad { id: int }")?;
+
+        assert_schema_compiles!(&good_path; includes = [&include_dir]);
+
+        let panic = panic::catch_unwind(|| assert_schema_compiles!(&bad_path))
+            .expect_err("bad schema compiles");
+        let message = panic
+            .downcast_ref::<String>()
+            .expect("panic message")
+            .clone();
+        assert!(message.starts_with("the schemas do not compile: "));
+        assert!(message.contains("bad.fbs"));
+        assert!(message.contains("expecting: ;"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,782 @@
+//! Generation of the [tonic] servers and clients of the `rpc_service` declarations (`tonic`
+//! feature, see [`Args::tonic`]).
+//!
+//! `flatc` does not implement the gRPC generator for Rust (`--grpc` only prints a warning), so
+//! the services are rendered from the binary schema of every input and appended to its
+//! generated Rust file. For `rpc_service Greeter` of `namespace MyGame;` the file gets, next to
+//! `pub mod my_game`:
+//!
+//! * `greeter_messages` with a message type per request and response table, owning the verified
+//!   bytes of the FlatBuffer (`HelloRequest::from_builder(&builder)?`, `request.get().name()`),
+//!   and the FlatBuffers codec of tonic;
+//! * `greeter_server` with the `Greeter` trait to implement (`#[tonic::async_trait]`) and
+//!   `GreeterServer` to add to `tonic::transport::Server`;
+//! * `greeter_client` with `GreeterClient`.
+//!
+//! The `streaming` attribute of the calls (`"none"`, `"server"`, `"client"` or `"bidi"`) selects
+//! the shape of the methods like with `tonic-build`. The generated code targets tonic 0.14 and
+//! needs `tonic`, `flatbuffers` and `bytes` among the dependencies of the crate:
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! flatc_rust::run(flatc_rust::Args {
+//!     inputs: &[Path::new("schemas/greeter.fbs")],
+//!     out_dir: Path::new("target/flatbuffers/"),
+//!     tonic: true,
+//!     ..Default::default()
+//! })
+//! .expect("flatc");
+//! ```
+//!
+//! `examples/greeter` serves and calls every kind of the RPCs with the generated code, and CI
+//! builds and tests it against tonic.
+//!
+//! [tonic]: https://docs.rs/tonic
+//! [`Args::tonic`]: ../struct.Args.html#structfield.tonic
+
+use std::collections::BTreeMap;
+
+use crate::reflection::{RpcCall, Service};
+use crate::{err_other, Result};
+
+/// Render the messages, the server and the client modules of the services
+pub fn generate(services: &[Service]) -> Result<String> {
+    let mut code = String::new();
+    for service in services {
+        code.push_str(&generate_service(service)?);
+    }
+    Ok(code)
+}
+
+/// The shape of an RPC according to its `streaming` attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Streaming {
+    None,
+    Server,
+    Client,
+    Bidi,
+}
+
+impl Streaming {
+    fn of(service: &Service, call: &RpcCall) -> Result<Streaming> {
+        let value = call
+            .attributes
+            .iter()
+            .find(|attribute| attribute.name == "streaming")
+            .and_then(|attribute| attribute.value.as_deref());
+        match value {
+            None | Some("none") => Ok(Streaming::None),
+            Some("server") => Ok(Streaming::Server),
+            Some("client") => Ok(Streaming::Client),
+            Some("bidi") => Ok(Streaming::Bidi),
+            Some(value) => Err(err_other(format!(
+                "{}.{} has unknown streaming `{}` (expected none, server, client or bidi)",
+                service.name, call.name, value
+            ))),
+        }
+    }
+
+    fn streams_requests(self) -> bool {
+        self == Streaming::Client || self == Streaming::Bidi
+    }
+
+    fn streams_responses(self) -> bool {
+        self == Streaming::Server || self == Streaming::Bidi
+    }
+
+    /// The method of `tonic::client::Grpc` and `tonic::server::Grpc`
+    fn grpc_method(self) -> &'static str {
+        match self {
+            Streaming::None => "unary",
+            Streaming::Server => "server_streaming",
+            Streaming::Client => "client_streaming",
+            Streaming::Bidi => "streaming",
+        }
+    }
+
+    /// The `tonic::server` trait of the per-call service
+    fn server_trait(self) -> &'static str {
+        match self {
+            Streaming::None => "UnaryService",
+            Streaming::Server => "ServerStreamingService",
+            Streaming::Client => "ClientStreamingService",
+            Streaming::Bidi => "StreamingService",
+        }
+    }
+}
+
+fn generate_service(service: &Service) -> Result<String> {
+    let name = type_name(short_name(&service.name));
+    let snake = snake_case(short_name(&service.name));
+
+    // The message types are named after the tables, which have to be unique in the service
+    let mut messages: BTreeMap<&str, &str> = BTreeMap::new();
+    for call in &service.calls {
+        for table in [&call.request, &call.response] {
+            let message = short_name(table);
+            match messages.insert(message, table) {
+                Some(other) if other != table => {
+                    return Err(err_other(format!(
+                        "{} uses both {} and {}, whose messages would be named {}",
+                        service.name, other, table, message
+                    )))
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let service_docs = docs(&service.documentation, "    ", || {
+        format!("The `{}` gRPC service", service.name)
+    });
+    let fill = |template: &str| {
+        template
+            .replace("{service}", &service.name)
+            .replace("{Name}", &name)
+            .replace("{snake}", &snake)
+            .replace("{service_docs}", &service_docs)
+    };
+
+    let mut message_items = String::new();
+    for (message, table) in &messages {
+        message_items.push_str(
+            &MESSAGE
+                .replace("{table}", table)
+                .replace("{Message}", &type_name(message))
+                .replace("{path}", &table_path(table)),
+        );
+    }
+
+    let mut client_methods = String::new();
+    let mut server_items = String::new();
+    let mut server_arms = String::new();
+    for call in &service.calls {
+        let streaming = Streaming::of(service, call)?;
+        let request = format!(
+            "super::{}_messages::{}",
+            snake,
+            type_name(short_name(&call.request))
+        );
+        let response = format!(
+            "super::{}_messages::{}",
+            snake,
+            type_name(short_name(&call.response))
+        );
+        let call_docs = docs(&call.documentation, "        ", || {
+            format!("The `{}` RPC", call.name)
+        });
+        let (client_request, into_request) = if streaming.streams_requests() {
+            (
+                format!("impl tonic::IntoStreamingRequest<Message = {}>", request),
+                "into_streaming_request",
+            )
+        } else {
+            (
+                format!("impl tonic::IntoRequest<{}>", request),
+                "into_request",
+            )
+        };
+        let client_response = if streaming.streams_responses() {
+            format!("tonic::codec::Streaming<{}>", response)
+        } else {
+            response.clone()
+        };
+        let server_request = if streaming.streams_requests() {
+            format!("tonic::Streaming<{}>", request)
+        } else {
+            request.clone()
+        };
+        let (server_response, response_stream) = if streaming.streams_responses() {
+            (
+                format!("Self::{}Stream", call.name),
+                STREAM_TYPE
+                    .replace("{Call}", &call.name)
+                    .replace("{response}", &response),
+            )
+        } else {
+            (response.clone(), String::new())
+        };
+        let (svc_response, svc_stream) = if streaming.streams_responses() {
+            (
+                "Self::ResponseStream",
+                format!(
+                    "                        type ResponseStream = T::{}Stream;\n",
+                    call.name
+                ),
+            )
+        } else {
+            ("Self::Response", String::new())
+        };
+        let fill_call = |template: &str| {
+            fill(template)
+                .replace("{Call}", &call.name)
+                .replace("{method}", &method_name(&call.name))
+                .replace("{call_docs}", &call_docs)
+                .replace("{client_request}", &client_request)
+                .replace("{into_request}", into_request)
+                .replace("{client_response}", &client_response)
+                .replace("{server_request}", &server_request)
+                .replace("{server_response}", &server_response)
+                .replace("{response_stream}", &response_stream)
+                .replace("{svc_response}", svc_response)
+                .replace("{svc_stream}", &svc_stream)
+                .replace("{request}", &request)
+                .replace("{response}", &response)
+                .replace("{grpc_method}", streaming.grpc_method())
+                .replace("{server_trait}", streaming.server_trait())
+        };
+        client_methods.push_str(&fill_call(CLIENT_METHOD));
+        if !server_items.is_empty() {
+            server_items.push('\n');
+        }
+        server_items.push_str(&fill_call(SERVER_METHOD));
+        server_arms.push_str(&fill_call(SERVER_ARM));
+    }
+
+    Ok(fill(SERVICE)
+        .replace("{messages}", &message_items)
+        .replace("{client_methods}", &client_methods)
+        .replace("{server_items}", &server_items)
+        .replace("{server_arms}", &server_arms))
+}
+
+/// The doc comment lines (indented), or the fallback line for the undocumented items
+fn docs<F: FnOnce() -> String>(documentation: &[String], indent: &str, fallback: F) -> String {
+    let lines = if documentation.is_empty() {
+        vec![format!(" {}", fallback())]
+    } else {
+        documentation.to_vec()
+    };
+    lines
+        .iter()
+        .map(|line| format!("{}///{}\n", indent, line.trim_end()))
+        .collect()
+}
+
+/// The last component of the qualified name
+fn short_name(qualified_name: &str) -> &str {
+    qualified_name.rsplit('.').next().unwrap_or(qualified_name)
+}
+
+/// The path of the table generated by `flatc` from the module of the services
+fn table_path(qualified_name: &str) -> String {
+    let mut components: Vec<&str> = qualified_name.split('.').collect();
+    let name = components.pop().unwrap_or_default();
+    let mut path = String::from("super::");
+    for namespace in components {
+        path.push_str(&snake_case(&escape_keyword(namespace)));
+        path.push_str("::");
+    }
+    path.push_str(&type_name(name));
+    path
+}
+
+/// The name of the type like `flatc` names the tables (the keywords get `_` appended)
+fn type_name(name: &str) -> String {
+    escape_keyword(name)
+}
+
+/// The name of the method of the RPC
+fn method_name(name: &str) -> String {
+    let method = snake_case(name);
+    if RUST_KEYWORDS.contains(&method.as_str()) {
+        format!("{}_", method)
+    } else {
+        method
+    }
+}
+
+fn escape_keyword(name: &str) -> String {
+    if RUST_KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Convert the name to snake case like `flatc` converts the namespaces into the Rust modules
+/// (`MyGame` is `my_game`, `XMLParser` is `xmlparser`, `Parser2Go` is `parser_2_go`)
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if let Some(previous) = previous {
+            let starts_word = (c.is_ascii_uppercase()
+                && (previous.is_ascii_lowercase() || previous.is_ascii_digit()))
+                || (c.is_ascii_digit() && previous.is_ascii_lowercase());
+            if starts_word {
+                snake.push('_');
+            }
+        }
+        snake.push(c.to_ascii_lowercase());
+        previous = Some(c);
+    }
+    snake
+}
+
+/// The names `flatc` escapes in the generated Rust code
+const RUST_KEYWORDS: &[&str] = &[
+    "as",
+    "break",
+    "const",
+    "continue",
+    "crate",
+    "else",
+    "enum",
+    "extern",
+    "false",
+    "fn",
+    "for",
+    "if",
+    "impl",
+    "in",
+    "let",
+    "loop",
+    "match",
+    "mod",
+    "move",
+    "mut",
+    "pub",
+    "ref",
+    "return",
+    "Self",
+    "self",
+    "static",
+    "struct",
+    "super",
+    "trait",
+    "true",
+    "type",
+    "unsafe",
+    "use",
+    "where",
+    "while",
+    "abstract",
+    "alignof",
+    "become",
+    "box",
+    "do",
+    "final",
+    "macro",
+    "offsetof",
+    "override",
+    "priv",
+    "proc",
+    "pure",
+    "sizeof",
+    "typeof",
+    "unsized",
+    "virtual",
+    "yield",
+    "std",
+    "usize",
+    "isize",
+    "u8",
+    "i8",
+    "u16",
+    "i16",
+    "u32",
+    "i32",
+    "u64",
+    "i64",
+    "u128",
+    "i128",
+    "f32",
+    "f64",
+    "follow",
+    "push",
+    "size",
+    "alignment",
+    "to_little_endian",
+    "from_little_endian",
+    "ENUM_MAX",
+    "ENUM_MIN",
+    "ENUM_VALUES",
+];
+
+const SERVICE: &str = r#"
+/// The FlatBuffers messages of the `{service}` gRPC service (generated by flatc-rust)
+pub mod {snake}_messages {
+    #![allow(dead_code)]
+
+    use std::marker::PhantomData;
+
+    use bytes::{Buf, BufMut};
+
+    /// A FlatBuffer owning its verified bytes, the request or the response of an RPC
+    pub trait Message: Sized + Send + 'static {
+        /// Verify the bytes of the FlatBuffer
+        fn from_bytes(bytes: Vec<u8>) -> Result<Self, flatbuffers::InvalidFlatbuffer>;
+
+        /// The bytes of the FlatBuffer
+        fn as_bytes(&self) -> &[u8];
+    }
+{messages}
+    /// The tonic codec encoding the `E` messages and decoding the `D` ones
+    #[derive(Debug)]
+    pub struct Codec<E, D>(PhantomData<fn() -> (E, D)>);
+
+    impl<E, D> Default for Codec<E, D> {
+        fn default() -> Self {
+            Codec(PhantomData)
+        }
+    }
+
+    impl<E: Message, D: Message> tonic::codec::Codec for Codec<E, D> {
+        type Encode = E;
+        type Decode = D;
+        type Encoder = Encoder<E>;
+        type Decoder = Decoder<D>;
+
+        fn encoder(&mut self) -> Encoder<E> {
+            Encoder(PhantomData)
+        }
+
+        fn decoder(&mut self) -> Decoder<D> {
+            Decoder(PhantomData)
+        }
+    }
+
+    /// The encoder of the [`Codec`]
+    #[derive(Debug)]
+    pub struct Encoder<E>(PhantomData<fn() -> E>);
+
+    impl<E: Message> tonic::codec::Encoder for Encoder<E> {
+        type Item = E;
+        type Error = tonic::Status;
+
+        fn encode(
+            &mut self,
+            item: E,
+            dst: &mut tonic::codec::EncodeBuf<'_>,
+        ) -> Result<(), tonic::Status> {
+            dst.put_slice(item.as_bytes());
+            Ok(())
+        }
+    }
+
+    /// The decoder of the [`Codec`]
+    #[derive(Debug)]
+    pub struct Decoder<D>(PhantomData<fn() -> D>);
+
+    impl<D: Message> tonic::codec::Decoder for Decoder<D> {
+        type Item = D;
+        type Error = tonic::Status;
+
+        fn decode(
+            &mut self,
+            src: &mut tonic::codec::DecodeBuf<'_>,
+        ) -> Result<Option<D>, tonic::Status> {
+            let bytes = src.copy_to_bytes(src.remaining()).to_vec();
+            D::from_bytes(bytes)
+                .map(Some)
+                .map_err(|e| tonic::Status::internal(format!("invalid FlatBuffer: {}", e)))
+        }
+    }
+}
+
+/// The tonic server of the `{service}` gRPC service (generated by flatc-rust)
+pub mod {snake}_server {
+    #![allow(dead_code, non_camel_case_types, clippy::wildcard_imports)]
+
+    use tonic::codegen::*;
+
+{service_docs}    #[async_trait]
+    pub trait {Name}: Send + Sync + 'static {
+{server_items}    }
+
+    /// The tower service serving the [`{Name}`] implementation
+    #[derive(Debug)]
+    pub struct {Name}Server<T> {
+        inner: Arc<T>,
+    }
+
+    impl<T> {Name}Server<T> {
+        /// The server of the implementation
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+
+        /// The server of the shared implementation
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self { inner }
+        }
+    }
+
+    impl<T> Clone for {Name}Server<T> {
+        fn clone(&self) -> Self {
+            Self {
+                inner: Arc::clone(&self.inner),
+            }
+        }
+    }
+
+    impl<T, B> Service<http::Request<B>> for {Name}Server<T>
+    where
+        T: {Name},
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::Body>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+{server_arms}                _ => Box::pin(async move {
+                    let mut response = http::Response::new(tonic::body::Body::default());
+                    let headers = response.headers_mut();
+                    headers.insert(
+                        tonic::Status::GRPC_STATUS,
+                        (tonic::Code::Unimplemented as i32).into(),
+                    );
+                    headers.insert(
+                        http::header::CONTENT_TYPE,
+                        tonic::metadata::GRPC_CONTENT_TYPE,
+                    );
+                    Ok(response)
+                }),
+            }
+        }
+    }
+
+    /// The name of the gRPC service
+    pub const SERVICE_NAME: &str = "{service}";
+
+    impl<T> tonic::server::NamedService for {Name}Server<T> {
+        const NAME: &'static str = SERVICE_NAME;
+    }
+}
+
+/// The tonic client of the `{service}` gRPC service (generated by flatc-rust)
+pub mod {snake}_client {
+    #![allow(dead_code, clippy::wildcard_imports)]
+
+    use std::convert::TryInto;
+
+    use tonic::codegen::*;
+
+{service_docs}    #[derive(Debug, Clone)]
+    pub struct {Name}Client<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+
+    impl {Name}Client<tonic::transport::Channel> {
+        /// Connect to the server at the endpoint
+        pub async fn connect<D>(dst: D) -> std::result::Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let channel = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(channel))
+        }
+    }
+
+    impl<T> {Name}Client<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::Body>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        /// The client sending the requests over the transport
+        pub fn new(inner: T) -> Self {
+            Self {
+                inner: tonic::client::Grpc::new(inner),
+            }
+        }
+{client_methods}    }
+}
+"#;
+
+const MESSAGE: &str = r#"
+    /// The message of the `{table}` table
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct {Message}(Vec<u8>);
+
+    impl {Message} {
+        /// Verify the FlatBuffer finished by the builder
+        pub fn from_builder(
+            builder: &flatbuffers::FlatBufferBuilder<'_>,
+        ) -> Result<Self, flatbuffers::InvalidFlatbuffer> {
+            Self::from_bytes(builder.finished_data().to_vec())
+        }
+
+        /// The root table of the message
+        pub fn get(&self) -> {path}<'_> {
+            // SAFETY: the bytes are verified by `from_bytes`
+            unsafe { flatbuffers::root_unchecked::<{path}<'_>>(&self.0) }
+        }
+    }
+
+    impl Message for {Message} {
+        fn from_bytes(bytes: Vec<u8>) -> Result<Self, flatbuffers::InvalidFlatbuffer> {
+            flatbuffers::root::<{path}<'_>>(&bytes)?;
+            Ok({Message}(bytes))
+        }
+
+        fn as_bytes(&self) -> &[u8] {
+            &self.0
+        }
+    }
+"#;
+
+const STREAM_TYPE: &str = r#"        /// The stream of the `{Call}` responses
+        type {Call}Stream: tokio_stream::Stream<
+                Item = std::result::Result<{response}, tonic::Status>,
+            > + Send
+            + 'static;
+
+"#;
+
+const SERVER_METHOD: &str = r#"{response_stream}{call_docs}        async fn {method}(
+            &self,
+            request: tonic::Request<{server_request}>,
+        ) -> std::result::Result<tonic::Response<{server_response}>, tonic::Status>;
+"#;
+
+const SERVER_ARM: &str = r#"                "/{service}/{Call}" => {
+                    struct {Call}Svc<T: {Name}>(Arc<T>);
+
+                    impl<T: {Name}> tonic::server::{server_trait}<{request}> for {Call}Svc<T> {
+                        type Response = {response};
+{svc_stream}                        type Future =
+                            BoxFuture<tonic::Response<{svc_response}>, tonic::Status>;
+
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<{server_request}>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            Box::pin(async move { <T as {Name}>::{method}(&inner, request).await })
+                        }
+                    }
+
+                    let inner = Arc::clone(&self.inner);
+                    Box::pin(async move {
+                        let codec = super::{snake}_messages::Codec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec);
+                        Ok(grpc.{grpc_method}({Call}Svc(inner), req).await)
+                    })
+                }
+"#;
+
+const CLIENT_METHOD: &str = r#"
+{call_docs}        pub async fn {method}(
+            &mut self,
+            request: {client_request},
+        ) -> std::result::Result<tonic::Response<{client_response}>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = super::{snake}_messages::Codec::default();
+            let path = http::uri::PathAndQuery::from_static("/{service}/{Call}");
+            let mut request = request.{into_request}();
+            request
+                .extensions_mut()
+                .insert(GrpcMethod::new("{service}", "{Call}"));
+            self.inner.{grpc_method}(request, path, codec).await
+        }
+"#;
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::{Args, Flatc};
+
+    #[test]
+    fn names_follow_flatc() {
+        assert_eq!(snake_case("MyGame"), "my_game");
+        assert_eq!(snake_case("XMLParser"), "xmlparser");
+        assert_eq!(snake_case("Parser2Go"), "parser_2_go");
+        assert_eq!(snake_case("V2"), "v2");
+        assert_eq!(snake_case("Some_Thing"), "some_thing");
+        assert_eq!(table_path("MyGame.u8.type"), "super::my_game::u_8_::type_");
+        assert_eq!(table_path("Monster"), "super::Monster");
+        assert_eq!(method_name("SayHello"), "say_hello");
+        assert_eq!(method_name("Move"), "move_");
+    }
+
+    #[test]
+    fn run_appends_services_to_generated_rust() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        fs::write(
+            temp_dir.path().join("common.fbs"),
+            "namespace Common;\ntable Ack { count: int; }\nrpc_service Pinger { Ping(Ack): Ack; }",
+        )?;
+        let input_path = temp_dir.path().join("greeter.fbs");
+        fs::write(
+            &input_path,
+            r#"include "common.fbs";
+namespace MyGame.HelloWorld;
+table HelloRequest { name: string; }
+table HelloReply { message: string; }
+/// Greets
+rpc_service Greeter {
+  /// Says hello
+  SayHello(HelloRequest): HelloReply;
+  SayManyHellos(HelloRequest): HelloReply (streaming: "server");
+  Collect(HelloRequest): Common.Ack (streaming: "client");
+  Chat(HelloRequest): HelloReply (streaming: "bidi");
+}"#,
+        )?;
+        let out_dir = temp_dir.path().join("out");
+        let args = Args {
+            inputs: &[&input_path],
+            out_dir: &out_dir,
+            tonic: true,
+            // The appended modules parse
+            verify_rust: cfg!(feature = "verify"),
+            ..Default::default()
+        };
+
+        Flatc::from_env_path().run(args)?;
+        let code = fs::read_to_string(out_dir.join("greeter_generated.rs"))?;
+        let (helpers, services) = code.split_at(code.find("pub mod greeter_messages").unwrap());
+        assert!(helpers.contains("pub mod my_game"));
+        assert!(services.contains("pub struct HelloRequest(Vec<u8>);"));
+        assert!(services.contains("pub fn get(&self) -> super::common::Ack<'_>"));
+        assert!(services.contains("    /// Greets\n    #[async_trait]\n    pub trait Greeter:"));
+        assert!(services.contains("        /// Says hello\n        async fn say_hello("));
+        assert!(services.contains("type SayManyHellosStream: tokio_stream::Stream<"));
+        assert!(services.contains("request: tonic::Request<tonic::Streaming<"));
+        assert!(services.contains("\"/MyGame.HelloWorld.Greeter/Chat\" => {"));
+        assert!(services.contains("Ok(grpc.client_streaming(CollectSvc(inner), req).await)"));
+        assert!(services.contains("pub struct GreeterClient<T>"));
+        assert!(services.contains("self.inner.server_streaming(request, path, codec).await"));
+        // The services of the included schemas belong to their own generated files
+        assert!(!services.contains("Pinger"));
+
+        fs::write(
+            &input_path,
+            "table Ping { id: int; }\nrpc_service Pinger { Ping(Ping): Ping (streaming: \"all\"); }",
+        )?;
+        let error = Flatc::from_env_path().run(args).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Pinger.Ping has unknown streaming `all`"));
+
+        let error = Flatc::from_env_path()
+            .run(Args {
+                lang: "python",
+                ..args
+            })
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("tonic needs the Rust helpers of "));
+
+        Ok(())
+    }
+}
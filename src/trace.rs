@@ -0,0 +1,181 @@
+//! Instrumentation of the discovery and the runs of `flatc` with `tracing` spans and events
+//! (`tracing` feature).
+//!
+//! Without the feature the functions do nothing, so the call sites do not need `cfg`s; the `log`
+//! records are emitted either way.
+
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+
+/// An entered span, exited on drop
+pub(crate) struct Span {
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+/// Enter the span of a run (`flatc_run`) into `out_dir`
+pub(crate) fn run(exec: &Path, input_count: usize, out_dir: &Path) -> Span {
+    #[cfg(not(feature = "tracing"))]
+    let _ = (exec, input_count, out_dir);
+    Span {
+        #[cfg(feature = "tracing")]
+        _span: tracing::info_span!(
+            "flatc_run",
+            exec = %exec.display(),
+            input_count,
+            out_dir = %out_dir.display()
+        )
+        .entered(),
+    }
+}
+
+/// Enter the span of the discovery of a working `flatc` (`flatc_discovery`)
+pub(crate) fn discovery() -> Span {
+    Span {
+        #[cfg(feature = "tracing")]
+        _span: tracing::info_span!("flatc_discovery").entered(),
+    }
+}
+
+/// Record the result of the discovery
+pub(crate) fn discovered(exec: Option<&Path>, candidate_count: usize) {
+    #[cfg(feature = "tracing")]
+    match exec {
+        Some(exec) => tracing::info!(exec = %exec.display(), candidate_count, "flatc found"),
+        None => tracing::warn!(candidate_count, "flatc not found"),
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (exec, candidate_count);
+}
+
+/// Record the spawning of the command
+pub(crate) fn spawned(cmd: &process::Command, child: &process::Child) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        exec = %Path::new(cmd.get_program()).display(),
+        args = ?cmd.get_args().collect::<Vec<_>>(),
+        pid = child.id(),
+        "flatc spawned"
+    );
+    #[cfg(not(feature = "tracing"))]
+    let _ = (cmd, child);
+}
+
+/// Record the exit of a spawned command
+pub(crate) fn exited(exec: &Path, status: process::ExitStatus, duration: Duration) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        exec = %exec.display(),
+        exit_code = status.code(),
+        duration_ms = duration.as_secs_f64() * 1000.0,
+        "flatc exited"
+    );
+    #[cfg(not(feature = "tracing"))]
+    let _ = (exec, status, duration);
+}
+
+/// Record the completion of a run (the generated files are counted by the runs which track
+/// them)
+pub(crate) fn completed(duration: Duration, generated_file_count: Option<usize>) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        duration_ms = duration.as_secs_f64() * 1000.0,
+        generated_file_count,
+        "flatc run completed"
+    );
+    #[cfg(not(feature = "tracing"))]
+    let _ = (duration, generated_file_count);
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use crate::{Args, Flatc};
+
+    /// Collects the names of the spans and the messages of the events with their fields
+    #[derive(Clone, Default)]
+    struct Collector {
+        records: Arc<Mutex<Vec<String>>>,
+        next_id: Arc<Mutex<u64>>,
+    }
+
+    struct Fields(String);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+
+    impl Subscriber for Collector {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes) -> Id {
+            let mut fields = Fields(span.metadata().name().to_owned());
+            span.record(&mut fields);
+            self.records.lock().expect("records").push(fields.0);
+            let mut next_id = self.next_id.lock().expect("next id");
+            *next_id += 1;
+            Id::from_u64(*next_id)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event) {
+            let mut fields = Fields(String::new());
+            event.record(&mut fields);
+            self.records.lock().expect("records").push(fields.0);
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn run_is_traced() -> crate::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let input_path = temp_dir.path().join("test.fbs");
+        std::fs::write(&input_path, "table Test { text: string; } root_type Test;")?;
+        let collector = Collector::default();
+
+        tracing::subscriber::with_default(collector.clone(), || {
+            Flatc::from_env_path().run_with_report(Args {
+                inputs: &[&input_path],
+                out_dir: temp_dir.path(),
+                quiet: true,
+                ..Default::default()
+            })
+        })?;
+
+        let records = collector.records.lock().expect("records");
+        assert!(records[0].starts_with("flatc_run exec="));
+        assert!(records[0].contains(" input_count=1 out_dir="));
+        assert!(records
+            .iter()
+            .any(|record| record.contains("flatc spawned") && record.contains("--rust")));
+        assert!(records
+            .iter()
+            .any(|record| record.contains("flatc exited") && record.contains("exit_code=0")));
+        assert!(records
+            .last()
+            .expect("completion")
+            .contains("message=flatc run completed duration_ms="));
+        assert!(records
+            .last()
+            .expect("completion")
+            .ends_with("generated_file_count=1"));
+
+        Ok(())
+    }
+}
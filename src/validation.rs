@@ -0,0 +1,275 @@
+//! Validation of the args before spawning `flatc`.
+
+use std::env;
+use std::error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+
+use crate::{normalize, Args, Error};
+
+/// The extensions of the input files `flatc` accepts (binary data files follow `--`)
+const INPUT_EXTENSIONS: &[&str] = &["fbs", "bfbs", "json", "json5", "proto"];
+
+/// A problem with the args found by [`Args::validate`]
+///
+/// [`Args::validate`]: struct.Args.html#method.validate
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// The input file does not exist
+    MissingInput(PathBuf),
+    /// The input file extension is none of `fbs`, `bfbs`, `json`, `json5`, `proto` (the files
+    /// following `--` are not checked)
+    UnexpectedExtension(PathBuf),
+    /// The include path does not exist or is not a directory
+    MissingInclude(PathBuf),
+    /// The `conform` baseline schema does not exist
+    MissingConform(PathBuf),
+    /// The `.proto` input is compiled without the proto mode (`lang: "proto"`), so `flatc` would
+    /// parse it as a FlatBuffers schema
+    ProtoWithoutProtoMode(PathBuf),
+    /// The JSON data input is not preceded by a schema (`.fbs` or `.bfbs`) to convert it with
+    JsonWithoutSchema(PathBuf),
+}
+
+impl Problem {
+    /// The path the problem is about
+    pub fn path(&self) -> &Path {
+        match self {
+            Problem::MissingInput(path)
+            | Problem::UnexpectedExtension(path)
+            | Problem::MissingInclude(path)
+            | Problem::MissingConform(path)
+            | Problem::ProtoWithoutProtoMode(path)
+            | Problem::JsonWithoutSchema(path) => path,
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::MissingInput(path) => {
+                write!(f, "input file {} does not exist", path.display())
+            }
+            Problem::UnexpectedExtension(path) => write!(
+                f,
+                "input file {} has unexpected extension (expected one of {}; binary data files \
+                 have to follow `--`)",
+                path.display(),
+                INPUT_EXTENSIONS.join(", ")
+            ),
+            Problem::MissingInclude(path) => {
+                write!(f, "include directory {} does not exist", path.display())
+            }
+            Problem::MissingConform(path) => {
+                write!(f, "conform schema {} does not exist", path.display())
+            }
+            Problem::ProtoWithoutProtoMode(path) => write!(
+                f,
+                "input file {} is a .proto file, which flatc only converts with lang \"proto\" \
+                 (convert it to .fbs first to generate the code)",
+                path.display()
+            ),
+            Problem::JsonWithoutSchema(path) => write!(
+                f,
+                "input file {} is JSON data, which needs a schema (.fbs or .bfbs) before it in \
+                 the inputs to be converted",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// The error listing all the problems found by [`Args::validate`]
+///
+/// It is returned wrapped into [`Error`] of `InvalidInput` kind:
+///
+/// ```no_run
+/// # let args = flatc_rust::Args::default();
+/// if let Err(e) = args.validate() {
+///     if let Some(e) = e
+///         .get_ref()
+///         .and_then(|e| e.downcast_ref::<flatc_rust::ValidationError>())
+///     {
+///         for problem in &e.problems {
+///             eprintln!("{}", problem);
+///         }
+///     }
+/// }
+/// ```
+///
+/// [`Args::validate`]: struct.Args.html#method.validate
+/// [`Error`]: type.Error.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// The problems in the order of the args
+    pub problems: Vec<Problem>,
+    /// The working directory the relative paths were resolved against
+    pub working_dir: PathBuf,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid flatc args:")?;
+        for problem in &self.problems {
+            write!(f, "\n  - {}", problem)?;
+            if problem.path().is_relative() {
+                write!(
+                    f,
+                    " (looked up as {})",
+                    self.working_dir.join(problem.path()).display()
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ValidationError {}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension().is_some_and(|extension| {
+        extensions
+            .iter()
+            .any(|expected| extension.eq_ignore_ascii_case(expected))
+    })
+}
+
+pub(crate) fn validate(args: &Args) -> Result<(), Error> {
+    for (first, duplicate) in normalize::form_duplicates(args.inputs)
+        .into_iter()
+        .chain(normalize::form_duplicates(args.includes))
+    {
+        warn!(
+            "{} duplicates {} (written differently), only the first one is passed to flatc",
+            duplicate.display(),
+            first.display()
+        );
+    }
+
+    let mut problems = Vec::new();
+
+    let proto_mode = args.lang == "proto" || args.extra.contains(&"--proto");
+    let mut binary_files = false;
+    let mut schema_seen = false;
+    for input in args.inputs {
+        if input.as_os_str() == "--" {
+            binary_files = true;
+            continue;
+        }
+        if !input.is_file() {
+            problems.push(Problem::MissingInput(input.to_path_buf()));
+        } else if binary_files {
+            continue;
+        } else if !has_extension(input, INPUT_EXTENSIONS) {
+            problems.push(Problem::UnexpectedExtension(input.to_path_buf()));
+        } else if has_extension(input, &["proto"]) && !proto_mode {
+            problems.push(Problem::ProtoWithoutProtoMode(input.to_path_buf()));
+        } else if has_extension(input, &["json", "json5"]) && !schema_seen {
+            problems.push(Problem::JsonWithoutSchema(input.to_path_buf()));
+        }
+        schema_seen |= has_extension(input, &["fbs", "bfbs"]);
+    }
+    for include in args.includes.iter().chain(args.conform_includes) {
+        if !include.is_dir() {
+            problems.push(Problem::MissingInclude(include.to_path_buf()));
+        }
+    }
+    if let Some(conform) = args.conform {
+        if !conform.is_file() {
+            problems.push(Problem::MissingConform(conform.to_path_buf()));
+        }
+    }
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(Error::new(
+        std::io::ErrorKind::InvalidInput,
+        ValidationError {
+            problems,
+            working_dir: env::current_dir()?,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn validate_lists_all_problems() -> crate::Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let schema_path = temp_dir.path().join("test.fbs");
+        fs::write(&schema_path, "table Test { text: string; } root_type Test;")?;
+        let text_path = temp_dir.path().join("test.txt");
+        fs::write(&text_path, "")?;
+        let proto_path = temp_dir.path().join("test.proto");
+        fs::write(&proto_path, "syntax = \"proto3\";")?;
+        let json_path = temp_dir.path().join("test.json");
+        fs::write(&json_path, "{ text: \"test\" }")?;
+        let missing_path = temp_dir.path().join("missing.fbs");
+        let missing_dir = temp_dir.path().join("missing");
+
+        let args = Args {
+            inputs: &[
+                &json_path,
+                &schema_path,
+                &missing_path,
+                &text_path,
+                &proto_path,
+                &json_path,
+                Path::new("--"),
+                &text_path,
+            ],
+            includes: &[temp_dir.path(), &missing_dir],
+            out_dir: temp_dir.path(),
+            ..Default::default()
+        };
+        let error = validate(&args).unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        let error = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<ValidationError>())
+            .expect("ValidationError");
+        assert_eq!(
+            error.problems,
+            vec![
+                Problem::JsonWithoutSchema(json_path.clone()),
+                Problem::MissingInput(missing_path.clone()),
+                Problem::UnexpectedExtension(text_path.clone()),
+                Problem::ProtoWithoutProtoMode(proto_path.clone()),
+                Problem::MissingInclude(missing_dir.clone()),
+            ]
+        );
+
+        let error = validate(&Args {
+            inputs: &[Path::new("missing.fbs")],
+            ..args
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains(&format!(
+            "input file missing.fbs does not exist (looked up as {})",
+            env::current_dir()?.join("missing.fbs").display()
+        )));
+
+        validate(&Args {
+            inputs: &[&schema_path, Path::new("--"), &text_path],
+            includes: &[temp_dir.path()],
+            ..args
+        })?;
+        validate(&Args {
+            lang: "proto",
+            inputs: &[&proto_path],
+            includes: &[temp_dir.path()],
+            ..args
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+//! Verification that the generated Rust code parses (`verify` feature).
+
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Result};
+
+/// A generated Rust file which `syn` fails to parse
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnparsableFile {
+    /// The generated file
+    pub path: PathBuf,
+    /// The line of the first syntax error (1-based)
+    pub line: usize,
+    /// The column of the first syntax error (0-based)
+    pub column: usize,
+    /// The syntax error reported by `syn`
+    pub message: String,
+}
+
+impl fmt::Display for UnparsableFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.message
+        )
+    }
+}
+
+/// The error listing all the generated Rust files which do not parse (see
+/// [`Args::verify_rust`])
+///
+/// It is returned wrapped into [`Error`] of `InvalidData` kind, like [`ValidationError`].
+///
+/// [`Args::verify_rust`]: struct.Args.html#structfield.verify_rust
+/// [`Error`]: type.Error.html
+/// [`ValidationError`]: struct.ValidationError.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationError {
+    /// The unparsable files in the order of generation
+    pub files: Vec<UnparsableFile>,
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "flatc has generated Rust code which does not parse (is flatc version compatible \
+             with the edition and the flatbuffers crate?):"
+        )?;
+        for file in &self.files {
+            write!(f, "\n  - {}", file)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for VerificationError {}
+
+/// Parse the file with `syn`, returning the first syntax error
+fn parse(path: &Path) -> Result<Option<UnparsableFile>> {
+    let content = fs::read_to_string(path)?;
+    Ok(syn::parse_file(&content).err().map(|error| {
+        let start = error.span().start();
+        UnparsableFile {
+            path: path.to_path_buf(),
+            line: start.line,
+            column: start.column,
+            message: error.to_string(),
+        }
+    }))
+}
+
+/// Check that every generated `.rs` file parses
+pub(crate) fn verify_rust_files(generated_files: &[PathBuf]) -> Result<()> {
+    let mut files = Vec::new();
+    for generated_file in generated_files {
+        if generated_file
+            .extension()
+            .is_some_and(|extension| extension == "rs")
+        {
+            files.extend(parse(generated_file)?);
+        }
+    }
+
+    if files.is_empty() {
+        return Ok(());
+    }
+    Err(Error::new(
+        std::io::ErrorKind::InvalidData,
+        VerificationError { files },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn verify_rust_files_lists_unparsable_files() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let valid_path = temp_dir.path().join("valid_generated.rs");
+        fs::write(&valid_path, "pub struct Test<'a> { _tab: &'a [u8] }\n")?;
+        let invalid_path = temp_dir.path().join("invalid_generated.rs");
+        fs::write(&invalid_path, "pub struct Test {\n    text: String;\n}\n")?;
+        let other_path = temp_dir.path().join("test.bfbs");
+        fs::write(&other_path, [0xff, 0x00])?;
+
+        verify_rust_files(&[valid_path.clone(), other_path.clone()])?;
+
+        let error = verify_rust_files(&[valid_path, invalid_path.clone(), other_path]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        let error = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<VerificationError>())
+            .expect("VerificationError");
+        assert_eq!(error.files.len(), 1);
+        assert_eq!(error.files[0].path, invalid_path);
+        assert_eq!(error.files[0].line, 2);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,473 @@
+//! Discovery of the FlatBuffers schemas declared across a Cargo workspace.
+//!
+//! Every workspace member may declare its schemas in `Cargo.toml`:
+//!
+//! ```toml
+//! [package.metadata.flatc-rust]
+//! inputs = ["schemas/message.fbs"]
+//! includes = ["schemas/"]
+//! ```
+//!
+//! Paths are relative to the member's manifest directory. Members without the metadata section
+//! fall back to the convention of keeping the schemas in a `flatbuffers/` folder (next to
+//! `Cargo.toml`), in which case every `.fbs` file there is an input and the folder itself is an
+//! include path.
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use log::info;
+
+use crate::outputs::DirSnapshot;
+use crate::{err_other, Args, Flatc, Result};
+
+/// The `[package.metadata.<key>]` section used to declare schemas
+pub const METADATA_KEY: &str = "flatc-rust";
+
+/// The folder (relative to the manifest directory) searched for schemas when a member does not
+/// declare them explicitly
+pub const CONVENTIONAL_SCHEMA_DIR: &str = "flatbuffers";
+
+/// The schemas declared by a single workspace member
+#[derive(Debug, Clone)]
+pub struct MemberSchemas {
+    /// Package name of the member
+    pub package: String,
+    /// The directory containing `Cargo.toml` of the member
+    pub manifest_dir: PathBuf,
+    /// The `.fbs` files to compile (absolute paths)
+    pub inputs: Vec<PathBuf>,
+    /// The include paths (absolute paths)
+    pub includes: Vec<PathBuf>,
+}
+
+/// The schemas declared across a workspace
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceSchemas {
+    /// The workspace members declaring at least one schema
+    pub members: Vec<MemberSchemas>,
+}
+
+impl WorkspaceSchemas {
+    /// All the inputs of the workspace (without duplicates)
+    pub fn inputs(&self) -> Vec<&Path> {
+        dedup(self.members.iter().flat_map(|member| &member.inputs))
+    }
+
+    /// All the include paths of the workspace (without duplicates)
+    pub fn includes(&self) -> Vec<&Path> {
+        dedup(self.members.iter().flat_map(|member| &member.includes))
+    }
+}
+
+fn dedup<'a, I: Iterator<Item = &'a PathBuf>>(paths: I) -> Vec<&'a Path> {
+    let mut unique: Vec<&Path> = Vec::new();
+    for path in paths {
+        if !unique.contains(&path.as_path()) {
+            unique.push(path);
+        }
+    }
+    unique
+}
+
+/// Discover the schemas of the workspace using `cargo metadata`
+///
+/// `manifest_path` points to the `Cargo.toml` of the workspace (or any of its members); the
+/// workspace of the current directory is used if it is `None`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn try_main() -> flatc_rust::Result<()> {
+/// let schemas = flatc_rust::workspace::discover_schemas(None)?;
+/// for member in &schemas.members {
+///     println!("{} declares {} schema(s)", member.package, member.inputs.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn discover_schemas(manifest_path: Option<&Path>) -> Result<WorkspaceSchemas> {
+    // Build scripts get the path to the cargo that runs them
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo"));
+    let mut cmd = process::Command::new(cargo);
+    cmd.args(["metadata", "--format-version", "1", "--no-deps"]);
+    if let Some(manifest_path) = manifest_path {
+        cmd.arg("--manifest-path").arg(manifest_path);
+    }
+    cmd.stdin(process::Stdio::null())
+        .stderr(process::Stdio::inherit());
+
+    info!("spawning command {:?}", cmd);
+    let output = cmd
+        .output()
+        .map_err(|e| err_other(format!("failed to spawn `{:?}`: {}", cmd, e)))?;
+    if !output.status.success() {
+        return Err(err_other(format!(
+            "cargo metadata ({:?}) exited with non-zero exit code",
+            cmd
+        )));
+    }
+
+    parse_metadata(&output.stdout)
+}
+
+/// Extract the schemas from `cargo metadata --format-version 1` output
+fn parse_metadata(metadata: &[u8]) -> Result<WorkspaceSchemas> {
+    let metadata: serde_json::Value = serde_json::from_slice(metadata).map_err(err_other)?;
+    let packages = metadata["packages"]
+        .as_array()
+        .ok_or_else(|| err_other("cargo metadata does not list packages"))?;
+
+    let mut members = Vec::new();
+    for package in packages {
+        let name = package["name"]
+            .as_str()
+            .ok_or_else(|| err_other("cargo metadata package has no name"))?;
+        let manifest_dir = package["manifest_path"]
+            .as_str()
+            .and_then(|manifest_path| Path::new(manifest_path).parent())
+            .ok_or_else(|| err_other(format!("package `{}` has no manifest path", name)))?;
+
+        let member = match package["metadata"].get(METADATA_KEY) {
+            Some(declared) => MemberSchemas {
+                package: name.to_owned(),
+                manifest_dir: manifest_dir.to_owned(),
+                inputs: declared_paths(name, manifest_dir, declared, "inputs")?,
+                includes: declared_paths(name, manifest_dir, declared, "includes")?,
+            },
+            None => match conventional_schemas(name, manifest_dir)? {
+                Some(member) => member,
+                None => continue,
+            },
+        };
+        if !member.inputs.is_empty() {
+            members.push(member);
+        }
+    }
+
+    Ok(WorkspaceSchemas { members })
+}
+
+fn declared_paths(
+    package: &str,
+    manifest_dir: &Path,
+    declared: &serde_json::Value,
+    key: &str,
+) -> Result<Vec<PathBuf>> {
+    let paths = match declared.get(key) {
+        Some(paths) => paths,
+        None => return Ok(Vec::new()),
+    };
+    paths
+        .as_array()
+        .ok_or_else(|| {
+            err_other(format!(
+                "`package.metadata.{}.{}` of `{}` must be a list of paths",
+                METADATA_KEY, key, package
+            ))
+        })?
+        .iter()
+        .map(|path| {
+            path.as_str()
+                .map(|path| manifest_dir.join(path))
+                .ok_or_else(|| {
+                    err_other(format!(
+                        "`package.metadata.{}.{}` of `{}` must be a list of paths",
+                        METADATA_KEY, key, package
+                    ))
+                })
+        })
+        .collect()
+}
+
+fn conventional_schemas(package: &str, manifest_dir: &Path) -> Result<Option<MemberSchemas>> {
+    let schema_dir = manifest_dir.join(CONVENTIONAL_SCHEMA_DIR);
+    if !schema_dir.is_dir() {
+        return Ok(None);
+    }
+
+    let mut inputs = Vec::new();
+    for entry in fs::read_dir(&schema_dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "fbs") {
+            inputs.push(path);
+        }
+    }
+    inputs.sort();
+
+    Ok(Some(MemberSchemas {
+        package: package.to_owned(),
+        manifest_dir: manifest_dir.to_owned(),
+        inputs,
+        includes: vec![schema_dir],
+    }))
+}
+
+/// Configuration of [`compile_workspace`]
+///
+/// [`compile_workspace`]: fn.compile_workspace.html
+#[derive(Debug, Clone, Copy)]
+pub struct WorkspaceConfig<'a> {
+    /// Path to `Cargo.toml` of the workspace (the workspace of the current directory is used if
+    /// it is `None`)
+    pub manifest_path: Option<&'a Path>,
+    /// The root output directory; the helpers of every member not listed in `member_out_dirs`
+    /// are generated into `<out_dir>/<package name>/` (required unless every member is listed)
+    ///
+    /// This is not the `OUT_DIR` of the members: their crates have to
+    /// `include!("<out_dir>/<package name>/<schema>_generated.rs")` the helpers by hand.
+    pub out_dir: &'a Path,
+    /// The output directories of specific members as `(package name, out_dir)` pairs, e.g. to
+    /// generate the helpers of a member straight into a directory its crate already includes
+    /// them from
+    pub member_out_dirs: &'a [(&'a str, &'a Path)],
+    /// Specify the programming language (`rust` is the default)
+    pub lang: &'a str,
+    /// Extra args to pass to flatc
+    pub extra: &'a [&'a str],
+}
+
+impl Default for WorkspaceConfig<'_> {
+    fn default() -> Self {
+        Self {
+            manifest_path: None,
+            out_dir: Path::new(""),
+            member_out_dirs: &[],
+            lang: "rust",
+            extra: &[],
+        }
+    }
+}
+
+impl WorkspaceConfig<'_> {
+    /// The output directory of the specified workspace member
+    pub fn member_out_dir(&self, package: &str) -> PathBuf {
+        match self
+            .member_out_dirs
+            .iter()
+            .find(|(member, _)| *member == package)
+        {
+            Some((_, out_dir)) => out_dir.to_path_buf(),
+            None => self.out_dir.join(package),
+        }
+    }
+}
+
+/// Compile the schemas of every workspace member in one coordinated pass
+///
+/// `flatc` is checked only once, all the invocations share the include paths of the whole
+/// workspace, and members declaring the very same inputs are compiled only once (the generated
+/// files are copied to the output directories of the rest of such members).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// # fn try_main() -> flatc_rust::Result<()> {
+/// flatc_rust::compile_workspace(flatc_rust::workspace::WorkspaceConfig {
+///     out_dir: Path::new("target/flatbuffers/"),
+///     ..Default::default()
+/// })?;
+/// // The helpers of the `messages` crate are now in `target/flatbuffers/messages/`
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The helpers are not generated into the `OUT_DIR` of the members, so every member crate
+/// includes them from its directory under `out_dir` (or from its `member_out_dirs` entry).
+pub fn compile_workspace(config: WorkspaceConfig) -> Result<WorkspaceSchemas> {
+    let schemas = discover_schemas(config.manifest_path)?;
+    if config.out_dir.as_os_str().is_empty() {
+        if let Some(member) = schemas.members.iter().find(|member| {
+            !config
+                .member_out_dirs
+                .iter()
+                .any(|(package, _)| *package == member.package)
+        }) {
+            return Err(err_other(format!(
+                "out_dir is empty and member_out_dirs does not list `{}`",
+                member.package
+            )));
+        }
+    }
+    let includes = schemas.includes();
+
+    let flatc = Flatc::from_env_path();
+    flatc.check()?;
+
+    // (inputs, out_dir, generated files) of every `flatc` invocation
+    let mut compiled: Vec<(&[PathBuf], PathBuf, Vec<PathBuf>)> = Vec::new();
+    for member in &schemas.members {
+        let out_dir = config.member_out_dir(&member.package);
+        fs::create_dir_all(&out_dir)?;
+
+        if let Some((_, compiled_out_dir, generated_files)) = compiled
+            .iter()
+            .find(|(inputs, _, _)| *inputs == member.inputs.as_slice())
+        {
+            info!(
+                "reusing the helpers of {:?} for `{}`",
+                compiled_out_dir, member.package
+            );
+            for generated_file in generated_files {
+                let target = out_dir.join(
+                    generated_file
+                        .strip_prefix(compiled_out_dir)
+                        .map_err(err_other)?,
+                );
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(generated_file, target)?;
+            }
+            continue;
+        }
+
+        let inputs: Vec<&Path> = member.inputs.iter().map(PathBuf::as_path).collect();
+        let before = DirSnapshot::take(&out_dir)?;
+        flatc.run(Args {
+            lang: config.lang,
+            inputs: &inputs,
+            out_dir: &out_dir,
+            includes: &includes,
+            extra: config.extra,
+            ..Default::default()
+        })?;
+        let generated_files = DirSnapshot::take(&out_dir)?.changed_since(&before);
+        compiled.push((&member.inputs, out_dir, generated_files));
+    }
+
+    Ok(schemas)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_metadata_uses_declared_schemas() {
+        let metadata = br#"{
+            "packages": [
+                {
+                    "name": "messages",
+                    "manifest_path": "/ws/messages/Cargo.toml",
+                    "metadata": {
+                        "flatc-rust": {
+                            "inputs": ["schemas/message.fbs"],
+                            "includes": ["schemas/", "../common/"]
+                        }
+                    }
+                },
+                {
+                    "name": "events",
+                    "manifest_path": "/ws/events/Cargo.toml",
+                    "metadata": {
+                        "flatc-rust": {
+                            "inputs": ["schemas/event.fbs"],
+                            "includes": ["../common/"]
+                        }
+                    }
+                },
+                {
+                    "name": "no-schemas",
+                    "manifest_path": "/ws/no-schemas/Cargo.toml",
+                    "metadata": null
+                }
+            ]
+        }"#;
+
+        let schemas = parse_metadata(metadata).expect("parse");
+
+        assert_eq!(schemas.members.len(), 2);
+        assert_eq!(schemas.members[0].package, "messages");
+        assert_eq!(
+            schemas.inputs(),
+            vec![
+                Path::new("/ws/messages/schemas/message.fbs"),
+                Path::new("/ws/events/schemas/event.fbs"),
+            ]
+        );
+        assert_eq!(
+            schemas.includes(),
+            vec![
+                Path::new("/ws/messages/schemas/"),
+                Path::new("/ws/messages/../common/"),
+                Path::new("/ws/events/../common/"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_metadata_rejects_malformed_declarations() {
+        let metadata = br#"{
+            "packages": [
+                {
+                    "name": "messages",
+                    "manifest_path": "/ws/messages/Cargo.toml",
+                    "metadata": { "flatc-rust": { "inputs": "schemas/message.fbs" } }
+                }
+            ]
+        }"#;
+
+        assert!(parse_metadata(metadata).is_err());
+    }
+
+    #[test]
+    fn compile_workspace_generates_per_member_helpers() -> Result<()> {
+        let temp_dir = tempfile::Builder::new().prefix("flatc-rust").tempdir()?;
+        let root = temp_dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"a\", \"b\"]\n",
+        )?;
+        for member in &["a", "b"] {
+            fs::create_dir_all(root.join(member).join("src"))?;
+            fs::create_dir_all(root.join(member).join(CONVENTIONAL_SCHEMA_DIR))?;
+            fs::write(
+                root.join(member).join("Cargo.toml"),
+                format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", member),
+            )?;
+            fs::write(root.join(member).join("src").join("lib.rs"), "")?;
+        }
+        fs::write(
+            root.join("a").join(CONVENTIONAL_SCHEMA_DIR).join("a.fbs"),
+            "table A { text: string; } root_type A;",
+        )?;
+        fs::write(
+            root.join("b").join(CONVENTIONAL_SCHEMA_DIR).join("b.fbs"),
+            "table B { number: int; } root_type B;",
+        )?;
+
+        let manifest_path = root.join("Cargo.toml");
+        let out_dir = root.join("out");
+        let config = WorkspaceConfig {
+            manifest_path: Some(&manifest_path),
+            out_dir: &out_dir,
+            ..Default::default()
+        };
+        let schemas = compile_workspace(config)?;
+
+        assert_eq!(schemas.members.len(), 2);
+        assert!(config.member_out_dir("a").join("a_generated.rs").exists());
+        assert!(config.member_out_dir("b").join("b_generated.rs").exists());
+
+        let out_dir = root.join("mapped");
+        let b_out_dir = root.join("b").join("generated");
+        let config = WorkspaceConfig {
+            out_dir: &out_dir,
+            member_out_dirs: &[("b", &b_out_dir)],
+            ..config
+        };
+        compile_workspace(config)?;
+
+        assert!(out_dir.join("a").join("a_generated.rs").exists());
+        assert!(b_out_dir.join("b_generated.rs").exists());
+        assert!(!out_dir.join("b").exists());
+
+        Ok(())
+    }
+}
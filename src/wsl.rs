@@ -0,0 +1,127 @@
+//! Running `flatc` installed inside WSL (see [`Flatc::with_wsl`]).
+//!
+//! `flatc` is run with `wsl --exec`, so the arguments are passed without a shell. The Windows
+//! paths of the arguments are translated to their Linux forms (`C:\schemas\monster.fbs` is
+//! `/mnt/c/schemas/monster.fbs`, the relative paths get forward slashes), and the paths of the
+//! mounted drives in the captured `flatc` output (the diagnostics, `-M` rules) are translated
+//! back, so the errors point to the files the Windows tools can open.
+//!
+//! The drives are expected at the default WSL mount root (`/mnt/`); the paths with spaces are
+//! only translated up to the first space in the output.
+//!
+//! [`Flatc::with_wsl`]: ../struct.Flatc.html#method.with_wsl
+
+use std::ffi::{OsStr, OsString};
+
+/// The launcher of the WSL commands
+pub(crate) const WSL_EXEC: &str = "wsl";
+
+/// The directory WSL mounts the Windows drives under
+const MOUNT_ROOT: &str = "/mnt/";
+
+/// The Linux form of the Windows path argument; the flags are left intact
+pub(crate) fn to_linux_arg(arg: &OsStr) -> OsString {
+    let arg = match arg.to_str() {
+        Some(arg) if !arg.starts_with('-') => arg,
+        _ => return arg.to_owned(),
+    };
+    // The verbatim paths of `canonicalize`, e.g. `\\?\C:\schemas`
+    let arg = arg.strip_prefix(r"\\?\").unwrap_or(arg);
+    let bytes = arg.as_bytes();
+    let is_absolute = bytes.len() >= 2
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes.len() == 2 || bytes[2] == b'\\' || bytes[2] == b'/');
+    if !is_absolute {
+        return arg.replace('\\', "/").into();
+    }
+    format!(
+        "{}{}{}",
+        MOUNT_ROOT,
+        char::from(bytes[0].to_ascii_lowercase()),
+        arg[2..].replace('\\', "/")
+    )
+    .into()
+}
+
+/// The `flatc` output with the paths of the mounted drives in their Windows forms
+pub(crate) fn to_windows_paths(output: &str) -> String {
+    let mut translated = String::with_capacity(output.len());
+    let mut rest = output;
+    while let Some(index) = rest.find(MOUNT_ROOT) {
+        translated.push_str(&rest[..index]);
+        let path = &rest[index + MOUNT_ROOT.len()..];
+        let drive = path.chars().next().filter(char::is_ascii_alphabetic);
+        let is_drive = drive.is_some()
+            && path[1..]
+                .chars()
+                .next()
+                .is_none_or(|c| c == '/' || is_path_end(c))
+            && !translated.ends_with(|c: char| c.is_alphanumeric() || c == '/');
+        let Some(drive) = drive.filter(|_| is_drive) else {
+            translated.push_str(MOUNT_ROOT);
+            rest = path;
+            continue;
+        };
+        let drive_path = &path[1..];
+        let end = drive_path.find(is_path_end).unwrap_or(drive_path.len());
+        translated.push(drive.to_ascii_uppercase());
+        translated.push(':');
+        if end == 0 {
+            translated.push('\\');
+        }
+        translated.push_str(&drive_path[..end].replace('/', "\\"));
+        rest = &drive_path[end..];
+    }
+    translated.push_str(rest);
+    translated
+}
+
+fn is_path_end(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ':' | '"' | '\'' | '`' | ')')
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{Args, Flatc};
+
+    #[test]
+    fn wsl_translates_paths_both_ways() -> crate::Result<()> {
+        let linux_arg = |arg: &str| to_linux_arg(OsStr::new(arg)).into_string().expect("UTF-8");
+        assert_eq!(
+            linux_arg(r"C:\schemas\monster.fbs"),
+            "/mnt/c/schemas/monster.fbs"
+        );
+        assert_eq!(linux_arg(r"\\?\D:\out"), "/mnt/d/out");
+        assert_eq!(linux_arg("E:"), "/mnt/e");
+        assert_eq!(linux_arg(r"schemas\monster.fbs"), "schemas/monster.fbs");
+        assert_eq!(linux_arg("--rust"), "--rust");
+
+        assert_eq!(
+            to_windows_paths(
+                "error:\n  /mnt/c/schemas/monster.fbs:1: 21: error: expecting: ;\n\
+                 out/monster_generated.rs: /mnt/d/common.fbs /mnt/e\n\
+                 /home/user/mnt/c/x /mnt/cd/y"
+            ),
+            "error:\n  C:\\schemas\\monster.fbs:1: 21: error: expecting: ;\n\
+             out/monster_generated.rs: D:\\common.fbs E:\\\n\
+             /home/user/mnt/c/x /mnt/cd/y"
+        );
+
+        let flatc = Flatc::from_path("flatc").with_wsl(true);
+        let command_line = flatc.command_line(Args {
+            inputs: &[Path::new(r"C:\schemas\monster.fbs")],
+            out_dir: Path::new(r"C:\out"),
+            ..Default::default()
+        })?;
+        assert_eq!(
+            command_line,
+            "wsl --exec flatc --rust -o /mnt/c/out /mnt/c/schemas/monster.fbs"
+        );
+
+        Ok(())
+    }
+}